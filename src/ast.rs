@@ -1,14 +1,43 @@
 pub mod parser;
 
-use std::{collections::HashMap, isize};
 use attribute::AttributeType;
 
-use crate::lexer::TokenKind;
+use crate::lexer::{TextSpan, TokenKind, KEYWORD_FUNC_DECL};
+
+// Indices into the arrays of the IR. Each kind of index has its own type, so they cant be mixed up.
+macro_rules! define_id
+{
+	( $( $name:ident ),* ) => 
+	{
+		$(
+			#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+			pub struct $name(pub u32);
+
+			#[allow(dead_code)]
+			impl $name
+			{
+				pub fn index(&self) -> usize
+				{
+					return self.0 as usize;
+				}
+			}
+		)*
+	};
+}
+pub(crate) use define_id;
+
+define_id!(
+	FunctionId,			/* The functions index in Root.functions */
+	FunctionTypeId,		/* The function types index in Root.function_types */
+	VariableId,			/* The variables index in its functions locals */
+	ScopeId				/* The depth of a scope in its function, the parameters are in scope 0 */
+);
 
 #[derive(Debug)]
 pub struct Root
 {
-	pub functions: Vec<Function>
+	pub functions: Vec<Function>,
+	pub function_types: Vec<FunctionType>,		/* The signatures of the functions and function pointers, each signature appears once */
 }
 
 // This will have a return type field, calling convenction, and other shit in the future
@@ -16,20 +45,29 @@ pub struct Root
 pub struct Function
 {
 	pub identifier: String,
-	pub index: u8,
+	pub index: FunctionId,
+	pub signature: FunctionTypeId,
+	pub span: TextSpan,			/* The span of the functions identifier */
 	pub return_type: Type,
 	pub attributes: AttributeType,
-	pub parameter_count: u8,
-	pub parameters_stack_size: usize,
+	pub parameters: Vec<VarDeclInfo>,
 	pub locals: Vec<Variable>,
 	pub code_block: Scope,
 }
 
+// The data type of a function pointer. func(i32, f64) -> i32
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionType
+{
+	pub parameters: Vec<Type>,
+	pub return_type: Type,
+	pub attributes: AttributeType,		/* Only the attributes that change the way the function is called (calling convenction, variadic) */
+}
+
 #[derive(Debug, Clone)]
 pub struct Scope
 {
 	pub statements: Vec<Statement>,
-	pub stack_size: usize,
 	// Will have more crap in the near future.
 }
 
@@ -39,9 +77,10 @@ pub enum Statement
 	Scope(Scope),
 	If(IfInfo),
 	For(ForLoopInfo),
+	VarDecl(VarDeclInfo),
 	Assign(VarUpdateInfo),
 	FunctionCall(FunctionCallInfo),
-	Return(Option<BinExpr>),
+	Return(ReturnInfo),
 }
 
 #[derive(Debug, Clone)]
@@ -59,16 +98,33 @@ pub struct ForLoopInfo
 	pub condition: Option<BinExpr>,
 	pub update: Option<Box<Statement>>,
 	pub code_block: Box<Statement>,
-	pub stack_size: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct VarDeclInfo
+{
+	pub identifier: String,
+	pub data_type: Type,			/* Void if it was not written (let x = 5;), then it is inferred by the semantic analysis */
+	pub value: Option<BinExpr>,		/* The initial value, if there is one */
+	pub attributes: AttributeType,	/* attribute::CONSTANT for constants (const X i32 = 5;) */
+	pub index: VariableId,			/* Set by the semantic analysis */
+	pub span: TextSpan,
 }
 
 #[derive(Debug, Clone)]
 pub struct VarUpdateInfo
 {
-	pub destination: Value,
+	pub destination: BinExprPart,	/* A variable or a pointer dereference */
 	pub value: BinExpr
 }
 
+#[derive(Debug, Clone)]
+pub struct ReturnInfo
+{
+	pub value: Option<BinExpr>,
+	pub span: TextSpan,
+}
+
 #[derive(Debug, Clone)]
 pub enum Value
 {
@@ -82,24 +138,37 @@ pub enum Value
 	U64(u64),		/* (Not funny) */
 	F32(f32),		/* (Not funny) */
 	F64(f64),		/* (Not funny) */
-	Var(u8),		/* The variables index in the variables array */
+	IntLit(i64),	/* A number literal, the semantic analysis gives it a data type (one of the above) */
+	FloatLit(f64),
+	Ident(String),	/* A name, the semantic analysis resolves it into a variable or a function */
+	Var(VariableId),
 	Dereference(DereferenceInfo),
 	FuncCall(FunctionCallInfo),
+	FuncAddress(FunctionId),	/* The address of a function (&function) */
 }
 
 #[derive(Debug, Clone)]
 pub struct DereferenceInfo
 {
 	pub expression: Box<BinExpr>,
-	pub dereference_count: u8,
-	pub data_type: Type,
+	pub dereference_count: u32,
 }
 
 #[derive(Debug, Clone)]
 pub struct FunctionCallInfo
 {
-	pub index: u8,
+	pub callee: Callee,
+	pub signature: FunctionTypeId,		/* The type of the called function, set by the semantic analysis */
 	pub arguments: Vec<BinExpr>,
+	pub span: TextSpan,
+}
+
+#[derive(Debug, Clone)]
+pub enum Callee
+{
+	Ident(String),				/* Not resolved yet */
+	Direct(FunctionId),
+	Indirect(Box<BinExpr>),		/* A function pointer */
 }
 
 #[derive(Debug, Clone)]
@@ -109,7 +178,15 @@ pub struct BinExpr
 }
 
 #[derive(Debug, Clone)]
-pub enum BinExprPart
+pub struct BinExprPart
+{
+	pub kind: BinExprPartKind,
+	pub data_type: Type,		/* Void until the semantic analysis annotates it */
+	pub span: TextSpan,
+}
+
+#[derive(Debug, Clone)]
+pub enum BinExprPartKind
 {
 	SelfOperation(Box<BinExprSelfOperation>),
 	Operation(Box<BinExprOperation>),
@@ -121,8 +198,7 @@ pub enum BinExprPart
 pub struct TypeCastInfo
 {
 	pub into_type: Type,
-	pub from_type: Type,
-	pub expression: BinExprPart,
+	pub expression: BinExprPart,		/* Its data type is the type that is casted from */
 }
 
 #[derive(Debug, Clone)]
@@ -170,17 +246,19 @@ pub enum BinExprOperator
 }
 
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Type
 {
 	pub kind: TypeKind,
 	
-	// Doesnt matter if self.kind != TypeKind::Pointer
-	pub points_to: TypeKind,	
-	pub pointer_level: u8,		
+	// The data type that the pointer points to, None if self.kind != TypeKind::Pointer. (Pointer types are interned, see Type::new_ptr)
+	pub points_to: Option<&'static Type>,
+
+	// Doesnt matter if self.kind != TypeKind::Function
+	pub signature: FunctionTypeId,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd)]
 pub enum TypeKind
 {
 	Void,
@@ -193,6 +271,7 @@ pub enum TypeKind
 	I64,
 	U64,
 	Pointer,
+	Function,
 
 	F32,
 	F64,
@@ -204,9 +283,8 @@ pub struct Variable
 {
 	pub data_type: Type,
 	pub attributes: AttributeType,
-	pub index: u8,
-	pub location: isize,
-	pub scope: u8,
+	pub index: VariableId,
+	pub scope: ScopeId,
 }
 
 impl Root
@@ -215,26 +293,45 @@ impl Root
 	{
 		return Self{
 			functions,
+			function_types: Vec::new(),
 		};
 	}
 }
 
 impl Function
 {
-	pub fn new(identifier: String, return_type: Type, attributes: AttributeType) -> Self
+	pub fn new(identifier: String, return_type: Type, attributes: AttributeType, span: TextSpan) -> Self
 	{
 		return Self{
 			identifier,
-			index: u8::MAX,
+			index: FunctionId(u32::MAX),
+			signature: FunctionTypeId(u32::MAX),
+			span,
 			return_type,
 			attributes,
-			parameters_stack_size: 0,
+			parameters: Vec::new(),
 			locals: Vec::new(),
-			parameter_count: 0,
 			code_block: Scope::new(Vec::new())
 		};
 	}
-	
+
+	pub fn function_type(&self) -> FunctionType
+	{
+		let parameters = self.parameters.iter().map(|parameter| parameter.data_type).collect();
+		return FunctionType::new(parameters, self.return_type, self.attributes & (attribute::SYS_V_ABI_X86_64 | attribute::VARIADIC));
+	}
+}
+
+impl FunctionType
+{
+	pub fn new(parameters: Vec<Type>, return_type: Type, attributes: AttributeType) -> Self
+	{
+		return Self {
+			parameters,
+			return_type,
+			attributes,
+		};
+	}
 }
 
 impl Scope
@@ -243,7 +340,6 @@ impl Scope
 	{
 		return Self {
 			statements,
-			stack_size: 0,
 		};
 	}
 
@@ -260,32 +356,33 @@ impl IfInfo
 		return Self {
 			condition,
 			then_block: Box::new(then_block),
-			else_block: if let Some(else_block) = else_block { Some(Box::new(else_block)) } else { None },
+			else_block: else_block.map(Box::new),
 		};
 	}
 }
 
 impl ForLoopInfo
 {
-	pub fn new(initializer: Option<Statement>, condition: Option<BinExpr>, update: Option<Statement>, code_block: Statement, stack_size: usize) -> Self
+	pub fn new(initializer: Option<Statement>, condition: Option<BinExpr>, update: Option<Statement>, code_block: Statement) -> Self
 	{
 		return Self {
-			initializer: if let Some(statement) = initializer { Some(Box::new(statement)) } else { None },
+			initializer: initializer.map(Box::new),
 			condition,
-			update: if let Some(statement) = update { Some(Box::new(statement)) } else { None },
+			update: update.map(Box::new),
 			code_block: Box::new(code_block),
-			stack_size
 		};
 	}
 }
 
 impl FunctionCallInfo
 {
-	pub fn new(index: u8, arguments: Vec<BinExpr>) -> Self
+	pub fn new(callee: Callee, arguments: Vec<BinExpr>, span: TextSpan) -> Self
 	{
 		return Self {
-			index,
+			callee,
+			signature: FunctionTypeId(u32::MAX),
 			arguments,
+			span,
 		};
 	}
 }
@@ -300,6 +397,18 @@ impl BinExpr
 	}
 }
 
+impl BinExprPart
+{
+	pub fn new(kind: BinExprPartKind, span: TextSpan) -> Self
+	{
+		return Self {
+			kind,
+			data_type: Type::new(TypeKind::Void),
+			span,
+		};
+	}
+}
+
 impl BinExprOperation
 {
 	pub fn new(operator: BinExprOperator, lhs: BinExprPart, rhs: BinExprPart) -> Self
@@ -325,21 +434,35 @@ impl BinExprSelfOperation
 
 impl Variable
 {
-	pub fn new(data_type: Type, attributes: AttributeType, index: u8, scope: u8) -> Self 
+	pub fn new(data_type: Type, attributes: AttributeType, index: VariableId, scope: ScopeId) -> Self 
 	{
 		return Self {
 			data_type,
 			attributes,
 			index,
-			location: 0,		/* Doesnt realy matter */
 			scope,
 		};
 	}
 }
 
+impl VarDeclInfo
+{
+	pub fn new(identifier: String, data_type: Type, value: Option<BinExpr>, span: TextSpan) -> Self
+	{
+		return Self {
+			identifier,
+			data_type,
+			value,
+			attributes: 0,
+			index: VariableId(u32::MAX),
+			span,
+		};
+	}
+}
+
 impl VarUpdateInfo
 {
-	pub fn new(destination: Value, value: BinExpr) -> Self
+	pub fn new(destination: BinExprPart, value: BinExpr) -> Self
 	{
 		return Self {
 			destination,
@@ -348,6 +471,17 @@ impl VarUpdateInfo
 	}
 }
 
+impl ReturnInfo
+{
+	pub fn new(value: Option<BinExpr>, span: TextSpan) -> Self
+	{
+		return Self {
+			value,
+			span,
+		};
+	}
+}
+
 impl TypeKind
 {
 	// Will return None if the given token kind is not a type
@@ -377,23 +511,22 @@ impl TypeKind
 			Self::I8  | Self::U8 								=> 1,
 			Self::I16 | Self::U16								=> 2,
 			Self::I32 | Self::U32 | Self::F32					=> 4,
-			Self::I64 | Self::U64 | Self::F64 | Self::Pointer 	=> 8,
+			Self::I64 | Self::U64 | Self::F64 | Self::Pointer |
+			Self::Function 										=> 8,
 		}
 	}
 
 	pub fn is_signed(&self) -> bool
 	{
-		return match self
-		{
+		return matches!(self,
 			Self::I8  | Self::I16 | Self::I32 | Self::I64 |
-			Self::F32 | Self::F64 => true,
-			_ => false,
-		}
+			Self::F32 | Self::F64
+		);
 	}
 
 	pub fn is_integer(&self) -> bool
 	{
-		return *self as u8 >= Self::I8 as u8 && *self as u8 <= Self::Pointer as u8;
+		return *self as u8 >= Self::I8 as u8 && *self as u8 <= Self::Function as u8;
 	}
 }
 
@@ -403,17 +536,43 @@ impl Type
 	{
 		return Self {
 			kind,
-			points_to: TypeKind::Void,
-			pointer_level: 0,
+			points_to: None,
+			signature: FunctionTypeId(0),
 		};
 	}
 
-	pub fn new_ptr(kind: TypeKind, points_to: TypeKind, pointer_level: u8) -> Self
+	// A pointer to the given data type. Each pointer type is allocated once and lives until the compiler exits,
+	// so Type stays Copy while pointers to pointers (**i32) are represented recursively.
+	pub fn new_ptr(points_to: Type) -> Self
 	{
+		thread_local! {
+			static POINTED_TYPES: std::cell::RefCell<std::collections::HashSet<&'static Type>> = std::cell::RefCell::new(std::collections::HashSet::new());
+		}
+
+		let points_to = POINTED_TYPES.with(|types| {
+			if let Some(existing) = types.borrow().get(&points_to)
+			{
+				return *existing;
+			}
+
+			let allocated: &'static Type = Box::leak(Box::new(points_to));
+			types.borrow_mut().insert(allocated);
+			return allocated;
+		});
+
 		return Self {
-			kind,
-			points_to,
-			pointer_level,
+			kind: TypeKind::Pointer,
+			points_to: Some(points_to),
+			signature: FunctionTypeId(0),
+		};
+	}
+
+	pub fn new_function(signature: FunctionTypeId) -> Self
+	{
+		return Self {
+			kind: TypeKind::Function,
+			points_to: None,
+			signature,
 		};
 	}
 
@@ -437,20 +596,31 @@ impl Type
 		return self.kind == TypeKind::Pointer;
 	}
 
-	pub fn dereference(&self, count: u8) -> Type
+	pub fn is_function(&self) -> bool
+	{
+		return self.kind == TypeKind::Function;
+	}
+
+	// The amount of times the data type can be dereferenced. (2 for **i32)
+	pub fn pointer_level(&self) -> u32
 	{
-		if self.kind != TypeKind::Pointer || count > self.pointer_level
+		return match self.points_to
 		{
-			panic!("Type.dereference was not called on a pointer data type.");
-		}
+			Some(points_to) => points_to.pointer_level() + 1,
+			None => 0,
+		};
+	}
 
-		let new_ptr_level = self.pointer_level - count;
-		if new_ptr_level == 0
+	pub fn dereference(&self, count: u32) -> Type
+	{
+		let mut data_type = *self;
+		for _ in 0..count
 		{
-			return Type::new(self.points_to);
+			data_type = *data_type.points_to.unwrap_or_else(|| {
+				panic!("Type.dereference was not called on a pointer data type.");
+			});
 		}
-
-		return Type::new_ptr(TypeKind::Pointer, self.points_to, new_ptr_level);
+		return data_type;
 	}
 }
 
@@ -458,11 +628,31 @@ impl std::fmt::Display for Type
 {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result 
 	{
+		if let Some(points_to) = self.points_to
+		{
+			return write!(f, "*{points_to}");
+		}
+
 		let _ = write!(f, "{}", format!("{:?}", self.kind).to_lowercase());
 		return Ok(());
 	}
 }
 
+impl std::fmt::Display for FunctionType
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result 
+	{
+		let parameters: Vec<String> = self.parameters.iter().map(|parameter| parameter.to_string()).collect();
+		let _ = write!(f, "{KEYWORD_FUNC_DECL}({}", parameters.join(", "));
+		if self.attributes & attribute::VARIADIC != 0
+		{
+			let _ = write!(f, ", ...");
+		}
+		let _ = write!(f, ") -> {}", self.return_type);
+		return Ok(());
+	}
+}
+
 impl BinExprOperator
 {
 	const LOWEST_PRECEDENCE: u8 = 1;
@@ -523,27 +713,35 @@ impl BinExprOperator
 	{
 		return *self as u8 >= Self::BitwiseNot as u8 && *self as u8 <= Self::Dereference as u8;
 	}
+
+	// The operators that work on the bits of integers, which floats dont have
+	pub fn is_integer_only(&self) -> bool
+	{
+		return matches!(self,
+			Self::BitwiseOr | Self::BitwiseXor | Self::BitwiseAnd | Self::BitwiseRightShift | Self::BitwiseLeftShift |
+			Self::Modulo | Self::BitwiseNot
+		);
+	}
 }
 
-// impl Value
-// {
-	// pub fn is_constant(&self) -> bool
-	// {
-	// 	return match *self
-	// 	{
-	// 		Value::Var(_) | Value::FuncCall(_) => false,
-	// 		_ => true,
-	// 	};
-	// }
-// }
+impl Value
+{
+	// Returns true for number values, which are known while compiling
+	pub fn is_constant(&self) -> bool
+	{
+		return matches!(*self,
+			Value::I8(_)  | Value::U8(_)  | Value::I16(_) | Value::U16(_) | Value::I32(_) | Value::U32(_) |
+			Value::I64(_) | Value::U64(_) | Value::F32(_) | Value::F64(_) | Value::IntLit(_) | Value::FloatLit(_)
+		);
+	}
+}
 
 impl TypeCastInfo
 {
-	pub fn new(into_type: Type, from_type: Type, expression: BinExprPart) -> Self
+	pub fn new(into_type: Type, expression: BinExprPart) -> Self
 	{
 		return Self {
 			into_type,
-			from_type,
 			expression,
 		}
 	}
@@ -551,12 +749,11 @@ impl TypeCastInfo
 
 impl DereferenceInfo
 {
-	pub fn new(expression: BinExpr, dereference_count: u8, data_type: Type) -> Self
+	pub fn new(expression: BinExpr, dereference_count: u32) -> Self
 	{
 		return Self {
 			expression: Box::new(expression),
 			dereference_count,
-			data_type,
 		};
 	}
 }
@@ -570,6 +767,10 @@ pub mod attribute
 	pub const EXTERN: 				AttributeType = 0b1 << 1;
 	pub const SYS_V_ABI_X86_64: 	AttributeType = 0b1 << 2;
 	pub const FUNCTION_PARAMETER: 	AttributeType = 0b1 << 3;
+	pub const VARIADIC: 			AttributeType = 0b1 << 4;		/* Takes a variable amount of arguments after its parameters, like printf */
+	pub const CONSTANT: 			AttributeType = 0b1 << 5;		/* A local constant, its value is known while compiling and it has no stack location */
+	pub const INLINE: 				AttributeType = 0b1 << 6;		/* The body of the function is substituted into its callers when optimizing (-O1 and above) */
+	pub const TAIL_RECURSIVE: 		AttributeType = 0b1 << 7;		/* Its recursive calls must be tail calls, which are compiled into jumps at every optimization level */
 	
 	pub fn from_token_kind(token_kind: &TokenKind) -> Option<AttributeType>
 	{
@@ -577,6 +778,8 @@ pub mod attribute
 		{
 			TokenKind::Global => Some(GLOBAL),
 			TokenKind::Extern => Some(EXTERN),
+			TokenKind::Inline => Some(INLINE),
+			TokenKind::TailRec => Some(TAIL_RECURSIVE),
 			_ => return None
 		}
 	}
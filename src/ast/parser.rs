@@ -2,9 +2,7 @@ mod common;
 mod function;
 mod statement;
 mod expression;
-mod variable;
 
-use function::FunctionManager;
 use crate::{error::CompileError, print_errln};
 
 use super::{super::lexer::*, *};
@@ -12,7 +10,6 @@ use super::{super::lexer::*, *};
 pub struct Parser<'a>
 {
 	ir: Root,
-	func_manager: FunctionManager,
 	tokens: Vec<Token>,
 	position: usize,
 	source: &'a str,
@@ -26,7 +23,6 @@ impl<'a> Parser<'a>
 		let source = lexer.source;
 		return Self{
 			ir: Root::new(Vec::new()),
-			func_manager: FunctionManager::new(),
 			tokens: lexer.collect(),
 			position: 0,
 			source,
@@ -50,7 +46,6 @@ impl<'a> Parser<'a>
 
 			}
 		}
-		self.ir.functions = self.func_manager.into_function_array();
 		return self.ir;
 	}
 
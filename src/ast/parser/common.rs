@@ -1,5 +1,3 @@
-use variable::LocalVariables;
-
 use super::*;
 
 impl<'a> Parser<'a>
@@ -36,55 +34,28 @@ impl<'a> Parser<'a>
 		return self.tokens[self.position];
 	}
 
-	pub fn value_type(&self, value: &Value, variables: &LocalVariables) -> Type
-	{
-		match value {
-			Value::I8(_)  => Type::new(TypeKind::I8),
-			Value::U8(_)  => Type::new(TypeKind::U8),
-			Value::I16(_) => Type::new(TypeKind::I16),
-			Value::U16(_) => Type::new(TypeKind::U16),
-			Value::I32(_) => Type::new(TypeKind::I32),
-			Value::U32(_) => Type::new(TypeKind::U32),
-			Value::I64(_) => Type::new(TypeKind::I64),
-			Value::U64(_) => Type::new(TypeKind::U64),
-			Value::F32(_) => Type::new(TypeKind::F32),
-			Value::F64(_) => Type::new(TypeKind::F64),
-			Value::Var(index) => 
-			{
-				let var = variables.get_variable_by_index(*index).unwrap();
-				return var.data_type;
-			},
-			Value::FuncCall(func_call) => return self.func_manager.get_by_index(func_call.index).unwrap().return_type,
-			Value::Dereference(info) => return info.data_type.dereference(info.dereference_count),
-		}
-	}
-
-	pub fn bin_expr_part_type(&self, part: &BinExprPart, variables: &LocalVariables) -> Type
-	{
-		match part
-		{
-			BinExprPart::Operation(operation) => return self.bin_expr_part_type(&operation.lhs, variables),
-			BinExprPart::SelfOperation(operation) => return self.bin_expr_part_type(&operation.expression, variables),
-			BinExprPart::TypeCast(info) => return info.into_type,
-			BinExprPart::Val(value) => self.value_type(value, variables),
-		}
-	}
-
 	// Self is not mutated if None is returned
 	pub fn parse_data_type(&mut self) -> Option<Type>
 	{
 		let position = self.position;
-		let mut pointer_level: u8 = 0;
+		let mut pointer_level: u32 = 0;
 		while self.current_token().kind == TokenKind::Asterisk
 		{
 			pointer_level += 1;
-			if let None = self.advance_token()
+			if self.advance_token().is_none()
 			{
 				self.position = position;
 				return None;
 			}
 		}
 
+		// Function pointer types are written like function declarations, without the identifiers. func(i32, *u8) -> i32
+		if self.current_token().kind == TokenKind::FuncDecl
+		{
+			let signature = self.parse_function_type();
+			return Some(Self::pointer_type(Type::new_function(signature), pointer_level));
+		}
+
 		let kind = if let Some(kind) = TypeKind::from_token_kind(&self.current_token().kind) 
 		{ 
 			kind 
@@ -95,13 +66,116 @@ impl<'a> Parser<'a>
 		};
 		self.advance_token();
 
-		if pointer_level == 0
+		return Some(Self::pointer_type(Type::new(kind), pointer_level));
+	}
+
+	// Wraps the data type in pointer_level pointers. (**i32 is a pointer to a pointer to an i32)
+	fn pointer_type(mut data_type: Type, pointer_level: u32) -> Type
+	{
+		for _ in 0..pointer_level
+		{
+			data_type = Type::new_ptr(data_type);
+		}
+		return data_type;
+	}
+
+	// Parses the data type of a function pointer, and returns its index in the function types array.
+	fn parse_function_type(&mut self) -> FunctionTypeId
+	{
+		let token_func = self.current_token();
+		let token_left_paren = self.advance_token().unwrap_or_else(|| {
+			print_errln!(CompileError::UnexpectedEof, self.source, token_func.span.end, "While parsing function pointer type.");
+		});
+		if token_left_paren.kind != TokenKind::LeftParen
+		{
+			print_errln!(CompileError::Syntax, self.source, token_left_paren.span.start, "Expected parameter list after {KEYWORD_FUNC_DECL}.");
+		}
+		self.advance_token().unwrap_or_else(|| {
+			print_errln!(CompileError::UnexpectedEof, self.source, token_left_paren.span.end, "While parsing function pointer type.");
+		});
+
+		let mut parameters: Vec<Type> = Vec::new();
+		let mut attributes = attribute::SYS_V_ABI_X86_64; 	/* Just for now, in the future there will be more calling convenctions */
+		while self.current_token().kind != TokenKind::RightParen
+		{
+			if self.current_token().kind == TokenKind::Ellipsis
+			{
+				if parameters.is_empty()
+				{
+					print_errln!(
+						CompileError::Syntax, 
+						self.source, 
+						self.current_token().span.start, 
+						"A variadic function must have at least one parameter before the ellipsis ( ... )."
+					);
+				}
+				attributes |= attribute::VARIADIC;
+				self.advance_token().unwrap_or_else(|| {
+					print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.end, "While parsing function pointer type.");
+				});
+				if self.current_token().kind != TokenKind::RightParen
+				{
+					print_errln!(
+						CompileError::Syntax, 
+						self.source, 
+						self.current_token().span.start, 
+						"The ellipsis ( ... ) must be the last thing in the parameter list."
+					);
+				}
+				break;
+			}
+
+			let token_type = self.current_token();
+			let data_type = self.parse_data_type().unwrap_or_else(|| {
+				print_errln!(CompileError::Syntax, self.source, token_type.span.start, "Expected parameter type in function pointer type.");
+			});
+			if data_type == Type::new(TypeKind::Void)
+			{
+				print_errln!(CompileError::Syntax, self.source, token_type.span.start, "A parameter cannot be of type \"{KEYWORD_VOID}\".");
+			}
+			parameters.push(data_type);
+
+			match self.current_token().kind
+			{
+				TokenKind::Comma =>
+				{
+					self.advance_token().unwrap_or_else(|| {
+						print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.end, "While parsing function pointer type.");
+					});
+				},
+				TokenKind::RightParen => break,
+				_ => { print_errln!(CompileError::Syntax, self.source, self.current_token().span.start, "Expected parameter seperator \",\" or closing parenthese."); }
+			}
+		}
+
+		let token_arrow = self.advance_token().unwrap_or_else(|| {
+			print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.end, "While parsing function pointer type.");
+		});
+		if token_arrow.kind != TokenKind::Arrow
 		{
-			return Some(Type::new(kind));
-		} else
+			print_errln!(CompileError::Syntax, self.source, token_arrow.span.start, "Expected return type specifier (Arrow operator \"->\") after parameter list in function pointer type.");
+		}
+		self.advance_token().unwrap_or_else(|| {
+			print_errln!(CompileError::UnexpectedEof, self.source, token_arrow.span.end, "While parsing function pointer type.");
+		});
+
+		let return_type = self.parse_data_type().unwrap_or_else(|| {
+			print_errln!(CompileError::Syntax, self.source, token_arrow.span.end, "Expected return type after return type specifier.");
+		});
+
+		return self.add_function_type(FunctionType::new(parameters, return_type, attributes));
+	}
+
+	// Returns the index of the function type in the function types array, and adds it if its not there.
+	pub fn add_function_type(&mut self, function_type: FunctionType) -> FunctionTypeId
+	{
+		if let Some(index) = self.ir.function_types.iter().position(|existing| *existing == function_type)
 		{
-			return Some(Type::new_ptr(TypeKind::Pointer, kind, pointer_level));
+			return FunctionTypeId(index as u32);
 		}
+
+		self.ir.function_types.push(function_type);
+		return FunctionTypeId((self.ir.function_types.len() - 1) as u32);
 	}
 
 
@@ -1,59 +1,16 @@
 use crate::{ast::*, error::CompileError, lexer::*, print_errln};
-use super::{Parser, variable::*};
-
-pub struct FunctionManager
-{
-	index: u8,
-	functions: HashMap<String, Function>,
-}
-
-impl FunctionManager
-{
-	pub fn new() -> Self
-	{
-		return Self {
-			index: 0,
-			functions: HashMap::new(),
-		};
-	}
-
-	// Returns the index of the new function
-	pub fn add(&mut self, mut function: Function) -> u8
-	{
-		function.index = self.index;
-		self.functions.insert(function.identifier.clone(), function);
-		self.index += 1;
-		return self.index - 1;
-	}
-
-	pub fn get(&self, identifier: &str) -> Option<&Function>
-	{
-		return self.functions.get(identifier);
-	}
-
-	pub fn get_by_index(&self, index: u8) -> Option<&Function>
-	{
-		return self.functions.values().find(|function| function.index == index);
-	}
-
-	pub fn into_function_array(self) -> Vec<Function>
-	{
-		let mut array: Vec<Function> = self.functions.into_values().collect();
-		array.sort_by_cached_key(|function| function.index);
-		return array;
-	}
-}
+use super::Parser;
 
 impl<'a> Parser<'a>
 {
 	pub fn parse_function_decl(&mut self)
 	{
-		if let None = self.advance_token()
+		if self.advance_token().is_none()
 		{
 			print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.end, "While parsing function.");
 		}
 
-		let attributes = self.parse_function_decl_attributes();
+		let mut attributes = self.parse_function_decl_attributes();
 		
 		let token_ident = self.current_token();
 		
@@ -75,7 +32,7 @@ impl<'a> Parser<'a>
 			print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.start, "While parsing function parameters.");
 		});
 
-		let mut variables = self.parse_function_decl_parameters(attributes);
+		let parameters = self.parse_function_decl_parameters(&mut attributes);
 
 		// Skip Closing parenthese, as its the exit condition for self.parse_function_decl_parameters(attributes);
 		let token_ret_type_specifier = self.advance_token().unwrap_or_else(|| {
@@ -97,49 +54,60 @@ impl<'a> Parser<'a>
 
 		let token_scope_start = self.current_token();
 		
-		let mut function = Function::new(identifier.to_string(), return_type, attributes);
-		function.parameter_count = variables.get_variable_count();
+		let mut function = Function::new(identifier.to_string(), return_type, attributes, token_ident.span);
+		function.parameters = parameters;
+		function.signature = self.add_function_type(function.function_type());
 		
 		if token_scope_start.kind == TokenKind::Semicolon
 		{
+			// There is no body to substitute into the callers, or to check for recursive calls
+			for (attribute, keyword) in [(attribute::INLINE, KEYWORD_INLINE), (attribute::TAIL_RECURSIVE, KEYWORD_TAILREC)]
+			{
+				if attributes & attribute != 0
+				{
+					print_errln!(
+						CompileError::Syntax,
+						self.source,
+						token_ident.span.start,
+						"Only functions with a body can be {keyword}."
+					);
+				}
+			}
 			self.advance_token();
-			function.code_block.stack_size = variables.end_scope();
-			let locals = variables.get_variables_info();	
-			function.locals = locals.vars;
-			function.parameters_stack_size = locals.parameters_stack_size;
-			self.func_manager.add(function);
+			self.ir.functions.push(function);
 			return;
 		} else if token_scope_start.kind != TokenKind::LeftCurly
 		{
 			print_errln!(CompileError::Syntax, self.source, token_scope_start.span.start, "Expected scope begin operator \"{{\" or semicolon after function return type.");
 		}
+
+		// There is no way of reading the variadic arguments in Slow (yet), so only external functions can be variadic.
+		if attributes & attribute::VARIADIC != 0
+		{
+			print_errln!(
+				CompileError::Syntax, 
+				self.source, 
+				token_ident.span.start, 
+				"Only {KEYWORD_EXTERN} functions can take a variable amount of arguments ( ... )."
+			);
+		}
 		
 		self.advance_token().unwrap_or_else(|| {
 			print_errln!(CompileError::UnexpectedEof, self.source, token_scope_start.span.start, "While parsing function scope.");
 		});
 
-		let mut code_block = Scope::new(Vec::new());
-
 		while self.current_token().kind != TokenKind::RightCurly
 		{
-			if let Some(statement) = self.parse_statement(&mut variables, &function)
+			if let Some(statement) = self.parse_statement()
 			{
-				code_block.add_statement(statement);
+				function.code_block.add_statement(statement);
 			}
 		}
 		self.advance_token();
-		code_block.stack_size = variables.end_scope();
-
-		let locals = variables.get_variables_info();
-		function.locals = locals.vars;
-		function.parameters_stack_size = locals.parameters_stack_size;
-
-		code_block.stack_size += locals.parameters_stack_size;
-		function.code_block = code_block;
-		self.func_manager.add(function);
+		self.ir.functions.push(function);
 	}
 
-	pub fn parse_scope(&mut self, variables: &mut LocalVariables, function: &Function) -> Scope
+	pub fn parse_scope(&mut self) -> Scope
 	{
 		let mut scope = Scope::new(Vec::new());
 
@@ -147,33 +115,55 @@ impl<'a> Parser<'a>
 			print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.start, "While parsing scope start.");
 		});
 
-		variables.start_scope();
-
 		while self.current_token().kind != TokenKind::RightCurly
 		{
-			if let Some(statement) = self.parse_statement(variables, function)
+			if let Some(statement) = self.parse_statement()
 			{
 				scope.add_statement(statement);
 			}
 		}
 
 		self.advance_token();
-
-		scope.stack_size = variables.end_scope();
 		return scope;
 	}
 
-	// NOTE: This function creates the LocalVariables struct and starts a scope, the callee must end the scope. end_scope()
-	fn parse_function_decl_parameters(&mut self, attributes: AttributeType) -> LocalVariables
+	// If the parameter list ends with an ellipsis ( ... ), attribute::VARIADIC is added to the attributes.
+	fn parse_function_decl_parameters(&mut self, attributes: &mut AttributeType) -> Vec<VarDeclInfo>
 	{
-		let mut args = LocalVariables::new(attributes);
-		args.start_scope();
+		let mut parameters: Vec<VarDeclInfo> = Vec::new();
 		loop 
 		{
 			let token_ident = self.current_token();
 			match token_ident.kind 
 			{
 				TokenKind::RightParen => break,
+				TokenKind::Ellipsis =>
+				{
+					if parameters.is_empty()
+					{
+						print_errln!(
+							CompileError::Syntax, 
+							self.source, 
+							token_ident.span.start, 
+							"A variadic function must have at least one parameter before the ellipsis ( ... )."
+						);
+					}
+
+					let token_right_paren = self.advance_token().unwrap_or_else(|| {
+						print_errln!(CompileError::UnexpectedEof, self.source, token_ident.span.end, "While parsing function parameters.");
+					});
+					if token_right_paren.kind != TokenKind::RightParen
+					{
+						print_errln!(
+							CompileError::Syntax, 
+							self.source, 
+							token_right_paren.span.start, 
+							"The ellipsis ( ... ) must be the last thing in the parameter list."
+						);
+					}
+					*attributes |= attribute::VARIADIC;
+					break;
+				},
 				TokenKind::Ident =>
 				{
 					let ident = self.get_text(&token_ident.span).to_string();
@@ -196,9 +186,7 @@ impl<'a> Parser<'a>
 						);
 					}
 
-					// NOTE: (to my future self getting a headache) because arguments will be pushed on the stack from right to left,
-					// The stack location (this variable will exist in the future) will just be positive
-					args.add_variable(ident, attribute::FUNCTION_PARAMETER, data_type);
+					parameters.push(VarDeclInfo::new(ident, data_type, None, token_ident.span));
 
 					let token_comma = self.current_token();
 
@@ -219,7 +207,7 @@ impl<'a> Parser<'a>
 				_ => { print_errln!(CompileError::Syntax, self.source, token_ident.span.start, "Unexpected token while parsing function parameters."); }
 			}
 		}
-		return args;	
+		return parameters;
 
 	}
 
@@ -238,14 +226,11 @@ impl<'a> Parser<'a>
 		return attributes;
 	}
 
-	pub fn parse_function_call(&mut self, variables: &LocalVariables) -> FunctionCallInfo
+	// Parses a call to a function, or to a function pointer. The semantic analysis decides which one it is.
+	pub fn parse_function_call(&mut self) -> FunctionCallInfo
 	{
-		let identifier = self.get_text(&self.current_token().span);
-
-		// Could clone the value, but i just love pointers sooo much (i do)
-		let function: *const Function = self.func_manager.get(identifier).unwrap_or_else(|| {
-			print_errln!(CompileError::UnknownIdentifier(identifier), self.source, self.current_token().span.start, "No such function.");
-		});
+		let token_ident = self.current_token();
+		let identifier = self.get_text(&token_ident.span).to_string();
 
 		let token_left_paren = self.advance_token().unwrap_or_else(|| {
 			print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.start, "While parsing function.");
@@ -259,61 +244,30 @@ impl<'a> Parser<'a>
 			print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.start, "While parsing function call.");
 		});
 
-		unsafe 
+		let mut arguments: Vec<BinExpr> = Vec::new();
+		while self.current_token().kind != TokenKind::RightParen
 		{
-			let mut arguments: Vec<BinExpr> = Vec::with_capacity((*function).locals.len() as usize);
-			for parameter in &(*function).locals[..(*function).parameter_count as usize]
-			{
-				if self.current_token().kind == TokenKind::RightParen
-				{
-					break;
-				}
-				
-				let data_type = parameter.data_type;
-				let argument = self.parse_expression(Some(data_type), variables);
-				arguments.push(argument);
+			arguments.push(self.parse_expression());
 
-				if self.current_token().kind == TokenKind::RightParen
-				{
-					break;
-				}
-
-				if self.current_token().kind != TokenKind::Comma
-				{
-					print_errln!(CompileError::Syntax, self.source, self.current_token().span.start, "Expected argument seperator \",\" or closing parenthese.");
-				}
-
-				self.advance_token().unwrap_or_else(|| {
-					print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.start, "While parsing function call arguments.");
-				});
-			}
-			
-			if self.current_token().kind != TokenKind::RightParen
+			if self.current_token().kind == TokenKind::RightParen
 			{
-				print_errln!(
-					CompileError::Syntax, 
-					self.source, 
-					token_left_paren.span.start, 
-					"The function \"{}\" takes {} parameters but more were given.", (*function).identifier, (*function).parameter_count
-				);
+				break;
 			}
 
-			if arguments.len() != (*function).parameter_count as usize 
+			if self.current_token().kind != TokenKind::Comma
 			{
-				print_errln!(
-					CompileError::Syntax, 
-					self.source, 
-					token_left_paren.span.start,
-					"The function \"{}\" takes {} parameters but {} were given.", (*function).identifier, (*function).parameter_count, arguments.len()
-				);
+				print_errln!(CompileError::Syntax, self.source, self.current_token().span.start, "Expected argument seperator \",\" or closing parenthese.");
 			}
+
 			self.advance_token().unwrap_or_else(|| {
-				print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.start, "After function call.");
+				print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.start, "While parsing function call arguments.");
 			});
-			
-			return FunctionCallInfo::new((*function).index, arguments);
 		}
 
-	}
+		self.advance_token().unwrap_or_else(|| {
+			print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.start, "After function call.");
+		});
 
-}
\ No newline at end of file
+		return FunctionCallInfo::new(Callee::Ident(identifier), arguments, token_ident.span);
+	}
+}
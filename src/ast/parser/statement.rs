@@ -1,43 +1,45 @@
 use crate::{ast::*, error::CompileError, lexer::*, print_errln};
-use super::{Parser, variable::*};
+use super::Parser;
 
 impl<'a> Parser<'a>
 {
-	pub fn parse_statement(&mut self, variables: &mut LocalVariables, function: &Function) -> Option<Statement>
+	pub fn parse_statement(&mut self) -> Option<Statement>
 	{
 		match self.current_token().kind {
-			TokenKind::LeftCurly 					=> return Some(Statement::Scope(self.parse_scope(variables, function))),
-			TokenKind::VarDecl 						=> return self.parse_var_decl(variables),
-			TokenKind::If							=> return Some(self.parse_if_stmt(variables, function)),
-			TokenKind::For							=> return Some(self.parse_for_stmt(variables, function)),
-			TokenKind::Return 						=> return Some(self.parse_return_stmt(variables, function)),
+			TokenKind::LeftCurly 					=> return Some(Statement::Scope(self.parse_scope())),
+			TokenKind::VarDecl | TokenKind::Const 	=> return Some(self.parse_var_decl()),
+			TokenKind::If							=> return Some(self.parse_if_stmt()),
+			TokenKind::For							=> return Some(self.parse_for_stmt()),
+			TokenKind::Return 						=> return Some(self.parse_return_stmt()),
 			TokenKind::Semicolon 					=> { self.advance_token(); return None; },
-			TokenKind::Asterisk 	=> return Some(self.parse_var_update(variables)),
+			TokenKind::Asterisk 	=> return Some(self.parse_var_update()),
 			TokenKind::Ident 		=>
 			{
 				if let Some(next_token) = self.peek(1)
 				{
 					if next_token.kind == TokenKind::LeftParen
 					{
-						let stmt = Some(Statement::FunctionCall(self.parse_function_call(variables)));
+						let stmt = Some(Statement::FunctionCall(self.parse_function_call()));
 						if self.current_token().kind != TokenKind::Semicolon
 						{
 							print_errln!(CompileError::Syntax, self.source, self.current_token().span.start, "Expected semicolon.");
-						}	
+						}
 						self.advance_token();
 						return stmt;
 					}
 				}
-				return Some(self.parse_var_update(variables));
+				return Some(self.parse_var_update());
 			},
 
 			_ => { print_errln!(CompileError::Syntax, self.source, self.current_token().span.start, "Unexpected token found at statement beginning."); }
 		}
 	}
 
-	fn parse_var_decl(&mut self, variables: &mut LocalVariables) -> Option<Statement>
+	// A variable declaration (let x i32 = 5;) or a constant declaration (const X i32 = 5;)
+	fn parse_var_decl(&mut self) -> Statement
 	{
 		let stmt_pos = self.current_token().span;
+		let attributes = if self.current_token().kind == TokenKind::Const { attribute::CONSTANT } else { 0 };
 		let token_ident = self.advance_token().unwrap_or_else(|| {
 			print_errln!(CompileError::UnexpectedEof, self.source, stmt_pos.end, "While parsing variable declaration. Expected identifier.");
 		});
@@ -51,45 +53,59 @@ impl<'a> Parser<'a>
 		self.advance_token().unwrap_or_else(|| {
 			print_errln!(CompileError::UnexpectedEof, self.source, token_ident.span.end, "While parsing variable declaration. Expected data type.");
 		});
-		
-		let data_type = self.parse_data_type().unwrap_or_else(|| {
-			print_errln!(CompileError::Syntax, self.source, token_ident.span.end, "Expected data type after variable identifier.");
-		});
 
-		if data_type == Type::new(TypeKind::Void)
+		// The data type can be omitted if there is an initial value, let x = 5;
+		let data_type = if self.current_token().kind == TokenKind::Equal
 		{
-			print_errln!(
-				CompileError::TypeError(Type::new(TypeKind::I32), Type::new(TypeKind::Void)), 
-				self.source, 
-				self.current_token().span.start,
-				"Cannot declare variable of type \"{KEYWORD_VOID}\", it makes no sense."
-			);
-		}
+			Type::new(TypeKind::Void)
+		} else
+		{
+			let data_type = self.parse_data_type().unwrap_or_else(|| {
+				print_errln!(
+					CompileError::Syntax,
+					self.source,
+					token_ident.span.end,
+					"Expected data type after variable identifier. (It can be omitted only if the variable is initialized, {KEYWORD_VAR_DECL} x = 5;)"
+				);
+			});
 
-		// FIXME: Might be able to use the variable inside its declaration expression.
-		let new_var = variables.add_variable(identifier, 0, data_type);
+			if data_type == Type::new(TypeKind::Void)
+			{
+				print_errln!(
+					CompileError::TypeError(Type::new(TypeKind::I32), Type::new(TypeKind::Void)),
+					self.source,
+					token_ident.span.start,
+					"Cannot declare variable of type \"{KEYWORD_VOID}\", it makes no sense."
+				);
+			}
+			data_type
+		};
 
 		let token_assign_or_semi = self.current_token();
 
 		self.advance_token().unwrap_or_else(|| {
 			print_errln!(
-				CompileError::UnexpectedEof, 
-				self.source, 
-				self.current_token().span.start, 
+				CompileError::UnexpectedEof,
+				self.source,
+				self.current_token().span.start,
 				"While parsing variable declaration. Expected semicolon or assign operator ( = )."
 			);
 		});
 
 		if token_assign_or_semi.kind == TokenKind::Semicolon
 		{
-			return None;
+			if attributes & attribute::CONSTANT != 0
+			{
+				print_errln!(CompileError::Syntax, self.source, token_assign_or_semi.span.start, "A constant must have a value.");
+			}
+			return Statement::VarDecl(VarDeclInfo::new(identifier, data_type, None, token_ident.span));
 		} else if token_assign_or_semi.kind != TokenKind::Equal
 		{
 			print_errln!(CompileError::Syntax, self.source, token_assign_or_semi.span.start, "Expected assign operator ( = ) or semicolon ( ; ).");
 		}
 
 		// Will get here is there is an initial assignment to the variable
-		let expr = self.parse_expression(Some(data_type), variables);
+		let expr = self.parse_expression();
 
 		if self.current_token().kind != TokenKind::Semicolon
 		{
@@ -97,23 +113,28 @@ impl<'a> Parser<'a>
 		}
 
 		self.advance_token();
-		return Some(Statement::Assign(VarUpdateInfo::new(
-			Value::Var(new_var.index),
-			expr
-		)));
+		let mut var_decl = VarDeclInfo::new(identifier, data_type, Some(expr), token_ident.span);
+		var_decl.attributes = attributes;
+		return Statement::VarDecl(var_decl);
 	}
 
 
-	fn parse_var_update(&mut self, variables: &mut LocalVariables) -> Statement
+	fn parse_var_update(&mut self) -> Statement
 	{
-		let destination = self.parse_value(None, variables, true).unwrap();
+		let destination_token = self.current_token();
+		let destination = self.parse_value();
+		if !matches!(destination, Some(Value::Ident(_)) | Some(Value::Dereference(_)))
+		{
+			print_errln!(CompileError::Syntax, self.source, destination_token.span.start, "Expected modifiable lvalue.");
+		}
+		let destination = BinExprPart::new(BinExprPartKind::Val(destination.unwrap()), destination_token.span);
 
 		match self.current_token().kind
 		{
 			TokenKind::Equal =>
 			{
 				self.advance_token();
-				let rvalue = self.parse_expression(Some(self.value_type(&destination, &variables)), variables);
+				let rvalue = self.parse_expression();
 				if self.current_token().kind != TokenKind::Semicolon
 				{
 					print_errln!(CompileError::Syntax, self.source, self.current_token().span.start, "Expected semicolon.");
@@ -126,18 +147,17 @@ impl<'a> Parser<'a>
 		}
 	}
 
-	fn parse_return_stmt(&mut self, variables: &mut LocalVariables, function: &Function) -> Statement
+	fn parse_return_stmt(&mut self) -> Statement
 	{
+		let token_return = self.current_token();
 		self.advance_token().unwrap_or_else(|| {
 			print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.start, "While parsing {KEYWORD_RETURN} statement.");
 		});
 
-		let mut stmt = Statement::Return(None);
-
-		if function.return_type != Type::new(TypeKind::Void)
+		let mut value = None;
+		if self.current_token().kind != TokenKind::Semicolon
 		{
-			let expr = self.parse_expression(Some(function.return_type), variables);
-			stmt = Statement::Return(Some(expr));
+			value = Some(self.parse_expression());
 		}
 
 		if self.current_token().kind != TokenKind::Semicolon
@@ -146,23 +166,23 @@ impl<'a> Parser<'a>
 		}
 		self.advance_token();
 
-		return stmt;
+		return Statement::Return(ReturnInfo::new(value, token_return.span));
 	}
 
-	fn parse_if_stmt(&mut self, variables: &mut LocalVariables, function: &Function) -> Statement
+	fn parse_if_stmt(&mut self) -> Statement
 	{
 		self.advance_token().unwrap_or_else(|| {
 			print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.start, "While parsing {KEYWORD_IF} statement.");
 		});
 
-		let expression = self.parse_expression(None, variables);
+		let expression = self.parse_expression();
 
-		let then_statement = self.parse_statement(variables, function).unwrap_or_else(|| {
+		let then_statement = self.parse_statement().unwrap_or_else(|| {
 			print_errln!(
-				CompileError::Syntax, 
-				self.source, 
-				self.current_token().span.start, 
-				"The \"then\" block of an \"{KEYWORD_IF}\" statement cannot be a variable declaration without a value."
+				CompileError::Syntax,
+				self.source,
+				self.current_token().span.start,
+				"The \"then\" block of an \"{KEYWORD_IF}\" statement cannot be empty."
 			);
 		});
 
@@ -174,12 +194,12 @@ impl<'a> Parser<'a>
 				print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.start, "While parsing {KEYWORD_ELSE} statement.");
 			});
 
-			else_statement = Some(self.parse_statement(variables, function).unwrap_or_else(|| {
+			else_statement = Some(self.parse_statement().unwrap_or_else(|| {
 				print_errln!(
-					CompileError::Syntax, 
-					self.source, 
-					self.current_token().span.start, 
-					"The \"{KEYWORD_ELSE}\" of an \"{KEYWORD_IF}\" statement cannot be a variable declaration without a value."
+					CompileError::Syntax,
+					self.source,
+					self.current_token().span.start,
+					"The \"{KEYWORD_ELSE}\" of an \"{KEYWORD_IF}\" statement cannot be empty."
 				);
 			}));
 		}
@@ -187,7 +207,7 @@ impl<'a> Parser<'a>
 		return Statement::If(IfInfo::new(expression, then_statement, else_statement));
 	}
 
-	fn parse_for_stmt(&mut self, variables: &mut LocalVariables, function: &Function) -> Statement
+	fn parse_for_stmt(&mut self) -> Statement
 	{
 		// For loop syntax: 	for let i i32 = 0; i < 420; i += 1;
 		// 							<CODE>
@@ -196,16 +216,11 @@ impl<'a> Parser<'a>
 			print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.start, "While parsing {KEYWORD_FOR} statement.");
 		});
 
-		// Start a scope before parsing initializer for statement (because it is most likely to be a variable declaration)
-		variables.start_scope();
-
-		let initializer = self.parse_statement(variables, function);
+		let initializer = self.parse_statement();
 		let condition;
-		let update;
-		let code_block;
 
 		// Condition parsing
-		if self.current_token().kind == TokenKind::Semicolon 
+		if self.current_token().kind == TokenKind::Semicolon
 		{
 			self.advance_token().unwrap_or_else(|| {
 				print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.start, "While parsing {KEYWORD_FOR} loop condition.");
@@ -213,7 +228,7 @@ impl<'a> Parser<'a>
 			condition = None;
 		} else
 		{
-			condition = Some(self.parse_expression(None, variables));
+			condition = Some(self.parse_expression());
 			if self.current_token().kind != TokenKind::Semicolon
 			{
 				print_errln!(CompileError::Syntax, self.source, self.current_token().span.start, "Expected semicolon after {KEYWORD_FOR} loop condition.");
@@ -222,15 +237,14 @@ impl<'a> Parser<'a>
 				print_errln!(CompileError::UnexpectedEof, self.source, self.current_token().span.start, "While parsing {KEYWORD_FOR} loop condition.");
 			});
 		}
-		
-		update = self.parse_statement(variables, function);
 
-		code_block = self.parse_statement(variables, function).unwrap_or_else(|| {
+		let update = self.parse_statement();
+
+		let code_block = self.parse_statement().unwrap_or_else(|| {
 			print_errln!(CompileError::Syntax, self.source, self.current_token().span.start, "{KEYWORD_FOR} loop code block must be a valid statement.");
 		});
 
-		let stack_size = variables.end_scope();
-		let for_stmt = Statement::For(ForLoopInfo::new(initializer, condition, update, code_block, stack_size));
+		let for_stmt = Statement::For(ForLoopInfo::new(initializer, condition, update, code_block));
 		return for_stmt;
 	}
-}
\ No newline at end of file
+}
@@ -1,300 +1,266 @@
 mod common;
 mod instructions;
-mod register_allocator;
 mod expression;
 mod function;
+mod register_allocator;
+mod peephole;
+mod encoder;
+mod elf;
+mod dialect;
+pub mod aarch64;
+pub mod c;
+pub mod wasm;
 
 use instructions::*;
-use register_allocator::*;
-use super::{ast::*, CompileError, print_err};
+use dialect::Dialect;
+use super::{ast::{attribute, FunctionTypeId, Type, TypeKind, Value}, mir::{self, *}, CompileError, print_err};
 
 const OUT_OBJECT_FILE_PATH: &str = "/tmp/slowc_compiled.obj";
-const OUT_ASM_FILE_PATH: &str = "/tmp/slowc_compiled.asm";
 
+const INTEGER_ARGUMENT_REGISTERS_SYS_V_ABI_X86_64: u8 = 6;		/* rdi, rsi, rdx, rcx, r8, r9 */
+const FLOAT_ARGUMENT_REGISTERS_SYS_V_ABI_X86_64: u8 = 8;		/* xmm0-7 */
+
+// The registers that virtual registers are kept in at -O2. The code generator uses RAX, RCX, RDX, R11, XMM0 and XMM1 as scratch registers,
+// so they are never given to virtual registers. The argument registers are only given to values that are not live while arguments are passed.
+const CALLEE_SAVED_REGISTERS_SYS_V_ABI_X86_64: [Register; 5] = [Register::RBX, Register::R12, Register::R13, Register::R14, Register::R15];
+const INTEGER_CALLER_SAVED_VARIABLE_REGISTERS_SYS_V_ABI_X86_64: [Register; 1] = [Register::R10];
+const INTEGER_ARGUMENT_VARIABLE_REGISTERS_SYS_V_ABI_X86_64: [Register; 4] = [Register::RDI, Register::RSI, Register::R8, Register::R9];
+const FLOAT_CALLER_SAVED_VARIABLE_REGISTERS_SYS_V_ABI_X86_64: [Register; 8] = [
+	Register::XMM8, Register::XMM9, Register::XMM10, Register::XMM11, Register::XMM12, Register::XMM13, Register::XMM14, Register::XMM15
+];
+const FLOAT_ARGUMENT_VARIABLE_REGISTERS_SYS_V_ABI_X86_64: [Register; 6] = [
+	Register::XMM2, Register::XMM3, Register::XMM4, Register::XMM5, Register::XMM6, Register::XMM7
+];
+
+// The machine and the operating system that the code is generated for
+#[derive(Clone, Copy, PartialEq)]
+pub enum Target
+{
+	X86_64Linux,
+	Aarch64Linux,		/* AArch64 assembly, which is always assembled by an assembler (--target=aarch64-linux) */
+	Wasm32,				/* WebAssembly text format, which is not assembled or linked (--target=wasm32) */
+}
+
+// How the object file is made from the generated instructions
+#[derive(Clone, Copy, PartialEq)]
+pub enum Assembler
+{
+	Builtin,		/* The instructions are encoded by slowc, which writes the object file */
+	Nasm,			/* The assembly is written into a file, and nasm assembles it (-S) */
+	Gnu,			/* The assembly is written in the syntax of the GNU assembler, which assembles it (--asm=gnu) */
+}
+
+impl Assembler
+{
+	// The dialect that the assembly is written in, or None if no assembly is written
+	fn dialect(&self) -> Option<&'static dyn Dialect>
+	{
+		return match self
+		{
+			Assembler::Builtin 	=> None,
+			Assembler::Nasm 	=> Some(&dialect::Nasm),
+			Assembler::Gnu 		=> Some(&dialect::Gnu),
+		};
+	}
+}
+
+// Where an argument is passed, according to the calling convenction
+#[derive(Clone, Copy)]
+enum ArgumentClass
+{
+	Register(Register),
+	Stack(usize),		/* The offset from RSP when calling the function */
+}
+
+// Generates x86-64 code from the MIR, as machine code or as assembly (in the NASM or the GNU syntax). Each virtual register has a home on the stack (or in a register, at -O2), and each instruction
+// loads its operands into scratch registers (RAX, RCX, RDX, R11, XMM0, XMM1), computes, and stores the result into its home.
 pub struct CodeGen<'a>
 {
-	ir: &'a Root,
-	registers: [RegisterInfo; ALLOCATABLE_REGS_COUNT],
-	attribute_segment: String,
-	data_segment: String,
-	text_segment: String,
+	program: &'a mir::Program,
+	optimization_level: u8,
+	assembler: Assembler,
+	attribute_segment: Vec<AttributeItem>,
+	data_segment: Vec<DataItem>,
+	text_segment: Vec<TextItem>,		/* Written as assembly when the program is done, after the peephole optimizer */
 
 	data_seg_var_index: usize,
 	text_seg_var_index: usize,
+
+	register_homes: Vec<isize>,			/* The offset from RBP of the home of each virtual register in the current function */
+	register_locations: Vec<Option<Register>>,	/* The register that holds each virtual register for the whole function, if it has one */
+	saved_registers: Vec<(Register, isize)>,	/* The callee-saved registers that the current function uses, and the offset from RBP where they are saved */
+	slot_locations: Vec<isize>,			/* The offset from RBP of each stack slot in the current function */
+	block_lables: Vec<Lable>,
+	function_epilogue: Option<Lable>,	/* Return terminators jump to this lable */
 }
 
 impl<'a> CodeGen<'a>
 {
 
-	pub fn new(ir: &'a Root) -> Self
+	pub fn new(program: &'a mir::Program, optimization_level: u8, assembler: Assembler) -> Self
 	{
-		let attribute_segment = String::from("bits 64");
-		let data_segment = String::from("\nsegment .data");
-		let text_segment = String::from("\nsegment .text");
-
 		return Self {
-			ir,
-			registers: Self::reg_alloc_init(),
-			attribute_segment,
-			data_segment,
-			text_segment,
+			program,
+			optimization_level,
+			assembler,
+			attribute_segment: Vec::new(),
+			data_segment: Vec::new(),
+			text_segment: Vec::new(),
 			data_seg_var_index: 0,
 			text_seg_var_index: 0,
+			register_homes: Vec::new(),
+			register_locations: Vec::new(),
+			saved_registers: Vec::new(),
+			slot_locations: Vec::new(),
+			block_lables: Vec::new(),
+			function_epilogue: None,
 		};
 	}
-	
+
 	pub fn generate<'b>(mut self) -> &'b str
 	{
-		for function in &self.ir.functions
+		let program = self.program;
+		for function in &program.functions
 		{
-			self.gen_function(&function);
+			self.gen_function(function);
 		}
 
-		if cfg!(debug_assertions)
+		match self.assembler.dialect()
 		{
-			self.reg_alloc_check_leaks();
+			Some(dialect) 	=> self.assemble(dialect),
+			None 			=> self.write_object_file(),
 		}
+		return OUT_OBJECT_FILE_PATH;
+	}
 
-		let mut final_asm = String::with_capacity(self.attribute_segment.len() + self.data_segment.len() + self.text_segment.len() + 1);
-		final_asm.push_str(&self.attribute_segment);
-		final_asm.push_str(&self.data_segment);
-		final_asm.push_str(&self.text_segment);
-		final_asm.push('\n');
-
-		std::fs::write(OUT_ASM_FILE_PATH, final_asm).unwrap_or_else(|err| {
-			print_err!(CompileError::FileWriteError(OUT_ASM_FILE_PATH), "Could not write to temporary assembly file. {err}");
+	// Encodes the instructions, and writes them into an ELF64 relocatable object file with the data segment
+	fn write_object_file(&self)
+	{
+		let code = encoder::encode(&self.text_segment, &self.data_segment);
+		let global_functions: Vec<&str> = self.program.functions.iter()
+			.filter(|function| function.attributes & attribute::GLOBAL != 0)
+			.map(|function| function.identifier.as_str())
+			.collect();
+		let object = elf::ObjectFile::new(code, &global_functions);
+
+		std::fs::write(OUT_OBJECT_FILE_PATH, object.to_bytes()).unwrap_or_else(|err| {
+			print_err!(CompileError::FileWriteError(OUT_OBJECT_FILE_PATH), "Could not write the object file. {err}");
 		});
-
-		std::process::Command::new("nasm")
-			.args(["-f", "elf64"])
-			.arg("-g")
-			.args(["-o", OUT_OBJECT_FILE_PATH])
-			.arg(OUT_ASM_FILE_PATH)
-			.spawn()
-			.expect("Dev error! failed to execute nasm.");
-
-		return &OUT_OBJECT_FILE_PATH;
 	}
 
-	
-	fn gen_scope(&mut self, scope: &Scope, locals: &Vec<Variable>)
+	// Writes the assembly in the dialect, and assembles it with the assembler of the dialect
+	fn assemble(&self, dialect: &dyn Dialect)
 	{
-		if scope.stack_size != 0
+		let mut final_asm = String::from(dialect.header());
+		for item in &self.attribute_segment
 		{
-			self.instr_sub(
-				&Placeholder::new(PlaceholderKind::Reg(Register::RSP), Type::new(TypeKind::U64)), 
-				&Placeholder::new(PlaceholderKind::Integer(scope.stack_size as u64), Type::new(TypeKind::U64))
-			);
+			final_asm.push_str(&dialect.attribute(item));
 		}
-
-		for statement in &scope.statements
+		final_asm.push_str(dialect.data_section());
+		for item in &self.data_segment
 		{
-			self.gen_statement(&statement, locals);
+			final_asm.push_str(&dialect.data_item(item));
 		}
-
-		if scope.stack_size != 0
+		final_asm.push_str(dialect.text_section());
+		for item in &self.text_segment
 		{
-			self.instr_add(
-				&Placeholder::new(PlaceholderKind::Reg(Register::RSP), Type::new(TypeKind::U64)), 
-				&Placeholder::new(PlaceholderKind::Integer(scope.stack_size as u64), Type::new(TypeKind::U64))
-			);
+			final_asm.push_str(&dialect.text_item(item));
 		}
-	}
+		final_asm.push_str(dialect.footer());
 
-	fn gen_statement(&mut self, statement: &Statement, locals: &Vec<Variable>)
-	{
-		match statement
-		{
-			Statement::Scope(scope)									=> self.gen_scope(scope, locals),
-			Statement::Assign(assign_data) 					=> self.gen_assign_stmt(assign_data, locals),
-			Statement::FunctionCall(function_call_info) 	=> { self.gen_function_call(locals, function_call_info); } 
-			Statement::Return(expression) 				=> self.gen_return_stmt(locals, expression),
-			Statement::If(if_info)									=> self.gen_if_stmt(locals, if_info),
-			Statement::For(for_info) 							=> self.gen_for_stmt(locals, for_info),
-		}
-	}
-
-	fn gen_assign_stmt(&mut self, assign_data: &VarUpdateInfo, locals: &Vec<Variable>)
-	{
-		let mut expression = self.gen_expression(&assign_data.value, locals);
-		let mut allocated_reg = None;
-		if expression.is_register()
-		{
-			allocated_reg = Some(self.reg_alloc_allocate(expression.data_type).unwrap());
-
-			let expr_placeholder = Placeholder::new(
-				PlaceholderKind::Reg(allocated_reg.unwrap()), 
-				expression.data_type
-			);
-			self.instr_mov(&expr_placeholder, &expression);
-			expression = expr_placeholder;
-		}
-
-		let destination = self.gen_value_access(locals, &assign_data.destination);
-
-		self.instr_mov(&destination, &expression);
+		let asm_file_path = dialect.asm_file_path();
+		std::fs::write(asm_file_path, final_asm).unwrap_or_else(|err| {
+			print_err!(CompileError::FileWriteError(asm_file_path), "Could not write to temporary assembly file. {err}");
+		});
 
-		if let Some(register) = allocated_reg
-		{
-			self.reg_alloc_free(register);
-		}
+		dialect.assembler_command()
+			.status()
+			.expect("Dev error! failed to execute the assembler.");
 	}
 
-	fn gen_return_stmt(&mut self, locals: &Vec<Variable>, expression: &Option<BinExpr>)
+	fn gen_block(&mut self, function: &mir::Function, index: usize)
 	{
-		let expr;
-		if let Some(exp) = expression
-		{
-			expr = exp;
-		} else
+		let block = &function.blocks[index];
+		self.write_lable(self.block_lables[index]);
+		let is_tail_call = self.is_tail_call(function, block);
+		for (i, instruction) in block.instructions.iter().enumerate()
 		{
-			self.gen_function_return();
-			return;
-		}
-
-		let expr_placeholder = self.gen_expression(expr, locals);
-		let return_register = Register::default_for_type(expr_placeholder.data_type);
+			if cfg!(debug_assertions)
+			{
+				self.text_segment.push(TextItem::Comment(self.program.instruction_text(function, instruction)));
+			}
 
-		// I hate Rust
-		if let PlaceholderKind::Reg(reg) = expr_placeholder.kind
-		{
-			if reg != return_register 
+			// The call replaces the return, so the terminator is not generated
+			if is_tail_call && i + 1 == block.instructions.len()
 			{
-				self.instr_mov(
-					&Placeholder::new(PlaceholderKind::Reg(return_register), expr_placeholder.data_type), 
-					&expr_placeholder
-				);
+				self.gen_tail_call(function, instruction);
+				return;
 			}
-		} else 
-		{
-			self.instr_mov(
-				&Placeholder::new(PlaceholderKind::Reg(return_register), expr_placeholder.data_type), 
-				&expr_placeholder
-			);
+			self.gen_instruction(function, instruction);
 		}
-
-		self.gen_function_return();
+		self.gen_terminator(function, index, &block.terminator);
 	}
 
-	fn gen_if_stmt(&mut self, locals: &Vec<Variable>, if_info: &IfInfo)
+	fn gen_terminator(&mut self, function: &mir::Function, index: usize, terminator: &Terminator)
 	{
-		let false_lable = self.generate_text_seg_lable();
-		let end_else = if let Some(_) = if_info.else_block { Some(self.generate_text_seg_lable()) } else { None };
-
-		#[cfg(debug_assertions)]
-		self.write_text_segment(&format!("\n\t; Start if statement {}, expression:", false_lable.index));
-
-		let expression = self.gen_expression(&if_info.condition, locals).of_type(Type::new(TypeKind::U8));
-
-		self.instr_test(&expression, &expression);
-		self.instr_jz(false_lable);
-
-		#[cfg(debug_assertions)]
-		self.write_text_segment(&format!("\n\t; If statement {}, then:", false_lable.index));
-
-		self.gen_statement(&if_info.then_block, locals);
-
-		if let Some(_) = if_info.else_block
-		{
-			self.instr_jmp(end_else.unwrap());
-
-			#[cfg(debug_assertions)]
-			self.write_lable_text_seg(&format!(" \t; Skip else block of if statement {}", false_lable.index));
-		}
-
-		self.write_lable(false_lable);
-		
-		if let Some(statement) = &if_info.else_block
+		// The blocks are written in order, so jumping into the next block is not needed
+		let next_block = BlockId(index as u32 + 1);
+		match *terminator
 		{
-			#[cfg(debug_assertions)]
-			self.write_text_segment(&format!(" \t; Else block of if statement {}", false_lable.index));
-
-			self.gen_statement(statement, locals);
-			
-			self.write_lable(end_else.unwrap());
+			Terminator::Jump(target) =>
+			{
+				if target != next_block
+				{
+					self.instr_jmp(self.block_lables[target.index()]);
+				}
+			},
+			Terminator::Branch { condition: Operand::Integer(value), then_block, else_block } =>
+			{
+				let target = if value != 0 { then_block } else { else_block };
+				self.gen_terminator(function, index, &Terminator::Jump(target));
+			},
+			Terminator::Branch { condition, then_block, else_block } =>
+			{
+				let condition = self.operand_source(function, condition, Type::new(TypeKind::U8));
+				self.instr_cmp(&condition, &Placeholder::new(PlaceholderKind::Integer(0), condition.data_type));
+				if then_block == next_block
+				{
+					self.instr_jz(self.block_lables[else_block.index()]);
+					return;
+				}
+
+				self.instr_jnz(self.block_lables[then_block.index()]);
+				if else_block != next_block
+				{
+					self.instr_jmp(self.block_lables[else_block.index()]);
+				}
+			},
+			Terminator::Return(value) =>
+			{
+				if let Some(value) = value
+				{
+					self.gen_return_value(function, value);
+				}
+
+				// The epilogue is right after the last block
+				if index + 1 != function.blocks.len()
+				{
+					self.instr_jmp(self.function_epilogue.unwrap());
+				}
+			},
 		}
-
-		#[cfg(debug_assertions)]
-		self.write_text_segment(&format!(" \t; End if statement {}", false_lable.index));
 	}
 
-	fn gen_for_stmt(&mut self, locals: &Vec<Variable>, for_info: &ForLoopInfo)
+	// Integers smaller than 32 bits are extended into 32 bits, as C compilers expect.
+	fn gen_return_value(&mut self, function: &mir::Function, value: Operand)
 	{
-		let loop_start = self.generate_text_seg_lable();
-		let condition_lable = self.generate_text_seg_lable();
-
-		let mut stack_size = for_info.stack_size;
-		if let Statement::Scope(scope) = &*for_info.code_block 	/* Borrow the dereference, sure */
-		{
-			stack_size += scope.stack_size;
-		}
-
-		if stack_size != 0
-		{
-			self.instr_sub(
-				&Placeholder::new(PlaceholderKind::Reg(Register::RSP), Type::new(TypeKind::U64)), 
-				&Placeholder::new(PlaceholderKind::Integer(stack_size as u64), Type::new(TypeKind::U64)),
-			);
-		}
-
-		if let Some(initializer_stmt) = &for_info.initializer
+		let return_type = function.return_type;
+		if return_type.is_integer()
 		{
-			#[cfg(debug_assertions)]
-			self.write_text_segment(&format!("\n\t; For loop initializer statement"));
-
-			self.gen_statement(initializer_stmt, locals);
-		}
-
-		if let Some(_) = for_info.condition
-		{
-			self.instr_jmp(condition_lable);
-		}
-		
-		self.write_lable(loop_start);
-
-		#[cfg(debug_assertions)]
-		self.write_text_segment(&format!("\t; For loop body"));
-
-		if let Statement::Scope(scope) = &*for_info.code_block 	/* Borrow the dereference, sure */
-		{
-			for statement in &scope.statements
-			{
-				self.gen_statement(statement, locals);
-			}
+			self.load_extended(function, Register::RAX, value, return_type, return_type.size().max(OP_DWORD));
 		} else
 		{
-			self.gen_statement(&for_info.code_block, locals);
-		}
-
-		if let Some(update_stmt) = &for_info.update
-		{
-			#[cfg(debug_assertions)]
-			self.write_text_segment(&format!("\n\t; For loop update statement"));
-
-			self.gen_statement(update_stmt, locals);
-		}
-
-		self.write_lable(condition_lable);
-
-		if let Some(condition_expr) = &for_info.condition
-		{
-			#[cfg(debug_assertions)]
-			self.write_text_segment(&format!("\t; For loop condition expression"));
-
-			let result = self.gen_expression(condition_expr, locals);
-			self.instr_test(&result, &result);
-			self.instr_jnz(loop_start);
-		} else
-		{
-			self.instr_jmp(loop_start);
-		}
-
-		if stack_size != 0
-		{
-			self.instr_add(
-				&Placeholder::new(PlaceholderKind::Reg(Register::RSP), Type::new(TypeKind::U64)), 
-				&Placeholder::new(PlaceholderKind::Integer(stack_size as u64), Type::new(TypeKind::U64)),
-			);		
+			self.load_operand(function, Register::XMM0, value, return_type);
 		}
 	}
-}
\ No newline at end of file
+}
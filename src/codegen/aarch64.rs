@@ -0,0 +1,393 @@
+mod instruction;
+mod call;
+
+use super::{attribute, print_err, CompileError, Type, TypeKind, OUT_OBJECT_FILE_PATH};
+use crate::mir::{self, *};
+
+const OUT_ASM_FILE_PATH: &str = "/tmp/slowc_compiled.s";
+
+// The assemblers that are tried in order, with their arguments. On an AArch64 machine the system assembler is used, and elsewhere a cross assembler.
+#[cfg(target_arch = "aarch64")]
+const ASSEMBLERS: [(&str, &[&str]); 1] = [("as", &[])];
+#[cfg(not(target_arch = "aarch64"))]
+const ASSEMBLERS: [(&str, &[&str]); 2] = [("aarch64-linux-gnu-as", &[]), ("llvm-mc", &["--triple=aarch64-linux-gnu", "--filetype=obj"])];
+
+const INTEGER_ARGUMENT_REGISTERS_AAPCS64: u8 = 8;		/* x0-x7 */
+const FLOAT_ARGUMENT_REGISTERS_AAPCS64: u8 = 8;			/* v0-v7 */
+
+// The scratch registers, which are never used for passing arguments. X16 holds addresses, and X17 the function pointer of indirect calls.
+const SCRATCH_REGISTERS: [u8; 3] = [9, 10, 11];
+const FLOAT_SCRATCH_REGISTERS: [u8; 2] = [16, 17];
+const ADDRESS_REGISTER: u8 = 16;
+const FUNCTION_POINTER_REGISTER: u8 = 17;
+
+// The frame record (the saved X29 and X30) is right above the frame, and the parameters that were passed on the stack are right above it
+const FRAME_RECORD_SIZE: usize = 16;
+
+// Where an argument is passed, according to AAPCS64
+#[derive(Clone, Copy)]
+enum ArgumentClass
+{
+	Register(u8),			/* The index of X0-X7 for integers, or of V0-V7 for floats */
+	Stack(usize),			/* The offset from SP when calling the function */
+}
+
+// Generates AArch64 assembly (in the GNU syntax) from the MIR, and assembles it. Every function follows AAPCS64, the calling convenction of the target.
+// Each virtual register has a home in the frame, and each instruction loads its operands into scratch registers (X9-X11, V16, V17),
+// computes, and stores the result into its home. Integers are kept in their registers extended into 64 bits (sign extended if they are signed),
+// so the homes are loaded with the extending loads and stored with the truncating stores of their size.
+pub struct CodeGen<'a>
+{
+	program: &'a mir::Program,
+	optimization_level: u8,
+	attribute_segment: String,
+	text_segment: String,
+
+	function_index: usize,
+	register_homes: Vec<usize>,			/* The offset from SP of the home of each virtual register in the current function */
+	slot_locations: Vec<usize>,			/* The offset from SP of each stack slot in the current function */
+	frame_size: usize,
+}
+
+impl<'a> CodeGen<'a>
+{
+	pub fn new(program: &'a mir::Program, optimization_level: u8) -> Self
+	{
+		return Self {
+			program,
+			optimization_level,
+			attribute_segment: String::new(),
+			text_segment: String::new(),
+			function_index: 0,
+			register_homes: Vec::new(),
+			slot_locations: Vec::new(),
+			frame_size: 0,
+		};
+	}
+
+	pub fn generate<'b>(mut self) -> &'b str
+	{
+		let program = self.program;
+		for (index, function) in program.functions.iter().enumerate()
+		{
+			self.function_index = index;
+			self.gen_function(function);
+		}
+
+		let mut final_asm = String::from(&self.attribute_segment);
+		final_asm.push_str("\n.text");
+		final_asm.push_str(&self.text_segment);
+		final_asm.push_str("\n.section .note.GNU-stack,\"\",%progbits\n");
+		std::fs::write(OUT_ASM_FILE_PATH, final_asm).unwrap_or_else(|err| {
+			print_err!(CompileError::FileWriteError(OUT_ASM_FILE_PATH), "Could not write to temporary assembly file. {err}");
+		});
+
+		Self::assemble();
+		return OUT_OBJECT_FILE_PATH;
+	}
+
+	fn assemble()
+	{
+		for (assembler, arguments) in ASSEMBLERS
+		{
+			let status = std::process::Command::new(assembler)
+				.args(arguments)
+				.args(["-o", OUT_OBJECT_FILE_PATH, OUT_ASM_FILE_PATH])
+				.status();
+			if status.is_ok()
+			{
+				return;
+			}
+		}
+		let assemblers: Vec<&str> = ASSEMBLERS.iter().map(|(assembler, _)| *assembler).collect();
+		print_err!(CompileError::Usage, "An AArch64 assembler is required for --target=aarch64-linux ({}).", assemblers.join(" or "));
+	}
+
+	fn gen_function(&mut self, function: &mir::Function)
+	{
+		if function.attributes & attribute::GLOBAL != 0
+		{
+			self.attribute_segment.push_str(&format!("\n.globl {}", function.identifier));
+		}
+		if function.is_extern()
+		{
+			return;
+		}
+
+		self.layout_frame(function);
+		self.text_segment.push_str(&format!("\n.p2align 2\n.type {0}, %function\n{0}:", function.identifier));
+
+		// Save the frame record, and make the frame
+		self.instr("stp x29, x30, [sp, #-16]!");
+		self.instr("mov x29, sp");
+		if self.frame_size != 0
+		{
+			self.adjust_sp("sub", self.frame_size);
+		}
+		self.store_parameters(function);
+
+		for index in 0..function.blocks.len()
+		{
+			self.gen_block(function, index);
+		}
+
+		self.write_lable(&self.epilogue_lable());
+		self.restore_frame();
+		self.instr("ret");
+		self.text_segment.push_str(&format!("\n.size {0}, .-{0}", function.identifier));
+	}
+
+	// Restores the stack frame of the caller, before returning (or jumping into a tail call)
+	fn restore_frame(&mut self)
+	{
+		self.instr("mov sp, x29");
+		self.instr("ldp x29, x30, [sp], #16");
+	}
+
+	// The frame is laid out from SP upwards: the stack arguments of the calls, the stack slots (each aligned on its size),
+	// and the homes of the virtual registers (each in 8 bytes). Its size is aligned on 16 bytes.
+	fn layout_frame(&mut self, function: &mir::Function)
+	{
+		let mut position = 0;
+		for instruction in function.blocks.iter().flat_map(|block| &block.instructions)
+		{
+			if let Instruction::Call { argument_types, .. } = instruction
+			{
+				position = position.max(Self::stack_arguments_size(argument_types));
+			}
+		}
+
+		self.slot_locations = function.slots.iter().map(|slot| {
+			let size = (slot.data_type.size() as usize).max(1);
+			position = position.next_multiple_of(size);
+			position += size;
+			position - size
+		}).collect();
+
+		position = position.next_multiple_of(8);
+		self.register_homes = (0..function.registers.len()).map(|index| position + index * 8).collect();
+		position += function.registers.len() * 8;
+		self.frame_size = position.next_multiple_of(16);
+	}
+
+	fn gen_block(&mut self, function: &mir::Function, index: usize)
+	{
+		let block = &function.blocks[index];
+		self.write_lable(&self.block_lable(BlockId(index as u32)));
+		let is_tail_call = self.is_tail_call(function, block);
+		for (i, instruction) in block.instructions.iter().enumerate()
+		{
+			if cfg!(debug_assertions)
+			{
+				self.text_segment.push_str(&format!("\n\t// {}", self.program.instruction_text(function, instruction)));
+			}
+
+			// The call replaces the return, so the terminator is not generated
+			if is_tail_call && i + 1 == block.instructions.len()
+			{
+				self.gen_tail_call(function, instruction);
+				return;
+			}
+			self.gen_instruction(function, instruction);
+		}
+		self.gen_terminator(function, index, &block.terminator);
+	}
+
+	fn gen_terminator(&mut self, function: &mir::Function, index: usize, terminator: &Terminator)
+	{
+		// The blocks are written in order, so jumping into the next block is not needed
+		let next_block = BlockId(index as u32 + 1);
+		match *terminator
+		{
+			Terminator::Jump(target) =>
+			{
+				if target != next_block
+				{
+					self.instr(&format!("b {}", self.block_lable(target)));
+				}
+			},
+			Terminator::Branch { condition: Operand::Integer(value), then_block, else_block } =>
+			{
+				let target = if value != 0 { then_block } else { else_block };
+				self.gen_terminator(function, index, &Terminator::Jump(target));
+			},
+			Terminator::Branch { condition, then_block, else_block } =>
+			{
+				let condition = self.load_operand(function, SCRATCH_REGISTERS[0], condition, Type::new(TypeKind::U8));
+				if then_block == next_block
+				{
+					self.instr(&format!("cbz {condition}, {}", self.block_lable(else_block)));
+					return;
+				}
+
+				self.instr(&format!("cbnz {condition}, {}", self.block_lable(then_block)));
+				if else_block != next_block
+				{
+					self.instr(&format!("b {}", self.block_lable(else_block)));
+				}
+			},
+			Terminator::Return(value) =>
+			{
+				if let Some(value) = value
+				{
+					self.load_operand(function, 0, value, function.return_type);
+				}
+
+				// The epilogue is right after the last block
+				if index + 1 != function.blocks.len()
+				{
+					self.instr(&format!("b {}", self.epilogue_lable()));
+				}
+			},
+		}
+	}
+
+	fn instr(&mut self, instruction: &str)
+	{
+		self.text_segment.push_str("\n\t");
+		self.text_segment.push_str(instruction);
+	}
+
+	fn write_lable(&mut self, lable: &str)
+	{
+		self.text_segment.push_str(&format!("\n{lable}:"));
+	}
+
+	// The lables start with .L, so they are not written into the symbol table
+	fn block_lable(&self, block: BlockId) -> String
+	{
+		return format!(".L{}_{}", self.function_index, block.index());
+	}
+
+	fn epilogue_lable(&self) -> String
+	{
+		return format!(".L{}_return", self.function_index);
+	}
+
+	// The name of the register with the given index, for a value of the data type. Integers of up to 32 bits are in W registers.
+	fn register_name(index: u8, data_type: Type) -> String
+	{
+		return match data_type.kind
+		{
+			TypeKind::F32 	=> format!("s{index}"),
+			TypeKind::F64 	=> format!("d{index}"),
+			_ if data_type.size() <= 4 => format!("w{index}"),
+			_ 				=> format!("x{index}"),
+		};
+	}
+
+	// Moves a 64 bit constant into the X register, 16 bits at a time
+	fn mov_immediate(&mut self, index: u8, value: u64)
+	{
+		self.instr(&format!("movz x{index}, #{:#x}", value & 0xFFFF));
+		for shift in [16, 32, 48]
+		{
+			let part = (value >> shift) & 0xFFFF;
+			if part != 0
+			{
+				self.instr(&format!("movk x{index}, #{part:#x}, lsl #{shift}"));
+			}
+		}
+	}
+
+	// Adds to SP or subtracts from it (with "add" or "sub"). Immediates have only 12 bits (and can be shifted by 12 bits).
+	fn adjust_sp(&mut self, operation: &str, amount: usize)
+	{
+		if amount < 1 << 12
+		{
+			self.instr(&format!("{operation} sp, sp, #{amount}"));
+		} else
+		{
+			self.mov_immediate(ADDRESS_REGISTER, amount as u64);
+			self.instr(&format!("{operation} sp, sp, x{ADDRESS_REGISTER}"));
+		}
+	}
+
+	// The address of the memory at the offset from the register (SP or X29), for an access of the size. The offset of a load or a store
+	// is an unsigned 12 bit multiple of the size, so farther offsets are added into X16 first.
+	fn frame_address(&mut self, base: &str, offset: usize, size: u8) -> String
+	{
+		let size = size.max(1) as usize;
+		if offset.is_multiple_of(size) && offset / size < 1 << 12
+		{
+			return format!("[{base}, #{offset}]");
+		}
+		self.mov_immediate(ADDRESS_REGISTER, offset as u64);
+		self.instr(&format!("add x{ADDRESS_REGISTER}, {base}, x{ADDRESS_REGISTER}"));
+		return format!("[x{ADDRESS_REGISTER}]");
+	}
+
+	// Loads the memory into the register, extended into 64 bits by the signedness of the data type. Returns the name of the register.
+	fn load(&mut self, index: u8, address: &str, data_type: Type) -> String
+	{
+		let register = Self::register_name(index, data_type);
+		let instruction = match (data_type.size(), data_type.is_integer() && data_type.is_signed())
+		{
+			_ if !data_type.is_integer() 	=> format!("ldr {register}, {address}"),
+			(1, true) 						=> format!("ldrsb x{index}, {address}"),
+			(2, true) 						=> format!("ldrsh x{index}, {address}"),
+			(4, true) 						=> format!("ldrsw x{index}, {address}"),
+			(1, false) 						=> format!("ldrb w{index}, {address}"),
+			(2, false) 						=> format!("ldrh w{index}, {address}"),
+			_ 								=> format!("ldr {register}, {address}"),
+		};
+		self.instr(&instruction);
+		return register;
+	}
+
+	// Stores the low bits of the register into the memory
+	fn store(&mut self, index: u8, address: &str, data_type: Type)
+	{
+		let register = Self::register_name(index, data_type);
+		let instruction = match data_type.size()
+		{
+			1 if data_type.is_integer() => format!("strb {register}, {address}"),
+			2 if data_type.is_integer() => format!("strh {register}, {address}"),
+			_ 							=> format!("str {register}, {address}"),
+		};
+		self.instr(&instruction);
+	}
+
+	// Loads the operand into the register (an integer register, or a vector register for floats). Returns the name of the register for the data type.
+	fn load_operand(&mut self, function: &mir::Function, index: u8, operand: Operand, data_type: Type) -> String
+	{
+		return match operand
+		{
+			Operand::Register(register) =>
+			{
+				let home_type = function.register_type(register);
+				let address = self.frame_address("sp", self.register_homes[register.index()], home_type.size());
+				self.load(index, &address, home_type);
+				Self::register_name(index, data_type)
+			},
+			Operand::Integer(bits) =>
+			{
+				self.mov_immediate(index, mir::integer_value(bits, data_type) as u64);
+				Self::register_name(index, data_type)
+			},
+			Operand::Float(value) =>
+			{
+				// The bits are moved through X16, which is not used for holding values
+				let register = Self::register_name(index, data_type);
+				if data_type.kind == TypeKind::F32
+				{
+					self.mov_immediate(ADDRESS_REGISTER, (value as f32).to_bits() as u64);
+					self.instr(&format!("fmov {register}, w{ADDRESS_REGISTER}"));
+				} else
+				{
+					self.mov_immediate(ADDRESS_REGISTER, value.to_bits());
+					self.instr(&format!("fmov {register}, x{ADDRESS_REGISTER}"));
+				}
+				register
+			},
+		};
+	}
+
+	// Stores the register into the home of the virtual register
+	fn store_register(&mut self, function: &mir::Function, index: u8, register: RegisterId)
+	{
+		let data_type = function.register_type(register);
+		let address = self.frame_address("sp", self.register_homes[register.index()], data_type.size());
+		self.store(index, &address, data_type);
+	}
+}
@@ -0,0 +1,158 @@
+use super::*;
+
+impl<'a> CodeGen<'a>
+{
+	// Classify the arguments. Integers and pointers are passed in X0-X7 and floats in V0-V7, and the arguments that dont fit are passed
+	// on the stack, each in an 8 byte slot, where the first one is at [sp] when calling the function. Variadic arguments are passed the same way.
+	fn classify_arguments_aapcs64(argument_types: &[Type]) -> Vec<ArgumentClass>
+	{
+		let mut argument_classes = Vec::with_capacity(argument_types.len());
+		let mut integer_arguments: u8 = 0;
+		let mut float_arguments: u8 = 0;
+		let mut stack_arguments: usize = 0;
+		for data_type in argument_types
+		{
+			if data_type.is_integer() && integer_arguments < INTEGER_ARGUMENT_REGISTERS_AAPCS64
+			{
+				argument_classes.push(ArgumentClass::Register(integer_arguments));
+				integer_arguments += 1;
+			} else if !data_type.is_integer() && float_arguments < FLOAT_ARGUMENT_REGISTERS_AAPCS64
+			{
+				argument_classes.push(ArgumentClass::Register(float_arguments));
+				float_arguments += 1;
+			} else
+			{
+				argument_classes.push(ArgumentClass::Stack(stack_arguments * 8));
+				stack_arguments += 1;
+			}
+		}
+		return argument_classes;
+	}
+
+	// The size of the arguments that are passed on the stack, aligned on 16 bytes as the stack must be
+	pub(super) fn stack_arguments_size(argument_types: &[Type]) -> usize
+	{
+		let stack_arguments = Self::classify_arguments_aapcs64(argument_types).iter().filter(|class| matches!(class, ArgumentClass::Stack(_))).count();
+		return (stack_arguments * 8).next_multiple_of(16);
+	}
+
+	// Stores the parameters into their homes. The parameters that were passed on the stack are above the frame record.
+	pub(super) fn store_parameters(&mut self, function: &mir::Function)
+	{
+		let signature = &self.program.function_types[function.signature.index()];
+		for (i, class) in Self::classify_arguments_aapcs64(&signature.parameters).iter().enumerate()
+		{
+			let register = function.parameters[i];
+			let data_type = function.register_type(register);
+			match *class
+			{
+				ArgumentClass::Register(index) => self.store_register(function, index, register),
+				ArgumentClass::Stack(offset) =>
+				{
+					let index = if data_type.is_integer() { SCRATCH_REGISTERS[0] } else { FLOAT_SCRATCH_REGISTERS[0] };
+					let address = self.frame_address("x29", FRAME_RECORD_SIZE + offset, data_type.size());
+					self.load(index, &address, data_type);
+					self.store_register(function, index, register);
+				},
+			}
+		}
+	}
+
+	// Whether the block ends with a call whose result is returned (return f(...)), which is then a jump into the called function (at -O1 and above,
+	// and for the recursive calls of tailrec functions at every level). The address of a stack slot must not be taken, and the arguments that
+	// are passed on the stack must fit where the parameters of this function were passed.
+	pub(super) fn is_tail_call(&self, function: &mir::Function, block: &Block) -> bool
+	{
+		let Some(Instruction::Call { destination, target, argument_types, .. }) = block.instructions.last() else
+		{
+			return false;
+		};
+		let returns_result = match block.terminator
+		{
+			Terminator::Return(Some(Operand::Register(value))) 	=> *destination == Some(value),
+			Terminator::Return(None) 							=> destination.is_none(),
+			_ 													=> false,
+		};
+		if !returns_result
+		{
+			return false;
+		}
+
+		let is_tail_recursive = function.attributes & attribute::TAIL_RECURSIVE != 0;
+		let is_recursive = matches!(target, CallTarget::Direct(callee) if self.program.functions[callee.index()].identifier == function.identifier);
+		if self.optimization_level == mir::OPTIMIZATION_LEVEL_NONE && !(is_tail_recursive && is_recursive)
+		{
+			return false;
+		}
+
+		let address_taken = function.blocks.iter().flat_map(|block| &block.instructions).any(|instruction| matches!(instruction, Instruction::SlotAddress { .. }));
+		let parameters = &self.program.function_types[function.signature.index()].parameters;
+		let fits = Self::stack_arguments_size(argument_types) <= Self::stack_arguments_size(parameters);
+		return (is_tail_recursive || !address_taken) && fits;
+	}
+
+	pub(super) fn gen_tail_call(&mut self, function: &mir::Function, call: &Instruction)
+	{
+		let Instruction::Call { target, arguments, argument_types, .. } = call else
+		{
+			panic!("Dev error! gen_tail_call called on an instruction that is not a call.");
+		};
+		self.gen_call(function, *target, arguments, argument_types, true);
+	}
+
+	// The arguments are already evaluated into virtual registers, so each one is loaded straight into its register or stack slot.
+	// The stack arguments of a tail call are written over the stack parameters of this function (which are already in their homes),
+	// and the call jumps into the function after restoring the frame of the caller, so the called function returns into the caller of this function.
+	pub(super) fn gen_call(&mut self, function: &mir::Function, target: CallTarget, arguments: &[Operand], argument_types: &[Type], is_tail_call: bool)
+	{
+		let argument_classes = Self::classify_arguments_aapcs64(argument_types);
+
+		// The stack arguments are written first, because writing them uses the scratch registers
+		for (i, argument) in arguments.iter().enumerate()
+		{
+			let data_type = argument_types[i];
+			if let ArgumentClass::Stack(offset) = argument_classes[i]
+			{
+				let index = if data_type.is_integer() { SCRATCH_REGISTERS[0] } else { FLOAT_SCRATCH_REGISTERS[0] };
+				self.load_operand(function, index, *argument, data_type);
+
+				// Integers are extended into the whole 8 byte slot
+				let slot_type = if data_type.is_integer() { Type::new(TypeKind::U64) } else { data_type };
+				let address = if is_tail_call
+				{
+					self.frame_address("x29", FRAME_RECORD_SIZE + offset, slot_type.size())
+				} else
+				{
+					self.frame_address("sp", offset, slot_type.size())
+				};
+				self.store(index, &address, slot_type);
+			}
+		}
+
+		for (i, argument) in arguments.iter().enumerate()
+		{
+			if let ArgumentClass::Register(index) = argument_classes[i]
+			{
+				self.load_operand(function, index, *argument, argument_types[i]);
+			}
+		}
+
+		if let CallTarget::Indirect(pointer) = target
+		{
+			self.load_operand(function, FUNCTION_POINTER_REGISTER, pointer, Type::new(TypeKind::U64));
+		}
+
+		if is_tail_call
+		{
+			self.restore_frame();
+		}
+		let instruction = match (target, is_tail_call)
+		{
+			(CallTarget::Direct(index), false) 	=> format!("bl {}", self.program.functions[index.index()].identifier),
+			(CallTarget::Direct(index), true) 	=> format!("b {}", self.program.functions[index.index()].identifier),
+			(CallTarget::Indirect(_), false) 	=> format!("blr x{FUNCTION_POINTER_REGISTER}"),
+			(CallTarget::Indirect(_), true) 	=> format!("br x{FUNCTION_POINTER_REGISTER}"),
+		};
+		self.instr(&instruction);
+	}
+}
@@ -0,0 +1,238 @@
+use super::*;
+
+impl<'a> CodeGen<'a>
+{
+	pub(super) fn gen_instruction(&mut self, function: &mir::Function, instruction: &Instruction)
+	{
+		let [first, second, _] = SCRATCH_REGISTERS;
+		match instruction
+		{
+			Instruction::Copy { destination, source } =>
+			{
+				let data_type = function.register_type(*destination);
+				let index = if data_type.is_integer() { first } else { FLOAT_SCRATCH_REGISTERS[0] };
+				self.load_operand(function, index, *source, data_type);
+				self.store_register(function, index, *destination);
+			},
+			Instruction::Binary { operator, destination, lhs, rhs } => self.gen_binary(function, *operator, *destination, *lhs, *rhs),
+			Instruction::Unary { operator, destination, source } =>
+			{
+				let data_type = function.register_type(*destination);
+				let source = self.load_operand(function, first, *source, data_type);
+				match operator
+				{
+					UnaryOperator::Not => self.instr(&format!("mvn {source}, {source}")),
+					UnaryOperator::IsZero =>
+					{
+						self.instr(&format!("cmp {source}, #0"));
+						self.instr(&format!("cset w{first}, eq"));
+					},
+				}
+				self.store_register(function, first, *destination);
+			},
+			Instruction::Compare { condition, data_type, destination, lhs, rhs } =>
+			{
+				// Integers are extended into 64 bits, so they are compared as 64 bit integers
+				if data_type.is_integer()
+				{
+					self.load_operand(function, first, *lhs, *data_type);
+					self.load_operand(function, second, *rhs, *data_type);
+					self.instr(&format!("cmp x{first}, x{second}"));
+				} else
+				{
+					let [lhs_index, rhs_index] = FLOAT_SCRATCH_REGISTERS;
+					let lhs = self.load_operand(function, lhs_index, *lhs, *data_type);
+					let rhs = self.load_operand(function, rhs_index, *rhs, *data_type);
+					self.instr(&format!("fcmp {lhs}, {rhs}"));
+				}
+				self.instr(&format!("cset w{first}, {}", Self::condition_code(*condition, *data_type)));
+				self.store_register(function, first, *destination);
+			},
+			Instruction::Convert { destination, source, from } => self.gen_convert(function, *destination, *source, *from),
+			Instruction::Load { destination, address } =>
+			{
+				let data_type = function.register_type(*destination);
+				let index = if data_type.is_integer() { first } else { FLOAT_SCRATCH_REGISTERS[0] };
+				let address = self.gen_address(function, *address, data_type);
+				self.load(index, &address, data_type);
+				self.store_register(function, index, *destination);
+			},
+			Instruction::Store { data_type, address, value } =>
+			{
+				let index = if data_type.is_integer() { first } else { FLOAT_SCRATCH_REGISTERS[0] };
+				self.load_operand(function, index, *value, *data_type);
+				let address = self.gen_address(function, *address, *data_type);
+				self.store(index, &address, *data_type);
+			},
+			Instruction::SlotAddress { destination, slot } =>
+			{
+				let offset = self.slot_locations[slot.index()];
+				if offset < 1 << 12
+				{
+					self.instr(&format!("add x{first}, sp, #{offset}"));
+				} else
+				{
+					self.mov_immediate(first, offset as u64);
+					self.instr(&format!("add x{first}, sp, x{first}"));
+				}
+				self.store_register(function, first, *destination);
+			},
+			Instruction::FunctionAddress { destination, function: index } =>
+			{
+				// The address of an external function is taken from the global offset table, which works in position independent executables too
+				let callee = &self.program.functions[index.index()];
+				let identifier = &callee.identifier;
+				if callee.is_extern()
+				{
+					self.instr(&format!("adrp x{first}, :got:{identifier}"));
+					self.instr(&format!("ldr x{first}, [x{first}, :got_lo12:{identifier}]"));
+				} else
+				{
+					self.instr(&format!("adrp x{first}, {identifier}"));
+					self.instr(&format!("add x{first}, x{first}, :lo12:{identifier}"));
+				}
+				self.store_register(function, first, *destination);
+			},
+			Instruction::Call { destination, target, arguments, argument_types, .. } =>
+			{
+				self.gen_call(function, *target, arguments, argument_types, false);
+				if let Some(destination) = destination
+				{
+					self.store_register(function, 0, *destination);
+				}
+			},
+		}
+	}
+
+	fn gen_binary(&mut self, function: &mir::Function, operator: BinaryOperator, destination: RegisterId, lhs: Operand, rhs: Operand)
+	{
+		let data_type = function.register_type(destination);
+		if !data_type.is_integer()
+		{
+			let [lhs_index, rhs_index] = FLOAT_SCRATCH_REGISTERS;
+			let result = self.load_operand(function, lhs_index, lhs, data_type);
+			let rhs = self.load_operand(function, rhs_index, rhs, data_type);
+			let mnemonic = match operator
+			{
+				BinaryOperator::Add => "fadd",
+				BinaryOperator::Sub => "fsub",
+				BinaryOperator::Mul => "fmul",
+				BinaryOperator::Div => "fdiv",
+				_ => panic!("Dev error! The operator {:?} was used on {data_type}.", operator),
+			};
+			self.instr(&format!("{mnemonic} {result}, {result}, {rhs}"));
+			self.store_register(function, lhs_index, destination);
+			return;
+		}
+
+		let [first, second, third] = SCRATCH_REGISTERS;
+		let result = self.load_operand(function, first, lhs, data_type);
+		let rhs = self.load_operand(function, second, rhs, data_type);
+		let is_signed = data_type.is_signed();
+		match operator
+		{
+			BinaryOperator::Add => self.instr(&format!("add {result}, {result}, {rhs}")),
+			BinaryOperator::Sub => self.instr(&format!("sub {result}, {result}, {rhs}")),
+			BinaryOperator::Mul => self.instr(&format!("mul {result}, {result}, {rhs}")),
+			BinaryOperator::Div => self.instr(&format!("{} {result}, {result}, {rhs}", if is_signed { "sdiv" } else { "udiv" })),
+			BinaryOperator::Modulo =>
+			{
+				// The remainder is the dividend minus the quotient times the divisor
+				let quotient = Self::register_name(third, data_type);
+				self.instr(&format!("{} {quotient}, {result}, {rhs}", if is_signed { "sdiv" } else { "udiv" }));
+				self.instr(&format!("msub {result}, {quotient}, {rhs}, {result}"));
+			},
+			BinaryOperator::And => self.instr(&format!("and {result}, {result}, {rhs}")),
+			BinaryOperator::Or 	=> self.instr(&format!("orr {result}, {result}, {rhs}")),
+			BinaryOperator::Xor => self.instr(&format!("eor {result}, {result}, {rhs}")),
+			BinaryOperator::ShiftLeft => self.instr(&format!("lsl {result}, {result}, {rhs}")),
+			BinaryOperator::ShiftRight =>
+			{
+				// The shift is logical, so the sign extension of small signed integers is cleared first, and done again on the result
+				if is_signed && data_type.size() < 4
+				{
+					self.instr(&format!("and {result}, {result}, #{:#x}", (1u32 << (data_type.size() * 8)) - 1));
+					self.instr(&format!("lsr {result}, {result}, {rhs}"));
+					self.instr(&format!("{} {result}, {result}", if data_type.size() == 1 { "sxtb" } else { "sxth" }));
+				} else
+				{
+					self.instr(&format!("lsr {result}, {result}, {rhs}"));
+				}
+			},
+		}
+		self.store_register(function, first, destination);
+	}
+
+	fn gen_convert(&mut self, function: &mir::Function, destination: RegisterId, source: Operand, from: Type)
+	{
+		let into = function.register_type(destination);
+		let [first, ..] = SCRATCH_REGISTERS;
+		let [float, other_float] = FLOAT_SCRATCH_REGISTERS;
+
+		// The integer is extended into 64 bits when it is loaded, and the store takes as many bits as the destination has
+		if from.is_integer() && into.is_integer()
+		{
+			self.load_operand(function, first, source, from);
+			self.store_register(function, first, destination);
+		} else if from.is_integer()
+		{
+			self.load_operand(function, first, source, from);
+			let result = Self::register_name(float, into);
+			self.instr(&format!("{} {result}, x{first}", if from.is_signed() { "scvtf" } else { "ucvtf" }));
+			self.store_register(function, float, destination);
+		} else if into.is_integer()
+		{
+			let value = self.load_operand(function, float, source, from);
+			self.instr(&format!("{} x{first}, {value}", if into.is_signed() { "fcvtzs" } else { "fcvtzu" }));
+			self.store_register(function, first, destination);
+		} else
+		{
+			let value = self.load_operand(function, float, source, from);
+			let result = Self::register_name(other_float, into);
+			if from == into
+			{
+				self.instr(&format!("fmov {result}, {value}"));
+			} else
+			{
+				self.instr(&format!("fcvt {result}, {value}"));
+			}
+			self.store_register(function, other_float, destination);
+		}
+	}
+
+	// The memory operand of the address of a load or a store. The pointer in a virtual register is loaded into X16.
+	fn gen_address(&mut self, function: &mir::Function, address: Address, data_type: Type) -> String
+	{
+		return match address
+		{
+			Address::Slot(slot) => self.frame_address("sp", self.slot_locations[slot.index()], data_type.size()),
+			Address::Register(register) =>
+			{
+				self.load_operand(function, ADDRESS_REGISTER, Operand::Register(register), Type::new(TypeKind::U64));
+				format!("[x{ADDRESS_REGISTER}]")
+			},
+		};
+	}
+
+	// Floats are compared with fcmp, which sets the flags so "mi" and "ls" are false when a float is NaN
+	fn condition_code(condition: Condition, data_type: Type) -> &'static str
+	{
+		let signed = data_type.is_signed() && data_type.is_integer();
+		let float = !data_type.is_integer();
+		return match condition
+		{
+			Condition::Equal 									=> "eq",
+			Condition::NotEqual 								=> "ne",
+			Condition::Greater if signed || float 				=> "gt",
+			Condition::Greater 									=> "hi",
+			Condition::Less if float 							=> "mi",
+			Condition::Less if signed 							=> "lt",
+			Condition::Less 									=> "lo",
+			Condition::GreaterEqual if signed || float 			=> "ge",
+			Condition::GreaterEqual 							=> "hs",
+			Condition::LessEqual if float 						=> "ls",
+			Condition::LessEqual if signed 						=> "le",
+			Condition::LessEqual 								=> "ls",
+		};
+	}
+}
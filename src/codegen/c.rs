@@ -0,0 +1,652 @@
+use super::{attribute, print_err, CompileError, FunctionTypeId, Type, TypeKind, Value};
+use crate::ast::{self, *};
+
+const OUT_C_FILE_PATH: &str = "/tmp/slowc_compiled.c";
+
+// The keywords of C99, which cannot be used as identifiers in the C source
+const C_KEYWORDS: [&str; 37] = [
+	"auto", "break", "case", "char", "const", "continue", "default", "do", "double", "else", "enum", "extern", "float", "for", "goto", "if",
+	"inline", "int", "long", "register", "restrict", "return", "short", "signed", "sizeof", "static", "struct", "switch", "typedef", "union",
+	"unsigned", "void", "volatile", "while", "_Bool", "_Complex", "_Imaginary",
+];
+
+// Translates the AST (after the semantic analysis) into a single C99 translation unit (--emit=c), which only needs stdint.h.
+// The C source behaves like the native code: integer arithmetic wraps (it is done on unsigned integers), >> is a logical shift,
+// shift counts are masked like the processor does, && and || evaluate both sides, and the operands are evaluated from left to right
+// (an operand that is evaluated before a function call is stored into a temporary, because the order is unspecified in C).
+// The one difference is the division of the smallest i32 or i64 by -1, which traps in the native code and wraps in the C source.
+pub struct CodeGen<'a>
+{
+	root: &'a Root,
+	declarations: String,			/* The function pointer types and the declarations of all the functions */
+	helpers: String,				/* The functions that the C source uses for signed division */
+	definitions: String,
+
+	variable_names: Vec<String>,	/* The C identifier of each variable of the current function, indexed by VariableId */
+	temporaries: usize,
+	indentation: usize,
+}
+
+impl<'a> CodeGen<'a>
+{
+	pub fn new(root: &'a Root) -> Self
+	{
+		return Self {
+			root,
+			declarations: String::new(),
+			helpers: String::new(),
+			definitions: String::new(),
+			variable_names: Vec::new(),
+			temporaries: 0,
+			indentation: 0,
+		};
+	}
+
+	pub fn generate(mut self)
+	{
+		// Function pointer types can have function pointer parameters, so each type is declared after the types it uses
+		let mut declared = vec![false; self.root.function_types.len()];
+		for index in 0..self.root.function_types.len()
+		{
+			self.declare_function_type(FunctionTypeId(index as u32), &mut declared);
+		}
+		if !self.root.function_types.is_empty()
+		{
+			self.declarations.push('\n');
+		}
+
+		let root = self.root;
+		for function in &root.functions
+		{
+			let declaration = format!("{}{};\n", Self::linkage(function), self.signature(function, false));
+			self.declarations.push_str(&declaration);
+		}
+		for function in root.functions.iter().filter(|function| function.attributes & attribute::EXTERN == 0)
+		{
+			self.gen_function(function);
+		}
+
+		let mut source = String::from("/* Generated by slowc */\n#include <stdint.h>\n\n");
+		source.push_str("/* Slow pointers are 8 bytes, the offsets of pointer arithmetic are counted with that size */\n");
+		source.push_str("typedef char slow_pointer_size_check[sizeof(void *) == 8 ? 1 : -1];\n\n");
+		source.push_str(&self.declarations);
+		source.push_str(&self.helpers);
+		source.push_str(&self.definitions);
+		std::fs::write(OUT_C_FILE_PATH, source).unwrap_or_else(|err| {
+			print_err!(CompileError::FileWriteError(OUT_C_FILE_PATH), "Could not write the C source file. {err}");
+		});
+	}
+
+	fn declare_function_type(&mut self, index: FunctionTypeId, declared: &mut Vec<bool>)
+	{
+		if declared[index.index()]
+		{
+			return;
+		}
+		declared[index.index()] = true;
+
+		let function_type = &self.root.function_types[index.index()];
+		for data_type in function_type.parameters.iter().chain([&function_type.return_type])
+		{
+			let base = data_type.dereference(data_type.pointer_level());
+			if base.is_function()
+			{
+				self.declare_function_type(base.signature, declared);
+			}
+		}
+
+		let parameters: Vec<String> = function_type.parameters.iter().map(|parameter| Self::c_type(*parameter)).collect();
+		let parameters = Self::parameter_list(parameters, function_type.attributes);
+		let declaration = format!("typedef {} (*{})({parameters});\n", Self::c_type(function_type.return_type), Self::function_type_name(index));
+		self.declarations.push_str(&declaration);
+	}
+
+	fn gen_function(&mut self, function: &ast::Function)
+	{
+		self.variable_names = vec![String::new(); function.locals.len()];
+		self.temporaries = 0;
+		for parameter in &function.parameters
+		{
+			self.variable_names[parameter.index.index()] = Self::variable_name(parameter);
+		}
+
+		let header = format!("\n{}{}\n{{\n", Self::linkage(function), self.signature(function, true));
+		self.definitions.push_str(&header);
+		self.indentation = 1;
+		for statement in &function.code_block.statements
+		{
+			self.gen_statement(statement);
+		}
+
+		// Reaching the end of a function that returns a value returns 0, like in the native code
+		let returns = matches!(function.code_block.statements.last(), Some(Statement::Return(_)));
+		if function.return_type.kind != TypeKind::Void && !returns
+		{
+			self.line("return 0;");
+		}
+		self.definitions.push_str("}\n");
+	}
+
+	fn gen_statement(&mut self, statement: &Statement)
+	{
+		match statement
+		{
+			Statement::Scope(scope) =>
+			{
+				self.line("{");
+				self.gen_scope(scope);
+				self.line("}");
+			},
+			Statement::VarDecl(var_decl) =>
+			{
+				// Constants were replaced by their values in the semantic analysis
+				if var_decl.attributes & attribute::CONSTANT != 0
+				{
+					return;
+				}
+
+				let name = Self::variable_name(var_decl);
+				let declaration = match &var_decl.value
+				{
+					Some(value) => format!("{} {name} = {};", Self::c_type(var_decl.data_type), self.gen_expression(&value.root)),
+					None 		=> format!("{} {name};", Self::c_type(var_decl.data_type)),
+				};
+				self.variable_names[var_decl.index.index()] = name;
+				self.line(&declaration);
+			},
+			Statement::Assign(assign) =>
+			{
+				// The value is evaluated before the destination. A variable is only written after the value was evaluated anyway.
+				let mut value = self.gen_expression(&assign.value.root);
+				let writes_variable = matches!(assign.destination.kind, BinExprPartKind::Val(Value::Var(_)));
+				if !writes_variable && !Self::is_constant(&assign.value.root) && (Self::has_call(&assign.value.root) || Self::has_call(&assign.destination))
+				{
+					value = self.temporary(assign.value.root.data_type, value);
+				}
+				let destination = self.gen_expression(&assign.destination);
+				self.line(&format!("{destination} = {value};"));
+			},
+			Statement::FunctionCall(function_call) =>
+			{
+				let call = self.gen_call(function_call);
+				self.line(&format!("{call};"));
+			},
+			Statement::Return(return_info) =>
+			{
+				match &return_info.value
+				{
+					Some(value) =>
+					{
+						let value = self.gen_expression(&value.root);
+						self.line(&format!("return {value};"));
+					},
+					None => self.line("return;"),
+				}
+			},
+			Statement::If(if_info) =>
+			{
+				let condition = self.gen_expression(&if_info.condition.root);
+				self.line(&format!("if ({condition})"));
+				self.gen_body(&if_info.then_block);
+				if let Some(else_block) = &if_info.else_block
+				{
+					self.line("else");
+					self.gen_body(else_block);
+				}
+			},
+			Statement::For(for_info) => self.gen_for(for_info),
+		}
+	}
+
+	// The loop is in a scope of its own, because the variables of the initializer belong to the loop.
+	// A condition that calls functions is evaluated at the start of each iteration, after the temporaries it needs.
+	fn gen_for(&mut self, for_info: &ForLoopInfo)
+	{
+		self.line("{");
+		self.indentation += 1;
+		if let Some(initializer) = &for_info.initializer
+		{
+			self.gen_statement(initializer);
+		}
+
+		let condition = for_info.condition.as_ref().filter(|condition| Self::has_call(&condition.root));
+		match &for_info.condition
+		{
+			Some(condition) if !Self::has_call(&condition.root) =>
+			{
+				let condition = self.gen_expression(&condition.root);
+				self.line(&format!("while ({condition})"));
+			},
+			_ => self.line("for (;;)"),
+		}
+		self.line("{");
+		self.indentation += 1;
+		if let Some(condition) = condition
+		{
+			let condition = self.gen_expression(&condition.root);
+			self.line(&format!("if (!({condition})) break;"));
+		}
+		match &*for_info.code_block
+		{
+			Statement::Scope(scope) => scope.statements.iter().for_each(|statement| self.gen_statement(statement)),
+			statement 				=> self.gen_statement(statement),
+		}
+		if let Some(update) = &for_info.update
+		{
+			self.gen_statement(update);
+		}
+		self.indentation -= 1;
+		self.line("}");
+		self.indentation -= 1;
+		self.line("}");
+	}
+
+	// The body of an if or an else, which is always written in braces
+	fn gen_body(&mut self, statement: &Statement)
+	{
+		self.line("{");
+		match statement
+		{
+			Statement::Scope(scope) => self.gen_scope(scope),
+			_ =>
+			{
+				self.indentation += 1;
+				self.gen_statement(statement);
+				self.indentation -= 1;
+			},
+		}
+		self.line("}");
+	}
+
+	fn gen_scope(&mut self, scope: &Scope)
+	{
+		self.indentation += 1;
+		for statement in &scope.statements
+		{
+			self.gen_statement(statement);
+		}
+		self.indentation -= 1;
+	}
+
+	fn gen_expression(&mut self, part: &BinExprPart) -> String
+	{
+		let data_type = Self::c_type(part.data_type);
+		return match &part.kind
+		{
+			BinExprPartKind::Val(value) => self.gen_value(value, part.data_type),
+			BinExprPartKind::SelfOperation(operation) =>
+			{
+				let operand = self.gen_expression(&operation.expression);
+				match operation.operator
+				{
+					BinExprOperator::AddressOf 	=> format!("(&{operand})"),
+					BinExprOperator::BitwiseNot => format!("(({data_type})~{operand})"),
+					BinExprOperator::BoolNot 	=> format!("(({data_type})({operand} == 0))"),
+					_ => panic!("Dev error! A self operation with a two-side operator. {:#?}", operation),
+				}
+			},
+			BinExprPartKind::Operation(operation) => self.gen_operation(operation, part.data_type),
+			BinExprPartKind::TypeCast(info) =>
+			{
+				// Pointers are converted through 64 bit integers, so any data type can be converted into a pointer and back
+				let expression = self.gen_expression(&info.expression);
+				let from = info.expression.data_type;
+				let into = info.into_type;
+				if from.is_pointer() || from.is_function() || into.is_pointer() || into.is_function()
+				{
+					format!("(({data_type})(uint64_t){expression})")
+				} else
+				{
+					format!("(({data_type}){expression})")
+				}
+			},
+		};
+	}
+
+	fn gen_operation(&mut self, operation: &BinExprOperation, data_type: Type) -> String
+	{
+		let operand_type = operation.lhs.data_type;
+		let [lhs, rhs]: [String; 2] = self.gen_operands(&[&operation.lhs, &operation.rhs]).try_into().unwrap();
+		let result_type = Self::c_type(data_type);
+
+		// Integers are computed as unsigned integers of at least 32 bits, which wrap instead of overflowing
+		let unsigned = if operand_type.size() == 8 { "uint64_t" } else { "uint32_t" };
+		let shift_mask = if operand_type.size() == 8 { 63 } else { 31 };
+		let symbol = match operation.operator
+		{
+			BinExprOperator::BoolEq 		=> "==",
+			BinExprOperator::BoolNotEq 		=> "!=",
+			BinExprOperator::BoolGreater 	=> ">",
+			BinExprOperator::BoolLess 		=> "<",
+			BinExprOperator::BoolGreaterEq 	=> ">=",
+			BinExprOperator::BoolLessEq 	=> "<=",
+			BinExprOperator::Add 			=> "+",
+			BinExprOperator::Sub 			=> "-",
+			BinExprOperator::Mul 			=> "*",
+			BinExprOperator::Div 			=> "/",
+			BinExprOperator::Modulo 		=> "%",
+			BinExprOperator::BitwiseAnd | BinExprOperator::BoolAnd 	=> "&",
+			BinExprOperator::BitwiseOr | BinExprOperator::BoolOr 	=> "|",
+			BinExprOperator::BitwiseXor 	=> "^",
+			BinExprOperator::BitwiseLeftShift 	=> "<<",
+			BinExprOperator::BitwiseRightShift 	=> ">>",
+			_ => panic!("Dev error! An operation with a self operator. {:#?}", operation),
+		};
+
+		return match operation.operator
+		{
+			operator if operator.is_boolean() && operator != BinExprOperator::BoolAnd && operator != BinExprOperator::BoolOr =>
+			{
+				format!("((uint8_t)({lhs} {symbol} {rhs}))")
+			},
+
+			// && and || are done on whether each side of a float is true, and on the bits of integers
+			BinExprOperator::BoolAnd | BinExprOperator::BoolOr if !operand_type.is_integer() =>
+			{
+				format!("(({result_type})(({lhs} != 0) {symbol} ({rhs} != 0)))")
+			},
+			BinExprOperator::Add | BinExprOperator::Sub | BinExprOperator::Mul if operand_type.is_integer() =>
+			{
+				let lhs = Self::unsigned_operand(&operation.lhs, lhs, unsigned);
+				let rhs = Self::unsigned_operand(&operation.rhs, rhs, unsigned);
+				format!("(({result_type})({lhs} {symbol} {rhs}))")
+			},
+			BinExprOperator::BitwiseLeftShift => format!("(({result_type})(({unsigned}){lhs} << ({rhs} & {shift_mask})))"),
+
+			// >> is a logical shift, so a signed integer is shifted as the unsigned integer of its size
+			BinExprOperator::BitwiseRightShift =>
+			{
+				let bits = operand_type.size() as u32 * 8;
+				format!("(({result_type})((uint{bits}_t){lhs} >> ({rhs} & {shift_mask})))")
+			},
+			BinExprOperator::Div | BinExprOperator::Modulo if operand_type.is_integer() && operand_type.is_signed() && operand_type.size() >= 4 =>
+			{
+				let helper = self.division_helper(operation.operator, operand_type);
+				format!("{helper}({lhs}, {rhs})")
+			},
+			_ if operand_type.is_integer() 	=> format!("(({result_type})({lhs} {symbol} {rhs}))"),
+			_ 								=> format!("({lhs} {symbol} {rhs})"),
+		};
+	}
+
+	// The smallest i32 or i64 divided by -1 does not fit in its type, which is undefined in C. The division is done by a function that
+	// wraps it around instead (the remainder is 0). Smaller integers are promoted to int, so their division does not overflow.
+	fn division_helper(&mut self, operator: BinExprOperator, data_type: Type) -> String
+	{
+		let bits = data_type.size() as u32 * 8;
+		let (name, result) = if operator == BinExprOperator::Div
+		{
+			(format!("slow_div_i{bits}"), format!("(int{bits}_t)((uint{bits}_t)0 - (uint{bits}_t)lhs)"))
+		} else
+		{
+			(format!("slow_mod_i{bits}"), String::from("0"))
+		};
+		let symbol = if operator == BinExprOperator::Div { "/" } else { "%" };
+		let definition = format!(
+			"\nstatic int{bits}_t {name}(int{bits}_t lhs, int{bits}_t rhs)\n{{\n\treturn rhs == -1 ? {result} : lhs {symbol} rhs;\n}}\n"
+		);
+		if !self.helpers.contains(&definition)
+		{
+			self.helpers.push_str(&definition);
+		}
+		return name;
+	}
+
+	// The low bits of a sum, a difference or a product depend only on the low bits of the operands, so an operand that is itself
+	// such an operation is used as the unsigned integer it was computed in. (a + b + c is not converted back after a + b)
+	fn unsigned_operand(part: &BinExprPart, operand: String, unsigned: &str) -> String
+	{
+		let is_wrapping = matches!(
+			&part.kind,
+			BinExprPartKind::Operation(operation) if matches!(operation.operator, BinExprOperator::Add | BinExprOperator::Sub | BinExprOperator::Mul)
+		);
+		let cast = format!("(({})", Self::c_type(part.data_type));
+		if is_wrapping && part.data_type.is_integer() && operand.starts_with(&cast)
+		{
+			return operand[cast.len()..operand.len() - 1].to_string();
+		}
+		return format!("({unsigned}){operand}");
+	}
+
+	// Slow evaluates the operands from left to right. If one of them calls a function, every operand but the last one is stored
+	// into a temporary first, so the function calls and the reads of the variables happen in the same order as in the native code.
+	fn gen_operands(&mut self, parts: &[&BinExprPart]) -> Vec<String>
+	{
+		let calls = parts.iter().any(|part| Self::has_call(part));
+		let mut operands = Vec::with_capacity(parts.len());
+		for (i, part) in parts.iter().enumerate()
+		{
+			let operand = self.gen_expression(part);
+			if calls && i + 1 < parts.len() && !Self::is_constant(part)
+			{
+				operands.push(self.temporary(part.data_type, operand));
+			} else
+			{
+				operands.push(operand);
+			}
+		}
+		return operands;
+	}
+
+	fn gen_value(&mut self, value: &Value, data_type: Type) -> String
+	{
+		if let Some(literal) = Self::literal(value, data_type)
+		{
+			return literal;
+		}
+
+		return match value
+		{
+			Value::Var(index) => self.variable_names[index.index()].clone(),
+			Value::Dereference(info) =>
+			{
+				let mut expression = self.gen_expression(&info.expression.root);
+				for _ in 0..info.dereference_count
+				{
+					expression = format!("(*{expression})");
+				}
+				expression
+			},
+			Value::FuncCall(function_call) => self.gen_call(function_call),
+			Value::FuncAddress(index) => format!("(&{})", Self::function_name(&self.root.functions[index.index()])),
+			_ => panic!("Dev error! gen_value() called with a value that was not resolved by the semantic analysis. {:#?}", value),
+		};
+	}
+
+	// The arguments are evaluated from left to right, and the function pointer (if there is one) after them
+	fn gen_call(&mut self, function_call: &FunctionCallInfo) -> String
+	{
+		let mut parts: Vec<&BinExprPart> = function_call.arguments.iter().map(|argument| &argument.root).collect();
+		if let Callee::Indirect(pointer) = &function_call.callee
+		{
+			parts.push(&pointer.root);
+		}
+		let mut operands = self.gen_operands(&parts);
+
+		let callee = match &function_call.callee
+		{
+			Callee::Direct(index) 	=> Self::function_name(&self.root.functions[index.index()]),
+			Callee::Indirect(_) 	=> operands.pop().unwrap(),
+			Callee::Ident(_) 		=> panic!("Dev error! The called function was not resolved by the semantic analysis."),
+		};
+		return format!("{callee}({})", operands.join(", "));
+	}
+
+	// Numbers are written so that C gives them their Slow data type, and the smallest values are written with the stdint.h macros
+	fn literal(value: &Value, data_type: Type) -> Option<String>
+	{
+		if (data_type.is_pointer() || data_type.is_function()) && value.is_constant()
+		{
+			return Some(format!("(({})0)", Self::c_type(data_type)));
+		}
+
+		return Some(match *value
+		{
+			Value::I8(i8::MIN) 		=> String::from("INT8_MIN"),
+			Value::I16(i16::MIN) 	=> String::from("INT16_MIN"),
+			Value::I32(i32::MIN) 	=> String::from("INT32_MIN"),
+			Value::I64(i64::MIN) 	=> String::from("INT64_MIN"),
+			Value::I8(number) 		=> format!("((int8_t){number})"),
+			Value::U8(number) 		=> format!("((uint8_t){number})"),
+			Value::I16(number) 		=> format!("((int16_t){number})"),
+			Value::U16(number) 		=> format!("((uint16_t){number})"),
+			Value::I32(number) 		=> if number < 0 { format!("({number})") } else { number.to_string() },
+			Value::U32(number) 		=> format!("{number}u"),
+			Value::I64(number) 		=> format!("INT64_C({number})"),
+			Value::U64(number) 		=> format!("UINT64_C({number})"),
+			Value::F32(number) 		=> format!("((float){})", Self::float_literal(number as f64)),
+			Value::F64(number) 		=> Self::float_literal(number),
+			_ 						=> return None,
+		});
+	}
+
+	// Rust prints the shortest digits that convert back into the same float, with a dot or an exponent
+	fn float_literal(number: f64) -> String
+	{
+		if number.is_nan()
+		{
+			return String::from("(0.0 / 0.0)");
+		}
+		if number.is_infinite()
+		{
+			return String::from(if number > 0.0 { "(1.0 / 0.0)" } else { "(-1.0 / 0.0)" });
+		}
+		return format!("({number:?})");
+	}
+
+	// Stores the value of an expression into a new variable, and returns the name of the variable
+	fn temporary(&mut self, data_type: Type, expression: String) -> String
+	{
+		let name = format!("_{}", self.temporaries);
+		self.temporaries += 1;
+		self.line(&format!("{} {name} = {expression};", Self::c_type(data_type)));
+		return name;
+	}
+
+	// Whether the expression part calls a function
+	fn has_call(part: &BinExprPart) -> bool
+	{
+		return match &part.kind
+		{
+			BinExprPartKind::Val(Value::FuncCall(_)) 		=> true,
+			BinExprPartKind::Val(Value::Dereference(info)) 	=> Self::has_call(&info.expression.root),
+			BinExprPartKind::Val(_) 						=> false,
+			BinExprPartKind::SelfOperation(operation) 		=> Self::has_call(&operation.expression),
+			BinExprPartKind::Operation(operation) 			=> Self::has_call(&operation.lhs) || Self::has_call(&operation.rhs),
+			BinExprPartKind::TypeCast(info) 				=> Self::has_call(&info.expression),
+		};
+	}
+
+	// Whether the value of the expression part does not depend on when it is evaluated
+	fn is_constant(part: &BinExprPart) -> bool
+	{
+		return matches!(&part.kind, BinExprPartKind::Val(value) if value.is_constant() || matches!(value, Value::FuncAddress(_)));
+	}
+
+	// The declaration of a function, with the names of the parameters if it is the definition
+	fn signature(&self, function: &ast::Function, with_names: bool) -> String
+	{
+		let parameters: Vec<String> = function.parameters.iter().map(|parameter| {
+			if with_names
+			{
+				return format!("{} {}", Self::c_type(parameter.data_type), self.variable_names[parameter.index.index()]);
+			}
+			return Self::c_type(parameter.data_type);
+		}).collect();
+		let parameters = Self::parameter_list(parameters, function.attributes);
+		return format!("{} {}({parameters})", Self::c_type(function.return_type), Self::function_name(function));
+	}
+
+	fn parameter_list(mut parameters: Vec<String>, attributes: attribute::AttributeType) -> String
+	{
+		// C needs a parameter before the ..., a variadic function without parameters is declared without a prototype
+		if attributes & attribute::VARIADIC != 0
+		{
+			if parameters.is_empty()
+			{
+				return String::new();
+			}
+			parameters.push(String::from("..."));
+		}
+
+		if parameters.is_empty()
+		{
+			return String::from("void");
+		}
+		return parameters.join(", ");
+	}
+
+	// Functions that are not global are local to the translation unit, like their symbols in the object file
+	fn linkage(function: &ast::Function) -> &'static str
+	{
+		if function.attributes & attribute::EXTERN != 0
+		{
+			return "extern ";
+		}
+		if function.attributes & attribute::GLOBAL != 0
+		{
+			return "";
+		}
+		if function.attributes & attribute::INLINE != 0
+		{
+			return "static inline ";
+		}
+		return "static ";
+	}
+
+	fn c_type(data_type: Type) -> String
+	{
+		if let Some(points_to) = data_type.points_to
+		{
+			return format!("{}*", Self::c_type(*points_to));
+		}
+
+		return String::from(match data_type.kind
+		{
+			TypeKind::Void 		=> "void",
+			TypeKind::I8 		=> "int8_t",
+			TypeKind::U8 		=> "uint8_t",
+			TypeKind::I16 		=> "int16_t",
+			TypeKind::U16 		=> "uint16_t",
+			TypeKind::I32 		=> "int32_t",
+			TypeKind::U32 		=> "uint32_t",
+			TypeKind::I64 		=> "int64_t",
+			TypeKind::U64 		=> "uint64_t",
+			TypeKind::F32 		=> "float",
+			TypeKind::F64 		=> "double",
+			TypeKind::Function 	=> return Self::function_type_name(data_type.signature),
+			TypeKind::Pointer 	=> panic!("Dev error! A pointer data type without the data type it points to."),
+		});
+	}
+
+	fn function_type_name(index: FunctionTypeId) -> String
+	{
+		return format!("slow_function_type{}", index.index());
+	}
+
+	// Variables get the index of the variable after their identifier, because C does not allow redeclaring a variable in the same scope,
+	// and a variable must not hide a function that has the same identifier
+	fn variable_name(variable: &VarDeclInfo) -> String
+	{
+		return format!("{}_{}", variable.identifier, variable.index.index());
+	}
+
+	fn function_name(function: &ast::Function) -> String
+	{
+		if C_KEYWORDS.contains(&function.identifier.as_str())
+		{
+			return format!("{}_", function.identifier);
+		}
+		return function.identifier.clone();
+	}
+
+	fn line(&mut self, line: &str)
+	{
+		for _ in 0..self.indentation
+		{
+			self.definitions.push('\t');
+		}
+		self.definitions.push_str(line);
+		self.definitions.push('\n');
+	}
+}
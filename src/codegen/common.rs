@@ -2,65 +2,41 @@ use super::*;
 
 impl<'a> CodeGen<'a>
 {
-	pub fn write_attribute_segment(&mut self, data: &str)
-	{
-		self.attribute_segment.push_str(data);
-	}
-
-	pub fn write_data_segment(&mut self, data: &str)
-	{
-		self.data_segment.push_str(data);
-	}
-
-	pub fn write_text_segment(&mut self, data: &str)
-	{
-		self.text_segment.push_str(data);
-	}
-
 	pub fn decl_attribute(&mut self, identifier: &str, attr: attribute::AttributeType)
 	{
 		if attr & attribute::GLOBAL != 0
 		{
-			self.write_attribute_segment(&format!("\nglobal {identifier}\n"));
+			self.attribute_segment.push(AttributeItem::Global(identifier.to_string()));
 		} else if attr & attribute::EXTERN != 0
 		{
-			self.write_attribute_segment(&format!("\nextern {identifier}\n"));
+			self.attribute_segment.push(AttributeItem::Extern(identifier.to_string()));
 		}
-		
 	}
 
 	pub fn write_lable_text_seg(&mut self, lable: &str)
 	{
-		self.write_text_segment(&format!("\n{lable}:"));
+		self.text_segment.push(TextItem::Function(lable.to_string()));
 	}
 
 	pub fn write_lable(&mut self, lable: Lable)
 	{
 		match lable.kind
 		{
-			LableKind::DataSeg => self.write_data_segment(&format!("\n\t{lable}: ")),
-			LableKind::TextSeg => self.write_text_segment(&format!("\n{lable}: ")),
+			LableKind::DataSeg => panic!("Dev error! write_lable called with a data segment lable, which decl_var_data_seg writes."),
+			LableKind::TextSeg => self.text_segment.push(TextItem::Lable(lable)),
 		}
 	}
 
 	pub fn decl_var_data_seg(&mut self, value: &Value) -> Lable
 	{
-		let lable = self.generate_data_seg_lable();
-		match value
+		if !matches!(value, Value::I8(_) | Value::U8(_) | Value::I16(_) | Value::U16(_) | Value::I32(_) | Value::U32(_) |
+			Value::I64(_) | Value::U64(_) | Value::F32(_) | Value::F64(_))
 		{
-			Value::I8(number) => self.write_data_segment(&format!("\n\t{lable}: db {number}")),
-			Value::U8(number) => self.write_data_segment(&format!("\n\t{lable}: db {number}")),
-			Value::I16(number) => self.write_data_segment(&format!("\n\t{lable}: dw {number}")),
-			Value::U16(number) => self.write_data_segment(&format!("\n\t{lable}: dw {number}")),
-			Value::I32(number) => self.write_data_segment(&format!("\n\t{lable}: dd {number}")),
-			Value::U32(number) => self.write_data_segment(&format!("\n\t{lable}: dd {number}")),
-			Value::I64(number) => self.write_data_segment(&format!("\n\t{lable}: dq {number}")),
-			Value::U64(number) => self.write_data_segment(&format!("\n\t{lable}: dq {number}")),
-			Value::F32(number) => self.write_data_segment(&format!("\n\t{lable}: dd {:?}", number)),
-			Value::F64(number) => self.write_data_segment(&format!("\n\t{lable}: dq {:?}", number)),
-			_ => panic!("Dev error! decl_var_data_seg called with a value that is not constant."),
+			panic!("Dev error! decl_var_data_seg called with a value that is not constant.");
 		}
 
+		let lable = self.generate_data_seg_lable();
+		self.data_segment.push(DataItem::new(lable, value.clone()));
 		return lable;
 	}
 
@@ -77,25 +53,4 @@ impl<'a> CodeGen<'a>
 		self.text_seg_var_index += 1;
 		return Lable::new(index, LableKind::TextSeg);
 	}
-
-	// Returns the size of a value, in bytes
-	pub fn value_type(&self, value: &Value, locals: &Vec<Variable>) -> Type
-	{
-		return match value
-		{
-			Value::I8(_)   								=> Type::new(TypeKind::I8),
-			Value::U8(_)	 							=> Type::new(TypeKind::U8),
-			Value::I16(_) 								=> Type::new(TypeKind::I16),
-			Value::U16(_) 								=> Type::new(TypeKind::U16),
-			Value::I32(_) 								=> Type::new(TypeKind::I32),
-			Value::U32(_) 								=> Type::new(TypeKind::U32),
-			Value::I64(_) 								=> Type::new(TypeKind::I64),
-			Value::U64(_)								=> Type::new(TypeKind::U64),
-			Value::F32(_)								=> Type::new(TypeKind::F32),
-			Value::F64(_)								=> Type::new(TypeKind::F64),
-			Value::Var(index) 						=> locals[*index as usize].data_type,
-			Value::FuncCall(info)	=> self.ir.functions[info.index as usize].return_type,
-			Value::Dereference(info) 	=> info.data_type.dereference(info.dereference_count),
-		}
-	}
 }
\ No newline at end of file
@@ -0,0 +1,311 @@
+use super::*;
+
+// The syntax of the assembly that is written with -S, and the assembler that assembles it. The instructions are the same in every dialect:
+// they are written in the Intel operand order (the destination first), and the dialects differ in the directives, in how the size of an
+// operand and an address are written, and in the comments.
+pub trait Dialect
+{
+	// The file that the assembly is written into
+	fn asm_file_path(&self) -> &'static str;
+
+	// The command that assembles the assembly file into the object file
+	fn assembler_command(&self) -> std::process::Command;
+
+	fn header(&self) -> &'static str;
+	fn data_section(&self) -> &'static str;
+	fn text_section(&self) -> &'static str;
+	fn footer(&self) -> &'static str;
+
+	fn attribute(&self, item: &AttributeItem) -> String;
+	fn data_item(&self, item: &DataItem) -> String;
+	fn comment(&self, comment: &str) -> String;
+
+	// The operand with its size, where the instruction needs it (the size of a memory operand or of an immediate)
+	fn sized(&self, size: OpSize, operand: &AsmOperand) -> String;
+	fn location(&self, location: &LocationExpr) -> String;
+
+	// The address of a function, relative to the instruction pointer (for lea)
+	fn function_address(&self, identifier: &str) -> String;
+
+	fn operand(&self, operand: &AsmOperand) -> String
+	{
+		return match operand
+		{
+			AsmOperand::Value(placeholder) => match &placeholder.kind
+			{
+				PlaceholderKind::Reg(register) 		=> register.to_string(),
+				PlaceholderKind::Integer(value) 	=> value.to_string(),
+				PlaceholderKind::Location(location) => self.location(location),
+			},
+			AsmOperand::Lable(lable) 			=> lable.to_string(),
+			AsmOperand::Function(identifier) 	=> identifier.clone(),
+		};
+	}
+
+	fn text_item(&self, item: &TextItem) -> String
+	{
+		return match item
+		{
+			TextItem::Function(identifier) 		=> format!("\n{identifier}:"),
+			TextItem::Lable(lable) 				=> format!("\n{lable}:"),
+			TextItem::Comment(comment) 			=> format!("\n\t{}", self.comment(comment)),
+			TextItem::Instruction(instruction) 	=> format!("\n\t{}", self.instruction(instruction)),
+		};
+	}
+
+	// Instructions that work on integers and on floats are written by the data type of their first operand
+	fn instruction(&self, instruction: &AsmInstruction) -> String
+	{
+		let operands = &instruction.operands;
+		let data_type = instruction.operand(0).map(|placeholder| placeholder.data_type);
+		let source_size = || instruction.operand(1).unwrap().data_type.size();
+		let operand = |index: usize| self.operand(&operands[index]);
+		let sized = |index: usize| self.sized(data_type.unwrap().size(), &operands[index]);
+		let float_suffix = || if data_type.unwrap() == Type::new(TypeKind::F64) { "sd" } else { "ss" };
+		let is_integer = data_type.is_some_and(|data_type| data_type.is_integer());
+		return match instruction.mnemonic
+		{
+			Mnemonic::Mov if is_integer 		=> format!("mov {}, {}", sized(0), operand(1)),
+			Mnemonic::Mov 						=> format!("mov{} {}, {}", float_suffix(), operand(0), operand(1)),
+			Mnemonic::Movzx | Mnemonic::Movsx 	=> format!(
+				"{} {}, {}",
+				if instruction.mnemonic == Mnemonic::Movzx { "movzx" } else { "movsx" }, sized(0), self.sized(source_size(), &operands[1])
+			),
+			Mnemonic::Movsxd 					=> format!("movsxd {}, {}", operand(0), self.sized(OP_DWORD, &operands[1])),
+			Mnemonic::Lea => match &operands[1]
+			{
+				AsmOperand::Function(identifier) => format!("lea {}, {}", operand(0), self.function_address(identifier)),
+				_ 								=> format!("lea {}, {}", sized(0), operand(1)),
+			},
+			Mnemonic::Cvttsf2si =>
+			{
+				let suffix = if instruction.operand(1).unwrap().data_type == Type::new(TypeKind::F64) { "sd" } else { "ss" };
+				format!("cvtt{suffix}2si {}, {}", sized(0), operand(1))
+			},
+			Mnemonic::Cvtsi2sf 					=> format!("cvtsi2{} {}, {}", float_suffix(), operand(0), self.sized(source_size(), &operands[1])),
+			Mnemonic::Cvtsf2sf if instruction.operand(1).unwrap().data_type == Type::new(TypeKind::F64) => {
+				format!("cvtsd2ss {}, {}", operand(0), operand(1))
+			},
+			Mnemonic::Cvtsf2sf 					=> format!("cvtss2sd {}, {}", operand(0), operand(1)),
+			Mnemonic::Push 						=> format!("push {}", sized(0)),
+			Mnemonic::Pop 						=> format!("pop {}", sized(0)),
+			Mnemonic::Ret 						=> String::from("ret"),
+			Mnemonic::Add if is_integer 		=> format!("add {}, {}", sized(0), operand(1)),
+			Mnemonic::Add 						=> format!("add{} {}, {}", float_suffix(), operand(0), operand(1)),
+			Mnemonic::Sub if is_integer 		=> format!("sub {}, {}", sized(0), operand(1)),
+			Mnemonic::Sub 						=> format!("sub{} {}, {}", float_suffix(), operand(0), operand(1)),
+			Mnemonic::Mul if is_integer 		=> format!("imul {}, {}", sized(0), operand(1)),
+			Mnemonic::Mul 						=> format!("mul{} {}, {}", float_suffix(), operand(0), operand(1)),
+			Mnemonic::Div 						=> format!("{} {}", if data_type.unwrap().is_signed() { "idiv" } else { "div" }, sized(0)),
+			Mnemonic::DivFloat 					=> format!("div{} {}, {}", float_suffix(), operand(0), operand(1)),
+			Mnemonic::Xor 						=> format!("xor {}, {}", sized(0), operand(1)),
+			Mnemonic::Or 						=> format!("or {}, {}", sized(0), operand(1)),
+			Mnemonic::And 						=> format!("and {}, {}", sized(0), operand(1)),
+			Mnemonic::Not 						=> format!("not {}", sized(0)),
+			Mnemonic::Shl 						=> format!("shl {}, {}", sized(0), operand(1)),
+			Mnemonic::Shr 						=> format!("shr {}, {}", sized(0), operand(1)),
+			Mnemonic::Call 						=> format!("call {}", operand(0)),
+			Mnemonic::Cdq 						=> String::from("cdq"),
+			Mnemonic::Cqo 						=> String::from("cqo"),
+			Mnemonic::Cmp if is_integer 		=> format!("cmp {}, {}", sized(0), operand(1)),
+			Mnemonic::Cmp 						=> format!("ucomi{} {}, {}", float_suffix(), operand(0), operand(1)),
+			Mnemonic::Test 						=> format!("test {}, {}", sized(0), operand(1)),
+			Mnemonic::Setcc(condition) 			=> format!("set{condition} {}", self.sized(OP_BYTE, &operands[0])),
+			Mnemonic::Jmp 						=> format!("jmp {}", operand(0)),
+			Mnemonic::Jcc(condition) 			=> format!("j{condition} {}", operand(0)),
+		};
+	}
+}
+
+// NASM, which takes the size of every operand that has one
+pub struct Nasm;
+
+// The GNU assembler, in the Intel syntax. The size is written only on memory operands (as "qword ptr"), since gas takes the size of
+// registers from their names.
+pub struct Gnu;
+
+impl Dialect for Nasm
+{
+	fn asm_file_path(&self) -> &'static str
+	{
+		return "/tmp/slowc_compiled.asm";
+	}
+
+	fn assembler_command(&self) -> std::process::Command
+	{
+		let mut command = std::process::Command::new("nasm");
+		command.args(["-f", "elf64", "-g", "-o", OUT_OBJECT_FILE_PATH, self.asm_file_path()]);
+		return command;
+	}
+
+	fn header(&self) -> &'static str
+	{
+		return "bits 64";
+	}
+
+	fn data_section(&self) -> &'static str
+	{
+		return "\nsegment .data";
+	}
+
+	fn text_section(&self) -> &'static str
+	{
+		return "\nsegment .text";
+	}
+
+	fn footer(&self) -> &'static str
+	{
+		return "\n";
+	}
+
+	fn attribute(&self, item: &AttributeItem) -> String
+	{
+		return match item
+		{
+			AttributeItem::Global(identifier) => format!("\nglobal {identifier}"),
+			AttributeItem::Extern(identifier) => format!("\nextern {identifier}"),
+		};
+	}
+
+	fn data_item(&self, item: &DataItem) -> String
+	{
+		let lable = item.lable;
+		return match item.value
+		{
+			Value::I8(number) 	=> format!("\n\t{lable}: db {number}"),
+			Value::U8(number) 	=> format!("\n\t{lable}: db {number}"),
+			Value::I16(number) 	=> format!("\n\t{lable}: dw {number}"),
+			Value::U16(number) 	=> format!("\n\t{lable}: dw {number}"),
+			Value::I32(number) 	=> format!("\n\t{lable}: dd {number}"),
+			Value::U32(number) 	=> format!("\n\t{lable}: dd {number}"),
+			Value::I64(number) 	=> format!("\n\t{lable}: dq {number}"),
+			Value::U64(number) 	=> format!("\n\t{lable}: dq {number}"),
+			Value::F32(number) 	=> format!("\n\t{lable}: dd {:?}", number),
+			Value::F64(number) 	=> format!("\n\t{lable}: dq {:?}", number),
+			_ 					=> panic!("Dev error! A data item that is not a constant."),
+		};
+	}
+
+	fn comment(&self, comment: &str) -> String
+	{
+		return format!("; {comment}");
+	}
+
+	fn sized(&self, size: OpSize, operand: &AsmOperand) -> String
+	{
+		return format!("{} {}", size_name(size), self.operand(operand));
+	}
+
+	fn location(&self, location: &LocationExpr) -> String
+	{
+		return location.to_string();
+	}
+
+	fn function_address(&self, identifier: &str) -> String
+	{
+		return format!("[rel {identifier}]");
+	}
+}
+
+impl Dialect for Gnu
+{
+	fn asm_file_path(&self) -> &'static str
+	{
+		return "/tmp/slowc_compiled.s";
+	}
+
+	fn assembler_command(&self) -> std::process::Command
+	{
+		let mut command = std::process::Command::new("as");
+		command.args(["--64", "-g", "-o", OUT_OBJECT_FILE_PATH, self.asm_file_path()]);
+		return command;
+	}
+
+	fn header(&self) -> &'static str
+	{
+		return ".intel_syntax noprefix";
+	}
+
+	fn data_section(&self) -> &'static str
+	{
+		return "\n.data";
+	}
+
+	fn text_section(&self) -> &'static str
+	{
+		return "\n.text";
+	}
+
+	// The stack does not need to be executable
+	fn footer(&self) -> &'static str
+	{
+		return "\n.section .note.GNU-stack,\"\",@progbits\n";
+	}
+
+	fn attribute(&self, item: &AttributeItem) -> String
+	{
+		return match item
+		{
+			AttributeItem::Global(identifier) => format!("\n.globl {identifier}"),
+			AttributeItem::Extern(identifier) => format!("\n.extern {identifier}"),
+		};
+	}
+
+	// Floats are written by their bits, since gas does not read infinities and NaNs
+	fn data_item(&self, item: &DataItem) -> String
+	{
+		let lable = item.lable;
+		return match item.value
+		{
+			Value::I8(number) 	=> format!("\n\t{lable}: .byte {number}"),
+			Value::U8(number) 	=> format!("\n\t{lable}: .byte {number}"),
+			Value::I16(number) 	=> format!("\n\t{lable}: .short {number}"),
+			Value::U16(number) 	=> format!("\n\t{lable}: .short {number}"),
+			Value::I32(number) 	=> format!("\n\t{lable}: .long {number}"),
+			Value::U32(number) 	=> format!("\n\t{lable}: .long {number}"),
+			Value::I64(number) 	=> format!("\n\t{lable}: .quad {number}"),
+			Value::U64(number) 	=> format!("\n\t{lable}: .quad {number}"),
+			Value::F32(number) 	=> format!("\n\t{lable}: .long {:#x}\t# {:?}", number.to_bits(), number),
+			Value::F64(number) 	=> format!("\n\t{lable}: .quad {:#x}\t# {:?}", number.to_bits(), number),
+			_ 					=> panic!("Dev error! A data item that is not a constant."),
+		};
+	}
+
+	fn comment(&self, comment: &str) -> String
+	{
+		return format!("# {comment}");
+	}
+
+	fn sized(&self, size: OpSize, operand: &AsmOperand) -> String
+	{
+		return match operand
+		{
+			AsmOperand::Value(Placeholder { kind: PlaceholderKind::Location(location), .. }) => {
+				format!("{} ptr {}", size_name(size), self.location(location))
+			},
+			_ => self.operand(operand),
+		};
+	}
+
+	fn location(&self, location: &LocationExpr) -> String
+	{
+		return location.to_string();
+	}
+
+	fn function_address(&self, identifier: &str) -> String
+	{
+		return format!("[rip + {identifier}]");
+	}
+}
+
+fn size_name<'b>(size: OpSize) -> &'b str
+{
+	match size
+	{
+		OP_BYTE => return "byte",
+		OP_WORD	=> return "word",
+		OP_DWORD => return "dword",
+		OP_QWORD => return "qword",
+		_ => panic!("Dev error! size_name({size}) called with a size thats not a power of 2."),
+	}
+}
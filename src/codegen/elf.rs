@@ -0,0 +1,268 @@
+use super::encoder::*;
+
+const ELF_HEADER_SIZE: usize = 64;
+const SECTION_HEADER_SIZE: usize = 64;
+const SYMBOL_SIZE: usize = 24;
+const RELOCATION_SIZE: usize = 24;
+
+const ET_REL: u16 = 1;
+const EM_X86_64: u16 = 62;
+
+const SHT_PROGBITS: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+const SHT_STRTAB: u32 = 3;
+const SHT_RELA: u32 = 4;
+const SHT_NOBITS: u32 = 8;
+
+const SHF_WRITE: u64 = 0x1;
+const SHF_ALLOC: u64 = 0x2;
+const SHF_EXECINSTR: u64 = 0x4;
+const SHF_INFO_LINK: u64 = 0x40;
+
+const STB_LOCAL: u8 = 0;
+const STB_GLOBAL: u8 = 1;
+const STT_NOTYPE: u8 = 0;
+const STT_FUNC: u8 = 2;
+const STT_SECTION: u8 = 3;
+const SHN_UNDEF: u16 = 0;
+
+const R_X86_64_PC32: u32 = 2;
+const R_X86_64_PLT32: u32 = 4;
+
+// The sections of the object file, by their index in the section header table (0 is the null section)
+const SECTION_TEXT: u16 = 1;
+const SECTION_DATA: u16 = 2;
+const SECTION_BSS: u16 = 3;
+const SECTION_SYMTAB: u16 = 5;
+const SECTION_STRTAB: u16 = 6;
+const SECTION_SHSTRTAB: u16 = 7;
+const SECTION_COUNT: u16 = 9;
+
+struct Symbol
+{
+	name: u32,			/* The offset of the name in the string table */
+	info: u8,			/* The binding and the type */
+	section: u16,
+	value: u64,
+	size: u64,
+}
+
+struct SectionHeader
+{
+	name: u32,
+	kind: u32,
+	flags: u64,
+	offset: u64,
+	size: u64,
+	link: u32,
+	info: u32,
+	alignment: u64,
+	entry_size: u64,
+}
+
+// An ELF64 relocatable object file for x86-64, with the .text, .data and .bss sections, the symbols of the functions and
+// the relocations of the text segment. The functions of the text segment are local symbols unless they are global, and
+// the functions that the text segment references but does not have are undefined global symbols, which the linker finds.
+pub struct ObjectFile
+{
+	code: MachineCode,
+	symbols: Vec<Symbol>,
+	first_global_symbol: usize,
+	function_symbols: Vec<(String, usize)>,	/* The index of the symbol of each function that relocations reference */
+	strings: Vec<u8>,
+}
+
+impl ObjectFile
+{
+	pub fn new(code: MachineCode, global_functions: &[&str]) -> Self
+	{
+		let mut object = Self {
+			code,
+			symbols: Vec::new(),
+			first_global_symbol: 0,
+			function_symbols: Vec::new(),
+			strings: vec![0],
+		};
+
+		object.symbols.push(Symbol { name: 0, info: 0, section: SHN_UNDEF, value: 0, size: 0 });
+		for section in [SECTION_TEXT, SECTION_DATA, SECTION_BSS]
+		{
+			object.symbols.push(Symbol { name: 0, info: STB_LOCAL << 4 | STT_SECTION, section, value: 0, size: 0 });
+		}
+
+		// The local symbols come before the global ones
+		let functions = std::mem::take(&mut object.code.functions);
+		let (global, local): (Vec<&FunctionSymbol>, Vec<&FunctionSymbol>) = functions.iter()
+			.partition(|function| global_functions.contains(&function.identifier.as_str()));
+		for function in local
+		{
+			object.push_symbol(&function.identifier, STB_LOCAL << 4 | STT_FUNC, SECTION_TEXT, function.offset, function.size);
+		}
+		object.first_global_symbol = object.symbols.len();
+		for function in global
+		{
+			object.push_symbol(&function.identifier, STB_GLOBAL << 4 | STT_FUNC, SECTION_TEXT, function.offset, function.size);
+		}
+
+		let mut external = Vec::new();
+		for relocation in &object.code.relocations
+		{
+			if let RelocationTarget::Function(identifier) = &relocation.target
+			{
+				if !external.contains(identifier)
+				{
+					external.push(identifier.clone());
+				}
+			}
+		}
+		for identifier in external
+		{
+			let index = object.push_symbol(&identifier, STB_GLOBAL << 4 | STT_NOTYPE, SHN_UNDEF, 0, 0);
+			object.function_symbols.push((identifier, index));
+		}
+		return object;
+	}
+
+	fn push_symbol(&mut self, identifier: &str, info: u8, section: u16, value: usize, size: usize) -> usize
+	{
+		let name = self.strings.len() as u32;
+		self.strings.extend(identifier.as_bytes());
+		self.strings.push(0);
+		self.symbols.push(Symbol { name, info, section, value: value as u64, size: size as u64 });
+		return self.symbols.len() - 1;
+	}
+
+	// The file is laid out as the ELF header, the contents of the sections (each aligned on 8 bytes), and the section header table
+	pub fn to_bytes(&self) -> Vec<u8>
+	{
+		let section_names = [
+			"", ".text", ".data", ".bss", ".rela.text", ".symtab", ".strtab", ".shstrtab", ".note.GNU-stack"
+		];
+		let mut names = Vec::new();
+		let mut name_offsets = Vec::new();
+		for name in section_names
+		{
+			name_offsets.push(names.len() as u32);
+			names.extend(name.as_bytes());
+			names.push(0);
+		}
+
+		let mut contents = vec![0; ELF_HEADER_SIZE];
+		let mut headers: Vec<SectionHeader> = Vec::with_capacity(SECTION_COUNT as usize);
+		headers.push(SectionHeader { name: 0, kind: 0, flags: 0, offset: 0, size: 0, link: 0, info: 0, alignment: 0, entry_size: 0 });
+
+		let mut push_section = |contents: &mut Vec<u8>, bytes: &[u8], mut header: SectionHeader| {
+			while !contents.len().is_multiple_of(8)
+			{
+				contents.push(0);
+			}
+			header.name = name_offsets[headers.len()];
+			header.offset = contents.len() as u64;
+			header.size = bytes.len() as u64;
+			contents.extend(bytes);
+			headers.push(header);
+		};
+
+		let section = |kind: u32, flags: u64, alignment: u64| SectionHeader {
+			name: 0, kind, flags, offset: 0, size: 0, link: 0, info: 0, alignment, entry_size: 0
+		};
+		push_section(&mut contents, &self.code.text, section(SHT_PROGBITS, SHF_ALLOC | SHF_EXECINSTR, 16));
+		push_section(&mut contents, &self.code.data, section(SHT_PROGBITS, SHF_ALLOC | SHF_WRITE, 8));
+		push_section(&mut contents, &[], section(SHT_NOBITS, SHF_ALLOC | SHF_WRITE, 8));
+		push_section(&mut contents, &self.relocation_bytes(), SectionHeader {
+			link: SECTION_SYMTAB as u32,
+			info: SECTION_TEXT as u32,
+			entry_size: RELOCATION_SIZE as u64,
+			..section(SHT_RELA, SHF_INFO_LINK, 8)
+		});
+		push_section(&mut contents, &self.symbol_bytes(), SectionHeader {
+			link: SECTION_STRTAB as u32,
+			info: self.first_global_symbol as u32,
+			entry_size: SYMBOL_SIZE as u64,
+			..section(SHT_SYMTAB, 0, 8)
+		});
+		push_section(&mut contents, &self.strings, section(SHT_STRTAB, 0, 1));
+		push_section(&mut contents, &names, section(SHT_STRTAB, 0, 1));
+
+		// The stack does not need to be executable
+		push_section(&mut contents, &[], section(SHT_PROGBITS, 0, 1));
+
+		while !contents.len().is_multiple_of(8)
+		{
+			contents.push(0);
+		}
+		let section_headers_offset = contents.len();
+		for header in &headers
+		{
+			contents.extend(header.name.to_le_bytes());
+			contents.extend(header.kind.to_le_bytes());
+			contents.extend(header.flags.to_le_bytes());
+			contents.extend(0u64.to_le_bytes());		/* The address, which relocatable files dont have */
+			contents.extend(header.offset.to_le_bytes());
+			contents.extend(header.size.to_le_bytes());
+			contents.extend(header.link.to_le_bytes());
+			contents.extend(header.info.to_le_bytes());
+			contents.extend(header.alignment.to_le_bytes());
+			contents.extend(header.entry_size.to_le_bytes());
+		}
+
+		let mut header = Vec::with_capacity(ELF_HEADER_SIZE);
+		header.extend([0x7F, b'E', b'L', b'F', 2, 1, 1, 0]);		/* 64 bit, little endian, version 1, System V */
+		header.extend([0; 8]);
+		header.extend(ET_REL.to_le_bytes());
+		header.extend(EM_X86_64.to_le_bytes());
+		header.extend(1u32.to_le_bytes());						/* The version */
+		header.extend(0u64.to_le_bytes());						/* The entry point */
+		header.extend(0u64.to_le_bytes());						/* The program header table, which relocatable files dont have */
+		header.extend((section_headers_offset as u64).to_le_bytes());
+		header.extend(0u32.to_le_bytes());						/* The flags */
+		header.extend((ELF_HEADER_SIZE as u16).to_le_bytes());
+		header.extend(0u16.to_le_bytes());						/* The size and the amount of program headers */
+		header.extend(0u16.to_le_bytes());
+		header.extend((SECTION_HEADER_SIZE as u16).to_le_bytes());
+		header.extend(SECTION_COUNT.to_le_bytes());
+		header.extend(SECTION_SHSTRTAB.to_le_bytes());
+		contents[..ELF_HEADER_SIZE].copy_from_slice(&header);
+
+		return contents;
+	}
+
+	fn symbol_bytes(&self) -> Vec<u8>
+	{
+		let mut bytes = Vec::with_capacity(self.symbols.len() * SYMBOL_SIZE);
+		for symbol in &self.symbols
+		{
+			bytes.extend(symbol.name.to_le_bytes());
+			bytes.push(symbol.info);
+			bytes.push(0);		/* The visibility, which is the default */
+			bytes.extend(symbol.section.to_le_bytes());
+			bytes.extend(symbol.value.to_le_bytes());
+			bytes.extend(symbol.size.to_le_bytes());
+		}
+		return bytes;
+	}
+
+	fn relocation_bytes(&self) -> Vec<u8>
+	{
+		let mut bytes = Vec::with_capacity(self.code.relocations.len() * RELOCATION_SIZE);
+		for relocation in &self.code.relocations
+		{
+			let symbol = match &relocation.target
+			{
+				RelocationTarget::Data => SECTION_DATA as u64,		/* The section symbols are in the order of the sections */
+				RelocationTarget::Function(identifier) => {
+					self.function_symbols.iter().find(|(function, _)| function == identifier).unwrap().1 as u64
+				},
+			};
+			let kind = match relocation.kind
+			{
+				RelocationKind::Pc32 	=> R_X86_64_PC32,
+				RelocationKind::Plt32 	=> R_X86_64_PLT32,
+			};
+			bytes.extend((relocation.offset as u64).to_le_bytes());
+			bytes.extend((symbol << 32 | kind as u64).to_le_bytes());
+			bytes.extend(relocation.addend.to_le_bytes());
+		}
+		return bytes;
+	}
+}
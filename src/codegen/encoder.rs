@@ -0,0 +1,658 @@
+use std::collections::HashMap;
+
+use super::*;
+
+const REX: u8 = 0x40;
+const REX_W: u8 = 0x08;		/* 64 bit operand size */
+const REX_R: u8 = 0x04;		/* Extends the reg field of ModRM */
+const REX_X: u8 = 0x02;		/* Extends the index field of SIB */
+const REX_B: u8 = 0x01;		/* Extends the rm field of ModRM, the base field of SIB, or the register in the opcode */
+
+const PREFIX_OPERAND_SIZE: u8 = 0x66;
+const PREFIX_F32: u8 = 0xF3;
+const PREFIX_F64: u8 = 0xF2;
+
+// The opcode extensions (the reg field of ModRM) of the instructions that take an immediate, or a single operand
+const EXTENSION_ADD: u8 = 0;
+const EXTENSION_OR: u8 = 1;
+const EXTENSION_AND: u8 = 4;
+const EXTENSION_SUB: u8 = 5;
+const EXTENSION_XOR: u8 = 6;
+const EXTENSION_CMP: u8 = 7;
+const EXTENSION_NOT: u8 = 2;
+const EXTENSION_DIV: u8 = 6;
+const EXTENSION_IDIV: u8 = 7;
+const EXTENSION_SHL: u8 = 4;
+const EXTENSION_SHR: u8 = 5;
+
+// The encoded text and data segments, with the references to symbols that the linker resolves
+pub struct MachineCode
+{
+	pub text: Vec<u8>,
+	pub data: Vec<u8>,
+	pub functions: Vec<FunctionSymbol>,
+	pub relocations: Vec<Relocation>,
+}
+
+pub struct FunctionSymbol
+{
+	pub identifier: String,
+	pub offset: usize,
+	pub size: usize,
+}
+
+// A 32 bit field in the text segment, which holds the address of the target relative to the end of the field
+pub struct Relocation
+{
+	pub offset: usize,
+	pub target: RelocationTarget,
+	pub kind: RelocationKind,
+	pub addend: i64,
+}
+
+pub enum RelocationTarget
+{
+	Data,					/* The start of the data segment */
+	Function(String),		/* A function that is not in this file */
+}
+
+#[derive(Clone, Copy)]
+pub enum RelocationKind
+{
+	Pc32,		/* The address of the target */
+	Plt32,		/* The address of the target, or of its entry in the procedure linkage table (for calls and jumps) */
+}
+
+// A 32 bit field that is relative to its end, and is filled when the text segment is done
+enum Reference
+{
+	Lable(usize),
+	Function(String, RelocationKind),
+}
+
+// A register, or memory that is addressed through the ModRM byte
+#[derive(Clone, Copy)]
+enum Rm
+{
+	Register(Register),
+	Memory(LocationExpr),
+}
+
+struct Encoder
+{
+	text: Vec<u8>,
+	lables: HashMap<usize, usize>,			/* The offset of each text segment lable */
+	data_lables: HashMap<usize, usize>,		/* The offset of each data segment lable in the data segment */
+	functions: Vec<FunctionSymbol>,
+	references: Vec<(usize, Reference)>,
+	relocations: Vec<Relocation>,
+}
+
+// Encodes the instructions of the text segment into x86-64 machine code, and lays out the data segment. Jumps between
+// lables and calls of functions in the text segment are resolved here, and references to the data segment and to
+// external functions become relocations.
+pub fn encode(text: &[TextItem], data: &[DataItem]) -> MachineCode
+{
+	let mut data_bytes = Vec::new();
+	let mut data_lables = HashMap::new();
+	for item in data
+	{
+		let bytes = item.bytes();
+		while data_bytes.len() % bytes.len() != 0
+		{
+			data_bytes.push(0);
+		}
+		data_lables.insert(item.lable.index, data_bytes.len());
+		data_bytes.extend(bytes);
+	}
+
+	let mut encoder = Encoder {
+		text: Vec::new(),
+		lables: HashMap::new(),
+		data_lables,
+		functions: Vec::new(),
+		references: Vec::new(),
+		relocations: Vec::new(),
+	};
+	for item in text
+	{
+		match item
+		{
+			TextItem::Function(identifier) =>
+			{
+				encoder.end_function();
+				encoder.functions.push(FunctionSymbol { identifier: identifier.clone(), offset: encoder.text.len(), size: 0 });
+			},
+			TextItem::Lable(lable) 				=> { encoder.lables.insert(lable.index, encoder.text.len()); },
+			TextItem::Comment(_) 				=> (),
+			TextItem::Instruction(instruction) 	=> encoder.encode_instruction(instruction),
+		}
+	}
+	encoder.end_function();
+	encoder.resolve_references();
+
+	return MachineCode {
+		text: encoder.text,
+		data: data_bytes,
+		functions: encoder.functions,
+		relocations: encoder.relocations,
+	};
+}
+
+impl Encoder
+{
+	fn end_function(&mut self)
+	{
+		if let Some(function) = self.functions.last_mut()
+		{
+			function.size = self.text.len() - function.offset;
+		}
+	}
+
+	// Fills the references to lables and to the functions of the text segment, and turns the references to other functions into relocations
+	fn resolve_references(&mut self)
+	{
+		for (offset, reference) in std::mem::take(&mut self.references)
+		{
+			let target = match &reference
+			{
+				Reference::Lable(index) => Some(self.lables[index]),
+				Reference::Function(identifier, _) => self.functions.iter().find(|function| function.identifier == *identifier).map(|function| function.offset),
+			};
+
+			match (target, reference)
+			{
+				(Some(target), _) =>
+				{
+					let relative = target as i64 - (offset as i64 + 4);
+					self.text[offset..offset + 4].copy_from_slice(&(relative as i32).to_le_bytes());
+				},
+				(None, Reference::Function(identifier, kind)) =>
+				{
+					self.relocations.push(Relocation { offset, target: RelocationTarget::Function(identifier), kind, addend: -4 });
+				},
+				(None, Reference::Lable(_)) => unreachable!(),
+			}
+		}
+	}
+
+	fn encode_instruction(&mut self, instruction: &AsmInstruction)
+	{
+		let operand = |index: usize| *instruction.operand(index).unwrap();
+		let is_integer = instruction.operand(0).is_some_and(|placeholder| placeholder.data_type.is_integer());
+		match instruction.mnemonic
+		{
+			Mnemonic::Mov if is_integer => self.encode_mov(&operand(0), &operand(1)),
+			Mnemonic::Mov =>
+			{
+				let prefix = float_prefix(operand(0).data_type);
+				match operand(0).kind
+				{
+					PlaceholderKind::Reg(destination) 	=> self.write_modrm(&[prefix], false, &[0x0F, 0x10], destination, rm(&operand(1)), &[]),
+					_ 									=> self.write_modrm(&[prefix], false, &[0x0F, 0x11], register(&operand(1)), rm(&operand(0)), &[]),
+				}
+			},
+			Mnemonic::Movzx | Mnemonic::Movsx =>
+			{
+				let (prefixes, rex_w) = operand_size(operand(0).data_type.size());
+				let opcode = match (instruction.mnemonic, operand(1).data_type.size())
+				{
+					(Mnemonic::Movzx, OP_BYTE) 	=> 0xB6,
+					(Mnemonic::Movzx, OP_WORD) 	=> 0xB7,
+					(Mnemonic::Movsx, OP_BYTE) 	=> 0xBE,
+					(Mnemonic::Movsx, OP_WORD) 	=> 0xBF,
+					(_, size) 					=> panic!("Dev error! {:?} from a source of {size} bytes.", instruction.mnemonic),
+				};
+				self.write_modrm(prefixes, rex_w, &[0x0F, opcode], register(&operand(0)), rm(&operand(1)), &[]);
+			},
+			Mnemonic::Movsxd => self.write_modrm(&[], true, &[0x63], register(&operand(0)), rm(&operand(1)), &[]),
+			Mnemonic::Lea => match &instruction.operands[1]
+			{
+				AsmOperand::Function(identifier) =>
+				{
+					// RIP relative, so the address does not depend on where the program is loaded
+					let destination = register_number(register(&operand(0)));
+					self.text.extend([REX | REX_W | if destination & 8 != 0 { REX_R } else { 0 }, 0x8D, 0b00_000_101 | (destination & 7) << 3]);
+					self.write_reference(Reference::Function(identifier.clone(), RelocationKind::Pc32));
+				},
+				_ =>
+				{
+					let (prefixes, rex_w) = operand_size(operand(0).data_type.size());
+					self.write_modrm(prefixes, rex_w, &[0x8D], register(&operand(0)), rm(&operand(1)), &[]);
+				},
+			},
+			Mnemonic::Cvttsf2si => self.write_modrm(
+				&[float_prefix(operand(1).data_type)], operand(0).data_type.size() == OP_QWORD, &[0x0F, 0x2C], register(&operand(0)), rm(&operand(1)), &[]
+			),
+			Mnemonic::Cvtsi2sf => self.write_modrm(
+				&[float_prefix(operand(0).data_type)], operand(1).data_type.size() == OP_QWORD, &[0x0F, 0x2A], register(&operand(0)), rm(&operand(1)), &[]
+			),
+			Mnemonic::Cvtsf2sf => self.write_modrm(&[float_prefix(operand(1).data_type)], false, &[0x0F, 0x5A], register(&operand(0)), rm(&operand(1)), &[]),
+			Mnemonic::Push => match operand(0).kind
+			{
+				PlaceholderKind::Reg(source) 						=> self.write_opcode_register(&[], false, 0x50, source, &[]),
+				PlaceholderKind::Integer(value) if fits_i8(value, OP_QWORD) => self.text.extend([0x6A, value as u8]),
+				PlaceholderKind::Integer(value) 					=> self.write_opcode(&[0x68], &immediate(value, OP_DWORD)),
+				PlaceholderKind::Location(_) 						=> self.write_extension(&[], false, &[0xFF], 6, rm(&operand(0)), &[]),
+			},
+			Mnemonic::Pop => match operand(0).kind
+			{
+				PlaceholderKind::Reg(destination) 	=> self.write_opcode_register(&[], false, 0x58, destination, &[]),
+				_ 									=> self.write_extension(&[], false, &[0x8F], 0, rm(&operand(0)), &[]),
+			},
+			Mnemonic::Ret => self.text.push(0xC3),
+			Mnemonic::Add if is_integer => self.encode_arithmetic(EXTENSION_ADD, &operand(0), &operand(1)),
+			Mnemonic::Sub if is_integer => self.encode_arithmetic(EXTENSION_SUB, &operand(0), &operand(1)),
+			Mnemonic::Cmp if is_integer => self.encode_arithmetic(EXTENSION_CMP, &operand(0), &operand(1)),
+			Mnemonic::Xor => self.encode_arithmetic(EXTENSION_XOR, &operand(0), &operand(1)),
+			Mnemonic::Or => self.encode_arithmetic(EXTENSION_OR, &operand(0), &operand(1)),
+			Mnemonic::And => self.encode_arithmetic(EXTENSION_AND, &operand(0), &operand(1)),
+			Mnemonic::Mul if is_integer => self.encode_mul(&operand(0), &operand(1)),
+			Mnemonic::Add | Mnemonic::Sub | Mnemonic::Mul | Mnemonic::DivFloat =>
+			{
+				let opcode = match instruction.mnemonic
+				{
+					Mnemonic::Add 	=> 0x58,
+					Mnemonic::Sub 	=> 0x5C,
+					Mnemonic::Mul 	=> 0x59,
+					_ 				=> 0x5E,
+				};
+				self.write_modrm(&[float_prefix(operand(0).data_type)], false, &[0x0F, opcode], register(&operand(0)), rm(&operand(1)), &[]);
+			},
+			Mnemonic::Cmp =>
+			{
+				let prefixes: &[u8] = if operand(0).data_type.kind == TypeKind::F64 { &[PREFIX_OPERAND_SIZE] } else { &[] };
+				self.write_modrm(prefixes, false, &[0x0F, 0x2E], register(&operand(0)), rm(&operand(1)), &[]);
+			},
+			Mnemonic::Div =>
+			{
+				let extension = if operand(0).data_type.is_signed() { EXTENSION_IDIV } else { EXTENSION_DIV };
+				self.encode_unary(extension, &operand(0));
+			},
+			Mnemonic::Not => self.encode_unary(EXTENSION_NOT, &operand(0)),
+			Mnemonic::Shl => self.encode_shift(EXTENSION_SHL, &operand(0), &operand(1)),
+			Mnemonic::Shr => self.encode_shift(EXTENSION_SHR, &operand(0), &operand(1)),
+			Mnemonic::Cdq => self.text.push(0x99),
+			Mnemonic::Cqo => self.text.extend([REX | REX_W, 0x99]),
+			Mnemonic::Test => self.encode_test(&operand(0), &operand(1)),
+			Mnemonic::Setcc(condition) => self.write_extension(&[], false, &[0x0F, 0x90 + condition_code(condition)], 0, rm(&operand(0)), &[]),
+			Mnemonic::Jmp | Mnemonic::Call | Mnemonic::Jcc(_) => self.encode_jump(instruction),
+		}
+	}
+
+	fn encode_jump(&mut self, instruction: &AsmInstruction)
+	{
+		match (instruction.mnemonic, &instruction.operands[0])
+		{
+			(Mnemonic::Jmp, AsmOperand::Lable(lable)) =>
+			{
+				self.text.push(0xE9);
+				self.write_reference(Reference::Lable(lable.index));
+			},
+			(Mnemonic::Jcc(condition), AsmOperand::Lable(lable)) =>
+			{
+				self.text.extend([0x0F, 0x80 + condition_code(condition)]);
+				self.write_reference(Reference::Lable(lable.index));
+			},
+			(Mnemonic::Jmp | Mnemonic::Call, AsmOperand::Function(identifier)) =>
+			{
+				self.text.push(if instruction.mnemonic == Mnemonic::Call { 0xE8 } else { 0xE9 });
+				self.write_reference(Reference::Function(identifier.clone(), RelocationKind::Plt32));
+			},
+			(Mnemonic::Jmp | Mnemonic::Call, AsmOperand::Value(target)) =>
+			{
+				let extension = if instruction.mnemonic == Mnemonic::Call { 2 } else { 4 };
+				self.write_extension(&[], false, &[0xFF], extension, rm(target), &[]);
+			},
+			(mnemonic, _) => panic!("Dev error! {mnemonic:?} with an operand that it cannot take."),
+		}
+	}
+
+	fn encode_mov(&mut self, destination: &Placeholder, source: &Placeholder)
+	{
+		let size = destination.data_type.size();
+		let (prefixes, rex_w) = operand_size(size);
+		match (destination.kind, source.kind)
+		{
+			(_, PlaceholderKind::Reg(source)) => self.write_modrm(prefixes, rex_w, &[byte_opcode(0x88, size)], source, rm(destination), &[]),
+			(PlaceholderKind::Reg(destination), PlaceholderKind::Location(_)) => self.write_modrm(prefixes, rex_w, &[byte_opcode(0x8A, size)], destination, rm(source), &[]),
+
+			// A 64 bit constant is moved into the 32 bit register if it fits, because writing a 32 bit register clears the upper half
+			(PlaceholderKind::Reg(destination), PlaceholderKind::Integer(value)) => match size
+			{
+				OP_QWORD if value <= u32::MAX as u64 	=> self.write_opcode_register(&[], false, 0xB8, destination, &immediate(value, OP_DWORD)),
+				OP_QWORD if fits_i32(value) 			=> self.write_extension(&[], true, &[0xC7], 0, Rm::Register(destination), &immediate(value, OP_DWORD)),
+				OP_QWORD 								=> self.write_opcode_register(&[], true, 0xB8, destination, &immediate(value, OP_QWORD)),
+				OP_BYTE 								=> self.write_opcode_register(&[], false, 0xB0, destination, &immediate(value, OP_BYTE)),
+				_ 										=> self.write_opcode_register(prefixes, false, 0xB8, destination, &immediate(value, size)),
+			},
+			(PlaceholderKind::Location(_), PlaceholderKind::Integer(value)) =>
+			{
+				let value = if size == OP_BYTE { vec![value as u8] } else { immediate32(value, size) };
+				self.write_extension(prefixes, rex_w, &[byte_opcode(0xC6, size)], 0, rm(destination), &value);
+			},
+			_ => panic!("Dev error! mov {destination}, {source} cannot be encoded."),
+		}
+	}
+
+	// add, or, and, sub, xor and cmp have the same forms, and the extension picks the operation
+	fn encode_arithmetic(&mut self, extension: u8, destination: &Placeholder, source: &Placeholder)
+	{
+		let size = destination.data_type.size();
+		let (prefixes, rex_w) = operand_size(size);
+		let opcode = extension << 3;
+		match (destination.kind, source.kind)
+		{
+			(_, PlaceholderKind::Reg(source)) => self.write_modrm(prefixes, rex_w, &[byte_opcode(opcode, size)], source, rm(destination), &[]),
+			(PlaceholderKind::Reg(destination), PlaceholderKind::Location(_)) => {
+				self.write_modrm(prefixes, rex_w, &[byte_opcode(opcode + 2, size)], destination, rm(source), &[]);
+			},
+			(_, PlaceholderKind::Integer(value)) if size == OP_BYTE => self.write_extension(prefixes, rex_w, &[0x80], extension, rm(destination), &[value as u8]),
+			(_, PlaceholderKind::Integer(value)) if fits_i8(value, size) => self.write_extension(prefixes, rex_w, &[0x83], extension, rm(destination), &[value as u8]),
+			(_, PlaceholderKind::Integer(value)) => self.write_extension(prefixes, rex_w, &[0x81], extension, rm(destination), &immediate32(value, size)),
+			_ => panic!("Dev error! An arithmetic instruction between {destination} and {source} cannot be encoded."),
+		}
+	}
+
+	// imul, whose destination is a register
+	fn encode_mul(&mut self, destination: &Placeholder, source: &Placeholder)
+	{
+		let size = destination.data_type.size();
+		let (prefixes, rex_w) = operand_size(size);
+		let destination_register = register(destination);
+		match source.kind
+		{
+			PlaceholderKind::Integer(value) if fits_i8(value, size) => {
+				self.write_modrm(prefixes, rex_w, &[0x6B], destination_register, Rm::Register(destination_register), &[value as u8]);
+			},
+			PlaceholderKind::Integer(value) => self.write_modrm(prefixes, rex_w, &[0x69], destination_register, Rm::Register(destination_register), &immediate32(value, size)),
+			_ => self.write_modrm(prefixes, rex_w, &[0x0F, 0xAF], destination_register, rm(source), &[]),
+		}
+	}
+
+	fn encode_unary(&mut self, extension: u8, operand: &Placeholder)
+	{
+		let size = operand.data_type.size();
+		let (prefixes, rex_w) = operand_size(size);
+		self.write_extension(prefixes, rex_w, &[byte_opcode(0xF6, size)], extension, rm(operand), &[]);
+	}
+
+	// The count is an immediate or CL
+	fn encode_shift(&mut self, extension: u8, destination: &Placeholder, count: &Placeholder)
+	{
+		let size = destination.data_type.size();
+		let (prefixes, rex_w) = operand_size(size);
+		match count.kind
+		{
+			PlaceholderKind::Integer(count) => self.write_extension(prefixes, rex_w, &[byte_opcode(0xC0, size)], extension, rm(destination), &[count as u8]),
+			PlaceholderKind::Reg(Register::CL) => self.write_extension(prefixes, rex_w, &[byte_opcode(0xD2, size)], extension, rm(destination), &[]),
+			_ => panic!("Dev error! A shift by {count}, which is not an immediate or CL."),
+		}
+	}
+
+	fn encode_test(&mut self, lhs: &Placeholder, rhs: &Placeholder)
+	{
+		let size = lhs.data_type.size();
+		let (prefixes, rex_w) = operand_size(size);
+		match (lhs.kind, rhs.kind)
+		{
+			(_, PlaceholderKind::Reg(rhs)) => self.write_modrm(prefixes, rex_w, &[byte_opcode(0x84, size)], rhs, rm(lhs), &[]),
+			(PlaceholderKind::Reg(lhs), PlaceholderKind::Location(_)) => self.write_modrm(prefixes, rex_w, &[byte_opcode(0x84, size)], lhs, rm(rhs), &[]),
+			(_, PlaceholderKind::Integer(value)) => {
+				let value = if size == OP_BYTE { vec![value as u8] } else { immediate32(value, size) };
+				self.write_extension(prefixes, rex_w, &[byte_opcode(0xF6, size)], 0, rm(lhs), &value);
+			},
+			_ => panic!("Dev error! test {lhs}, {rhs} cannot be encoded."),
+		}
+	}
+
+	fn write_opcode(&mut self, opcode: &[u8], immediate: &[u8])
+	{
+		self.text.extend(opcode);
+		self.text.extend(immediate);
+	}
+
+	// An instruction whose register is in the low bits of the opcode (like push rbx)
+	fn write_opcode_register(&mut self, prefixes: &[u8], rex_w: bool, opcode: u8, register: Register, immediate: &[u8])
+	{
+		let number = register_number(register);
+		let rex = if rex_w { REX_W } else { 0 } | if number & 8 != 0 { REX_B } else { 0 };
+		self.text.extend(prefixes);
+		if rex != 0 || needs_rex(register)
+		{
+			self.text.push(REX | rex);
+		}
+		self.text.push(opcode + (number & 7));
+		self.text.extend(immediate);
+	}
+
+	// An instruction whose ModRM reg field is an extension of the opcode
+	fn write_extension(&mut self, prefixes: &[u8], rex_w: bool, opcode: &[u8], extension: u8, rm: Rm, immediate: &[u8])
+	{
+		self.write_modrm_fields(prefixes, rex_w, opcode, extension, false, rm, immediate);
+	}
+
+	// An instruction with a register in the ModRM reg field, and a register or memory in the rm field
+	fn write_modrm(&mut self, prefixes: &[u8], rex_w: bool, opcode: &[u8], register: Register, rm: Rm, immediate: &[u8])
+	{
+		self.write_modrm_fields(prefixes, rex_w, opcode, register_number(register), needs_rex(register), rm, immediate);
+	}
+
+	// Writes the prefixes, REX (if it is needed), the opcode, ModRM, SIB (if it is needed), the displacement and the immediate.
+	// Addresses in the data segment are RIP relative, so they are relative to the end of the instruction, which is after the immediate.
+	#[allow(clippy::too_many_arguments)]
+	fn write_modrm_fields(&mut self, prefixes: &[u8], rex_w: bool, opcode: &[u8], reg: u8, mut force_rex: bool, rm: Rm, immediate: &[u8])
+	{
+		let mut rex = if rex_w { REX_W } else { 0 } | if reg & 8 != 0 { REX_R } else { 0 };
+		let mut sib = None;
+		let mut displacement = Vec::new();
+		let mut data_offset = None;
+		let (mode, rm_bits) = match rm
+		{
+			Rm::Register(register) =>
+			{
+				let number = register_number(register);
+				force_rex |= needs_rex(register);
+				rex |= if number & 8 != 0 { REX_B } else { 0 };
+				(0b11, number & 7)
+			},
+			Rm::Memory(location) => match location.parts()
+			{
+				(LocationExprPart::Reg(base), LocationExprPart::Offset(offset), multiplier) =>
+				{
+					let base = register_number(base);
+					let offset = offset * multiplier.unwrap_or(1) as isize;
+					rex |= if base & 8 != 0 { REX_B } else { 0 };
+					if base & 7 == 4
+					{
+						sib = Some(0b00_100_100);
+					}
+
+					// RBP and R13 without a displacement mean RIP relative, so they get a displacement of 0
+					if offset == 0 && base & 7 != 5
+					{
+						(0b00, base & 7)
+					} else if offset as i8 as isize == offset
+					{
+						displacement.push(offset as u8);
+						(0b01, base & 7)
+					} else
+					{
+						displacement.extend((offset as i32).to_le_bytes());
+						(0b10, base & 7)
+					}
+				},
+				(LocationExprPart::Reg(base), LocationExprPart::Reg(index), multiplier) =>
+				{
+					let base = register_number(base);
+					let index = register_number(index);
+					if index == 4
+					{
+						panic!("Dev error! RSP cannot be the index of an address.");
+					}
+					rex |= if base & 8 != 0 { REX_B } else { 0 } | if index & 8 != 0 { REX_X } else { 0 };
+					sib = Some((multiplier.unwrap_or(1).trailing_zeros() as u8) << 6 | (index & 7) << 3 | base & 7);
+					if base & 7 == 5
+					{
+						displacement.push(0);
+						(0b01, 0b100)
+					} else
+					{
+						(0b00, 0b100)
+					}
+				},
+				(LocationExprPart::Offset(address), LocationExprPart::Offset(offset), multiplier) =>
+				{
+					sib = Some(0b00_100_101);
+					displacement.extend(((address + offset * multiplier.unwrap_or(1) as isize) as i32).to_le_bytes());
+					(0b00, 0b100)
+				},
+				(LocationExprPart::Labl(lable), LocationExprPart::Offset(offset), _) =>
+				{
+					data_offset = Some(self.data_lables[&lable.index] as i64 + offset as i64);
+					(0b00, 0b101)
+				},
+				_ => panic!("Dev error! The address {location} cannot be encoded."),
+			},
+		};
+
+		self.text.extend(prefixes);
+		if rex != 0 || force_rex
+		{
+			self.text.push(REX | rex);
+		}
+		self.text.extend(opcode);
+		self.text.push(mode << 6 | (reg & 7) << 3 | rm_bits);
+		self.text.extend(sib);
+		self.text.extend(displacement);
+		if let Some(data_offset) = data_offset
+		{
+			self.relocations.push(Relocation {
+				offset: self.text.len(),
+				target: RelocationTarget::Data,
+				kind: RelocationKind::Pc32,
+				addend: data_offset - 4 - immediate.len() as i64,
+			});
+			self.text.extend([0; 4]);
+		}
+		self.text.extend(immediate);
+	}
+
+	// A 32 bit field that is relative to its end, and is filled when the text segment is done
+	fn write_reference(&mut self, reference: Reference)
+	{
+		self.references.push((self.text.len(), reference));
+		self.text.extend([0; 4]);
+	}
+}
+
+fn rm(placeholder: &Placeholder) -> Rm
+{
+	return match placeholder.kind
+	{
+		PlaceholderKind::Reg(register) 		=> Rm::Register(register),
+		PlaceholderKind::Location(location) => Rm::Memory(location),
+		PlaceholderKind::Integer(_) 		=> panic!("Dev error! The immediate {placeholder} is used as a register or memory."),
+	};
+}
+
+fn register(placeholder: &Placeholder) -> Register
+{
+	let PlaceholderKind::Reg(register) = placeholder.kind else
+	{
+		panic!("Dev error! {placeholder} is used as a register.");
+	};
+	return register;
+}
+
+// The number of the register in the encoding of instructions. The registers from R8 (and from XMM8) need the REX prefix.
+fn register_number(register: Register) -> u8
+{
+	if !register.is_general()
+	{
+		return register as u8 - Register::XMM0 as u8;
+	}
+	if matches!(register, Register::AH | Register::BH | Register::CH | Register::DH)
+	{
+		panic!("Dev error! The high byte registers are not encoded.");
+	}
+
+	let general = [
+		Register::RAX, Register::RCX, Register::RDX, Register::RBX, Register::RSP, Register::RBP, Register::RSI, Register::RDI,
+		Register::R8, Register::R9, Register::R10, Register::R11, Register::R12, Register::R13, Register::R14, Register::R15,
+	];
+	return general.iter().position(|general| *general == register.base_register()).unwrap() as u8;
+}
+
+// SPL, BPL, SIL and DIL are encoded like AH, CH, DH and BH, unless the instruction has the REX prefix
+fn needs_rex(register: Register) -> bool
+{
+	return matches!(register, Register::SPL | Register::BPL | Register::SIL | Register::DIL);
+}
+
+// The operand size prefix for 16 bit instructions, and whether REX.W is needed for 64 bit instructions
+fn operand_size(size: OpSize) -> (&'static [u8], bool)
+{
+	return match size
+	{
+		OP_WORD 	=> (&[PREFIX_OPERAND_SIZE], false),
+		OP_QWORD 	=> (&[], true),
+		_ 			=> (&[], false),
+	};
+}
+
+// The instructions on bytes have the opcode before the one for the larger sizes
+fn byte_opcode(opcode: u8, size: OpSize) -> u8
+{
+	return if size == OP_BYTE { opcode } else { opcode + 1 };
+}
+
+fn float_prefix(data_type: Type) -> u8
+{
+	return if data_type.kind == TypeKind::F64 { PREFIX_F64 } else { PREFIX_F32 };
+}
+
+fn condition_code(condition: ConditionCode) -> u8
+{
+	return match condition
+	{
+		ConditionCode::E 	=> 0x4,
+		ConditionCode::Ne 	=> 0x5,
+		ConditionCode::G 	=> 0xF,
+		ConditionCode::A 	=> 0x7,
+		ConditionCode::L 	=> 0xC,
+		ConditionCode::B 	=> 0x2,
+		ConditionCode::Ge 	=> 0xD,
+		ConditionCode::Ae 	=> 0x3,
+		ConditionCode::Le 	=> 0xE,
+		ConditionCode::Be 	=> 0x6,
+	};
+}
+
+fn immediate(value: u64, size: OpSize) -> Vec<u8>
+{
+	return value.to_le_bytes()[..size as usize].to_vec();
+}
+
+// The immediate of an instruction whose operand size is 16, 32 or 64 bits. 64 bit instructions take a sign extended 32 bit immediate.
+fn immediate32(value: u64, size: OpSize) -> Vec<u8>
+{
+	if size == OP_QWORD && !fits_i32(value)
+	{
+		panic!("Dev error! The immediate {value} does not fit in a sign extended 32 bit immediate.");
+	}
+	return immediate(value, size.min(OP_DWORD));
+}
+
+// Whether the value (of an operand of the size) is the same when it is truncated into a byte and sign extended
+fn fits_i8(value: u64, size: OpSize) -> bool
+{
+	let shift = 64 - 8 * size as u32;
+	let value = ((value << shift) as i64) >> shift;
+	return value as i8 as i64 == value;
+}
+
+fn fits_i32(value: u64) -> bool
+{
+	return value as i64 as i32 as i64 == value as i64;
+}
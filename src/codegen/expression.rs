@@ -2,409 +2,320 @@ use super::*;
 
 impl<'a> CodeGen<'a>
 {
-	pub fn gen_expression(&mut self, expression: &BinExpr, locals: &Vec<Variable>) -> Placeholder
+	pub fn gen_instruction(&mut self, function: &mir::Function, instruction: &Instruction)
 	{
-		return self.gen_bin_expr(expression, locals);
-	}
-	
-	// Will return a pointer to the result
-	pub fn gen_value_access(&mut self, locals: &Vec<Variable>, value: &Value) -> Placeholder
-	{
-		match value
+		match instruction
 		{
-			Value::Var(variable_index) =>
+			Instruction::Copy { destination, source } =>
 			{
-				let variable = locals[*variable_index as usize];
-				return Placeholder::new(
-					PlaceholderKind::Location(LocationExpr::new(
-						LocationExprPart::Reg(Register::RBP),
-						LocationExprPart::Offset(variable.location), 
-						None,
-					)), 
-					variable.data_type
-				);
+				let data_type = function.register_type(*destination);
+				let value = self.load_operand(function, Register::default_for_type(data_type), *source, data_type);
+				self.instr_mov(&self.register_home(function, *destination), &value);
 			},
-			Value::Dereference(info) =>
+			Instruction::Binary { operator, destination, lhs, rhs } => self.gen_binary(function, *operator, *destination, *lhs, *rhs),
+			Instruction::Unary { operator, destination, source } => self.gen_unary(function, *operator, *destination, *source),
+			Instruction::Compare { condition, data_type, destination, lhs, rhs } =>
 			{
-				if info.dereference_count == 1
-				{
-					let expression = self.gen_expression(&info.expression, locals);
-					return Placeholder::new(
-						PlaceholderKind::Location(LocationExpr::from_placeholder(&expression)), 
-						info.data_type.dereference(1)
-					);
-				}
-
-				let mut info = info.clone();
-				info.dereference_count -= 1;
-				let expression = self.gen_pointer_dereference(locals, &info);
-				return Placeholder::new(
-					PlaceholderKind::Location(LocationExpr::from_placeholder(&expression)), 
-					info.data_type.dereference(info.dereference_count)
-				);
-			}
-			_ => panic!("Dev error! gen_value_access() called with none-writable value. {:#?}", value),
-		}
-	}
+				let lhs = self.load_operand(function, Register::default_for_type(*data_type), *lhs, *data_type);
+				let rhs = self.operand_source(function, *rhs, *data_type);
+				self.instr_cmp(&lhs, &rhs);
 
-	fn gen_value(&mut self, value: &Value, locals: &Vec<Variable>) -> Placeholder 
-	{
-		return match value
-		{
-			Value::I8(number) 			=> Placeholder::new(PlaceholderKind::Integer(*number as u64), Type::new(TypeKind::I8)),
-			Value::U8(number) 			=> Placeholder::new(PlaceholderKind::Integer(*number as u64), Type::new(TypeKind::U8)),
-			Value::I16(number) 		=> Placeholder::new(PlaceholderKind::Integer(*number as u64), Type::new(TypeKind::I16)),
-			Value::U16(number) 		=> Placeholder::new(PlaceholderKind::Integer(*number as u64), Type::new(TypeKind::U16)),
-			Value::I32(number) 		=> Placeholder::new(PlaceholderKind::Integer(*number as u64), Type::new(TypeKind::I32)),
-			Value::U32(number) 		=> Placeholder::new(PlaceholderKind::Integer(*number as u64), Type::new(TypeKind::U32)),
-			Value::I64(number) 		=> Placeholder::new(PlaceholderKind::Integer(*number as u64), Type::new(TypeKind::I64)),
-			Value::U64(number) 		=> Placeholder::new(PlaceholderKind::Integer(*number as u64), Type::new(TypeKind::U64)),
-			Value::F64(_) | Value::F32(_) 	=> 
+				// Floats are compared with ucomis, which sets the flags like an unsigned comparison does
+				let al = Placeholder::new(PlaceholderKind::Reg(Register::AL), Type::new(TypeKind::U8));
+				self.instr_setcc(*condition, data_type.is_signed() && data_type.is_integer(), &al);
+				self.instr_mov(&self.register_home(function, *destination), &al);
+			},
+			Instruction::Convert { destination, source, from } => self.gen_convert(function, *destination, *source, *from),
+			Instruction::Load { destination, address } =>
 			{
-				let lable = self.decl_var_data_seg(value);
-				Placeholder::new(
-					PlaceholderKind::Location(
-						LocationExpr::new(
-							LocationExprPart::Labl(lable), 
-							LocationExprPart::Offset(0), 
-							None
-						),
-					),
-					self.value_type(value, locals),
-				)
-			}
-			Value::Var(_) 											=> self.gen_value_access(locals, value),
-			Value::FuncCall(function_call_info) 	=> self.gen_function_call(locals, function_call_info).unwrap(),
-			Value::Dereference(info)				=> self.gen_pointer_dereference(locals, info),
-		}	
-	}
-	
-	fn gen_bin_operation(&mut self, operator: BinExprOperator, lhs: &Placeholder, rhs: &Placeholder) -> Placeholder 
-	{
-		if operator.is_boolean()
-		{
-			
-			if operator == BinExprOperator::BoolAnd || operator == BinExprOperator::BoolOr
+				let data_type = function.register_type(*destination);
+				let address = self.gen_address(function, address, data_type);
+				let value = Placeholder::new(PlaceholderKind::Reg(Register::default_for_type(data_type)), data_type);
+				self.instr_mov(&value, &address);
+				self.instr_mov(&self.register_home(function, *destination), &value);
+			},
+			Instruction::Store { data_type, address, value } =>
 			{
-				// TODO: Make an is_writable function in Placeholder, and check if lhs is a writable, so no need to move to RAX and stuff
-				let dst_register = Register::from_op_size(Register::RAX, lhs.data_type.size());
-				let destination = Placeholder::new(PlaceholderKind::Reg(dst_register), lhs.data_type);
-				self.instr_mov(&destination, lhs);
-				match operator
-				{
-					BinExprOperator::BoolAnd => self.instr_and(&destination, rhs),
-					BinExprOperator::BoolOr  => self.instr_or(&destination, rhs),
-					_ => panic!("Rust doesnt work"),
-				}
-				return destination;
-			}
-			
-			let destination = Placeholder::new(PlaceholderKind::Reg(Register::AL), Type::new(TypeKind::U8));
-			self.instr_cmp(lhs, rhs); 
-			
-			match operator
+				let value = self.operand_source(function, *value, *data_type);
+				let value = if value.is_register() || value.is_constant() { value } else { self.load_operand_placeholder(&value) };
+				let address = self.gen_address(function, address, *data_type);
+				self.instr_mov(&address, &value);
+			},
+			Instruction::SlotAddress { destination, slot } =>
 			{
-				BinExprOperator::BoolEq 		=> self.instr_sete(&destination),
-				BinExprOperator::BoolNotEq		=> self.instr_setne(&destination),
-				BinExprOperator::BoolGreater	=> self.instr_setg(&destination),
-				BinExprOperator::BoolLess		=> self.instr_setl(&destination),
-				BinExprOperator::BoolGreaterEq	=> self.instr_setge(&destination),
-				BinExprOperator::BoolLessEq		=> self.instr_setle(&destination),
-				_ => panic!("Rust doesnt work"),
-			}
-			return destination;
-		} else
-		{
-			// TODO: Make an is_writable function in Placeholder, and check if lhs is a writable, so no need to move to RAX and stuff
-			let dst_register = Register::default_for_type(lhs.data_type);
-			let destination = Placeholder::new(PlaceholderKind::Reg(dst_register), lhs.data_type);
-			self.instr_mov(&destination, lhs);
-			
-			match operator {
-				BinExprOperator::BitwiseOr 			=> self.instr_or(&destination, rhs),
-				BinExprOperator::BitwiseXor 		=> self.instr_xor(&destination, rhs),
-				BinExprOperator::BitwiseAnd 		=> self.instr_and(&destination, rhs),
-				BinExprOperator::BitwiseRightShift 	=> self.instr_shr(&destination, rhs),
-				BinExprOperator::BitwiseLeftShift 	=> self.instr_shl(&destination, rhs),
-				BinExprOperator::Add 				=> self.instr_add(&destination, rhs),
-				BinExprOperator::Sub 				=> self.instr_sub(&destination, rhs),
-				BinExprOperator::Mul 				=> self.instr_mul(&destination, rhs),
-				BinExprOperator::Div 				=> self.instr_div(&destination, rhs, false),
-				BinExprOperator::Modulo 			=> self.instr_div(&destination, rhs, true),
-				_ => panic!("Rust doesnt work."),
-			}
-			
-			return destination;
+				let rax = Placeholder::new(PlaceholderKind::Reg(Register::RAX), Type::new(TypeKind::U64));
+				self.instr_lea(&rax, &self.slot_location(function, *slot));
+				self.instr_mov(&self.register_home(function, *destination), &rax);
+			},
+			Instruction::FunctionAddress { destination, function: index } =>
+			{
+				let rax = Placeholder::new(PlaceholderKind::Reg(Register::RAX), Type::new(TypeKind::U64));
+				self.instr_lea_function(&rax, &self.program.functions[index.index()].identifier);
+				self.instr_mov(&self.register_home(function, *destination), &rax);
+			},
+			Instruction::Call { destination, target, signature, arguments, argument_types } =>
+			{
+				self.gen_call(function, *destination, *target, *signature, arguments, argument_types);
+			},
 		}
 	}
 
-	fn gen_bin_self_operation(&mut self, locals: &Vec<Variable>, operation: &BinExprSelfOperation) -> Placeholder
+	fn gen_binary(&mut self, function: &mir::Function, operator: BinaryOperator, destination: RegisterId, lhs: Operand, rhs: Operand)
 	{
-		let mut expression = self.gen_bin_expr_recurse(locals, &operation.expression);
-		if operation.operator != BinExprOperator::AddressOf
+		let data_type = function.register_type(destination);
+		if !data_type.is_integer()
 		{
-			if !expression.is_register()
+			let result = self.load_operand(function, Register::XMM0, lhs, data_type);
+			let rhs = self.operand_source(function, rhs, data_type);
+			match operator
 			{
-				let new_placeholder = Placeholder::new(
-					PlaceholderKind::Reg(Register::default_for_type(expression.data_type)), 
-					expression.data_type
-				);
-	
-				self.instr_mov(&new_placeholder, &expression);
-				expression = new_placeholder;
+				BinaryOperator::Add => self.instr_add(&result, &rhs),
+				BinaryOperator::Sub => self.instr_sub(&result, &rhs),
+				BinaryOperator::Mul => self.instr_mul(&result, &rhs),
+				BinaryOperator::Div => self.instr_div_float(&result, &rhs),
+				_ => panic!("Dev error! The operator {:?} was used on {data_type}.", operator),
 			}
+			self.instr_mov(&self.register_home(function, destination), &result);
+			return;
 		}
 
-		match operation.operator {
-			BinExprOperator::BitwiseNot 	=> self.instr_not(&expression),
-			BinExprOperator::BoolNot		=> {self.instr_test(&expression, &expression); self.instr_setz(&expression); },
-			BinExprOperator::AddressOf		=>
-			{
-				let rax = Placeholder::new(PlaceholderKind::Reg(Register::RAX), Type::new(TypeKind::U64));
-				self.instr_lea(&rax, &expression);
-				return rax;
-			}
-			_ => panic!("gen_bin_expr_recurse(), BinExprPart::SelfOperation.operator, not a binary operator.\n{:#?}", operation),
-		}
-		return expression;
-	}
-	
-	fn gen_bin_expr(&mut self, bin_expr: &BinExpr, locals: &Vec<Variable>) -> Placeholder
-	{
-		return self.gen_bin_expr_recurse(locals, &bin_expr.root)		
-	}
-	
-	fn gen_bin_expr_recurse(&mut self, locals: &Vec<Variable>, expr_part: &BinExprPart) -> Placeholder
-	{
-		match expr_part {
-			BinExprPart::Val(value) => return self.gen_value(value, locals),
-			BinExprPart::TypeCast(type_cast_info) => return self.gen_type_cast(locals, type_cast_info),
-			BinExprPart::SelfOperation(operation) => return self.gen_bin_self_operation(locals, operation),
-			BinExprPart::Operation(operation) =>
+		let result = match operator
+		{
+			BinaryOperator::Mul 							=> self.gen_multiplication(function, data_type, lhs, rhs),
+			BinaryOperator::Div | BinaryOperator::Modulo 	=> self.gen_division(function, operator, data_type, lhs, rhs),
+			BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight =>
 			{
-				let mut lhs_allocated_reg = None;
-				let mut lhs = self.gen_bin_expr_recurse(locals, &operation.lhs);
-				if lhs.is_register()
-				{
-					lhs_allocated_reg = Some(self.reg_alloc_allocate(lhs.data_type).unwrap());
-					let new_lhs = Placeholder::new(PlaceholderKind::Reg(lhs_allocated_reg.unwrap()), lhs.data_type);
-					self.instr_mov(&new_lhs, &lhs);
-					lhs = new_lhs;
-				}
+				let result = self.load_operand(function, Register::RAX, lhs, data_type);
 
-				let mut rhs_allocated_reg = None;
-				let mut rhs = self.gen_bin_expr_recurse(locals, &operation.rhs);
-				if rhs.is_register()
+				// The processor uses only the low 5 bits of the count (6 bits for 64 bit operands)
+				let count = if let Operand::Integer(count) = rhs
+				{
+					Placeholder::new(PlaceholderKind::Integer(count & if data_type.size() == OP_QWORD { 63 } else { 31 }), Type::new(TypeKind::U8))
+				} else
 				{
-					rhs_allocated_reg = Some(self.reg_alloc_allocate(rhs.data_type).unwrap());
-					let new_rhs = Placeholder::new(PlaceholderKind::Reg(rhs_allocated_reg.unwrap()), rhs.data_type);
-					self.instr_mov(&new_rhs, &rhs);
-					rhs = new_rhs;
-				}	
-				
-				let result = self.gen_bin_operation(operation.operator, &lhs, &rhs);
+					self.load_operand(function, Register::RCX, rhs, data_type);
+					Placeholder::new(PlaceholderKind::Reg(Register::CL), Type::new(TypeKind::U8))
+				};
 
-				if let Some(allocated_register) = lhs_allocated_reg
+				if operator == BinaryOperator::ShiftLeft
+				{
+					self.instr_shl(&result, &count);
+				} else
 				{
-					self.reg_alloc_free(allocated_register);
+					self.instr_shr(&result, &count);
 				}
-
-				if let Some(allocated_register) = rhs_allocated_reg
+				result
+			},
+			_ =>
+			{
+				let result = self.load_operand(function, Register::RAX, lhs, data_type);
+				let rhs = self.operand_source(function, rhs, data_type);
+				match operator
 				{
-					self.reg_alloc_free(allocated_register);
+					BinaryOperator::Add => self.instr_add(&result, &rhs),
+					BinaryOperator::Sub => self.instr_sub(&result, &rhs),
+					BinaryOperator::And => self.instr_and(&result, &rhs),
+					BinaryOperator::Or 	=> self.instr_or(&result, &rhs),
+					BinaryOperator::Xor => self.instr_xor(&result, &rhs),
+					_ 					=> panic!("Rust doesnt work."),
 				}
-				return result;
+				result
 			},
-		}
+		};
+		self.instr_mov(&self.register_home(function, destination), &result);
 	}
 
-	fn gen_type_cast(&mut self, locals: &Vec<Variable>, type_cast_info: &TypeCastInfo) -> Placeholder
+	// imul has no byte form with two operands, so small integers are multiplied as 32 bit integers. The low bits of the result are the same.
+	fn gen_multiplication(&mut self, function: &mir::Function, data_type: Type, lhs: Operand, rhs: Operand) -> Placeholder
 	{
-		let mut expression = self.gen_bin_expr_recurse(locals, &type_cast_info.expression);
+		let size = data_type.size().max(OP_DWORD);
+		let result = self.load_extended(function, Register::RAX, lhs, data_type, size);
+		let rhs = self.load_extended(function, Register::RCX, rhs, data_type, size);
+		self.instr_mul(&result, &rhs);
+		return result.of_type(data_type);
+	}
+
+	// Integers smaller than 64 bits are divided as 32 bit integers (which cant overflow the quotient like an i8 -128 / -1 does),
+	// by dividing EDX:EAX (or RDX:RAX) by ECX (or RCX). The quotient is in RAX and the remainder in RDX.
+	fn gen_division(&mut self, function: &mir::Function, operator: BinaryOperator, data_type: Type, lhs: Operand, rhs: Operand) -> Placeholder
+	{
+		let size = data_type.size().max(OP_DWORD);
+		let dividend = self.load_extended(function, Register::RAX, lhs, data_type, size);
+		let divisor = self.load_extended(function, Register::RCX, rhs, data_type, size);
+		if data_type.is_signed()
+		{
+			if size == OP_QWORD { self.instr_cqo(); } else { self.instr_cdq(); }
+		} else
+		{
+			let edx = Placeholder::new(PlaceholderKind::Reg(Register::EDX), Type::new(TypeKind::U32));
+			self.instr_xor(&edx, &edx);
+		}
+		self.instr_div(&divisor);
 
-		if type_cast_info.from_type.kind == TypeKind::Pointer && type_cast_info.from_type.kind == TypeKind::Pointer
+		if operator == BinaryOperator::Modulo
 		{
-			return expression.of_type(type_cast_info.into_type);
+			return Placeholder::new(PlaceholderKind::Reg(Register::RDX.of_size(data_type.size())), data_type);
 		}
+		return dividend.of_type(data_type);
+	}
 
-		if type_cast_info.from_type.is_integer() && type_cast_info.into_type.is_integer()
+	fn gen_unary(&mut self, function: &mir::Function, operator: UnaryOperator, destination: RegisterId, source: Operand)
+	{
+		let data_type = function.register_type(destination);
+		let result = self.load_operand(function, Register::RAX, source, data_type);
+		match operator
 		{
-			if !type_cast_info.from_type.is_signed() && !type_cast_info.into_type.is_signed()
+			UnaryOperator::Not => self.instr_not(&result),
+			UnaryOperator::IsZero =>
 			{
-				if type_cast_info.into_type.size() < type_cast_info.from_type.size()
-				{
-					return expression.of_type(type_cast_info.into_type);
-				}
-
-				// Here we know that sizeof(into_type) > sizeof(from_type), 
-				// so the casting is just a bitwise AND operation (to remove crap that was in the register)
-				let rax = Placeholder::new(
-					PlaceholderKind::Reg(Register::RAX.of_size(type_cast_info.into_type.size())), 
-					type_cast_info.into_type
-				);
-
-				match type_cast_info.into_type.kind
+				let al = Placeholder::new(PlaceholderKind::Reg(Register::AL), Type::new(TypeKind::U8));
+				self.instr_test(&result, &result);
+				self.instr_setz(&al);
+				if data_type.size() != OP_BYTE
 				{
-					TypeKind::U16 | TypeKind::U32 => self.instr_movzx(&rax, &expression),
-					TypeKind::U64 | TypeKind::Pointer =>
-					{
-						if type_cast_info.from_type == Type::new(TypeKind::U16) || type_cast_info.from_type == Type::new(TypeKind::U8)
-						{
-							self.instr_movzx(&rax, &expression);
-						} else
-						{
-							// TODO: When switching to the GNU assembler, replace this crap with instr_and();
-							if expression.is_register_eq(Register::RAX.of_size(expression.data_type.size()))
-							{
-								let allocated_reg = self.reg_alloc_allocate(expression.data_type).unwrap();
-								let placeholder = Placeholder::new(PlaceholderKind::Reg(allocated_reg), expression.data_type);
-								self.instr_mov(
-									&placeholder,
-									&expression 
-								);
-								self.reg_alloc_free(allocated_reg);
-								expression = placeholder;
-							}
-							self.instr_xor(&rax, &rax);
-							self.instr_mov(&rax.of_type(expression.data_type), &expression);
-
-						} 
-					},
-					_ => panic!("type_cast_info.into_type cannot be U8 as it must be greater than type_cast_info.from_type"),
+					self.instr_movzx(&result.of_type(Type::new(TypeKind::U32)), &al);
 				}
-				
-				return rax;
+			},
+		}
+		self.instr_mov(&self.register_home(function, destination), &result);
+	}
 
-			// If one is signed and the other is unsigned
-			} else
+	fn gen_convert(&mut self, function: &mir::Function, destination: RegisterId, source: Operand, from: Type)
+	{
+		let into = function.register_type(destination);
+		let result = if from.is_integer() && into.is_integer()
+		{
+			self.load_extended(function, Register::RAX, source, from, OP_QWORD).of_type(into)
+		} else if from.is_integer()
+		{
+			// cvtsi2sd converts a signed integer, so unsigned 32 bit integers are extended into 64 bits first
+			let rax = self.load_extended(function, Register::RAX, source, from, OP_QWORD);
+			let xmm0 = Placeholder::new(PlaceholderKind::Reg(Register::XMM0), into);
+			self.instr_cvtsi2sf(&xmm0, &rax);
+			xmm0
+		} else if into.is_integer()
+		{
+			let xmm0 = self.load_operand(function, Register::XMM0, source, from);
+			let rax = Placeholder::new(PlaceholderKind::Reg(Register::RAX), Type::new(TypeKind::I64));
+			self.instr_cvttsf2si(&rax, &xmm0);
+			rax.of_type(into)
+		} else
+		{
+			let xmm0 = self.load_operand(function, Register::XMM0, source, from);
+			if from != into
 			{
-				if type_cast_info.into_type.size() <= type_cast_info.from_type.size()
-				{
-					return expression.of_type(type_cast_info.into_type);
-				}
-
-				// if true, then from_type is unsigned. Also we know that sizeof(into_type) > sizeof(from_type)
-				if type_cast_info.into_type.is_signed()
-				{
-					let rax = Placeholder::new(
-						PlaceholderKind::Reg(Register::RAX.of_size(type_cast_info.into_type.size())), 
-						type_cast_info.into_type
-					);
+				self.instr_cvtsf2sf(&xmm0.of_type(into), &xmm0);
+			}
+			xmm0.of_type(into)
+		};
+		self.instr_mov(&self.register_home(function, destination), &result);
+	}
 
-					match type_cast_info.into_type.kind
-					{
-						TypeKind::I16 | TypeKind::I32 => self.instr_movzx(&rax, &expression),
-						TypeKind::I64 =>
-						{
-							if type_cast_info.from_type == Type::new(TypeKind::I16) || type_cast_info.from_type == Type::new(TypeKind::I8)
-							{
-								self.instr_movsx(&rax, &expression);
-							} else
-							{
-								if !expression.is_register_eq(Register::RAX.of_size(expression.data_type.size()))
-								{
-									self.instr_mov(
-										&Placeholder::new(
-											PlaceholderKind::Reg(Register::RAX.of_size(expression.data_type.size())), 
-											expression.data_type
-										), 
-										&expression
-									);
-								}
-								self.instr_cdqe();
-							} 
-						},
-						_ => panic!("type_cast_info.into_type cannot be U8 as it must be greater than type_cast_info.from_type"),
-					}
-					
-					return rax;	
+	// The memory that an address points to. A pointer in a virtual register is loaded into R11 first.
+	fn gen_address(&mut self, function: &mir::Function, address: &Address, data_type: Type) -> Placeholder
+	{
+		match *address
+		{
+			Address::Slot(slot) => return self.slot_location(function, slot).of_type(data_type),
+			Address::Register(pointer) =>
+			{
+				let r11 = Placeholder::new(PlaceholderKind::Reg(Register::R11), Type::new(TypeKind::U64));
+				self.instr_mov(&r11, &self.register_home(function, pointer).of_type(Type::new(TypeKind::U64)));
+				return Placeholder::new(PlaceholderKind::Location(LocationExpr::from_placeholder(&r11)), data_type);
+			},
+		}
+	}
 
-				// Means into_type is unsigned and from_type is signed and sizeof(into_type) > sizeof(from_type)
-				} else
+	// A placeholder that can be used as the source operand of an instruction whose destination is a register.
+	// 64 bit constants that dont fit in a sign extended 32 bit immediate are loaded into RCX.
+	pub fn operand_source(&mut self, function: &mir::Function, operand: Operand, data_type: Type) -> Placeholder
+	{
+		match operand
+		{
+			Operand::Register(register) => return self.register_home(function, register),
+			Operand::Integer(value) =>
+			{
+				let constant = Placeholder::new(PlaceholderKind::Integer(value), data_type);
+				if data_type.size() == OP_QWORD && value as i64 != value as i32 as i64
 				{
-					let rax = Placeholder::new(
-						PlaceholderKind::Reg(Register::RAX.of_size(type_cast_info.into_type.size())), 
-						type_cast_info.into_type
-					);
-
-					if expression.is_register_eq(Register::RAX.of_size(expression.data_type.size()))
-					{
-						let allocated_reg = self.reg_alloc_allocate(expression.data_type).unwrap();
-						let placeholder = Placeholder::new(PlaceholderKind::Reg(allocated_reg), expression.data_type);
-						self.instr_mov(&placeholder, &expression);
-						self.reg_alloc_free(allocated_reg);
-						expression = placeholder
-					}
-
-					self.instr_xor(&rax, &rax);
-					self.instr_mov(
-						&Placeholder::new(PlaceholderKind::Reg(Register::RAX.of_size(expression.data_type.size())), expression.data_type), 
-						&expression
-					);
-
-					return rax;
+					let rcx = Placeholder::new(PlaceholderKind::Reg(Register::RCX), data_type);
+					self.instr_mov(&rcx, &constant);
+					return rcx;
 				}
-			}
+				return constant;
+			},
+			Operand::Float(number) =>
+			{
+				let value = if data_type.kind == TypeKind::F32 { Value::F32(number as f32) } else { Value::F64(number) };
+				let lable = self.decl_var_data_seg(&value);
+				return Placeholder::new(
+					PlaceholderKind::Location(LocationExpr::new(LocationExprPart::Labl(lable), LocationExprPart::Offset(0), None)),
+					data_type
+				);
+			},
+		}
+	}
 
-		// Float into integer
-		} else if type_cast_info.into_type.is_integer() && !type_cast_info.from_type.is_integer()
-		{
-			let rax = Placeholder::new(PlaceholderKind::Reg(Register::RAX.of_size(type_cast_info.into_type.size())), type_cast_info.into_type);
-			self.instr_cvttsf2si(
-					&rax,
-				&expression
-			);
-			return rax;
-		// Integer into float
-		} else if !type_cast_info.into_type.is_integer() && type_cast_info.from_type.is_integer()
+	// Moves the operand into the register, and returns the register (of the size of the data type)
+	pub fn load_operand(&mut self, function: &mir::Function, register: Register, operand: Operand, data_type: Type) -> Placeholder
+	{
+		let data_type = if let Operand::Register(source) = operand { function.register_type(source) } else { data_type };
+		let destination = Placeholder::new(PlaceholderKind::Reg(register.of_size(data_type.size())), data_type);
+		let source = match operand
 		{
-			let xmm0 = Placeholder::new(PlaceholderKind::Reg(Register::XMM0), type_cast_info.into_type);
-			self.instr_cvtsi2sf(
-				&xmm0, 
-				&expression
-			);
-			return xmm0;
+			Operand::Integer(value) => Placeholder::new(PlaceholderKind::Integer(value), data_type),
+			_ 						=> self.operand_source(function, operand, data_type),
+		};
+		self.instr_mov(&destination, &source);
+		return destination;
+	}
 
-		// F32 into F64 or F64 into F32
-		} else
-		{
-			let xmm0 = Placeholder::new(PlaceholderKind::Reg(Register::XMM0), type_cast_info.into_type);
-			self.instr_cvtsf2sf(
-				&xmm0,	
-				&expression
-			);
-			return xmm0;
-		}
+	// Moves a value from memory into the default register of its data type
+	fn load_operand_placeholder(&mut self, source: &Placeholder) -> Placeholder
+	{
+		let destination = Placeholder::new(PlaceholderKind::Reg(Register::default_for_type(source.data_type)), source.data_type);
+		self.instr_mov(&destination, source);
+		return destination;
 	}
 
-	fn gen_pointer_dereference(&mut self, locals: &Vec<Variable>, dereference_info: &DereferenceInfo) -> Placeholder
+	// Moves an integer operand into the register, sign or zero extended (by the signedness of its data type) into the given size (4 or 8 bytes).
+	// Writing into a 32 bit register clears the high 32 bits, so the result is also extended into 64 bits if the operand is unsigned.
+	pub fn load_extended(&mut self, function: &mir::Function, register: Register, operand: Operand, data_type: Type, size: OpSize) -> Placeholder
 	{
-		let expression = self.gen_expression(&dereference_info.expression, locals);
-		let mut result = Placeholder::new(PlaceholderKind::Reg(Register::RAX), dereference_info.data_type);
-		let start_dereference_cnt;
-		if expression.is_location()
+		let extended_type = Type::new(match (size, data_type.is_signed())
 		{
-			start_dereference_cnt = 0;
-			self.instr_mov(&result, &expression);
-		} else
+			(OP_QWORD, true) 	=> TypeKind::I64,
+			(OP_QWORD, false) 	=> TypeKind::U64,
+			(_, true) 			=> TypeKind::I32,
+			(_, false) 			=> TypeKind::U32,
+		});
+		let destination = Placeholder::new(PlaceholderKind::Reg(register.of_size(size)), extended_type);
+		let source = match operand
 		{
-			start_dereference_cnt = 1;
-			self.instr_mov(
-				&result, 
-				&Placeholder::new(PlaceholderKind::Location(LocationExpr::from_placeholder(&expression)), expression.data_type)
-			);
-			result = result.of_type(result.data_type.dereference(1));
-		}
+			Operand::Integer(value) =>
+			{
+				let value = integer_value(value, data_type) as u64;
+				let value = if size == OP_QWORD { value } else { value as u32 as u64 };
+				self.instr_mov(&destination, &Placeholder::new(PlaceholderKind::Integer(value), extended_type));
+				return destination;
+			},
+			_ => self.operand_source(function, operand, data_type),
+		};
 
-		for _ in start_dereference_cnt..dereference_info.dereference_count
+		let source_size = source.data_type.size();
+		if source_size == size
+		{
+			self.instr_mov(&destination, &source);
+		} else if source_size < OP_DWORD
 		{
-			self.instr_mov(
-				&result, 
-				&Placeholder::new(PlaceholderKind::Location(LocationExpr::from_placeholder(&result)), result.data_type)
-			);
-			result = result.of_type(result.data_type.dereference(1));
+			if source.data_type.is_signed() { self.instr_movsx(&destination, &source); } else { self.instr_movzx(&destination, &source); }
+		} else if source.data_type.is_signed()
+		{
+			self.instr_movsxd(&destination, &source);
+		} else
+		{
+			self.instr_mov(&destination.of_type(source.data_type), &source);
 		}
-
-		return result;
+		return destination;
 	}
-}
\ No newline at end of file
+}
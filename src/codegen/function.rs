@@ -2,16 +2,21 @@ use super::*;
 
 impl<'a> CodeGen<'a>
 {
-	pub fn gen_function(&mut self, function: &Function)
+	pub fn gen_function(&mut self, function: &mir::Function)
 	{
 		self.decl_attribute(&function.identifier, function.attributes);
-		
-		if function.code_block.statements.len() == 0
+
+		if function.is_extern()
 		{
 			return;
 		}
 
+		let text_start = self.text_segment.len();
 		self.write_lable_text_seg(&function.identifier);
+		self.function_epilogue = Some(self.generate_text_seg_lable());
+		self.block_lables = (0..function.blocks.len()).map(|_| self.generate_text_seg_lable()).collect();
+		self.allocate_registers(function);
+		let frame_size = self.layout_frame(function);
 
 		// Save stack frame
 		self.instr_push(&Placeholder::new(PlaceholderKind::Reg(Register::RBP), Type::new(TypeKind::U64)));
@@ -19,15 +24,17 @@ impl<'a> CodeGen<'a>
 			&Placeholder::new(PlaceholderKind::Reg(Register::RBP), Type::new(TypeKind::U64)),
 			&Placeholder::new(PlaceholderKind::Reg(Register::RSP), Type::new(TypeKind::U64))
 		);
-		if function.code_block.stack_size != 0
+		if frame_size != 0
 		{
 			self.instr_sub(
 				&Placeholder::new(PlaceholderKind::Reg(Register::RSP), Type::new(TypeKind::U64)),
-				&Placeholder::new(PlaceholderKind::Integer(function.code_block.stack_size as u64), Type::new(TypeKind::U64)), 
+				&Placeholder::new(PlaceholderKind::Integer(frame_size as u64), Type::new(TypeKind::U64)),
 			);
 		}
-
-		self.instr_add_spacing();
+		for (register, location) in self.saved_registers.clone()
+		{
+			self.instr_mov(&Self::frame_location(location, Type::new(TypeKind::U64)), &Placeholder::new(PlaceholderKind::Reg(register), Type::new(TypeKind::U64)));
+		}
 
 		// Store parameters according to the functions calling convenction.
 		if function.attributes & attribute::SYS_V_ABI_X86_64 != 0
@@ -35,162 +42,324 @@ impl<'a> CodeGen<'a>
 			self.store_parameters_sys_v_abi_x86_64(function);
 		}
 
-		for statement in &function.code_block.statements
+		for index in 0..function.blocks.len()
 		{
-			self.gen_statement(statement, &function.locals);
+			self.gen_block(function, index);
 		}
 
-		self.gen_function_return();
+		self.write_lable(self.function_epilogue.unwrap());
+		self.restore_frame();
+		self.instr_ret();
+		self.function_epilogue = None;
+
+		// The peephole optimizer works on a single function at a time
+		if self.optimization_level > mir::OPTIMIZATION_LEVEL_NONE
+		{
+			let mut text = self.text_segment.split_off(text_start);
+			peephole::optimize(&mut text);
+			self.text_segment.append(&mut text);
+		}
 	}
 
-	pub fn gen_function_return(&mut self)
+	// Restores the callee-saved registers and the stack frame of the caller, before returning (or jumping into a tail call)
+	fn restore_frame(&mut self)
 	{
+		for (register, location) in self.saved_registers.clone()
+		{
+			self.instr_mov(&Placeholder::new(PlaceholderKind::Reg(register), Type::new(TypeKind::U64)), &Self::frame_location(location, Type::new(TypeKind::U64)));
+		}
 		self.instr_mov(
 			&Placeholder::new(PlaceholderKind::Reg(Register::RSP), Type::new(TypeKind::U64)),
 			&Placeholder::new(PlaceholderKind::Reg(Register::RBP), Type::new(TypeKind::U64))
 		);
 		self.instr_pop(&Placeholder::new(PlaceholderKind::Reg(Register::RBP), Type::new(TypeKind::U64)));
-		self.instr_ret();
 	}
 
-	pub fn gen_function_call(&mut self, locals: &Vec<Variable>, function_call_info: &FunctionCallInfo) -> Option<Placeholder>
+	// Gives each stack slot and each virtual register a location below RBP, aligned on its size, and a location for each callee-saved register that is used.
+	// Parameters that were passed on the stack are used where the caller has put them. Returns the size of the frame, aligned on 16 bytes.
+	fn layout_frame(&mut self, function: &mir::Function) -> usize
 	{
-		let function = &self.ir.functions[function_call_info.index as usize];
-		
-		self.reg_alloc_save_used();
+		let mut position: isize = 0;
+		self.slot_locations = function.slots.iter().map(|slot| Self::allocate_frame(&mut position, slot.data_type.size())).collect();
+		self.register_homes = function.registers.iter().map(|data_type| Self::allocate_frame(&mut position, data_type.size())).collect();
 
-		if function.attributes & attribute::SYS_V_ABI_X86_64 != 0
+		let signature = &self.program.function_types[function.signature.index()];
+		for (i, class) in Self::classify_arguments_sys_v_abi_x86_64(&signature.parameters).iter().enumerate()
 		{
-			self.gen_sys_v_abi_x86_64_call(locals, function_call_info);
+			if let ArgumentClass::Stack(offset) = class
+			{
+				self.register_homes[function.parameters[i].index()] = 8 + 8 + *offset as isize;		/* Return address(8), base pointer(8) */
+			}
 		}
-		
-		self.reg_alloc_free_used();
-		
-		if function.return_type == Type::new(TypeKind::Void) 
+
+		for register in CALLEE_SAVED_REGISTERS_SYS_V_ABI_X86_64
 		{
-			return None;
-		} else 
-		{ 
-			return Some(Placeholder::new(
-				PlaceholderKind::Reg(Register::from_op_size(Register::default_for_type(function.return_type), function.return_type.size())), 
-				function.return_type	
-			));
+			if self.register_locations.contains(&Some(register))
+			{
+				self.saved_registers.push((register, Self::allocate_frame(&mut position, OP_QWORD)));
+			}
 		}
+
+		let frame_size = -position as usize;
+		return frame_size + (16 - frame_size % 16) % 16;
 	}
 
-	fn gen_sys_v_abi_x86_64_call(&mut self, locals: &Vec<Variable>, function_call_info: &FunctionCallInfo)
+	fn allocate_frame(position: &mut isize, size: u8) -> isize
 	{
-		let function = &self.ir.functions[function_call_info.index as usize];
+		let size = size as isize;
+		*position = (*position - size) & !(size - 1);
+		return *position;
+	}
 
-		if function.parameters_stack_size != 0
+	// The location of the home of a virtual register, which is a register if it has one
+	pub fn register_home(&self, function: &mir::Function, register: RegisterId) -> Placeholder
+	{
+		let data_type = function.register_type(register);
+		if let Some(location) = self.register_locations[register.index()]
 		{
-			self.instr_sub(
-				&Placeholder::new(PlaceholderKind::Reg(Register::RSP), Type::new(TypeKind::U64)), 
-				&Placeholder::new(PlaceholderKind::Integer(function.parameters_stack_size as u64), Type::new(TypeKind::U64))
-			);
+			return Placeholder::new(PlaceholderKind::Reg(location.of_size(data_type.size())), data_type);
 		}
+		return Self::frame_location(self.register_homes[register.index()], data_type);
+	}
 
-		// rdi, rsi, rdx, rcx, r8, r9,
-		let mut allocated_registers: Vec<Register> = Vec::with_capacity(6);
+	fn frame_location(offset: isize, data_type: Type) -> Placeholder
+	{
+		return Placeholder::new(
+			PlaceholderKind::Location(LocationExpr::new(LocationExprPart::Reg(Register::RBP), LocationExprPart::Offset(offset), None)),
+			data_type
+		);
+	}
+
+	pub fn slot_location(&self, function: &mir::Function, slot: SlotId) -> Placeholder
+	{
+		return Placeholder::new(
+			PlaceholderKind::Location(LocationExpr::new(
+				LocationExprPart::Reg(Register::RBP),
+				LocationExprPart::Offset(self.slot_locations[slot.index()]),
+				None,
+			)),
+			function.slots[slot.index()].data_type
+		);
+	}
+
+	// Classify the arguments. Arguments that dont fit in rdi, rsi, rdx, rcx, r8, r9 or xmm0-7 are passed on the stack,
+	// each in an 8 byte slot, where the first one is at [rsp] when calling the function.
+	pub(super) fn classify_arguments_sys_v_abi_x86_64(argument_types: &[Type]) -> Vec<ArgumentClass>
+	{
+		let mut argument_classes: Vec<ArgumentClass> = Vec::with_capacity(argument_types.len());
 		let mut integer_arguments: u8 = 0;
 		let mut float_arguments: u8 = 0;
-		let mut stack_position: u8 = 0;
-
-		for (i, argument) in function_call_info.arguments.iter().enumerate()
+		let mut stack_arguments: usize = 0;
+		for data_type in argument_types
 		{
-			let placeholder;
-			let argument = self.gen_expression(argument, locals);
-			let arg_data = function.locals[i];
-			// println!("{}\n\n", self.text_segment);
-
-			if arg_data.data_type.is_integer() && integer_arguments < 6
+			if data_type.is_integer() && integer_arguments < INTEGER_ARGUMENT_REGISTERS_SYS_V_ABI_X86_64
 			{
-				let register = Self::int_argument_2_register_sys_v_abi_x86_64(integer_arguments, arg_data.data_type.size());
-				self.reg_alloc_allocate_forced(register);
-				allocated_registers.push(register);
-				placeholder = Placeholder::new(PlaceholderKind::Reg(register), arg_data.data_type);
+				argument_classes.push(ArgumentClass::Register(Self::int_argument_2_register_sys_v_abi_x86_64(integer_arguments, OP_QWORD)));
 				integer_arguments += 1;
-			} else if !arg_data.data_type.is_integer() && float_arguments < 15
+			} else if !data_type.is_integer() && float_arguments < FLOAT_ARGUMENT_REGISTERS_SYS_V_ABI_X86_64
 			{
-				let register = Self::float_argument_2_register_sys_v_abi_x86_64(float_arguments);
-				self.reg_alloc_allocate_forced(register);
-				allocated_registers.push(register);
-				placeholder = Placeholder::new(PlaceholderKind::Reg(register), arg_data.data_type);
+				argument_classes.push(ArgumentClass::Register(Self::float_argument_2_register_sys_v_abi_x86_64(float_arguments)));
 				float_arguments += 1;
 			} else
 			{
-				placeholder = Placeholder::new(
-					PlaceholderKind::Location(
-						LocationExpr::new(
-							LocationExprPart::Reg(Register::RSP),
-							LocationExprPart::Offset(stack_position as isize), 
-							None
-						)
-					), 
-					arg_data.data_type
-				);
-				stack_position += arg_data.data_type.size();
+				argument_classes.push(ArgumentClass::Stack(stack_arguments * 8));
+				stack_arguments += 1;
 			}
+		}
+		return argument_classes;
+	}
 
-			self.instr_mov(&placeholder, &argument);
-			// println!("{}\n\n", self.text_segment);
+	pub fn gen_call(&mut self, function: &mir::Function, destination: Option<RegisterId>, target: CallTarget, signature: FunctionTypeId, arguments: &[Operand], argument_types: &[Type])
+	{
+		let function_type = &self.program.function_types[signature.index()];
+		if function_type.attributes & attribute::SYS_V_ABI_X86_64 != 0
+		{
+			self.gen_sys_v_abi_x86_64_call(function, target, arguments, argument_types, function_type.attributes & attribute::VARIADIC != 0, false);
 		}
 
-		self.instr_call(&function.identifier);
+		if let Some(destination) = destination
+		{
+			let data_type = function.register_type(destination);
+			let result = Placeholder::new(PlaceholderKind::Reg(Register::default_for_type(data_type)), data_type);
+			self.instr_mov(&self.register_home(function, destination), &result);
+		}
+	}
 
-		for register in allocated_registers
+	// Whether the block ends with a call whose result is returned (return f(...)), which is then a jump into the called function (at -O1 and above,
+	// and for the recursive calls of tailrec functions at every level). The called function returns straight into the caller of this function,
+	// so the stack frame of this function must not be needed after the call. The address of a stack slot must not be taken (the semantic analysis checks
+	// this in tailrec functions), and the arguments that are passed on the stack must fit where the parameters of this function were passed.
+	pub fn is_tail_call(&self, function: &mir::Function, block: &Block) -> bool
+	{
+		let Some(Instruction::Call { destination, target, argument_types, signature, .. }) = block.instructions.last() else
+		{
+			return false;
+		};
+		let returns_result = match block.terminator
 		{
-			self.reg_alloc_free(register);
+			Terminator::Return(Some(Operand::Register(value))) 	=> *destination == Some(value),
+			Terminator::Return(None) 							=> destination.is_none(),
+			_ 													=> false,
+		};
+		if !returns_result || self.program.function_types[signature.index()].attributes & attribute::SYS_V_ABI_X86_64 == 0
+		{
+			return false;
 		}
 
-		if function.parameters_stack_size != 0
+		let is_tail_recursive = function.attributes & attribute::TAIL_RECURSIVE != 0;
+		let is_recursive = matches!(target, CallTarget::Direct(callee) if self.program.functions[callee.index()].identifier == function.identifier);
+		if self.optimization_level == mir::OPTIMIZATION_LEVEL_NONE && !(is_tail_recursive && is_recursive)
 		{
-			self.instr_add(
-				&Placeholder::new(PlaceholderKind::Reg(Register::RSP), Type::new(TypeKind::U64)), 
-				&Placeholder::new(PlaceholderKind::Integer(stack_position as u64), Type::new(TypeKind::U64))
-			);
+			return false;
 		}
+
+		let address_taken = function.blocks.iter().flat_map(|block| &block.instructions).any(|instruction| matches!(instruction, Instruction::SlotAddress { .. }));
+		let parameters = &self.program.function_types[function.signature.index()].parameters;
+		let fits = Self::stack_arguments_size(argument_types) <= Self::stack_arguments_size(parameters);
+		return (is_tail_recursive || !address_taken) && fits;
+	}
+
+	// The size of the arguments that are passed on the stack
+	fn stack_arguments_size(argument_types: &[Type]) -> usize
+	{
+		return Self::classify_arguments_sys_v_abi_x86_64(argument_types).iter().filter(|class| matches!(class, ArgumentClass::Stack(_))).count() * 8;
+	}
+
+	pub fn gen_tail_call(&mut self, function: &mir::Function, call: &Instruction)
+	{
+		let Instruction::Call { target, signature, arguments, argument_types, .. } = call else
+		{
+			panic!("Dev error! gen_tail_call called on an instruction that is not a call.");
+		};
+		let is_variadic = self.program.function_types[signature.index()].attributes & attribute::VARIADIC != 0;
+		self.gen_sys_v_abi_x86_64_call(function, *target, arguments, argument_types, is_variadic, true);
 	}
 
-	fn store_parameters_sys_v_abi_x86_64(&mut self, function: &Function)
+	// The arguments are already evaluated into virtual registers, so each one is loaded straight into its register or stack slot.
+	// A tail call jumps into the function after restoring the stack frame of the caller, and the called function returns into the caller of this function.
+	// Its stack arguments are written below the stack first and moved over the stack parameters of this function at the end, because
+	// the other arguments may still be read from the stack parameters.
+	fn gen_sys_v_abi_x86_64_call(&mut self, function: &mir::Function, target: CallTarget, arguments: &[Operand], argument_types: &[Type], is_variadic: bool, is_tail_call: bool)
 	{
-		let mut integer_parameters: u8 = 0;
-		let mut float_parameters: u8 = 0;
+		let argument_classes = Self::classify_arguments_sys_v_abi_x86_64(argument_types);
+		let stack_arguments = argument_classes.iter().filter(|class| matches!(class, ArgumentClass::Stack(_))).count();
+		let float_arguments = argument_classes.iter().filter(|class| matches!(class, ArgumentClass::Register(register) if !register.is_general())).count();
+
+		// The frame is aligned on 16 bytes, and the stack must stay aligned on 16 bytes when calling a function.
+		let stack_size = stack_arguments * 8;
+		let stack_size = stack_size + stack_size % 16;
+		let rsp = Placeholder::new(PlaceholderKind::Reg(Register::RSP), Type::new(TypeKind::U64));
+		if stack_size != 0
+		{
+			self.instr_sub(&rsp, &Placeholder::new(PlaceholderKind::Integer(stack_size as u64), Type::new(TypeKind::U64)));
+		}
 
-		for parameter in &function.locals
+		// The stack arguments are written first, because writing them uses RAX and XMM0 (which is also an argument register)
+		for (i, argument) in arguments.iter().enumerate()
 		{
-			if parameter.attributes & attribute::FUNCTION_PARAMETER == 0
+			// Integers are extended into the whole 8 byte slot
+			let data_type = argument_types[i];
+			if let ArgumentClass::Stack(position) = argument_classes[i]
 			{
-				break;
+				let value = if data_type.is_integer()
+				{
+					self.load_extended(function, Register::RAX, *argument, data_type, OP_QWORD)
+				} else
+				{
+					self.load_operand(function, Register::XMM0, *argument, data_type)
+				};
+				let slot = Placeholder::new(
+					PlaceholderKind::Location(LocationExpr::new(LocationExprPart::Reg(Register::RSP), LocationExprPart::Offset(position as isize), None)),
+					value.data_type
+				);
+				self.instr_mov(&slot, &value);
 			}
+		}
 
-			let source;
-			let destination = Placeholder::new(
-				PlaceholderKind::Location(
-					LocationExpr::new(
-						LocationExprPart::Reg(Register::RBP), 
-						LocationExprPart::Offset(parameter.location),
-						None, 
-					)
-				), 
-				parameter.data_type
-			);
-			
-			if parameter.data_type.is_integer() && integer_parameters < 6
+		// Integers smaller than 32 bits are extended into 32 bits, as C compilers expect.
+		for (i, argument) in arguments.iter().enumerate()
+		{
+			let data_type = argument_types[i];
+			if let ArgumentClass::Register(register) = argument_classes[i]
 			{
-				let register = Self::int_argument_2_register_sys_v_abi_x86_64(integer_parameters, parameter.data_type.size());
-				source = Placeholder::new(PlaceholderKind::Reg(register), parameter.data_type);
-				integer_parameters += 1;
-			} else if !parameter.data_type.is_integer() && float_parameters < 15
+				if data_type.is_integer()
+				{
+					self.load_extended(function, register, *argument, data_type, data_type.size().max(OP_DWORD));
+				} else
+				{
+					self.load_operand(function, register, *argument, data_type);
+				}
+			}
+		}
+
+		// RAX is not used for passing arguments (AL is set below)
+		if is_tail_call
+		{
+			let rax = Placeholder::new(PlaceholderKind::Reg(Register::RAX), Type::new(TypeKind::U64));
+			for class in &argument_classes
 			{
-				let register = Self::float_argument_2_register_sys_v_abi_x86_64(float_parameters);
-				source = Placeholder::new(PlaceholderKind::Reg(register), parameter.data_type);
-				float_parameters += 1;
-			} else
+				if let ArgumentClass::Stack(position) = *class
+				{
+					let argument = Placeholder::new(
+						PlaceholderKind::Location(LocationExpr::new(LocationExprPart::Reg(Register::RSP), LocationExprPart::Offset(position as isize), None)),
+						Type::new(TypeKind::U64)
+					);
+					self.instr_mov(&rax, &argument);
+					self.instr_mov(&Self::frame_location(8 + 8 + position as isize, Type::new(TypeKind::U64)), &rax);		/* Return address(8), base pointer(8) */
+				}
+			}
+		}
+
+		// R11 is not used for passing arguments
+		let function_pointer = Placeholder::new(PlaceholderKind::Reg(Register::R11), Type::new(TypeKind::U64));
+		if let CallTarget::Indirect(pointer) = target
+		{
+			self.load_operand(function, Register::R11, pointer, Type::new(TypeKind::U64));
+		}
+
+		// When calling a variadic function, AL holds the amount of vector registers that were used for passing arguments.
+		if is_variadic
+		{
+			self.instr_mov(
+				&Placeholder::new(PlaceholderKind::Reg(Register::EAX), Type::new(TypeKind::U32)),
+				&Placeholder::new(PlaceholderKind::Integer(float_arguments as u64), Type::new(TypeKind::U32))
+			);
+		}
+
+		if is_tail_call
+		{
+			self.restore_frame();
+			match target
+			{
+				CallTarget::Direct(index) 	=> self.instr_jmp_function(&self.program.functions[index.index()].identifier),
+				CallTarget::Indirect(_) 	=> self.instr_jmp_indirect(&function_pointer),
+			}
+			return;
+		}
+
+		match target
+		{
+			CallTarget::Direct(index) 	=> self.instr_call(&self.program.functions[index.index()].identifier),
+			CallTarget::Indirect(_) 	=> self.instr_call_indirect(&function_pointer),
+		}
+
+		if stack_size != 0
+		{
+			self.instr_add(&rsp, &Placeholder::new(PlaceholderKind::Integer(stack_size as u64), Type::new(TypeKind::U64)));
+		}
+	}
+
+	fn store_parameters_sys_v_abi_x86_64(&mut self, function: &mir::Function)
+	{
+		let signature = &self.program.function_types[function.signature.index()];
+		for (i, class) in Self::classify_arguments_sys_v_abi_x86_64(&signature.parameters).iter().enumerate()
+		{
+			// Parameters that were passed on the stack are already in place
+			if let ArgumentClass::Register(register) = class
 			{
-				break;
+				let home = self.register_home(function, function.parameters[i]);
+				self.instr_mov(&home, &Placeholder::new(PlaceholderKind::Reg(register.of_size(home.data_type.size())), home.data_type));
 			}
-			self.instr_mov(&destination, &source);
 		}
 	}
 
@@ -212,6 +381,6 @@ impl<'a> CodeGen<'a>
 
 	fn float_argument_2_register_sys_v_abi_x86_64(argument: u8) -> Register
 	{
-		return Register::try_from(Register::XMM1 as u8 + argument).unwrap();
+		return Register::try_from(Register::XMM0 as u8 + argument).unwrap();
 	}
-}
\ No newline at end of file
+}
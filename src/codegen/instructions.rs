@@ -95,6 +95,68 @@ pub enum Register
 	XMM15,
 }
 
+// An item of the text segment. Instructions keep their operands structured until the text segment is written,
+// so the peephole optimizer can look at them.
+pub enum TextItem
+{
+	Function(String),		/* The lable of a function */
+	Lable(Lable),
+	Comment(String),
+	Instruction(AsmInstruction),
+}
+
+// A symbol that the object file exports, or that it takes from another object file
+pub enum AttributeItem
+{
+	Global(String),
+	Extern(String),
+}
+
+// A constant in the data segment
+pub struct DataItem
+{
+	pub lable: Lable,
+	pub value: Value,
+}
+
+#[derive(Clone)]
+pub struct AsmInstruction
+{
+	pub mnemonic: Mnemonic,
+	pub operands: Vec<AsmOperand>,		/* The destination (if the instruction has one) is first */
+}
+
+#[derive(Clone, PartialEq)]
+pub enum AsmOperand
+{
+	Value(Placeholder),
+	Lable(Lable),
+	Function(String),		/* The identifier of a function, for call and lea */
+}
+
+// Instructions that work on integers and on floats (like mov and movsd) have one mnemonic, and the data type of their operands picks the instruction
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mnemonic
+{
+	Mov, Movzx, Movsx, Movsxd, Lea,
+	Cvttsf2si, Cvtsi2sf, Cvtsf2sf,
+	Push, Pop,
+	Add, Sub, Mul, Div, DivFloat,
+	Xor, Or, And, Not, Shl, Shr,
+	Cdq, Cqo,
+	Cmp, Test, Setcc(ConditionCode),
+	Jmp, Jcc(ConditionCode), Call, Ret,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConditionCode
+{
+	E, Ne,
+	G, A,
+	L, B,
+	Ge, Ae,
+	Le, Be,
+}
 
 impl std::fmt::Display for Placeholder
 {
@@ -172,6 +234,18 @@ impl LocationExpr
 		};
 	}
 	
+	// The base, the offset and the multiplier of the offset
+	pub fn parts(&self) -> (LocationExprPart, LocationExprPart, Option<usize>)
+	{
+		return (self.base, self.offset, self.offset_multiplier);
+	}
+
+	// Whether the address is computed with the register, or with one of its aliases (like EAX for RAX)
+	pub fn uses_register(&self, register: Register) -> bool
+	{
+		return [self.base, self.offset].iter().any(|part| matches!(part, LocationExprPart::Reg(used) if used.base_register() == register.base_register()));
+	}
+
 	pub fn from_placeholder(placeholder: &Placeholder) -> Self
 	{
 		match placeholder.kind
@@ -197,43 +271,6 @@ impl Lable
 impl Register
 {
 	pub const COUNT: u8 = 84;
-	pub const COUNT_FULL: u8 = 16 + 16;
-
-	// The size of the register in bytes
-	pub fn data_type(&self) -> Type
-	{
-		if *self as u8 >= Register::XMM0 as u8 && *self as u8 <= Register::XMM15 as u8
-		{
-			return Type::new(TypeKind::F64);
-		}
-
-		match self {
-			Register::RAX | Register::RBX | Register::RCX | Register::RDX |
-			Register::RSI | Register::RDI | Register::RSP | Register::RBP |
-			Register::R8  | Register::R9  | Register::R10 | Register::R11 |
-			Register::R12 | Register::R13 | Register::R14 | Register::R15 =>
-			{
-				return Type::new(TypeKind::U64);
-			},
-
-			Register::EAX | Register::EBX | Register::ECX | Register::EDX |
-			Register::ESI | Register::EDI | Register::ESP | Register::EBP |
-			Register::R8D  | Register::R9D  | Register::R10D | Register::R11D |
-			Register::R12D | Register::R13D | Register::R14D | Register::R15D =>
-			{
-				return Type::new(TypeKind::U32);
-			},
-
-			Register::AX | Register::BX | Register::CX | Register::DX |
-			Register::SI | Register::DI | Register::SP | Register::BP |
-			Register::R8W  | Register::R9W  | Register::R10W | Register::R11W |
-			Register::R12W | Register::R13W | Register::R14W | Register::R15W =>
-			{
-				return Type::new(TypeKind::U16);
-			},
-			_ => return Type::new(TypeKind::U8),
-		}
-	}
 
 	pub fn is_general(&self) -> bool
 	{
@@ -271,6 +308,12 @@ impl Register
 		return Register::try_from(idx).unwrap();
 	}
 
+	// The size of the register. Vector registers hold a single float, so they are 8 bytes.
+	pub fn size(&self) -> OpSize
+	{
+		return [OP_BYTE, OP_WORD, OP_DWORD, OP_QWORD].into_iter().find(|size| self.of_size(*size) == *self).unwrap_or(OP_BYTE);
+	}
+
 	pub fn of_size(&self, size: OpSize) -> Register
 	{
 		let base = self.base_register();
@@ -288,7 +331,7 @@ impl TryFrom<OpSize> for Register
 			return Err(());
 		}
 
-		return unsafe { Ok(std::mem::transmute(value)) };
+		return unsafe { Ok(std::mem::transmute::<OpSize, Register>(value)) };
 	}
 }
 impl Placeholder
@@ -303,42 +346,25 @@ impl Placeholder
 
 	pub fn is_constant(&self) -> bool
 	{
-		match self.kind
-		{
-			PlaceholderKind::Integer(_) => return true,
-			_ => return false,
-		}
+		return matches!(self.kind, PlaceholderKind::Integer(_));
 	}
 
 	pub fn is_register(&self) -> bool
 	{
-		return match self.kind
-		{
-			PlaceholderKind::Reg(_) => true,
-			_ => false,
-		};
+		return matches!(self.kind, PlaceholderKind::Reg(_));
 	}
 
-	pub fn is_location(&self) -> bool
+	// Whether the placeholder is the register (or one of its aliases), or an address that is computed with it
+	pub fn uses_register(&self, register: Register) -> bool
 	{
 		return match self.kind
 		{
-			PlaceholderKind::Location(_) => true,
-			_ => false
+			PlaceholderKind::Reg(used) 			=> used.base_register() == register.base_register(),
+			PlaceholderKind::Location(location) => location.uses_register(register),
+			PlaceholderKind::Integer(_) 		=> false,
 		};
 	}
 
-	// If the placeholder is not a register false is returned, if its a register, then return placeholder.reg == register
-	pub fn is_register_eq(&self, register: Register) -> bool
-	{
-		if let PlaceholderKind::Reg(reg) = self.kind
-		{
-			return reg == register;
-		}
-
-		return false;
-	}
-
 	pub fn of_type(&self, data_type: Type) -> Placeholder
 	{
 		if let PlaceholderKind::Reg(register) = self.kind
@@ -383,655 +409,317 @@ impl PartialEq for Placeholder
 	}
 }
 
-impl<'a> CodeGen<'a>
+impl AsmOperand
 {
-	fn size_2_opsize<'b>(size: OpSize) -> &'b str
+	// The placeholder of the operand, if it is not a lable or a function
+	pub fn placeholder(&self) -> Option<&Placeholder>
 	{
-		match size
+		return match self
 		{
-			OP_BYTE => return "byte",
-			OP_WORD	=> return "word",
-			OP_DWORD => return "dword",
-			OP_QWORD => return "qword",
-			_ => panic!("Dev error! size_2_opsize({size}) called with a size thats not a power of 2."),
-		}
+			AsmOperand::Value(placeholder) 	=> Some(placeholder),
+			_ 								=> None,
+		};
 	}
+}
 
-	pub fn instr_add_spacing(&mut self)
+impl AsmInstruction
+{
+	pub fn new(mnemonic: Mnemonic, operands: Vec<AsmOperand>) -> Self
 	{
-		self.write_text_segment("\n");
+		return Self {
+			mnemonic,
+			operands,
+		};
 	}
 
-	pub fn instr_mov(&mut self, destination: &Placeholder, source: &Placeholder)
+	// The placeholder of an operand, if the operand exists and is not a lable or a function
+	pub fn operand(&self, index: usize) -> Option<&Placeholder>
 	{
-		if destination == source
-		{
-			return;
-		}
+		return self.operands.get(index)?.placeholder();
+	}
+}
 
-		let mut source_placeholder = *source;
-		if let PlaceholderKind::Location(_) = destination.kind
+impl ConditionCode
+{
+	pub fn from_condition(condition: Condition, signed: bool) -> Self
+	{
+		return match (condition, signed)
+		{
+			(Condition::Equal, _) 				=> ConditionCode::E,
+			(Condition::NotEqual, _) 			=> ConditionCode::Ne,
+			(Condition::Greater, true) 			=> ConditionCode::G,
+			(Condition::Greater, false) 		=> ConditionCode::A,
+			(Condition::Less, true) 			=> ConditionCode::L,
+			(Condition::Less, false) 			=> ConditionCode::B,
+			(Condition::GreaterEqual, true) 	=> ConditionCode::Ge,
+			(Condition::GreaterEqual, false) 	=> ConditionCode::Ae,
+			(Condition::LessEqual, true) 		=> ConditionCode::Le,
+			(Condition::LessEqual, false) 		=> ConditionCode::Be,
+		};
+	}
+}
+
+impl std::fmt::Display for ConditionCode
+{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+	{
+		return write!(f, "{}", format!("{:?}", self).to_lowercase());
+	}
+}
+
+impl DataItem
+{
+	pub fn new(lable: Lable, value: Value) -> Self
+	{
+		return Self {
+			lable,
+			value,
+		};
+	}
+
+	// The bytes of the constant, in little endian
+	pub fn bytes(&self) -> Vec<u8>
+	{
+		return match self.value
 		{
-			if let PlaceholderKind::Location(_) = source.kind
-			{
-				source_placeholder = Placeholder::new(
-					PlaceholderKind::Reg(Register::default_for_type(source.data_type)), 
-					source.data_type
-				);
-				self.instr_mov(&source_placeholder, source);
-			}
-		}
+			Value::I8(number) 	=> number.to_le_bytes().to_vec(),
+			Value::U8(number) 	=> number.to_le_bytes().to_vec(),
+			Value::I16(number) 	=> number.to_le_bytes().to_vec(),
+			Value::U16(number) 	=> number.to_le_bytes().to_vec(),
+			Value::I32(number) 	=> number.to_le_bytes().to_vec(),
+			Value::U32(number) 	=> number.to_le_bytes().to_vec(),
+			Value::I64(number) 	=> number.to_le_bytes().to_vec(),
+			Value::U64(number) 	=> number.to_le_bytes().to_vec(),
+			Value::F32(number) 	=> number.to_le_bytes().to_vec(),
+			Value::F64(number) 	=> number.to_le_bytes().to_vec(),
+			_ 					=> panic!("Dev error! A data item that is not a constant."),
+		};
+	}
+}
 
-		if destination.data_type.is_integer()
+impl<'a> CodeGen<'a>
+{
+	fn write_instruction(&mut self, mnemonic: Mnemonic, operands: &[&Placeholder])
+	{
+		let operands = operands.iter().map(|placeholder| AsmOperand::Value(**placeholder)).collect();
+		self.text_segment.push(TextItem::Instruction(AsmInstruction::new(mnemonic, operands)));
+	}
+
+	pub fn instr_mov(&mut self, destination: &Placeholder, source: &Placeholder)
+	{
+		if destination == source
 		{
-			self.write_text_segment(&format!("\n\tmov {} {destination}, {source_placeholder}", Self::size_2_opsize(destination.data_type.size())));
 			return;
 		}
-		if destination.data_type == Type::new(TypeKind::F64)
-		{
-			self.write_text_segment(&format!("\n\tmovsd {destination}, {source_placeholder}"));
-		} else if destination.data_type == Type::new(TypeKind::F32)
-		{
-			self.write_text_segment(&format!("\n\tmovss {destination}, {source_placeholder}"));
-		}
+		self.write_instruction(Mnemonic::Mov, &[destination, source]);
 	}
 
 	pub fn instr_movzx(&mut self, destination: &Placeholder, source: &Placeholder)
 	{
-		self.write_text_segment(&format!(
-			"\n\tmovzx {} {destination}, {} {source}",
-			Self::size_2_opsize(destination.data_type.size()),
-			Self::size_2_opsize(source.data_type.size())
-		));
+		self.write_instruction(Mnemonic::Movzx, &[destination, source]);
 	}
 
 	pub fn instr_movsx(&mut self, destination: &Placeholder, source: &Placeholder)
 	{
-		self.write_text_segment(&format!(
-			"\n\tmovsx {} {destination}, {} {source}",
-			Self::size_2_opsize(destination.data_type.size()),
-			Self::size_2_opsize(source.data_type.size())
-		));
+		self.write_instruction(Mnemonic::Movsx, &[destination, source]);
+	}
+
+	// Sign extend a 32 bit source into a 64 bit register
+	pub fn instr_movsxd(&mut self, destination: &Placeholder, source: &Placeholder)
+	{
+		self.write_instruction(Mnemonic::Movsxd, &[destination, source]);
 	}
 
 	pub fn instr_lea(&mut self, destination: &Placeholder, source: &Placeholder)
 	{
-		self.write_text_segment(&format!("\n\tlea {} {destination}, {source}", Self::size_2_opsize(destination.data_type.size())));
+		self.write_instruction(Mnemonic::Lea, &[destination, source]);
 	}
 
 	// Convert single floating point (64/32 bit) into an integer
 	pub fn instr_cvttsf2si(&mut self, destination: &Placeholder, source: &Placeholder)
 	{
-		let destination = if destination.data_type.size() < 4 { destination.of_type(Type::new(TypeKind::U32)) } else { *destination };
-		if source.data_type == Type::new(TypeKind::F64)
-		{
-			self.write_text_segment(&format!("\n\tcvttsd2si {} {destination}, {source}", Self::size_2_opsize(destination.data_type.size())));
-		} else if source.data_type == Type::new(TypeKind::F32)
-		{
-			self.write_text_segment(&format!("\n\tcvttss2si {} {destination}, {source}", Self::size_2_opsize(destination.data_type.size())));
-		} else
+		if source.data_type.is_integer()
 		{
 			panic!("instr_cvttsf2si called with a non floating point source.");
 		}
+		self.write_instruction(Mnemonic::Cvttsf2si, &[destination, source]);
 	}
 
 	// Convert single integer into single floating point (32/64 bit)
 	pub fn instr_cvtsi2sf(&mut self, destination: &Placeholder, source: &Placeholder)
 	{
-		let source = if source.data_type.size() < 4 { source.of_type(Type::new(TypeKind::U32)) } else { *source };
-		if destination.data_type == Type::new(TypeKind::F64)
-		{
-			self.write_text_segment(&format!("\n\tcvtsi2sd {destination}, {} {source}", Self::size_2_opsize(source.data_type.size())));
-		} else if destination.data_type == Type::new(TypeKind::F32)
-		{
-			self.write_text_segment(&format!("\n\tcvtsi2ss {destination}, {} {source}", Self::size_2_opsize(source.data_type.size()) ));
-		} else
+		if destination.data_type.is_integer()
 		{
 			panic!("instr_cvttsi2sf called with a non floating point destination.");
 		}
+		self.write_instruction(Mnemonic::Cvtsi2sf, &[destination, source]);
 	}
 
 	pub fn instr_cvtsf2sf(&mut self, destination: &Placeholder, source: &Placeholder)
 	{
-		if source.data_type == Type::new(TypeKind::F64)
-		{
-			self.write_text_segment(&format!("\n\tcvtsd2ss {destination}, {source}"));
-		} else if source.data_type == Type::new(TypeKind::F32)
-		{
-			self.write_text_segment(&format!("\n\tcvtss2sd {destination}, {source}"));
-		} else
+		if source.data_type.is_integer()
 		{
 			panic!("instr_cvttsi2sf called with a non floating point source.");
 		}
+		self.write_instruction(Mnemonic::Cvtsf2sf, &[destination, source]);
 	}
 
 	pub fn instr_push(&mut self, source: &Placeholder)
 	{
-		if let PlaceholderKind::Reg(register) = source.kind
-		{
-			if register.is_general()
-			{
-				self.write_text_segment(&format!("\n\tpush {} {source}", Self::size_2_opsize(source.data_type.size())));
-				return;
-			}
-
-			self.instr_sub(
-				&Placeholder::new(PlaceholderKind::Reg(Register::RSP), Type::new(TypeKind::U64)),
-				&Placeholder::new(PlaceholderKind::Integer(source.data_type.size() as u64), Type::new(TypeKind::U64))
-			);
-
-			self.instr_mov(
-				&Placeholder::new(PlaceholderKind::Location(LocationExpr::new(
-					LocationExprPart::Reg(Register::RSP),
-					LocationExprPart::Offset(0),
-					None,
-					)), source.data_type), 
-				source
-			);
-			return;
-		}
-		self.write_text_segment(&format!("\n\tpush {} {source}", Self::size_2_opsize(source.data_type.size())));
+		self.write_instruction(Mnemonic::Push, &[source]);
 	}
 
 	pub fn instr_pop(&mut self, destination: &Placeholder)
 	{
-		if let PlaceholderKind::Reg(register) = destination.kind
-		{
-			if register.is_general()
-			{
-				self.write_text_segment(&format!("\n\tpop {} {destination}", Self::size_2_opsize(destination.data_type.size())));
-				return;
-			}
-
-			self.instr_mov(
-				destination,
-				&Placeholder::new(PlaceholderKind::Location(LocationExpr::new(
-					LocationExprPart::Reg(Register::RSP),
-					LocationExprPart::Offset(0),
-					None
-					)), destination.data_type)
-			);
-
-			self.instr_add(
-				&Placeholder::new(PlaceholderKind::Reg(Register::RSP), Type::new(TypeKind::U64)), 
-				&Placeholder::new(PlaceholderKind::Integer(destination.data_type.size() as u64), Type::new(TypeKind::U64))
-			);
-			return;
-		}
-		self.write_text_segment(&format!("\n\tpop {} {destination}", Self::size_2_opsize(destination.data_type.size())));
+		self.write_instruction(Mnemonic::Pop, &[destination]);
 	}
 
 	pub fn instr_ret(&mut self)
 	{
-		self.write_text_segment("\n\tret");
+		self.write_instruction(Mnemonic::Ret, &[]);
 	}
 
 	pub fn instr_add(&mut self, destination: &Placeholder, source: &Placeholder)
 	{
-		if destination.data_type.is_integer()
-		{
-			self.write_text_segment(&format!("\n\tadd {} {destination}, {source}", Self::size_2_opsize(destination.data_type.size())));
-		} else if destination.data_type == Type::new(TypeKind::F64)
-		{
-			self.write_text_segment(&format!("\n\taddsd {destination}, {source}"));
-		} else if destination.data_type == Type::new(TypeKind::F32)
-		{
-			self.write_text_segment(&format!("\n\taddss {destination}, {source}"));
-		}
+		self.write_instruction(Mnemonic::Add, &[destination, source]);
 	}
 
 	pub fn instr_sub(&mut self, destination: &Placeholder, source: &Placeholder)
 	{
-		if destination.data_type.is_integer()
-		{
-			self.write_text_segment(&format!("\n\tsub {} {destination}, {source}", Self::size_2_opsize(destination.data_type.size())));
-		} else if destination.data_type == Type::new(TypeKind::F64)
-		{
-			self.write_text_segment(&format!("\n\tsubsd {destination}, {source}"));
-		} else if destination.data_type == Type::new(TypeKind::F32)
-		{
-			self.write_text_segment(&format!("\n\tsubss {destination}, {source}"));
-		}
+		self.write_instruction(Mnemonic::Sub, &[destination, source]);
 	}
 
+	// The low bits of a multiplication are the same for signed and unsigned integers, so imul is used for both.
+	// The destination must be a register, and for integers it cant be a byte register.
 	pub fn instr_mul(&mut self, destination: &Placeholder, source: &Placeholder)
 	{
-		let mut allocated_register = None;
-		let mut src_placeholder = *source;
-		if source.is_constant()
-		{
-			allocated_register = self.reg_alloc_allocate(source.data_type);
-			src_placeholder = Placeholder::new(PlaceholderKind::Reg(allocated_register.unwrap()), src_placeholder.data_type);
-			self.instr_mov(&src_placeholder, &source);
-		}
-
-		if destination.data_type.is_integer()
-		{
-			if destination.data_type.is_signed()
-			{
-				if destination.data_type.size() == OP_BYTE
-				{
-					if !destination.is_register_eq(Register::AL)	
-					{
-						self.instr_mov(
-							&Placeholder::new(PlaceholderKind::Reg(Register::AL), destination.data_type), 
-							destination
-						);
-					}
-					self.write_text_segment(&format!("\n\timul {} {src_placeholder}", Self::size_2_opsize(src_placeholder.data_type.size())));
-
-					let al_placeholder = Placeholder::new(PlaceholderKind::Reg(Register::AL), destination.data_type);
-					if *destination != al_placeholder
-					{
-						self.instr_mov(&destination, &al_placeholder);
-					}
-				} else		/* If its not a byte multiplication */
-				{
-					self.write_text_segment(&format!("\n\timul {} {destination}, {src_placeholder}", Self::size_2_opsize(destination.data_type.size())));
-				}
-			} else 		/* If doing an unsigned multiplication */
-			{
-				let rax = Register::from_op_size(Register::RAX, destination.data_type.size());
-				if !destination.is_register_eq(rax)	
-				{
-					self.instr_mov(
-						&Placeholder::new(PlaceholderKind::Reg(rax), destination.data_type), 
-						destination
-					);
-				}
-				if !src_placeholder.is_register()
-				{
-					allocated_register = self.reg_alloc_allocate(source.data_type);
-					self.instr_mov(&src_placeholder, &source);
-				}
-
-				self.write_text_segment(&format!("\n\tmul {} {src_placeholder}", Self::size_2_opsize(src_placeholder.data_type.size())));
-
-				if !destination.is_register_eq(rax)	
-				{
-					self.instr_mov(
-						destination,
-						&Placeholder::new(PlaceholderKind::Reg(rax), destination.data_type)
-					);
-				}
-			}
-		} else		/* If doing floating point multiplication */
-		{
-			let mut dst_placeholder = *destination;
-			let mut allocated_dst_reg = None;
-
-			if !destination.is_register()
-			{
-				allocated_dst_reg = self.reg_alloc_allocate(dst_placeholder.data_type);
-				dst_placeholder = Placeholder::new(PlaceholderKind::Reg(allocated_dst_reg.unwrap()), dst_placeholder.data_type);
-				self.instr_mov(&dst_placeholder, destination);
-			}
-
-			if destination.data_type == Type::new(TypeKind::F64)
-			{
-				self.write_text_segment(&format!("\n\tmulsd {dst_placeholder}, {src_placeholder}"));
-			} else if destination.data_type == Type::new(TypeKind::F32)
-			{
-				self.write_text_segment(&format!("\n\tmulss {dst_placeholder}, {src_placeholder}"));
-			}
-
-			if *destination != dst_placeholder
-			{
-				self.instr_mov(destination, &dst_placeholder);
-			}
-
-			if let Some(allocated_dst_reg) = allocated_dst_reg
-			{
-				self.reg_alloc_free(allocated_dst_reg);
-			}
-		}
-
-		if let Some(allocated_register) = allocated_register
-		{
-			self.reg_alloc_free(allocated_register);
-		}
+		self.write_instruction(Mnemonic::Mul, &[destination, source]);
 	}
 
-	pub fn instr_div(&mut self, destination: &Placeholder, source: &Placeholder, get_modulo_in_dest: bool)
+	// Divides RDX:RAX (EDX:EAX for 32 bit sources) by the source. The quotient is in RAX and the remainder in RDX.
+	pub fn instr_div(&mut self, source: &Placeholder)
 	{
-		let mut dst = *destination;
-		let mut src = *source;
-		let mut src_reg = None;
-		let mut dst_reg = None;
-		
-		if source.is_constant()
-		{
-			src_reg = self.reg_alloc_allocate(source.data_type);
-			src = Placeholder::new(PlaceholderKind::Reg(src_reg.unwrap()), source.data_type);
-			self.instr_mov(&src, &source);
-		}
-
-		if destination.data_type.is_integer()
-		{
-			let rax = Register::from_op_size(Register::RAX, destination.data_type.size());
-			let rax_placeholder = Placeholder::new(PlaceholderKind::Reg(rax), destination.data_type);
-			if !destination.is_register_eq(rax)
-			{
-				self.instr_mov(&rax_placeholder, destination);			
-			}
-
-			let rdx_allocated = Register::from_op_size(Register::RDX, src.data_type.size());
-			
-			let ah_placeholder = Placeholder::new(PlaceholderKind::Reg(Register::AH), src.data_type);
-			let rdx_placeholder = Placeholder::new(PlaceholderKind::Reg(rdx_allocated), src.data_type);
-			if destination.data_type.is_signed()
-			{
-				match src.data_type.size()
-				{
-					OP_BYTE  => self.instr_cbw(),
-					OP_WORD  => self.instr_cwd(),
-					OP_DWORD => self.instr_cdq(),
-					OP_QWORD => self.instr_cqo(),
-					_ => todo!("Unimplemented Sign convertion."),
-				}
-				self.write_text_segment(&format!("\n\tidiv {} {src}", Self::size_2_opsize(src.data_type.size())));
-			} else
-			{
-				if src.data_type.size() == OP_BYTE
-				{
-					self.instr_xor(&ah_placeholder, &ah_placeholder);
-				} else
-				{
-					if src.data_type.size() != OP_BYTE && !destination.is_register_eq(rdx_allocated)
-					{
-						self.reg_alloc_allocate_forced(rdx_allocated);
-					}
-					self.instr_xor(&rdx_placeholder, &rdx_placeholder);
-				}
-				self.write_text_segment(&format!("\n\tdiv {} {src}", Self::size_2_opsize(src.data_type.size())));
-			}
-
-			if !destination.is_register_eq(rax) && !get_modulo_in_dest
-			{
-				self.instr_mov(destination, &rax_placeholder);
-			} else if get_modulo_in_dest
-			{
-				if src.data_type.size() == OP_BYTE && !destination.is_register_eq(Register::AH)
-				{
-					self.instr_mov(destination, &ah_placeholder);
-				} else if src.data_type.size() != OP_BYTE && !destination.is_register_eq(rdx_allocated)
-				{
-					self.instr_mov(destination, &rdx_placeholder);
-				}
-			}
-
-			if !src.data_type.is_signed() && src.data_type.size() != OP_BYTE && !destination.is_register_eq(rdx_allocated)
-			{
-				self.reg_alloc_free(rdx_allocated);
-			}
-
-		} else
-		{
-			if !destination.is_register()
-			{
-				dst_reg = self.reg_alloc_allocate(destination.data_type);
-				dst = Placeholder::new(PlaceholderKind::Reg(dst_reg.unwrap()), destination.data_type);
-				self.instr_mov(&dst, &destination);
-			}
-
-			if src.data_type == Type::new(TypeKind::F64)
-			{
-				self.write_text_segment(&format!("\n\tdivsd {dst}, {src}"));
-			} else if src.data_type == Type::new(TypeKind::F32)
-			{
-				self.write_text_segment(&format!("\n\tdivss {dst}, {src}"));
-			}
-		}
-
-		if let Some(src_reg) = src_reg
-		{
-			self.reg_alloc_free(src_reg);
-		}
+		self.write_instruction(Mnemonic::Div, &[source]);
+	}
 
-		if let Some(dst_reg) = dst_reg
-		{
-			self.reg_alloc_free(dst_reg);
-		}
+	pub fn instr_div_float(&mut self, destination: &Placeholder, source: &Placeholder)
+	{
+		self.write_instruction(Mnemonic::DivFloat, &[destination, source]);
 	}
 
 	pub fn instr_xor(&mut self, destination: &Placeholder, source: &Placeholder)
 	{
-		self.write_text_segment(&format!("\n\txor {} {destination}, {source}", Self::size_2_opsize(destination.data_type.size())));
+		self.write_instruction(Mnemonic::Xor, &[destination, source]);
 	}
 	
 	pub fn instr_or(&mut self, destination: &Placeholder, source: &Placeholder)
 	{
-		self.write_text_segment(&format!("\n\tor {} {destination}, {source}", Self::size_2_opsize(destination.data_type.size())));
+		self.write_instruction(Mnemonic::Or, &[destination, source]);
 	}
 
-	pub fn instr_not(&mut self, destination: &Placeholder)
+	pub fn instr_and(&mut self, destination: &Placeholder, source: &Placeholder)
 	{
-		self.write_text_segment(&format!("\n\tnot {} {destination}", Self::size_2_opsize(destination.data_type.size())));
+		self.write_instruction(Mnemonic::And, &[destination, source]);
 	}
 
-	pub fn instr_call(&mut self, identifier: &str)
+	pub fn instr_not(&mut self, destination: &Placeholder)
 	{
-		self.write_text_segment(&format!("\n\tcall {identifier}"));
+		self.write_instruction(Mnemonic::Not, &[destination]);
 	}
 
-	pub fn instr_cbw(&mut self)
+	// The shift count is either an immediate or CL
+	pub fn instr_shl(&mut self, destination: &Placeholder, count: &Placeholder)
 	{
-		self.write_text_segment("\n\tcbw");
+		self.write_instruction(Mnemonic::Shl, &[destination, count]);
 	}
-	
-	pub fn instr_cwd(&mut self)
+
+	pub fn instr_shr(&mut self, destination: &Placeholder, count: &Placeholder)
 	{
-		self.write_text_segment("\n\tcwd");
+		self.write_instruction(Mnemonic::Shr, &[destination, count]);
 	}
 
-	pub fn instr_cdq(&mut self)
+	pub fn instr_call(&mut self, identifier: &str)
 	{
-		self.write_text_segment("\n\tcdq");
+		self.text_segment.push(TextItem::Instruction(AsmInstruction::new(Mnemonic::Call, vec![AsmOperand::Function(identifier.to_string())])));
 	}
 
-	pub fn instr_cqo(&mut self)
+	// Call the function that the placeholder points to
+	pub fn instr_call_indirect(&mut self, function: &Placeholder)
 	{
-		self.write_text_segment("\n\tcqo");
+		self.write_instruction(Mnemonic::Call, &[function]);
 	}
 
-	pub fn instr_cdqe(&mut self)
+	// Jump into a function, as a tail call
+	pub fn instr_jmp_function(&mut self, identifier: &str)
 	{
-		self.write_text_segment("\n\tcdqe");
+		self.text_segment.push(TextItem::Instruction(AsmInstruction::new(Mnemonic::Jmp, vec![AsmOperand::Function(identifier.to_string())])));
 	}
 
-	pub fn instr_cmp(&mut self, lhs: &Placeholder, rhs: &Placeholder)
+	// Jump into the function that the placeholder points to, as a tail call
+	pub fn instr_jmp_indirect(&mut self, function: &Placeholder)
 	{
-		let mut lhs_placeholder = *lhs;
-		let mut allocated_register = None;
-		if (lhs.is_location() && rhs.is_location()) || (lhs.is_constant() && rhs.is_constant())
-		{
-			allocated_register = self.reg_alloc_allocate(lhs.data_type);
-			lhs_placeholder = Placeholder::new(PlaceholderKind::Reg(allocated_register.unwrap()), lhs.data_type);
-			self.instr_mov(&lhs_placeholder, lhs);
-		}
-
-		if lhs_placeholder.data_type.is_integer()
-		{
-			self.write_text_segment(&format!("\n\tcmp {} {lhs_placeholder}, {rhs}", Self::size_2_opsize(lhs_placeholder.data_type.size())));
-		} else if lhs_placeholder.data_type == Type::new(TypeKind::F64)
-		{
-			self.write_text_segment(&format!("\n\tucomisd {lhs_placeholder}, {rhs}"));
-		} else if lhs_placeholder.data_type == Type::new(TypeKind::F32)
-		{
-			self.write_text_segment(&format!("\n\tucomiss {lhs_placeholder}, {rhs}"));
-		}
-
-		if let Some(register) = allocated_register
-		{
-			self.reg_alloc_free(register);
-		}
+		self.write_instruction(Mnemonic::Jmp, &[function]);
 	}
 
-	pub fn instr_sete(&mut self, destination: &Placeholder)
+	// Load the address of a function
+	pub fn instr_lea_function(&mut self, destination: &Placeholder, identifier: &str)
 	{
-		let destination = destination.of_type(Type::new(TypeKind::U8));
-		self.write_text_segment(&format!("\n\tsete {} {destination}", Self::size_2_opsize(destination.data_type.size())));
+		self.text_segment.push(TextItem::Instruction(AsmInstruction::new(
+			Mnemonic::Lea,
+			vec![AsmOperand::Value(*destination), AsmOperand::Function(identifier.to_string())]
+		)));
 	}
 
-	pub fn instr_setne(&mut self, destination: &Placeholder)
+	pub fn instr_cdq(&mut self)
 	{
-		let destination = destination.of_type(Type::new(TypeKind::U8));
-		self.write_text_segment(&format!("\n\tsetne {} {destination}", Self::size_2_opsize(destination.data_type.size())));
+		self.write_instruction(Mnemonic::Cdq, &[]);
 	}
 
-	pub fn instr_setg(&mut self, destination: &Placeholder)
+	pub fn instr_cqo(&mut self)
 	{
-		let destination = destination.of_type(Type::new(TypeKind::U8));
-		if destination.data_type.is_signed() && destination.data_type.is_integer()
-		{
-			self.write_text_segment(&format!("\n\tsetg {} {destination}", Self::size_2_opsize(destination.data_type.size())));
-		} else
-		{
-			self.write_text_segment(&format!("\n\tseta {} {destination}", Self::size_2_opsize(destination.data_type.size())));
-		}
+		self.write_instruction(Mnemonic::Cqo, &[]);
 	}
 
-	pub fn instr_setl(&mut self, destination: &Placeholder)
+	// Floats are compared with ucomis
+	pub fn instr_cmp(&mut self, lhs: &Placeholder, rhs: &Placeholder)
 	{
-		let destination = destination.of_type(Type::new(TypeKind::U8));
-		if destination.data_type.is_signed() && destination.data_type.is_integer()
-		{
-			self.write_text_segment(&format!("\n\tsetl {} {destination}", Self::size_2_opsize(destination.data_type.size())));
-		} else
-		{
-			self.write_text_segment(&format!("\n\tsetb {} {destination}", Self::size_2_opsize(destination.data_type.size())));
-		}
+		self.write_instruction(Mnemonic::Cmp, &[lhs, rhs]);
 	}
 
-	pub fn instr_setge(&mut self, destination: &Placeholder)
+	pub fn instr_test(&mut self, lhs: &Placeholder, rhs: &Placeholder)
 	{
-		let destination = destination.of_type(Type::new(TypeKind::U8));
-		if destination.data_type.is_signed() && destination.data_type.is_integer()
-		{
-			self.write_text_segment(&format!("\n\tsetge {} {destination}", Self::size_2_opsize(destination.data_type.size())));
-		} else
-		{
-			self.write_text_segment(&format!("\n\tsetae {} {destination}", Self::size_2_opsize(destination.data_type.size())));
-		}
+		self.write_instruction(Mnemonic::Test, &[lhs, rhs]);
 	}
 
-	pub fn instr_setle(&mut self, destination: &Placeholder)
+	// Sets the byte destination to 1 if the condition is true after a cmp (or ucomis) instruction, and to 0 otherwise.
+	// Signed integers use the signed conditions, unsigned integers, addresses and floats the unsigned ones.
+	pub fn instr_setcc(&mut self, condition: Condition, signed: bool, destination: &Placeholder)
 	{
-		let destination = destination.of_type(Type::new(TypeKind::U8));
-		if destination.data_type.is_signed() && destination.data_type.is_integer()
-		{
-			self.write_text_segment(&format!("\n\tsetle {} {destination}", Self::size_2_opsize(destination.data_type.size())));
-		} else
-		{
-			self.write_text_segment(&format!("\n\tsetbe {} {destination}", Self::size_2_opsize(destination.data_type.size())));
-		}
+		self.write_instruction(Mnemonic::Setcc(ConditionCode::from_condition(condition, signed)), &[destination]);
 	}
 
 	pub fn instr_setz(&mut self, destination: &Placeholder)
 	{
-		self.write_text_segment(&format!("\n\tsetz {} {destination}", Self::size_2_opsize(destination.data_type.size())));
+		self.write_instruction(Mnemonic::Setcc(ConditionCode::E), &[destination]);
 	}
 
-	pub fn instr_test(&mut self, lhs: &Placeholder, rhs: &Placeholder)
+	fn write_jump(&mut self, mnemonic: Mnemonic, lable: Lable)
 	{
-		let mut lhs_placeholder = *lhs;
-		let mut allocated_register = None;	
-		if (lhs.is_location() && rhs.is_location()) || (lhs.is_constant() && (rhs.is_location() || rhs.is_constant()))
-		{
-			allocated_register = self.reg_alloc_allocate(lhs.data_type);
-			lhs_placeholder = Placeholder::new(PlaceholderKind::Reg(allocated_register.unwrap()), lhs.data_type);
-			self.instr_mov(&lhs_placeholder, lhs);
-		}
-
-		self.write_text_segment(&format!("\n\ttest {} {lhs_placeholder}, {rhs}", Self::size_2_opsize(lhs_placeholder.data_type.size())));
-
-		if let Some(allocated_register) = allocated_register
-		{
-			self.reg_alloc_free(allocated_register);
-		}
+		self.text_segment.push(TextItem::Instruction(AsmInstruction::new(mnemonic, vec![AsmOperand::Lable(lable)])));
 	}
 
 	pub fn instr_jmp(&mut self, lable: Lable)
 	{
-		self.write_text_segment(&format!("\n\tjmp {lable}"));
+		self.write_jump(Mnemonic::Jmp, lable);
 	}
 
 	pub fn instr_jz(&mut self, lable: Lable)
 	{
-		self.write_text_segment(&format!("\n\tjz {lable}"));
+		self.write_jump(Mnemonic::Jcc(ConditionCode::E), lable);
 	}
 
 	pub fn instr_jnz(&mut self, lable: Lable)
 	{
-		self.write_text_segment(&format!("\n\tjnz {lable}"));
-	}
-
-	pub fn instr_shr(&mut self, destination: &Placeholder, source: &Placeholder)
-	{
-		let mut src_placeholder = *source;
-		if !source.is_constant() && !source.is_register_eq(Register::from_op_size(Register::RCX, source.data_type.size()))
-		{
-			self.reg_alloc_allocate_forced(Register::CL);
-			src_placeholder = Placeholder::new(PlaceholderKind::Reg(Register::CL), source.data_type);
-			self.instr_mov(
-				&Placeholder::new(
-					PlaceholderKind::Reg(Register::from_op_size(Register::RCX, source.data_type.size())), 
-					source.data_type
-				), 
-				source
-			);
-		}
-
-		if src_placeholder.is_constant()
-		{
-			self.write_text_segment(&format!("\n\tshr {} {destination}, {src_placeholder}", Self::size_2_opsize(destination.data_type.size())));
-		} else
-		{
-			self.write_text_segment(&format!(
-				"\n\tshr {} {destination}, {}", Self::size_2_opsize(destination.data_type.size()), Register::CL
-			));
-		}
-
-		if !source.is_constant()
-		{
-			self.reg_alloc_free(Register::CL);
-		}
+		self.write_jump(Mnemonic::Jcc(ConditionCode::Ne), lable);
 	}
-
-	pub fn instr_shl(&mut self, destination: &Placeholder, source: &Placeholder)
-	{
-		let mut src_placeholder = *source;
-		if !source.is_constant() && !source.is_register_eq(Register::from_op_size(Register::RCX, source.data_type.size()))
-		{
-			self.reg_alloc_allocate_forced(Register::CL);
-			src_placeholder = Placeholder::new(PlaceholderKind::Reg(Register::CL), source.data_type);
-			self.instr_mov(
-				&Placeholder::new(
-					PlaceholderKind::Reg(Register::from_op_size(Register::RCX, source.data_type.size())), 
-					source.data_type
-				), 
-				source
-			);
-		}
-
-		if src_placeholder.is_constant()
-		{
-			self.write_text_segment(&format!("\n\tshl {} {destination}, {src_placeholder}", Self::size_2_opsize(destination.data_type.size())));
-		} else
-		{
-			self.write_text_segment(&format!(
-				"\n\tshl {} {destination}, {}", Self::size_2_opsize(destination.data_type.size()), Register::CL
-			));
-		}
-
-		if !source.is_constant()
-		{
-			self.reg_alloc_free(Register::CL);
-		}
-	}
-
-	pub fn instr_and(&mut self, destination: &Placeholder, source: &Placeholder)
-	{
-		self.write_text_segment(&format!("\n\tand {} {destination}, {source}", Self::size_2_opsize(destination.data_type.size())));
-	}
-}
\ No newline at end of file
+}
@@ -0,0 +1,672 @@
+use super::*;
+
+// The peephole optimizer (at -O1 and above). It looks at short sequences of instructions in the text segment, and replaces them with
+// shorter or faster ones, until nothing changes:
+// 	- Moves into the place that they move from, moves back into the place that was just moved from, and writes into registers
+// 	  that are not read again are removed.
+// 	- A value that is moved into a register only to be moved somewhere else (maybe after computing on it) is moved there directly.
+// 	- A value that is moved into a register only to be compared (or used as the source of an instruction) is used where it is.
+// 	- A multiplication by a power of two becomes a shift.
+// 	- Adjustments of RSP are merged, and a push followed by a pop becomes a move.
+// 	- mov reg, 0 becomes xor reg, reg, when the flags are not read before they are written again.
+// The code generator never keeps a value in a scratch register (or in the flags) across a lable, so a register that is
+// written before it is read again (or is not read before a lable) is no longer needed.
+// The amount of instructions that is_dead_after looks at before it decides that the register may be read
+const DEAD_SCAN_LIMIT: usize = 64;
+
+pub fn optimize(text: &mut Vec<TextItem>)
+{
+	loop
+	{
+		let mut changed = false;
+		let mut i = 0;
+		while i < text.len()
+		{
+			// A change can make the instruction before the index part of a new sequence
+			if optimize_at(text, i)
+			{
+				changed = true;
+				i = i.saturating_sub(1);
+				continue;
+			}
+			i += 1;
+		}
+		if !changed
+		{
+			return;
+		}
+	}
+}
+
+// Tries each rule on the instructions that start at the index. The rules change the text only at the index and after it.
+fn optimize_at(text: &mut Vec<TextItem>, i: usize) -> bool
+{
+	if instruction(text, i).is_none()
+	{
+		return false;
+	}
+	return remove_useless_move(text, i)
+		|| remove_dead_write(text, i)
+		|| remove_move_back(text, i)
+		|| forward_move(text, i)
+		|| compare_in_place(text, i)
+		|| multiply_with_shift(text, i)
+		|| forward_operand(text, i)
+		|| merge_stack_adjustments(text, i)
+		|| replace_push_pop(text, i)
+		|| zero_with_xor(text, i);
+}
+
+// 	mov rax, rax 	=>
+// mov eax, eax is kept, it clears the high 32 bits of rax.
+fn remove_useless_move(text: &mut Vec<TextItem>, i: usize) -> bool
+{
+	let first = instruction(text, i).unwrap();
+	if first.mnemonic != Mnemonic::Mov || first.operand(0) != first.operand(1) || !same_size(first.operand(0), first.operand(1))
+	{
+		return false;
+	}
+	if register_operand(first, 0).is_some_and(is_zero_extending)
+	{
+		return false;
+	}
+	text.remove(i);
+	return true;
+}
+
+// An instruction that only writes a register that is not read again is removed
+// 	mov rdi, rax 	=>
+// 	ret
+fn remove_dead_write(text: &mut Vec<TextItem>, i: usize) -> bool
+{
+	let first = instruction(text, i).unwrap();
+	if !writes_only(first.mnemonic) || first.mnemonic == Mnemonic::Pop
+	{
+		return false;
+	}
+	let Some(register) = register_operand(first, 0) else { return false; };
+	if !is_dead_after(text, i, register)
+	{
+		return false;
+	}
+	text.remove(i);
+	return true;
+}
+
+// 	mov rax, [rbp + -8] 	=> 	mov rax, [rbp + -8]
+// 	mov [rbp + -8], rax
+fn remove_move_back(text: &mut Vec<TextItem>, i: usize) -> bool
+{
+	let first = instruction(text, i).unwrap();
+	let Some(j) = next_instruction(text, i) else { return false; };
+	let second = instruction(text, j).unwrap();
+	if first.mnemonic != Mnemonic::Mov || second.mnemonic != Mnemonic::Mov
+	{
+		return false;
+	}
+
+	let (destination, source) = (first.operand(0).unwrap(), first.operand(1).unwrap());
+	if Some(destination) != second.operand(1) || Some(source) != second.operand(0) || !same_size(Some(destination), Some(source))
+	{
+		return false;
+	}
+
+	// mov rax, [rax] changes the address of the source
+	if let PlaceholderKind::Reg(register) = destination.kind
+	{
+		if source.uses_register(register)
+		{
+			return false;
+		}
+	}
+
+	// mov edi, eax followed by mov eax, edi clears the high 32 bits of rax
+	if register_operand(second, 0).is_some_and(is_zero_extending)
+	{
+		return false;
+	}
+	text.remove(j);
+	return true;
+}
+
+// 	mov rax, [rbp + -8] 	=> 	mov rbx, [rbp + -8]
+// 	add rax, 5 				=> 	add rbx, 5
+// 	mov rbx, rax
+// The destination of the last move must be a register if there are instructions between the moves, or if the first instruction is not a move.
+// A move of the low part of the register is done from the low part of the source.
+// 	mov rax, [rbp + -8] 	=> 	mov dil, byte [rbp + -8]
+// 	mov dil, al
+fn forward_move(text: &mut Vec<TextItem>, i: usize) -> bool
+{
+	let first = instruction(text, i).unwrap();
+	let produces = writes_only(first.mnemonic) && !matches!(first.mnemonic, Mnemonic::Pop | Mnemonic::Setcc(_));
+	let Some(register) = register_operand(first, 0).filter(|_| produces) else { return false; };
+	let written = *first.operand(0).unwrap();
+	let Some(source) = first.operand(1).copied() else { return false; };
+
+	// Instructions that only change the register, with operands that dont use it
+	let mut computations = Vec::new();
+	let mut j = i;
+	let last = loop
+	{
+		let Some(next) = next_instruction(text, j) else { return false; };
+		j = next;
+		let instruction = instruction(text, j).unwrap();
+		if instruction.mnemonic == Mnemonic::Mov
+		{
+			break instruction;
+		}
+		let is_computation = matches!(
+			instruction.mnemonic,
+			Mnemonic::Add | Mnemonic::Sub | Mnemonic::Mul | Mnemonic::DivFloat | Mnemonic::Xor | Mnemonic::Or | Mnemonic::And | Mnemonic::Not | Mnemonic::Shl | Mnemonic::Shr
+		);
+		if !is_computation || register_operand(instruction, 0) != Some(register) || instruction.operand(1).is_some_and(|operand| operand.uses_register(register))
+		{
+			return false;
+		}
+		computations.push(j);
+	};
+
+	let Some(moved) = register_operand(last, 1).filter(|moved| moved.base_register() == register.base_register()) else { return false; };
+	let destination = *last.operand(0).unwrap();
+	if destination.uses_register(register) || !is_dead_after(text, j, register)
+	{
+		return false;
+	}
+
+	let source = if moved == register
+	{
+		source
+	} else if computations.is_empty() && first.mnemonic == Mnemonic::Mov && register.is_general() && !source.is_constant() && destination.data_type.size() < written.data_type.size()
+	{
+		source.of_type(destination.data_type)
+	} else
+	{
+		return false;
+	};
+	if moved == register && destination.data_type.size() != written.data_type.size()
+	{
+		return false;
+	}
+
+	if computations.is_empty() && first.mnemonic == Mnemonic::Mov
+	{
+		if !destination.is_register() && !source.is_register() && !fits_immediate(&source)
+		{
+			return false;
+		}
+	} else
+	{
+		let PlaceholderKind::Reg(new_register) = destination.kind else { return false; };
+		let uses_destination = computations.iter().any(|index| {
+			return instruction(text, *index).unwrap().operand(1).is_some_and(|operand| operand.uses_register(new_register));
+		});
+		if uses_destination || new_register.is_general() != register.is_general()
+		{
+			return false;
+		}
+	}
+
+	text.remove(j);
+	for index in computations
+	{
+		let instruction = instruction_mut(text, index);
+		let data_type = instruction.operand(0).unwrap().data_type;
+		instruction.operands[0] = AsmOperand::Value(destination.of_type(data_type));
+	}
+	let first = instruction_mut(text, i);
+	first.operands[0] = AsmOperand::Value(destination);
+	first.operands[1] = AsmOperand::Value(source);
+	return true;
+}
+
+// 	mov rax, rbx 	=> 	cmp rbx, 4
+// 	cmp rax, 4
+fn compare_in_place(text: &mut Vec<TextItem>, i: usize) -> bool
+{
+	let first = instruction(text, i).unwrap();
+	let Some(register) = register_operand(first, 0).filter(|_| first.mnemonic == Mnemonic::Mov) else { return false; };
+	let Some(j) = next_instruction(text, i) else { return false; };
+	let second = instruction(text, j).unwrap();
+	if second.mnemonic != Mnemonic::Cmp || register_operand(second, 0) != Some(register)
+	{
+		return false;
+	}
+
+	// ucomis compares a register, and cmp cant compare two memory operands
+	let (value, rhs) = (*first.operand(1).unwrap(), *second.operand(1).unwrap());
+	let is_valid = value.is_register() || (value.data_type.is_integer() && !value.is_constant() && (rhs.is_register() || rhs.is_constant()));
+	if !is_valid || rhs.uses_register(register) || !same_size(Some(&value), Some(&rhs)) || !is_dead_after(text, j, register)
+	{
+		return false;
+	}
+
+	instruction_mut(text, j).operands[0] = AsmOperand::Value(value);
+	text.remove(i);
+	return true;
+}
+
+// 	mov rcx, [rbp + -8] 	=> 	add rax, [rbp + -8]
+// 	add rax, rcx
+fn forward_operand(text: &mut Vec<TextItem>, i: usize) -> bool
+{
+	let first = instruction(text, i).unwrap();
+	let Some(register) = register_operand(first, 0).filter(|_| first.mnemonic == Mnemonic::Mov) else { return false; };
+	let Some(j) = next_instruction(text, i) else { return false; };
+	let second = instruction(text, j).unwrap();
+	let takes_operand = matches!(
+		second.mnemonic,
+		Mnemonic::Add | Mnemonic::Sub | Mnemonic::Mul | Mnemonic::DivFloat | Mnemonic::Xor | Mnemonic::Or | Mnemonic::And | Mnemonic::Cmp | Mnemonic::Test
+	);
+	if !takes_operand || register_operand(second, 1) != Some(register)
+	{
+		return false;
+	}
+
+	// Two operands cant both be in memory, and imul takes an immediate only with three operands
+	let (lhs, value) = (*second.operand(0).unwrap(), *first.operand(1).unwrap());
+	let is_valid = if value.is_constant()
+	{
+		second.mnemonic != Mnemonic::Mul && fits_immediate(&value)
+	} else
+	{
+		value.is_register() || lhs.is_register()
+	};
+	if !is_valid || lhs.uses_register(register) || !is_dead_after(text, j, register)
+	{
+		return false;
+	}
+
+	instruction_mut(text, j).operands[1] = AsmOperand::Value(value);
+	text.remove(i);
+	return true;
+}
+
+// 	mov ecx, 8 		=> 	shl eax, 3
+// 	imul eax, ecx
+// A multiplication by 1 is removed.
+fn multiply_with_shift(text: &mut Vec<TextItem>, i: usize) -> bool
+{
+	let first = instruction(text, i).unwrap();
+	if first.mnemonic == Mnemonic::Mul && first.operand(0).unwrap().data_type.is_integer()
+	{
+		let Some(PlaceholderKind::Integer(value)) = first.operand(1).map(|operand| operand.kind) else { return false; };
+		if !value.is_power_of_two()
+		{
+			return false;
+		}
+		if value == 1
+		{
+			text.remove(i);
+			return true;
+		}
+		let instruction = instruction_mut(text, i);
+		instruction.mnemonic = Mnemonic::Shl;
+		instruction.operands[1] = AsmOperand::Value(Placeholder::new(PlaceholderKind::Integer(value.trailing_zeros() as u64), Type::new(TypeKind::U8)));
+		return true;
+	}
+
+	let Some(register) = register_operand(first, 0).filter(|_| first.mnemonic == Mnemonic::Mov) else { return false; };
+	let Some(PlaceholderKind::Integer(value)) = first.operand(1).map(|operand| operand.kind) else { return false; };
+	let Some(j) = next_instruction(text, i) else { return false; };
+	let second = instruction(text, j).unwrap();
+	if second.mnemonic != Mnemonic::Mul || register_operand(second, 1) != Some(register) || !value.is_power_of_two() || !is_dead_after(text, j, register)
+	{
+		return false;
+	}
+	if second.operand(0).unwrap().uses_register(register)
+	{
+		return false;
+	}
+
+	if value == 1
+	{
+		text.remove(j);
+		text.remove(i);
+		return true;
+	}
+	let instruction = instruction_mut(text, j);
+	instruction.mnemonic = Mnemonic::Shl;
+	instruction.operands[1] = AsmOperand::Value(Placeholder::new(PlaceholderKind::Integer(value.trailing_zeros() as u64), Type::new(TypeKind::U8)));
+	text.remove(i);
+	return true;
+}
+
+// 	add rsp, 16 			=> 	mov [rbp + -8], rax
+// 	mov [rbp + -8], rax
+// 	sub rsp, 16
+// Instructions that dont use RSP are skipped, the adjustment is moved into the next one.
+fn merge_stack_adjustments(text: &mut Vec<TextItem>, i: usize) -> bool
+{
+	let Some(adjustment) = stack_adjustment(instruction(text, i).unwrap()) else { return false; };
+	if adjustment == 0
+	{
+		text.remove(i);
+		return true;
+	}
+
+	let mut j = i;
+	while let Some(next) = next_instruction(text, j)
+	{
+		j = next;
+		let instruction = instruction(text, j).unwrap();
+		if let Some(next_adjustment) = stack_adjustment(instruction)
+		{
+			let total = adjustment + next_adjustment;
+			let instruction = instruction_mut(text, j);
+			instruction.mnemonic = if total < 0 { Mnemonic::Sub } else { Mnemonic::Add };
+			instruction.operands[1] = AsmOperand::Value(Placeholder::new(PlaceholderKind::Integer(total.unsigned_abs()), Type::new(TypeKind::U64)));
+			text.remove(i);
+			return true;
+		}
+
+		let uses_stack = matches!(instruction.mnemonic, Mnemonic::Push | Mnemonic::Pop | Mnemonic::Call | Mnemonic::Ret | Mnemonic::Jmp | Mnemonic::Jcc(_))
+			|| instruction.operands.iter().any(|operand| operand.placeholder().is_some_and(|operand| operand.uses_register(Register::RSP)));
+		if uses_stack
+		{
+			return false;
+		}
+	}
+	return false;
+}
+
+// 	push rbx 	=> 	mov rax, rbx
+// 	pop rax
+fn replace_push_pop(text: &mut Vec<TextItem>, i: usize) -> bool
+{
+	let first = instruction(text, i).unwrap();
+	let Some(j) = next_instruction(text, i) else { return false; };
+	let second = instruction(text, j).unwrap();
+	if first.mnemonic != Mnemonic::Push || second.mnemonic != Mnemonic::Pop
+	{
+		return false;
+	}
+
+	let (source, destination) = (*first.operand(0).unwrap(), *second.operand(0).unwrap());
+	if !source.is_register() && !destination.is_register()
+	{
+		return false;
+	}
+	text.remove(j);
+	if source == destination
+	{
+		text.remove(i);
+	} else
+	{
+		*instruction_mut(text, i) = AsmInstruction::new(Mnemonic::Mov, vec![AsmOperand::Value(destination), AsmOperand::Value(source)]);
+	}
+	return true;
+}
+
+// 	mov rax, 0 	=> 	xor eax, eax
+// Writing into a 32 bit register clears the high 32 bits, so 64 bit registers are cleared through their low 32 bits.
+fn zero_with_xor(text: &mut [TextItem], i: usize) -> bool
+{
+	let first = instruction(text, i).unwrap();
+	let Some(register) = register_operand(first, 0).filter(|register| first.mnemonic == Mnemonic::Mov && register.is_general()) else { return false; };
+	if !matches!(first.operand(1).unwrap().kind, PlaceholderKind::Integer(0)) || !are_flags_dead_after(text, i)
+	{
+		return false;
+	}
+
+	let destination = first.operand(0).unwrap();
+	let destination = if destination.data_type.size() == OP_QWORD
+	{
+		Placeholder::new(PlaceholderKind::Reg(register.of_size(OP_DWORD)), Type::new(TypeKind::U32))
+	} else
+	{
+		*destination
+	};
+	*instruction_mut(text, i) = AsmInstruction::new(Mnemonic::Xor, vec![AsmOperand::Value(destination), AsmOperand::Value(destination)]);
+	return true;
+}
+
+fn instruction(text: &[TextItem], i: usize) -> Option<&AsmInstruction>
+{
+	return match text.get(i)?
+	{
+		TextItem::Instruction(instruction) 	=> Some(instruction),
+		_ 									=> None,
+	};
+}
+
+fn instruction_mut(text: &mut [TextItem], i: usize) -> &mut AsmInstruction
+{
+	match &mut text[i]
+	{
+		TextItem::Instruction(instruction) 	=> return instruction,
+		_ 									=> panic!("Dev error! instruction_mut called with an index that is not an instruction."),
+	}
+}
+
+// The index of the instruction after the one at the index, skipping comments. A sequence of instructions ends at a lable.
+fn next_instruction(text: &[TextItem], i: usize) -> Option<usize>
+{
+	for (j, item) in text.iter().enumerate().skip(i + 1)
+	{
+		match item
+		{
+			TextItem::Comment(_) 		=> continue,
+			TextItem::Instruction(_) 	=> return Some(j),
+			_ 							=> return None,
+		}
+	}
+	return None;
+}
+
+fn register_operand(instruction: &AsmInstruction, index: usize) -> Option<Register>
+{
+	return match instruction.operand(index)?.kind
+	{
+		PlaceholderKind::Reg(register) 	=> Some(register),
+		_ 								=> None,
+	};
+}
+
+fn same_size(lhs: Option<&Placeholder>, rhs: Option<&Placeholder>) -> bool
+{
+	return lhs.zip(rhs).is_some_and(|(lhs, rhs)| lhs.data_type.size() == rhs.data_type.size());
+}
+
+// Writing into a 32 bit general purpose register clears the high 32 bits of the 64 bit register, so the write is not useless
+fn is_zero_extending(register: Register) -> bool
+{
+	return register.is_general() && register.size() == OP_DWORD;
+}
+
+// Whether a source can be the immediate of an instruction (other than mov reg, imm). Constants must fit in a sign extended
+// 32 bit immediate for 64 bit operands, or in the size of the operand.
+fn fits_immediate(source: &Placeholder) -> bool
+{
+	let PlaceholderKind::Integer(value) = source.kind else { return false; };
+	let size = source.data_type.size();
+	if size == OP_QWORD
+	{
+		return value as i64 == value as i32 as i64;
+	}
+	let bits = size as u32 * 8;
+	return value < 1 << bits || value as i64 >= -(1i64 << (bits - 1));
+}
+
+// How much an add rsp, imm (or sub rsp, imm) moves RSP
+fn stack_adjustment(instruction: &AsmInstruction) -> Option<i64>
+{
+	if register_operand(instruction, 0) != Some(Register::RSP)
+	{
+		return None;
+	}
+	let PlaceholderKind::Integer(value) = instruction.operand(1)?.kind else { return None; };
+	return match instruction.mnemonic
+	{
+		Mnemonic::Add => Some(value as i64),
+		Mnemonic::Sub => Some(-(value as i64)),
+		_ => None,
+	};
+}
+
+// Instructions that write their first operand without reading it
+fn writes_only(mnemonic: Mnemonic) -> bool
+{
+	return matches!(
+		mnemonic,
+		Mnemonic::Mov | Mnemonic::Movzx | Mnemonic::Movsx | Mnemonic::Movsxd | Mnemonic::Lea
+			| Mnemonic::Cvttsf2si | Mnemonic::Cvtsi2sf | Mnemonic::Cvtsf2sf | Mnemonic::Pop | Mnemonic::Setcc(_)
+	);
+}
+
+// Whether the instruction reads a part of the register that is higher than its low bytes that were overwritten.
+// Vector registers are always read and written whole.
+fn reads_register(instruction: &AsmInstruction, register: Register, overwritten: OpSize) -> bool
+{
+	// Instructions that use registers that are not their operands. A call reads the argument registers (and AL for variadic functions),
+	// and the caller of the function reads the return value and the callee-saved registers. A tail call does both.
+	let base = register.base_register();
+	match instruction.mnemonic
+	{
+		Mnemonic::Div | Mnemonic::Cdq | Mnemonic::Cqo 						=> return true,
+		Mnemonic::Push | Mnemonic::Pop if base == Register::RSP 			=> return true,
+		Mnemonic::Call if base == Register::RAX || is_argument_register(base) => return true,
+		Mnemonic::Ret if base == Register::RAX || base == Register::XMM0 || !is_caller_saved(base) => return true,
+		Mnemonic::Jmp if is_tail_call(instruction) && (base == Register::RAX || is_argument_register(base) || !is_caller_saved(base)) => return true,
+		_ => (),
+	}
+
+	return instruction.operands.iter().enumerate().any(|(index, operand)| {
+		let Some(operand) = operand.placeholder() else { return false; };
+		return match operand.kind
+		{
+			PlaceholderKind::Reg(used) if used.base_register() == register.base_register() =>
+			{
+				let size = if used.is_general() { operand.data_type.size() } else { OP_QWORD };
+				!(index == 0 && writes_only(instruction.mnemonic)) && size > overwritten
+			},
+			_ => operand.uses_register(register) && !operand.is_register(),
+		};
+	});
+}
+
+// A jump into a function, instead of into a lable of the current function
+fn is_tail_call(instruction: &AsmInstruction) -> bool
+{
+	return instruction.mnemonic == Mnemonic::Jmp && !matches!(instruction.operands[..], [AsmOperand::Lable(_)]);
+}
+
+fn is_argument_register(register: Register) -> bool
+{
+	let base = register.base_register();
+	return [Register::RDI, Register::RSI, Register::RDX, Register::RCX, Register::R8, Register::R9].contains(&base)
+		|| (base as u8 >= Register::XMM0 as u8 && base as u8 <= Register::XMM7 as u8);
+}
+
+// The registers that a called function may change
+fn is_caller_saved(register: Register) -> bool
+{
+	let base = register.base_register();
+	return !base.is_general() || [Register::RAX, Register::RCX, Register::RDX, Register::RSI, Register::RDI, Register::R8, Register::R9, Register::R10, Register::R11].contains(&base);
+}
+
+// Whether the value in the register is never read after the instruction at the index, until all of its bytes are overwritten.
+// The instructions are followed through jumps, and each lable is visited once (a path that comes back to a lable reads nothing new).
+fn is_dead_after(text: &[TextItem], i: usize, register: Register) -> bool
+{
+	let value_size = register.size();
+	let mut visited: Vec<Lable> = Vec::new();
+	let mut paths: Vec<(usize, OpSize)> = vec![(i, 0)];
+	let mut scanned = 0;
+	'paths: while let Some((start, mut overwritten)) = paths.pop()
+	{
+		let mut index = start + 1;
+		while let Some(item) = text.get(index)
+		{
+			index += 1;
+			scanned += 1;
+			if scanned > DEAD_SCAN_LIMIT
+			{
+				return false;
+			}
+			let instruction = match item
+			{
+				TextItem::Instruction(instruction) => instruction,
+				TextItem::Lable(lable) =>
+				{
+					if visited.contains(lable)
+					{
+						continue 'paths;
+					}
+					visited.push(*lable);
+					continue;
+				},
+				TextItem::Comment(_) 	=> continue,
+				TextItem::Function(_) 	=> continue 'paths,
+			};
+
+			if reads_register(instruction, register, overwritten)
+			{
+				return false;
+			}
+
+			// Writing into a 32 bit register clears the high 32 bits
+			if let Some(written) = instruction.operand(0).filter(|_| writes_only(instruction.mnemonic))
+			{
+				if written.is_register() && written.uses_register(register)
+				{
+					let size = written.data_type.size();
+					overwritten = overwritten.max(if !register.is_general() || size >= OP_DWORD { OP_QWORD } else { size });
+				}
+			}
+			if overwritten >= value_size || (instruction.mnemonic == Mnemonic::Call && is_caller_saved(register)) || is_tail_call(instruction)
+			{
+				continue 'paths;
+			}
+
+			match (instruction.mnemonic, &instruction.operands[..])
+			{
+				(Mnemonic::Jmp, [AsmOperand::Lable(target)]) =>
+				{
+					let Some(position) = lable_position(text, *target, index) else { return false; };
+					index = position;
+				},
+				(Mnemonic::Jcc(_), [AsmOperand::Lable(target)]) =>
+				{
+					let Some(position) = lable_position(text, *target, index) else { return false; };
+					paths.push((position - 1, overwritten));
+				},
+				_ => (),
+			}
+		}
+	}
+	return true;
+}
+
+// Jumps are usually into a lable that is near them, so it is searched after the jump first and then before it
+fn lable_position(text: &[TextItem], lable: Lable, jump: usize) -> Option<usize>
+{
+	let is_lable = |item: &TextItem| matches!(item, TextItem::Lable(other) if *other == lable);
+	return text[jump..].iter().position(is_lable).map(|position| jump + position).or_else(|| text[..jump].iter().rposition(is_lable));
+}
+
+// Whether the flags are written before they are read again after the instruction at the index
+fn are_flags_dead_after(text: &[TextItem], i: usize) -> bool
+{
+	for item in &text[i + 1..]
+	{
+		let TextItem::Instruction(instruction) = item else
+		{
+			if let TextItem::Comment(_) = item
+			{
+				continue;
+			}
+			return true;
+		};
+
+		let is_integer = instruction.operand(0).is_some_and(|operand| operand.data_type.is_integer());
+		match instruction.mnemonic
+		{
+			Mnemonic::Setcc(_) | Mnemonic::Jcc(_) => return false,
+			Mnemonic::Cmp | Mnemonic::Test | Mnemonic::Div | Mnemonic::Call | Mnemonic::Ret | Mnemonic::Jmp => return true,
+			Mnemonic::Add | Mnemonic::Sub | Mnemonic::Mul | Mnemonic::Xor | Mnemonic::Or | Mnemonic::And if is_integer => return true,
+			_ => (),
+		}
+	}
+	return true;
+}
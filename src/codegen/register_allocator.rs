@@ -1,305 +1,179 @@
 use super::*;
 
-pub const ALLOCATABLE_REGS_COUNT: usize = Register::COUNT_FULL as usize - 3 - 1;
-const GENERAL_REGISTERS: usize = ALLOCATABLE_REGS_COUNT - 15;
-const XMM_REGS_INDEX: usize = GENERAL_REGISTERS;
-
-#[derive(Debug, Clone, Copy)]
-pub struct RegisterInfo
+// The part of a function in which a virtual register is live. Each instruction n (counting the instructions and the terminators
+// of the blocks in order, from 1) reads its operands at 2n and writes its result at 2n + 1. The parameters are written at 0.
+struct LiveInterval
 {
-	pub register: Register,
-	push_count: u8,	
-	pub was_saved_before_call: bool,
-
-	// Using an option, because the register might not have lower parts (for example RDI doesnt have an high 8 bits sub-register)
-	// In the future there will also be ZMM registers
-	pub is_free: bool,					/* If the higher parts of the registers are free */
-	pub is_l8_free: bool,
-	pub is_h8_free: Option<bool>,		/* If exists, specifies if the high 8 bits of the low 16 bits are free */
+	register: RegisterId,
+	start: usize,
+	end: usize,
+	crosses_call: bool,				/* Live while a function is called, so only a callee-saved register keeps it */
+	live_with_arguments: bool,		/* Live while arguments are passed (when the function starts, or when calling a function) */
 }
 
-impl RegisterInfo
+impl<'a> CodeGen<'a>
 {
-	pub fn new(register: Register) -> Self
+	// At -O2, gives the virtual registers physical registers with linear scan. The intervals are visited by their start, and an interval
+	// that finds no free register takes the register of the interval that ends last, which stays in its home on the stack for the whole function.
+	// Registers are handed out whole (a value in RSI is accessed through ESI, SI or SIL), so two values never share a register with its aliases.
+	// A value that is live while a function is called gets a callee-saved register (which the prologue saves once), so nothing is saved around calls.
+	pub fn allocate_registers(&mut self, function: &mir::Function)
 	{
-		let is_free = true;
-		let is_l8_free = true;
-		let is_h8_free;
-
-		match register
+		self.register_locations = vec![None; function.registers.len()];
+		self.saved_registers.clear();
+		if self.optimization_level < mir::OPTIMIZATION_LEVEL_REGISTERS
 		{
-			Register::RAX | Register::RBX | Register::RCX | Register::RDX => is_h8_free = Some(true),
-			_ => is_h8_free = None,
+			return;
 		}
 
-		return Self {
-			register,
-			was_saved_before_call: false,
-			push_count: 0,
-			is_free,
-			is_l8_free,
-			is_h8_free,
-		};
-	}
-
-	pub fn is_used(&self) -> bool
-	{
-		return !self.is_free || !self.is_l8_free || (self.is_h8_free != None && !self.is_h8_free.unwrap());
-	}
-}
+		// Parameters that were passed on the stack are used where the caller has put them
+		let signature = &self.program.function_types[function.signature.index()];
+		let mut on_stack = vec![false; function.registers.len()];
+		for (i, class) in Self::classify_arguments_sys_v_abi_x86_64(&signature.parameters).iter().enumerate()
+		{
+			on_stack[function.parameters[i].index()] = matches!(class, ArgumentClass::Stack(_));
+		}
 
-impl<'a> CodeGen<'a>
-{
-	pub fn reg_alloc_init() -> [RegisterInfo; ALLOCATABLE_REGS_COUNT]
-	{
-		let mut register_index: u8 = 0;
-		let registers: [RegisterInfo; ALLOCATABLE_REGS_COUNT] = core::array::from_fn(|_| {
-			loop
-			{
-				let reg = Register::try_from(register_index).unwrap();
+		let mut intervals = Self::live_intervals(function);
+		intervals.retain(|interval| !on_stack[interval.register.index()]);
+		intervals.sort_by_key(|interval| interval.start);
 
-				if reg as u8 >= Register::XMM1 as u8 && reg as u8 <= Register::XMM15 as u8
-				{
-					register_index += 1;
-				} else
-				{
-					match reg
-					{
-						Register::RBX | Register::RCX | Register::RDX => register_index += 5,
-						Register::RAX => { register_index += 5; continue; },
-						Register::RSP | Register::RBP => { register_index += 4; continue; },
-						Register::XMM0 => { register_index += 1; continue; },
-						_ => register_index += 4,
-					}
-				}
-				return RegisterInfo::new(reg);
-			}
-		});
-		
-    	return registers;
-	}
-	
-	pub fn reg_alloc_allocate(&mut self, data_type: Type) -> Option<Register>
-	{
-		if data_type.is_integer()
+		// The intervals that have a register, and are live at the start of the current interval
+		let mut active: Vec<(usize, RegisterId, Register)> = Vec::new();
+		for interval in &intervals
 		{
-			for i in 0..GENERAL_REGISTERS
+			active.retain(|(end, _, _)| *end >= interval.start);
+
+			let candidates = Self::candidate_registers(interval, function.register_type(interval.register));
+			if let Some(register) = candidates.iter().find(|register| !active.iter().any(|(_, _, used)| used == *register))
 			{
-				if let Some(allocated_register) = self.reg_alloc_allocate_sub_reg(i, data_type.size())
-				{
-					return Some(allocated_register);
-				}
+				self.register_locations[interval.register.index()] = Some(*register);
+				active.push((interval.end, interval.register, *register));
+				continue;
 			}
-		} else
-		{
-			for i in XMM_REGS_INDEX..self.registers.len()
+
+			let spilled = active.iter_mut()
+				.filter(|(_, _, used)| candidates.contains(used))
+				.max_by_key(|(end, _, _)| *end);
+			if let Some((end, register, location)) = spilled
 			{
-				if let Some(allocated_register) = self.reg_alloc_allocate_sub_reg(i, data_type.size())
+				if *end > interval.end
 				{
-					return Some(allocated_register);
+					self.register_locations[register.index()] = None;
+					self.register_locations[interval.register.index()] = Some(*location);
+					*end = interval.end;
+					*register = interval.register;
 				}
 			}
 		}
-		return None;
-	}
-
-	pub fn reg_alloc_allocate_forced(&mut self, register: Register)
-	{
-		let index = Self::reg_alloc_register_2_index(register);
-		self.reg_alloc_allocate_sub_reg_forced(index, register.base_register());
-	}
-	
-	pub fn reg_alloc_free(&mut self, register: Register)
-	{
-		for i in 0..self.registers.len()
-		{
-			let reg_info = self.registers[i];
-			if register as OpSize >= reg_info.register as OpSize && register as OpSize <= reg_info.register as OpSize + Self::reg_alloc_get_last_sub_reg_offset(register)
-			{
-				self.reg_alloc_free_sub_reg(i, register);
-				return;
-			}
-		}
-	}
-	
-	pub fn reg_alloc_check_leaks(&self)
-	{
-		for i in 0..self.registers.len()
-		{
-			if !self.reg_alloc_is_all_sub_regs_free(i)
-			{
-				println!("Found allocated register:\n{:#?}", self.registers[i]);
-			}
-		}
-	}
 
-	pub fn reg_alloc_save_used(&mut self)
-	{
-		for i in 0..self.registers.len()
+		if cfg!(debug_assertions)
 		{
-			if self.registers[i].is_used()
+			for (i, interval) in intervals.iter().enumerate()
 			{
-				self.instr_push(&Placeholder::new(
-					PlaceholderKind::Reg(self.registers[i].register), 
-					self.registers[i].register.data_type()
-				));
-				self.registers[i].was_saved_before_call = true;
-				self.registers[i].is_free = true;
-				self.registers[i].is_l8_free = true;
-				if self.registers[i].is_h8_free != None
+				let Some(location) = self.register_locations[interval.register.index()] else { continue; };
+				for other in &intervals[i + 1..]
 				{
-					self.registers[i].is_h8_free = Some(true);
+					if other.start <= interval.end && self.register_locations[other.register.index()] == Some(location)
+					{
+						panic!("Dev error! The virtual registers %{} and %{} are both in {location} in {}.", interval.register.0, other.register.0, function.identifier);
+					}
 				}
 			}
 		}
 	}
 
-	pub fn reg_alloc_free_used(&mut self)
+	// The registers that an interval can be kept in, the caller-saved ones first because they dont have to be saved by the prologue.
+	// The argument registers are written when the function starts and when calling a function, and the registers that are not
+	// callee-saved are destroyed by calls. There are no callee-saved vector registers, so floats that cross a call stay on the stack.
+	fn candidate_registers(interval: &LiveInterval, data_type: Type) -> Vec<Register>
 	{
-		for i in (0..self.registers.len()).rev()
+		let (arguments, caller_saved, callee_saved): (&[Register], &[Register], &[Register]) = if data_type.is_integer()
 		{
-			if self.registers[i].was_saved_before_call
-			{
-				self.instr_pop(&Placeholder::new(
-					PlaceholderKind::Reg(self.registers[i].register), 
-					self.registers[i].register.data_type()
-				));
-
-				self.registers[i].was_saved_before_call = false;
-				self.registers[i].is_free = false;
-			}
-		}
-	}
-
-	pub fn reg_alloc_register_2_index(register: Register) -> usize
-	{
-		let reg_int = register as usize;
-		if reg_int >= Register::RAX as usize && reg_int <= Register::DH as usize
+			(&INTEGER_ARGUMENT_VARIABLE_REGISTERS_SYS_V_ABI_X86_64, &INTEGER_CALLER_SAVED_VARIABLE_REGISTERS_SYS_V_ABI_X86_64, &CALLEE_SAVED_REGISTERS_SYS_V_ABI_X86_64)
+		} else
 		{
-			return reg_int / 5 - 1;
-		}
-		if reg_int < Register::RSP as usize
+			(&FLOAT_ARGUMENT_VARIABLE_REGISTERS_SYS_V_ABI_X86_64, &FLOAT_CALLER_SAVED_VARIABLE_REGISTERS_SYS_V_ABI_X86_64, &[])
+		};
+
+		if interval.crosses_call
 		{
-			return (reg_int - (4 * 5)) / 4 + (4 - 1);
+			return callee_saved.to_vec();
 		}
-		if reg_int < Register::XMM1 as usize
+		let mut candidates = Vec::with_capacity(arguments.len() + caller_saved.len() + callee_saved.len());
+		if !interval.live_with_arguments
 		{
-			return (reg_int - (4 * 5)) / 4 + 4 - 3;
+			candidates.extend_from_slice(arguments);
 		}
-
-		return reg_int - 16 * 4 + 4 + 1 + 3;
+		candidates.extend_from_slice(caller_saved);
+		candidates.extend_from_slice(callee_saved);
+		return candidates;
 	}
 
-
-	// OpSize - in bytes
-	fn reg_alloc_allocate_sub_reg(&mut self, reg_info_idx: usize, size: OpSize) -> Option<Register>
+	// The interval of each virtual register that is used, from the first position where it is live to the last one.
+	// A register that is live when a block starts (or ends) is live from the start (or until the end) of the block.
+	fn live_intervals(function: &mir::Function) -> Vec<LiveInterval>
 	{
-		let reg_info = &mut self.registers[reg_info_idx];
+		let cfg = Cfg::new(function);
+		let liveness = Liveness::new(function, &cfg);
+		let mut ranges: Vec<Option<(usize, usize)>> = vec![None; function.registers.len()];
+		let mut extend = |register: RegisterId, position: usize| {
+			let range = ranges[register.index()].get_or_insert((position, position));
+			range.0 = range.0.min(position);
+			range.1 = range.1.max(position);
+		};
 
-		if !reg_info.is_free
+		for parameter in &function.parameters
 		{
-			return None;
+			extend(*parameter, 0);
 		}
 
-		if reg_info.register as u8 >= Register::XMM1 as u8 && reg_info.register as u8 <= Register::XMM15 as u8
-		{
-			reg_info.is_free = false;
-			return Some(reg_info.register);
-		}
-		
-		if size == 1
+		let mut calls = Vec::new();
+		let mut position = 2;
+		for (block, live_out) in function.blocks.iter().zip(&liveness.live_out)
 		{
-			if reg_info.is_l8_free
+			let block_start = position;
+			let block_end = block_start + 2 * block.instructions.len();
+
+			let mut live = live_out.clone();
+			for register in (0..live.len()).filter(|register| live[*register])
 			{
-				reg_info.is_l8_free = false;
-				return Some(Register::try_from(reg_info.register as OpSize + 3).unwrap());
+				extend(RegisterId(register as u32), block_end + 1);
 			}
-			if reg_info.is_h8_free != None && reg_info.is_h8_free.unwrap()
+			block.terminator.for_each_used_register(&mut |register| extend(register, block_end));
+			Liveness::update_terminator(&mut live, &block.terminator);
+
+			for (i, instruction) in block.instructions.iter().enumerate().rev()
 			{
-				reg_info.is_h8_free = Some(false);
-				return Some(Register::try_from(reg_info.register as OpSize + 4).unwrap());
+				let position = block_start + 2 * i;
+				if let Some(destination) = instruction.destination()
+				{
+					extend(destination, position + 1);
+				}
+				instruction.for_each_used_register(&mut |register| extend(register, position));
+				if let Instruction::Call { .. } = instruction
+				{
+					calls.push(position);
+				}
+				Liveness::update(&mut live, instruction);
 			}
-		}
-	
-		if !reg_info.is_l8_free || (reg_info.is_h8_free != None && !reg_info.is_h8_free.unwrap())
-		{
-			return None;
-		}
-	
-		reg_info.is_free = false;
-		// Pink jelly in skull hurts
-		if reg_info.register as OpSize >= Register::RAX as OpSize && reg_info.register as OpSize <= Register::DH as OpSize  
-		{
-			return Some(Register::try_from(reg_info.register as OpSize + (3 - size.trailing_zeros() as OpSize)).unwrap())
-		}
-		return Some(Register::try_from(reg_info.register as OpSize + (3 - size.trailing_zeros() as OpSize) ).unwrap())
-	}
-	
-	fn reg_alloc_free_sub_reg(&mut self, reg_info_idx: usize, register: Register)
-	{
-		if self.registers[reg_info_idx].push_count != 0 && !self.registers[reg_info_idx].was_saved_before_call
-		{
-			self.registers[reg_info_idx].push_count -= 1;
-			self.registers[reg_info_idx].is_free = false;
-
-			self.instr_pop(&Placeholder::new(
-				PlaceholderKind::Reg(self.registers[reg_info_idx].register), 
-				self.registers[reg_info_idx].register.data_type()
-			));
-
-			return;
-		}
-		
-		let reg_info = &mut self.registers[reg_info_idx];
 
-		reg_info.is_free = true;
-		if register as OpSize - reg_info.register as OpSize == 4 	/* Means it was a high 8 bits register (AH, BH, CH, DH) */
-		{
-			reg_info.is_h8_free = Some(true);
-			return;
-		}
-		reg_info.is_l8_free = true;
-		return;
-	}
-
-	fn reg_alloc_allocate_sub_reg_forced(&mut self, reg_info_idx: usize, register: Register)
-	{
-		if let Some(_) = self.reg_alloc_allocate_sub_reg(reg_info_idx, register.data_type().size())
-		{
-			return;
-		}
-
-		self.registers[reg_info_idx].push_count += 1;
-		self.instr_push(&Placeholder::new(
-			PlaceholderKind::Reg(self.registers[reg_info_idx].register), 
-			self.registers[reg_info_idx].register.data_type()
-		));
-	}
-
-	fn reg_alloc_get_last_sub_reg_offset(register: Register) -> OpSize 
-	{
-		if register as u8 >= Register::XMM1 as u8 && register as u8 <= Register::XMM15 as u8
-		{
-			return 0;
-		}
-
-		// If its a register that has an high 8 bits sub-register, the offset is 4, and if not, then the offset is 3
-		return if register as OpSize >= Register::RAX as OpSize && register as OpSize <= Register::DH as OpSize { 4 } else { 3 };
-	}
-	
-	fn reg_alloc_is_all_sub_regs_free(&self, reg_info_idx: usize) -> bool
-	{
-		let reg_info = self.registers[reg_info_idx];
-
-		if reg_info.register as u8 >= Register::XMM1 as u8 && reg_info.register as u8 <= Register::XMM15 as u8 
-		{
-			return reg_info.is_free;
-		}
-
-		if reg_info.is_h8_free != None && !reg_info.is_h8_free.unwrap()
-		{
-			return false;
-		}
-		return reg_info.is_free && reg_info.is_l8_free;
+			for register in (0..live.len()).filter(|register| live[*register])
+			{
+				extend(RegisterId(register as u32), block_start);
+			}
+			position = block_end + 2;
+		}
+
+		return ranges.into_iter().enumerate().filter_map(|(register, range)| {
+			let (start, end) = range?;
+			return Some(LiveInterval {
+				register: RegisterId(register as u32),
+				start,
+				end,
+				crosses_call: calls.iter().any(|call| start <= *call && end > *call + 1),
+				live_with_arguments: start == 0 || calls.iter().any(|call| start <= *call && end >= *call),
+			});
+		}).collect();
 	}
-}
\ No newline at end of file
+}
@@ -0,0 +1,1001 @@
+use super::{attribute, print_err, CompileError, FunctionTypeId, Type, TypeKind, Value};
+use crate::ast::{self, *};
+
+const OUT_WAT_FILE_PATH: &str = "/tmp/slowc_compiled.wat";
+
+// The stack is the first MiB of the linear memory, and grows down from its end. The memory above it belongs to the host. (For malloc)
+const STACK_SIZE: usize = 1 << 20;
+const PAGE_SIZE: usize = 1 << 16;
+
+// Where a variable lives. Variables whose address is taken live in the frame of the function, on the stack in the linear memory.
+#[derive(Clone)]
+enum Home
+{
+	Local(String),		/* A local of the wasm function */
+	Frame(usize),		/* The offset from the frame pointer ($__frame) */
+}
+
+// Generates a WebAssembly module in the text format (--target=wasm32) from the AST (after the semantic analysis).
+// The integers up to 32 bits are i32 values (kept sign or zero extended from their size, like in the registers of the native code), i64 and u64 are i64 values,
+// and pointers are i32 addresses into the linear memory, which are stored in 8 bytes so the pointer arithmetic of Slow works the same.
+// Function pointers are indices into the table of the module, where 0 is the null pointer. External functions are imported from the "env" module,
+// and global functions are exported. The variadic arguments are passed like the C compilers for wasm32 pass them, in a buffer on the stack
+// whose address is the last argument.
+pub struct CodeGen<'a>
+{
+	root: &'a Root,
+	table: Vec<FunctionId>,				/* The functions whose address is taken, the table index of each one is its index here plus 1 */
+	functions: String,
+
+	function: FunctionId,				/* The function that is generated */
+	body: String,
+	locals: Vec<(String, &'static str)>,
+	variables: Vec<Option<(Home, Type)>>,	/* Indexed by VariableId */
+	frame_size: usize,
+	labels: usize,
+	indentation: usize,
+}
+
+impl<'a> CodeGen<'a>
+{
+	pub fn new(root: &'a Root) -> Self
+	{
+		return Self {
+			root,
+			table: Vec::new(),
+			functions: String::new(),
+			function: FunctionId(0),
+			body: String::new(),
+			locals: Vec::new(),
+			variables: Vec::new(),
+			frame_size: 0,
+			labels: 0,
+			indentation: 0,
+		};
+	}
+
+	pub fn generate(mut self)
+	{
+		let root = self.root;
+		for function in &root.functions
+		{
+			Self::visit_statements(&function.code_block.statements, &mut |part| {
+				if let BinExprPartKind::Val(Value::FuncAddress(index)) = &part.kind
+				{
+					if !self.table.contains(index)
+					{
+						self.table.push(*index);
+					}
+				}
+			});
+		}
+
+		let mut module = String::from("(module");
+		for (index, function_type) in root.function_types.iter().enumerate()
+		{
+			let parameters = Self::wasm_parameters(function_type);
+			let result = Self::wasm_result(function_type.return_type);
+			module.push_str(&format!("\n\t(type {} (func{parameters}{result}))", Self::function_type_name(FunctionTypeId(index as u32))));
+		}
+
+		// Imports must be before the definitions
+		for function in root.functions.iter().filter(|function| function.attributes & attribute::EXTERN != 0)
+		{
+			module.push_str(&format!(
+				"\n\t(import \"env\" \"{}\" (func {} (type {})))", function.identifier, Self::name(&function.identifier), Self::function_type_name(function.signature)
+			));
+		}
+
+		module.push_str(&format!("\n\t(memory (export \"memory\") {})", STACK_SIZE / PAGE_SIZE));
+		module.push_str(&format!("\n\t(global $__stack_pointer (mut i32) (i32.const {STACK_SIZE}))"));
+		if !self.table.is_empty()
+		{
+			let elements: Vec<String> = self.table.iter().map(|index| Self::name(&root.functions[index.index()].identifier)).collect();
+			module.push_str(&format!("\n\t(table {} funcref)", self.table.len() + 1));
+			module.push_str(&format!("\n\t(elem (i32.const 1) func {})", elements.join(" ")));
+		}
+
+		for function in root.functions.iter().filter(|function| function.attributes & attribute::EXTERN == 0)
+		{
+			self.gen_function(function);
+		}
+		module.push_str(&self.functions);
+		module.push_str("\n)\n");
+
+		std::fs::write(OUT_WAT_FILE_PATH, module).unwrap_or_else(|err| {
+			print_err!(CompileError::FileWriteError(OUT_WAT_FILE_PATH), "Could not write the WebAssembly file. {err}");
+		});
+	}
+
+	fn gen_function(&mut self, function: &ast::Function)
+	{
+		self.function = function.index;
+		self.body.clear();
+		self.locals.clear();
+		self.variables = vec![None; function.locals.len()];
+		self.frame_size = 0;
+		self.labels = 0;
+		self.indentation = 2;
+
+		let mut address_taken = vec![false; function.locals.len()];
+		Self::visit_statements(&function.code_block.statements, &mut |part| {
+			if let BinExprPartKind::SelfOperation(operation) = &part.kind
+			{
+				if let (BinExprOperator::AddressOf, BinExprPartKind::Val(Value::Var(index))) = (operation.operator, &operation.expression.kind)
+				{
+					address_taken[index.index()] = true;
+				}
+			}
+		});
+
+		// The parameters arrive in locals, and the ones whose address is taken are copied into the frame
+		let mut parameters = String::new();
+		let mut copies = Vec::new();
+		for parameter in &function.parameters
+		{
+			let name = Self::variable_name(parameter);
+			parameters.push_str(&format!(" (param {name} {})", Self::value_type(parameter.data_type)));
+			if address_taken[parameter.index.index()]
+			{
+				let offset = self.allocate(parameter.data_type.size() as usize);
+				self.variables[parameter.index.index()] = Some((Home::Frame(offset), parameter.data_type));
+				copies.push((name, offset, parameter.data_type));
+			} else
+			{
+				self.variables[parameter.index.index()] = Some((Home::Local(name), parameter.data_type));
+			}
+		}
+		for var_decl in Self::declarations(&function.code_block.statements)
+		{
+			if address_taken[var_decl.index.index()]
+			{
+				let offset = self.allocate(var_decl.data_type.size() as usize);
+				self.variables[var_decl.index.index()] = Some((Home::Frame(offset), var_decl.data_type));
+			} else
+			{
+				let name = Self::variable_name(var_decl);
+				self.locals.push((name.clone(), Self::value_type(var_decl.data_type)));
+				self.variables[var_decl.index.index()] = Some((Home::Local(name), var_decl.data_type));
+			}
+		}
+
+		// The recursive calls of a tailrec function jump back to the start of the function
+		let is_tail_recursive = function.attributes & attribute::TAIL_RECURSIVE != 0;
+		if is_tail_recursive
+		{
+			self.line("loop $__tail");
+			self.indentation += 1;
+		}
+		for statement in &function.code_block.statements
+		{
+			self.gen_statement(statement);
+		}
+		if is_tail_recursive
+		{
+			self.indentation -= 1;
+			self.line("end");
+		}
+
+		// Reaching the end of a function that returns a value returns 0, like in the native code
+		self.restore_frame();
+		if function.return_type.kind != TypeKind::Void
+		{
+			self.gen_literal(&Value::I64(0), function.return_type);
+		}
+
+		let export = if function.attributes & attribute::GLOBAL != 0 { format!(" (export \"{}\")", function.identifier) } else { String::new() };
+		let result = Self::wasm_result(function.return_type);
+		self.functions.push_str(&format!("\n\n\t(func {}{export}{parameters}{result}", Self::name(&function.identifier)));
+		if self.frame_size != 0
+		{
+			self.locals.push((String::from("$__frame"), "i32"));
+		}
+		for (name, value_type) in &self.locals
+		{
+			self.functions.push_str(&format!("\n\t\t(local {name} {value_type})"));
+		}
+
+		if self.frame_size != 0
+		{
+			let frame_size = self.frame_size.next_multiple_of(16);
+			self.functions.push_str(&format!(
+				"\n\t\tglobal.get $__stack_pointer\n\t\ti32.const {frame_size}\n\t\ti32.sub\n\t\tlocal.tee $__frame\n\t\tglobal.set $__stack_pointer"
+			));
+			for (name, offset, data_type) in copies
+			{
+				self.functions.push_str(&format!("\n\t\tlocal.get $__frame\n\t\tlocal.get {name}"));
+				let store = Self::store(data_type, offset);
+				self.functions.push_str(&format!("\n\t\t{}", store.replace('\n', "\n\t\t")));
+			}
+		}
+		let body = std::mem::take(&mut self.body);
+		self.functions.push_str(&body);
+		self.functions.push_str("\n\t)");
+	}
+
+	fn gen_statement(&mut self, statement: &Statement)
+	{
+		match statement
+		{
+			Statement::Scope(scope) => scope.statements.iter().for_each(|statement| self.gen_statement(statement)),
+			Statement::VarDecl(var_decl) =>
+			{
+				// Constants were replaced by their values in the semantic analysis
+				if var_decl.attributes & attribute::CONSTANT != 0
+				{
+					return;
+				}
+				if let Some(value) = &var_decl.value
+				{
+					self.gen_variable_store(var_decl.index, &value.root);
+				}
+			},
+			Statement::Assign(assign) => match &assign.destination.kind
+			{
+				BinExprPartKind::Val(Value::Var(index)) => self.gen_variable_store(*index, &assign.value.root),
+				BinExprPartKind::Val(Value::Dereference(info)) if self.is_unaffected(&assign.value.root) =>
+				{
+					self.gen_dereference_address(info);
+					self.gen_expression(&assign.value.root);
+					self.line(&Self::store(assign.destination.data_type, 0));
+				},
+				BinExprPartKind::Val(Value::Dereference(info)) =>
+				{
+					// The value is evaluated before the address, so it waits in a scratch local
+					let data_type = assign.destination.data_type;
+					let scratch = self.scratch(data_type);
+					self.gen_expression(&assign.value.root);
+					self.line(&format!("local.set {scratch}"));
+					self.gen_dereference_address(info);
+					self.line(&format!("local.get {scratch}"));
+					self.line(&Self::store(data_type, 0));
+				},
+				_ => panic!("Dev error! An assignment into a none-writable expression. {:#?}", assign.destination),
+			},
+			Statement::FunctionCall(function_call) =>
+			{
+				if self.is_recursive_call(function_call)
+				{
+					self.gen_tail_call(function_call);
+					return;
+				}
+
+				self.gen_call(function_call);
+				if self.root.function_types[function_call.signature.index()].return_type.kind != TypeKind::Void
+				{
+					self.line("drop");
+				}
+			},
+			Statement::Return(return_info) =>
+			{
+				if let Some(value) = &return_info.value
+				{
+					if let BinExprPartKind::Val(Value::FuncCall(function_call)) = &value.root.kind
+					{
+						if self.is_recursive_call(function_call)
+						{
+							self.gen_tail_call(function_call);
+							return;
+						}
+					}
+					self.gen_expression(&value.root);
+				}
+				self.restore_frame();
+				self.line("return");
+			},
+			Statement::If(if_info) =>
+			{
+				self.gen_condition(&if_info.condition.root);
+				self.line("if");
+				self.indentation += 1;
+				self.gen_statement(&if_info.then_block);
+				if let Some(else_block) = &if_info.else_block
+				{
+					self.indentation -= 1;
+					self.line("else");
+					self.indentation += 1;
+					self.gen_statement(else_block);
+				}
+				self.indentation -= 1;
+				self.line("end");
+			},
+			Statement::For(for_info) =>
+			{
+				if let Some(initializer) = &for_info.initializer
+				{
+					self.gen_statement(initializer);
+				}
+
+				let label = self.labels;
+				self.labels += 1;
+				self.line(&format!("block $__break{label}"));
+				self.indentation += 1;
+				self.line(&format!("loop $__continue{label}"));
+				self.indentation += 1;
+				if let Some(condition) = &for_info.condition
+				{
+					self.gen_condition(&condition.root);
+					self.line("i32.eqz");
+					self.line(&format!("br_if $__break{label}"));
+				}
+				self.gen_statement(&for_info.code_block);
+				if let Some(update) = &for_info.update
+				{
+					self.gen_statement(update);
+				}
+				self.line(&format!("br $__continue{label}"));
+				self.indentation -= 1;
+				self.line("end");
+				self.indentation -= 1;
+				self.line("end");
+			},
+		}
+	}
+
+	fn gen_variable_store(&mut self, index: VariableId, value: &BinExprPart)
+	{
+		let (home, data_type) = self.variables[index.index()].clone().expect("Dev error! A variable without a home.");
+		match home
+		{
+			Home::Local(name) =>
+			{
+				self.gen_expression(value);
+				self.line(&format!("local.set {name}"));
+			},
+			Home::Frame(offset) =>
+			{
+				self.line("local.get $__frame");
+				self.gen_expression(value);
+				self.line(&Self::store(data_type, offset));
+			},
+		}
+	}
+
+	// Whether evaluating other expressions before this one cannot change its value. (A literal, or a variable that no pointer can point to)
+	fn is_unaffected(&self, part: &BinExprPart) -> bool
+	{
+		return match &part.kind
+		{
+			BinExprPartKind::Val(Value::Var(index)) => matches!(self.variables[index.index()], Some((Home::Local(_), _))),
+			BinExprPartKind::Val(Value::Dereference(_) | Value::FuncCall(_)) => false,
+			BinExprPartKind::Val(_) => true,
+			_ => false,
+		};
+	}
+
+	// Leaves an i32 that is not 0 if the condition is true
+	fn gen_condition(&mut self, condition: &BinExprPart)
+	{
+		self.gen_expression(condition);
+		match Self::value_type(condition.data_type)
+		{
+			"i32" => (),
+			value_type =>
+			{
+				self.line(&format!("{value_type}.const 0"));
+				self.line(&format!("{value_type}.ne"));
+			},
+		}
+	}
+
+	// Pushes the value of the expression part. The operands are evaluated from left to right, like in the native code.
+	fn gen_expression(&mut self, part: &BinExprPart)
+	{
+		let data_type = part.data_type;
+		let value_type = Self::value_type(data_type);
+		match &part.kind
+		{
+			BinExprPartKind::Val(value) => self.gen_value(value, data_type),
+			BinExprPartKind::SelfOperation(operation) => match operation.operator
+			{
+				BinExprOperator::AddressOf => self.gen_address(&operation.expression),
+				BinExprOperator::BitwiseNot =>
+				{
+					self.gen_expression(&operation.expression);
+					self.line(&format!("{value_type}.const -1"));
+					self.line(&format!("{value_type}.xor"));
+					self.normalize(data_type);
+				},
+				BinExprOperator::BoolNot if data_type.is_integer() =>
+				{
+					self.gen_expression(&operation.expression);
+					self.line(&format!("{value_type}.eqz"));
+					if value_type == "i64"
+					{
+						self.line("i64.extend_i32_u");
+					}
+				},
+				BinExprOperator::BoolNot =>
+				{
+					self.gen_expression(&operation.expression);
+					self.line(&format!("{value_type}.const 0"));
+					self.line(&format!("{value_type}.eq"));
+					self.line(&format!("{value_type}.convert_i32_u"));
+				},
+				_ => panic!("Dev error! A self operation with a two-side operator. {:#?}", operation),
+			},
+			BinExprPartKind::Operation(operation) => self.gen_operation(operation, data_type),
+			BinExprPartKind::TypeCast(info) =>
+			{
+				self.gen_expression(&info.expression);
+				self.gen_convert(info.expression.data_type, info.into_type);
+			},
+		}
+	}
+
+	fn gen_operation(&mut self, operation: &BinExprOperation, data_type: Type)
+	{
+		let operator = operation.operator;
+		let operand_type = operation.lhs.data_type;
+		let value_type = Self::value_type(operand_type);
+		let is_float = !operand_type.is_integer();
+		let signed = if operand_type.is_integer() && operand_type.is_signed() { "_s" } else { "_u" };
+		let is_boolean_float = is_float && (operator == BinExprOperator::BoolAnd || operator == BinExprOperator::BoolOr);
+
+		self.gen_expression(&operation.lhs);
+		if is_boolean_float
+		{
+			self.line(&format!("{value_type}.const 0"));
+			self.line(&format!("{value_type}.ne"));
+		}
+
+		// >> is a logical shift, so the sign extension of small signed integers is cleared first
+		if operator == BinExprOperator::BitwiseRightShift && operand_type.is_signed() && operand_type.size() < 4
+		{
+			self.line(&format!("i32.const {}", (1u32 << (operand_type.size() * 8)) - 1));
+			self.line("i32.and");
+		}
+
+		self.gen_expression(&operation.rhs);
+		if is_boolean_float
+		{
+			self.line(&format!("{value_type}.const 0"));
+			self.line(&format!("{value_type}.ne"));
+		}
+
+		// The offset of a pointer is an i64
+		if operand_type.is_pointer() && !operation.rhs.data_type.is_pointer()
+		{
+			self.line("i32.wrap_i64");
+		}
+
+		let instruction = match operator
+		{
+			BinExprOperator::BoolEq 		=> String::from("eq"),
+			BinExprOperator::BoolNotEq 		=> String::from("ne"),
+			BinExprOperator::BoolGreater 	=> if is_float { String::from("gt") } else { format!("gt{signed}") },
+			BinExprOperator::BoolLess 		=> if is_float { String::from("lt") } else { format!("lt{signed}") },
+			BinExprOperator::BoolGreaterEq 	=> if is_float { String::from("ge") } else { format!("ge{signed}") },
+			BinExprOperator::BoolLessEq 	=> if is_float { String::from("le") } else { format!("le{signed}") },
+			BinExprOperator::Add 			=> String::from("add"),
+			BinExprOperator::Sub 			=> String::from("sub"),
+			BinExprOperator::Mul 			=> String::from("mul"),
+			BinExprOperator::Div 			=> if is_float { String::from("div") } else { format!("div{signed}") },
+			BinExprOperator::Modulo 		=> format!("rem{signed}"),
+			BinExprOperator::BitwiseAnd | BinExprOperator::BoolAnd 	=> String::from("and"),
+			BinExprOperator::BitwiseOr | BinExprOperator::BoolOr 	=> String::from("or"),
+			BinExprOperator::BitwiseXor 		=> String::from("xor"),
+			BinExprOperator::BitwiseLeftShift 	=> String::from("shl"),
+			BinExprOperator::BitwiseRightShift 	=> String::from("shr_u"),
+			_ => panic!("Dev error! An operation with a self operator. {:#?}", operation),
+		};
+
+		if is_boolean_float
+		{
+			self.line(&format!("i32.{instruction}"));
+			self.line(&format!("{}.convert_i32_u", Self::value_type(data_type)));
+			return;
+		}
+		self.line(&format!("{value_type}.{instruction}"));
+
+		// The bitwise operators and the remainder keep the extension of small integers, the others may not.
+		// >> shifts a small signed integer without its sign extension, so the result is extended again.
+		let is_signed_shift = operator == BinExprOperator::BitwiseRightShift && operand_type.is_signed() && operand_type.size() < 4;
+		if matches!(operator, BinExprOperator::Add | BinExprOperator::Sub | BinExprOperator::Mul | BinExprOperator::Div | BinExprOperator::BitwiseLeftShift)
+			|| is_signed_shift
+		{
+			self.normalize(data_type);
+		}
+	}
+
+	fn gen_value(&mut self, value: &Value, data_type: Type)
+	{
+		match value
+		{
+			Value::Var(index) =>
+			{
+				let (home, data_type) = self.variables[index.index()].clone().expect("Dev error! A variable without a home.");
+				match home
+				{
+					Home::Local(name) => self.line(&format!("local.get {name}")),
+					Home::Frame(offset) =>
+					{
+						self.line("local.get $__frame");
+						self.line(&Self::load(data_type, offset));
+					},
+				}
+			},
+			Value::Dereference(info) =>
+			{
+				self.gen_dereference_address(info);
+				self.line(&Self::load(data_type, 0));
+			},
+			Value::FuncCall(function_call) => self.gen_call(function_call),
+			Value::FuncAddress(index) =>
+			{
+				let table_index = self.table.iter().position(|function| function == index).unwrap() + 1;
+				self.line(&format!("i32.const {table_index}"));
+			},
+			_ => self.gen_literal(value, data_type),
+		}
+	}
+
+	fn gen_literal(&mut self, value: &Value, data_type: Type)
+	{
+		let value_type = Self::value_type(data_type);
+		let literal = match *value
+		{
+			Value::I64(number) if value_type == "i32" 	=> (number as u32).to_string(),
+			Value::U64(number) if value_type == "i32" 	=> (number as u32).to_string(),
+			Value::I8(number) 	=> number.to_string(),
+			Value::U8(number) 	=> number.to_string(),
+			Value::I16(number) 	=> number.to_string(),
+			Value::U16(number) 	=> number.to_string(),
+			Value::I32(number) 	=> number.to_string(),
+			Value::U32(number) 	=> number.to_string(),
+			Value::I64(number) 	=> number.to_string(),
+			Value::U64(number) 	=> number.to_string(),
+			Value::F32(number) 	=> Self::float_literal(number as f64),
+			Value::F64(number) 	=> Self::float_literal(number),
+			_ => panic!("Dev error! gen_literal() called with a value that was not resolved by the semantic analysis. {:#?}", value),
+		};
+		self.line(&format!("{value_type}.const {literal}"));
+	}
+
+	fn float_literal(number: f64) -> String
+	{
+		if number.is_nan()
+		{
+			return String::from("nan");
+		}
+		if number.is_infinite()
+		{
+			return String::from(if number > 0.0 { "inf" } else { "-inf" });
+		}
+		return format!("{number:?}");
+	}
+
+	// Pushes the address of a variable (which lives in the frame) or of a dereference
+	fn gen_address(&mut self, part: &BinExprPart)
+	{
+		match &part.kind
+		{
+			BinExprPartKind::Val(Value::Var(index)) =>
+			{
+				let Some((Home::Frame(offset), _)) = self.variables[index.index()] else
+				{
+					panic!("Dev error! The address of a variable that does not live in the frame.");
+				};
+				self.line("local.get $__frame");
+				if offset != 0
+				{
+					self.line(&format!("i32.const {offset}"));
+					self.line("i32.add");
+				}
+			},
+			BinExprPartKind::Val(Value::Dereference(info)) => self.gen_dereference_address(info),
+			_ => panic!("Dev error! The address of a none-writable expression. {:#?}", part),
+		}
+	}
+
+	// All dereferences except the last one load the pointers, the last one is the address itself
+	fn gen_dereference_address(&mut self, info: &DereferenceInfo)
+	{
+		self.gen_expression(&info.expression.root);
+		for _ in 1..info.dereference_count
+		{
+			self.line("i32.load");
+		}
+	}
+
+	// Converts the value on the stack from a data type into another
+	fn gen_convert(&mut self, from: Type, into: Type)
+	{
+		let from_type = Self::value_type(from);
+		let into_type = Self::value_type(into);
+		let signed = if from.is_signed() { "_s" } else { "_u" };
+		match (from.is_integer(), into.is_integer())
+		{
+			(true, true) =>
+			{
+				match (from_type, into_type)
+				{
+					("i32", "i64") => self.line(&format!("i64.extend_i32{signed}")),
+					("i64", "i32") => self.line("i32.wrap_i64"),
+					_ => (),
+				}
+				self.normalize(into);
+			},
+			(true, false) => self.line(&format!("{into_type}.convert_{from_type}{signed}")),
+
+			// Floats that dont fit in the integer saturate, instead of trapping
+			(false, true) =>
+			{
+				// u32 is truncated into an i64, so values above i32::MAX dont saturate
+				let is_wide = into_type == "i64" || into.kind == TypeKind::U32;
+				if into.kind == TypeKind::U64
+				{
+					self.line(&format!("i64.trunc_sat_{from_type}_u"));
+				} else if is_wide
+				{
+					self.line(&format!("i64.trunc_sat_{from_type}_s"));
+				} else
+				{
+					self.line(&format!("i32.trunc_sat_{from_type}_s"));
+				}
+				if is_wide && into_type == "i32"
+				{
+					self.line("i32.wrap_i64");
+				}
+				self.normalize(into);
+			},
+			(false, false) if from_type == "f32" && into_type == "f64" 	=> self.line("f64.promote_f32"),
+			(false, false) if from_type == "f64" && into_type == "f32" 	=> self.line("f32.demote_f64"),
+			(false, false) 												=> (),
+		}
+	}
+
+	// The arguments are evaluated from left to right, and the function pointer (if there is one) after them
+	fn gen_call(&mut self, function_call: &FunctionCallInfo)
+	{
+		let function_type = &self.root.function_types[function_call.signature.index()];
+		let parameters = function_type.parameters.len();
+		for argument in function_call.arguments.iter().take(parameters)
+		{
+			self.gen_expression(&argument.root);
+		}
+
+		// The variadic arguments are stored into a buffer in the frame, each call has a buffer of its own so the calls can be nested
+		if function_type.attributes & attribute::VARIADIC != 0
+		{
+			let variadic = &function_call.arguments[parameters.min(function_call.arguments.len())..];
+			let size: usize = variadic.iter().map(|argument| 8.max(Self::variadic_size(argument.root.data_type))).sum();
+			let buffer = self.allocate(size.max(8));
+			let mut offset = buffer;
+			for argument in variadic
+			{
+				let data_type = argument.root.data_type;
+				let size = Self::variadic_size(data_type);
+				offset = offset.next_multiple_of(size);
+				self.line("local.get $__frame");
+				self.gen_expression(&argument.root);
+				let store = match data_type.kind
+				{
+					TypeKind::F32 => { self.line("f64.promote_f32"); format!("f64.store offset={offset}") },
+					TypeKind::F64 => format!("f64.store offset={offset}"),
+					_ if size == 8 => format!("i64.store offset={offset}"),
+					_ => format!("i32.store offset={offset}"),
+				};
+				self.line(&store);
+				offset += size;
+			}
+			self.line("local.get $__frame");
+			self.line(&format!("i32.const {buffer}"));
+			self.line("i32.add");
+		}
+
+		match &function_call.callee
+		{
+			Callee::Direct(index) => self.line(&format!("call {}", Self::name(&self.root.functions[index.index()].identifier))),
+			Callee::Indirect(pointer) =>
+			{
+				self.gen_expression(&pointer.root);
+				self.line(&format!("call_indirect (type {})", Self::function_type_name(function_call.signature)));
+			},
+			Callee::Ident(_) => panic!("Dev error! The called function was not resolved by the semantic analysis."),
+		}
+	}
+
+	// A recursive call of a tailrec function sets the parameters to the arguments, and jumps to the start of the function
+	fn gen_tail_call(&mut self, function_call: &FunctionCallInfo)
+	{
+		for argument in &function_call.arguments
+		{
+			self.gen_expression(&argument.root);
+		}
+		let function = &self.root.functions[self.function.index()];
+		for parameter in function.parameters.iter().rev()
+		{
+			self.line(&format!("local.set {}", Self::variable_name(parameter)));
+		}
+		self.line("br $__tail");
+	}
+
+	fn is_recursive_call(&self, function_call: &FunctionCallInfo) -> bool
+	{
+		let is_tail_recursive = self.root.functions[self.function.index()].attributes & attribute::TAIL_RECURSIVE != 0;
+		return is_tail_recursive && matches!(function_call.callee, Callee::Direct(index) if index == self.function);
+	}
+
+	// The size of a variadic argument in the buffer. Small integers are passed as 32 bit integers, and f32 as f64.
+	fn variadic_size(data_type: Type) -> usize
+	{
+		return match data_type.kind
+		{
+			TypeKind::I64 | TypeKind::U64 | TypeKind::F32 | TypeKind::F64 	=> 8,
+			_ 																=> 4,
+		};
+	}
+
+	// Integers smaller than 32 bits are kept sign extended (if they are signed) or zero extended into the i32
+	fn normalize(&mut self, data_type: Type)
+	{
+		match data_type.kind
+		{
+			TypeKind::I8 	=> self.line("i32.extend8_s"),
+			TypeKind::I16 	=> self.line("i32.extend16_s"),
+			TypeKind::U8 	=> { self.line("i32.const 255"); self.line("i32.and"); },
+			TypeKind::U16 	=> { self.line("i32.const 65535"); self.line("i32.and"); },
+			_ 				=> (),
+		}
+	}
+
+	fn restore_frame(&mut self)
+	{
+		if self.frame_size != 0
+		{
+			self.line("local.get $__frame");
+			self.line(&format!("i32.const {}", self.frame_size.next_multiple_of(16)));
+			self.line("i32.add");
+			self.line("global.set $__stack_pointer");
+		}
+	}
+
+	// Allocates space in the frame, aligned on its size, and returns its offset from the frame pointer
+	fn allocate(&mut self, size: usize) -> usize
+	{
+		let offset = self.frame_size.next_multiple_of(size.min(8));
+		self.frame_size = offset + size;
+		return offset;
+	}
+
+	// A local that holds a value while an address is computed
+	fn scratch(&mut self, data_type: Type) -> String
+	{
+		let value_type = Self::value_type(data_type);
+		let name = format!("$__{value_type}");
+		if !self.locals.iter().any(|(local, _)| *local == name)
+		{
+			self.locals.push((name.clone(), value_type));
+		}
+		return name;
+	}
+
+	// Pointers are stored in 8 bytes, so the pointers that a pointer points to are 8 bytes apart, like in the native code
+	fn load(data_type: Type, offset: usize) -> String
+	{
+		let instruction = match data_type.kind
+		{
+			TypeKind::I8 	=> "i32.load8_s",
+			TypeKind::U8 	=> "i32.load8_u",
+			TypeKind::I16 	=> "i32.load16_s",
+			TypeKind::U16 	=> "i32.load16_u",
+			TypeKind::I64 | TypeKind::U64 	=> "i64.load",
+			TypeKind::F32 	=> "f32.load",
+			TypeKind::F64 	=> "f64.load",
+			_ 				=> "i32.load",
+		};
+		return Self::with_offset(instruction, offset);
+	}
+
+	fn store(data_type: Type, offset: usize) -> String
+	{
+		let instruction = match data_type.kind
+		{
+			TypeKind::I8 | TypeKind::U8 	=> "i32.store8",
+			TypeKind::I16 | TypeKind::U16 	=> "i32.store16",
+			TypeKind::I32 | TypeKind::U32 	=> "i32.store",
+			TypeKind::I64 | TypeKind::U64 	=> "i64.store",
+			TypeKind::F32 					=> "f32.store",
+			TypeKind::F64 					=> "f64.store",
+			TypeKind::Pointer | TypeKind::Function => return format!("i64.extend_i32_u\n{}", Self::with_offset("i64.store", offset)),
+			TypeKind::Void 					=> panic!("Dev error! A store of {data_type}."),
+		};
+		return Self::with_offset(instruction, offset);
+	}
+
+	fn with_offset(instruction: &str, offset: usize) -> String
+	{
+		if offset == 0
+		{
+			return instruction.to_string();
+		}
+		return format!("{instruction} offset={offset}");
+	}
+
+	fn value_type(data_type: Type) -> &'static str
+	{
+		return match data_type.kind
+		{
+			TypeKind::I64 | TypeKind::U64 	=> "i64",
+			TypeKind::F32 					=> "f32",
+			TypeKind::F64 					=> "f64",
+			_ 								=> "i32",
+		};
+	}
+
+	// A variadic function takes the address of the buffer of its variadic arguments after its parameters
+	fn wasm_parameters(function_type: &FunctionType) -> String
+	{
+		let mut parameters: String = function_type.parameters.iter().map(|parameter| format!(" (param {})", Self::value_type(*parameter))).collect();
+		if function_type.attributes & attribute::VARIADIC != 0
+		{
+			parameters.push_str(" (param i32)");
+		}
+		return parameters;
+	}
+
+	fn wasm_result(return_type: Type) -> String
+	{
+		if return_type.kind == TypeKind::Void
+		{
+			return String::new();
+		}
+		return format!(" (result {})", Self::value_type(return_type));
+	}
+
+	fn function_type_name(index: FunctionTypeId) -> String
+	{
+		return format!("$slow_function_type{}", index.index());
+	}
+
+	fn variable_name(variable: &VarDeclInfo) -> String
+	{
+		return format!("{}_{}", Self::name(&variable.identifier), variable.index.index());
+	}
+
+	// The names of the text format are ASCII, so other characters (of Hebrew identifiers) are written as their code point
+	fn name(identifier: &str) -> String
+	{
+		let mut name = String::from("$");
+		for character in identifier.chars()
+		{
+			if character.is_ascii_alphanumeric() || character == '_'
+			{
+				name.push(character);
+			} else
+			{
+				name.push_str(&format!("\\u{:x}", character as u32));
+			}
+		}
+		return name;
+	}
+
+	// The declarations of the variables in the statements, and in the statements that they have
+	fn declarations(statements: &[Statement]) -> Vec<&VarDeclInfo>
+	{
+		let mut declarations = Vec::new();
+		for statement in statements
+		{
+			match statement
+			{
+				Statement::VarDecl(var_decl) if var_decl.attributes & attribute::CONSTANT == 0 => declarations.push(var_decl),
+				Statement::Scope(scope) => declarations.extend(Self::declarations(&scope.statements)),
+				Statement::If(if_info) =>
+				{
+					declarations.extend(Self::declarations(std::slice::from_ref(&if_info.then_block)));
+					if let Some(else_block) = &if_info.else_block
+					{
+						declarations.extend(Self::declarations(std::slice::from_ref(else_block)));
+					}
+				},
+				Statement::For(for_info) =>
+				{
+					for statement in [&for_info.initializer, &for_info.update].into_iter().flatten()
+					{
+						declarations.extend(Self::declarations(std::slice::from_ref(statement)));
+					}
+					declarations.extend(Self::declarations(std::slice::from_ref(&for_info.code_block)));
+				},
+				_ => (),
+			}
+		}
+		return declarations;
+	}
+
+	// Calls the visitor on every expression part in the statements
+	fn visit_statements(statements: &[Statement], visitor: &mut impl FnMut(&BinExprPart))
+	{
+		for statement in statements
+		{
+			match statement
+			{
+				Statement::Scope(scope) => Self::visit_statements(&scope.statements, visitor),
+				Statement::If(if_info) =>
+				{
+					Self::visit_expression(&if_info.condition.root, visitor);
+					Self::visit_statements(std::slice::from_ref(&if_info.then_block), visitor);
+					if let Some(else_block) = &if_info.else_block
+					{
+						Self::visit_statements(std::slice::from_ref(else_block), visitor);
+					}
+				},
+				Statement::For(for_info) =>
+				{
+					for statement in [&for_info.initializer, &for_info.update].into_iter().flatten()
+					{
+						Self::visit_statements(std::slice::from_ref(statement), visitor);
+					}
+					if let Some(condition) = &for_info.condition
+					{
+						Self::visit_expression(&condition.root, visitor);
+					}
+					Self::visit_statements(std::slice::from_ref(&for_info.code_block), visitor);
+				},
+				Statement::VarDecl(var_decl) =>
+				{
+					if let Some(value) = &var_decl.value
+					{
+						Self::visit_expression(&value.root, visitor);
+					}
+				},
+				Statement::Assign(assign) =>
+				{
+					Self::visit_expression(&assign.destination, visitor);
+					Self::visit_expression(&assign.value.root, visitor);
+				},
+				Statement::FunctionCall(function_call) => Self::visit_call(function_call, visitor),
+				Statement::Return(return_info) =>
+				{
+					if let Some(value) = &return_info.value
+					{
+						Self::visit_expression(&value.root, visitor);
+					}
+				},
+			}
+		}
+	}
+
+	fn visit_expression(part: &BinExprPart, visitor: &mut impl FnMut(&BinExprPart))
+	{
+		visitor(part);
+		match &part.kind
+		{
+			BinExprPartKind::Val(Value::Dereference(info)) 	=> Self::visit_expression(&info.expression.root, visitor),
+			BinExprPartKind::Val(Value::FuncCall(call)) 	=> Self::visit_call(call, visitor),
+			BinExprPartKind::Val(_) 						=> (),
+			BinExprPartKind::SelfOperation(operation) 		=> Self::visit_expression(&operation.expression, visitor),
+			BinExprPartKind::Operation(operation) =>
+			{
+				Self::visit_expression(&operation.lhs, visitor);
+				Self::visit_expression(&operation.rhs, visitor);
+			},
+			BinExprPartKind::TypeCast(info) 				=> Self::visit_expression(&info.expression, visitor),
+		}
+	}
+
+	fn visit_call(function_call: &FunctionCallInfo, visitor: &mut impl FnMut(&BinExprPart))
+	{
+		for argument in &function_call.arguments
+		{
+			Self::visit_expression(&argument.root, visitor);
+		}
+		if let Callee::Indirect(pointer) = &function_call.callee
+		{
+			Self::visit_expression(&pointer.root, visitor);
+		}
+	}
+
+	fn line(&mut self, line: &str)
+	{
+		for line in line.lines()
+		{
+			self.body.push('\n');
+			for _ in 0..self.indentation
+			{
+				self.body.push('\t');
+			}
+			self.body.push_str(line);
+		}
+	}
+}
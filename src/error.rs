@@ -11,6 +11,10 @@ pub enum CompileError<'a>
 	TypeError(Type, Type),			/* ExpectedType, GivenType */
 	InvalidPreprocessorCommand(&'a str),
 	FileWriteError(&'a str),
+	WarningsAsErrors,
+	DivisionByZero,
+	TailCall,
+	LinkerError(i32),				/* The exit code of the linker */
 }
 
 pub enum ExitCodes
@@ -24,6 +28,10 @@ pub enum ExitCodes
 	TypeError,
 	InvalidPreprocessorCommand,
 	FileWriteError,
+	WarningsAsErrors,
+	DivisionByZero,
+	TailCall,
+	LinkerError,
 }
 
 pub struct LineInfo
@@ -140,7 +148,7 @@ pub fn get_exit_code(compile_error: CompileError) -> ExitCodes
 
 		CompileError::TypeError(expected, given) =>
 		{
-			eprint!("Type error. Expected {} but type {} was given. ", expected.to_string(), given.to_string());
+			eprint!("Type error. Expected {} but type {} was given. ", expected, given);
 			return ExitCodes::TypeError;
 		},
 
@@ -154,7 +162,31 @@ pub fn get_exit_code(compile_error: CompileError) -> ExitCodes
 		{
 			eprint!("Could not write to file \"{file_path}\".");
 			return ExitCodes::FileWriteError;
-		}
+		},
+
+		CompileError::WarningsAsErrors =>
+		{
+			eprint!("Warnings that are treated as errors were reported. ");
+			return ExitCodes::WarningsAsErrors;
+		},
+
+		CompileError::DivisionByZero =>
+		{
+			eprint!("Division by zero. ");
+			return ExitCodes::DivisionByZero;
+		},
+
+		CompileError::TailCall =>
+		{
+			eprint!("Cannot be compiled into a tail call. ");
+			return ExitCodes::TailCall;
+		},
+
+		CompileError::LinkerError(code) =>
+		{
+			eprint!("The linker failed with exit code {code}. ");
+			return ExitCodes::LinkerError;
+		},
 	}
 }
 
@@ -167,7 +199,7 @@ macro_rules! print_err
 	{
 		// Print "slow: error - " while "slow" is white bold and "error" is in red bold
 		eprint!("\x1b[1mslowc\x1b[0m: \x1b[31;1merror\x1b[0m - "); 	
-		let exit_code = crate::error::get_exit_code($compile_error);
+		let exit_code = $crate::error::get_exit_code($compile_error);
 		eprintln!($($print_data)*);
 		std::process::exit(exit_code as i32 + 1); 	/* +1 because error codes start from 1 and enums start from 0 */
 	}
@@ -178,8 +210,8 @@ macro_rules! print_errln {
 	($compile_error:expr, $source:expr, $source_index:expr, $( $print_data:tt )* ) => {
 		// Print "slow: error - " while "slow" is in bold and "error" is in red bold
 		eprint!("\x1b[1mslowc\x1b[0m: \x1b[31;1merror\x1b[0m - "); 	
-		let exit_code = crate::error::get_exit_code($compile_error);
-		let line = crate::error::get_line_from_index($source, $source_index);
+		let exit_code = $crate::error::get_exit_code($compile_error);
+		let line = $crate::error::get_line_from_index($source, $source_index);
 		eprintln!($($print_data)*);
 		eprintln!("\tOn line {}: {}", line.line_index + 1, line.line_contents);
 		eprintln!("\t  {}{}\x1b[1mHere: <---->\x1b[0m", str::repeat(" ", line.column), str::repeat("\t", line.tabs_count as usize));
@@ -187,6 +219,19 @@ macro_rules! print_errln {
 	};
 }
 
+// Like print_errln!(), but does not exit. Used for warnings that are treated as errors, which are all reported before exiting.
+#[macro_export]
+macro_rules! print_errln_no_exit {
+	($source:expr, $source_index:expr, $( $print_data:tt )* ) => {
+		// Print "slow: error - " while "slow" is in bold and "error" is in red bold
+		eprint!("\x1b[1mslowc\x1b[0m: \x1b[31;1merror\x1b[0m - ");
+		eprintln!($($print_data)*);
+		let line = $crate::error::get_line_from_index($source, $source_index);
+		eprintln!("\tOn line {}: {}", line.line_index + 1, line.line_contents);
+		eprintln!("\t  {}{}\x1b[1mHere: <---->\x1b[0m", str::repeat(" ", line.column), str::repeat("\t", line.tabs_count as usize));
+	};
+}
+
 // Prints a formatted warning message to stdout.
 #[macro_export]
 macro_rules! print_wrn
@@ -205,7 +250,7 @@ macro_rules! print_wrnln {
 		// Print "slow: warning - " while "slow" is in bold and "warning" is in yellow bold
 		print!("\x1b[1mslowc\x1b[0m: \x1b[93;1mwarning\x1b[0m - ");
 		println!($($print_data)*);
-		let line = crate::error::get_line_from_index($source, $source_index);
+		let line = $crate::error::get_line_from_index($source, $source_index);
 		println!("\tOn line {}: {}", line.line_index + 1, line.line_contents);
 		println!("\t  {}{}\x1b[1mHere: <---->\x1b[0m", str::repeat(" ", line.column), str::repeat("\t", line.tabs_count as usize));
 	};
@@ -25,6 +25,7 @@ macro_rules! create_keyword {
 // Note: variables will be declared in the following format: "create i32 my_number = 420;"
 // or "ויהי חתום32 מספר_או_משהו = 420;" read from right to left, and the semicolon if actually on the end of the sentence (at the left part)
 pub const KEYWORD_VAR_DECL	: &str = create_keyword!("ויהי", "let");
+pub const KEYWORD_CONST		: &str = create_keyword!("קבוע", "const");
 pub const KEYWORD_VOID		: &str = create_keyword!("כלום", "void");
 pub const KEYWORD_I8		: &str = create_keyword!("חתום8", "i8");
 pub const KEYWORD_U8		: &str = create_keyword!("חיובי8", "u8");
@@ -40,6 +41,8 @@ pub const KEYWORD_FUNC_DECL	: &str = create_keyword!("פונקציה", "func");
 pub const KEYWORD_RETURN	: &str = create_keyword!("החזר", "return");
 pub const KEYWORD_GLOBAL	: &str = create_keyword!("גלובלי", "global");
 pub const KEYWORD_EXTERN 	: &str = create_keyword!("חיצוני", "extern");
+pub const KEYWORD_INLINE 	: &str = create_keyword!("מוטבע", "inline");
+pub const KEYWORD_TAILREC 	: &str = create_keyword!("זנבי", "tailrec");
 pub const KEYWORD_IF 		: &str = create_keyword!("אם", "if");
 pub const KEYWORD_ELSE 		: &str = create_keyword!("אחרת", "else");
 pub const KEYWORD_AND 		: &str = create_keyword!("וגם", "and");
@@ -63,9 +66,9 @@ impl<'a> Iterator for Lexer<'a>
 		
 		while let Some(ch) = self.current
 		{
-			if ch == '/' && self.peek() != None && *self.peek().unwrap() == '/'
+			if ch == '/' && self.peek().is_some() && *self.peek().unwrap() == '/'
 			{
-				while self.current != None && self.current.unwrap() != '\n'
+				while self.current.is_some() && self.current.unwrap() != '\n'
 				{
 					self.advance();
 				}
@@ -89,7 +92,7 @@ impl<'a> Iterator for Lexer<'a>
 		// 	));
 		// }
 		
-		if self.position >= self.source.len() || self.current == Option::None
+		if self.position >= self.source.len() || self.current.is_none()
 		{
 			return None;
 		}
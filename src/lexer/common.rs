@@ -4,7 +4,7 @@ impl<'a> Lexer<'a>
 {
 	pub fn is_number_start(ch: char) -> bool
 	{
-		return ch.is_digit(10);
+		return ch.is_ascii_digit();
 	}
 	
 	// Operators such as (, ), *, +, -, ;, /, \, 
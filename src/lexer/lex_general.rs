@@ -7,7 +7,7 @@ impl<'a> Lexer<'a>
 		let mut number = String::with_capacity(8);
 		while let Some(ch) = self.current
 		{
-			if !ch.is_digit(0x10) && ch != '.'
+			if !ch.is_ascii_hexdigit() && ch != '.'
 			{
 				break;
 			}
@@ -130,6 +130,12 @@ impl<'a> Lexer<'a>
 					kind = TokenKind::Equal
 				}
 			},
+			'.' if next_ch == '.' && self.peek() == Some(&'.') =>
+			{
+				self.advance();
+				self.advance();
+				kind = TokenKind::Ellipsis;
+			},
 			'^' => kind = TokenKind::BitwiseXor,
 			'*' => kind = TokenKind::Asterisk,
 			'/' => kind = TokenKind::ForwardSlash,
@@ -159,7 +165,6 @@ impl<'a> Lexer<'a>
 	pub fn lex_name(&mut self) -> Token
 	{
 		let start = self.position;
-		let end: usize;
 		let ch = self.current.unwrap();
 		let mut name = String::from(ch);
 		while let Some(next_ch) = self.advance()
@@ -173,33 +178,35 @@ impl<'a> Lexer<'a>
 			}	
 		}
 
-		end = self.position;
-		let kind: TokenKind;
-
-		match &name[..] {
-			KEYWORD_VAR_DECL 	=> kind = TokenKind::VarDecl,
-			KEYWORD_VOID		=> kind = TokenKind::Void,
-			KEYWORD_I8			=> kind = TokenKind::I8,
-			KEYWORD_U8			=> kind = TokenKind::U8,
-			KEYWORD_I16			=> kind = TokenKind::I16,
-			KEYWORD_U16			=> kind = TokenKind::U16,
-			KEYWORD_I32			=> kind = TokenKind::I32,
-			KEYWORD_U32			=> kind = TokenKind::U32,
-			KEYWORD_I64			=> kind = TokenKind::I64,
-			KEYWORD_U64			=> kind = TokenKind::U64,
-			KEYWORD_F32			=> kind = TokenKind::F32,
-			KEYWORD_F64			=> kind = TokenKind::F64,
-			KEYWORD_FUNC_DECL	=> kind = TokenKind::FuncDecl,
-			KEYWORD_RETURN		=> kind = TokenKind::Return,
-			KEYWORD_GLOBAL		=> kind = TokenKind::Global,
-			KEYWORD_EXTERN		=> kind = TokenKind::Extern,
-			KEYWORD_IF			=> kind = TokenKind::If,
-			KEYWORD_ELSE		=> kind = TokenKind::Else,
-			KEYWORD_AND			=> kind = TokenKind::BoolAnd,
-			KEYWORD_OR			=> kind = TokenKind::BoolOr,
-			KEYWORD_FOR			=> kind = TokenKind::For,
-			_ => kind = TokenKind::Ident
-		}
+		let end = self.position;
+		let kind = match &name[..]
+		{
+			KEYWORD_VAR_DECL 	=> TokenKind::VarDecl,
+			KEYWORD_CONST 		=> TokenKind::Const,
+			KEYWORD_VOID		=> TokenKind::Void,
+			KEYWORD_I8			=> TokenKind::I8,
+			KEYWORD_U8			=> TokenKind::U8,
+			KEYWORD_I16			=> TokenKind::I16,
+			KEYWORD_U16			=> TokenKind::U16,
+			KEYWORD_I32			=> TokenKind::I32,
+			KEYWORD_U32			=> TokenKind::U32,
+			KEYWORD_I64			=> TokenKind::I64,
+			KEYWORD_U64			=> TokenKind::U64,
+			KEYWORD_F32			=> TokenKind::F32,
+			KEYWORD_F64			=> TokenKind::F64,
+			KEYWORD_FUNC_DECL	=> TokenKind::FuncDecl,
+			KEYWORD_RETURN		=> TokenKind::Return,
+			KEYWORD_GLOBAL		=> TokenKind::Global,
+			KEYWORD_EXTERN		=> TokenKind::Extern,
+			KEYWORD_INLINE		=> TokenKind::Inline,
+			KEYWORD_TAILREC		=> TokenKind::TailRec,
+			KEYWORD_IF			=> TokenKind::If,
+			KEYWORD_ELSE		=> TokenKind::Else,
+			KEYWORD_AND			=> TokenKind::BoolAnd,
+			KEYWORD_OR			=> TokenKind::BoolOr,
+			KEYWORD_FOR			=> TokenKind::For,
+			_ 					=> TokenKind::Ident,
+		};
 
 		return Token::new(
 			kind,
@@ -45,10 +45,12 @@ pub enum TokenKind
 	LeftCurly,
 	RightCurly,
 	Arrow,
+	Ellipsis,
 	Comma,
 	Semicolon,
 	Ident,
 	VarDecl,
+	Const,
 	Void,
 	I8,
 	U8,
@@ -64,6 +66,8 @@ pub enum TokenKind
 	Return,
 	Global,
 	Extern,
+	Inline,
+	TailRec,
 	If,
 	Else,
 	For,
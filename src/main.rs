@@ -1,31 +1,150 @@
+// The code base is written in an explicit C-like style: explicit returns, Allman braces (so "} else" is followed by a new line),
+// and register names as enum variants.
+#![allow(clippy::needless_return, clippy::suspicious_else_formatting, clippy::upper_case_acronyms)]
+
 mod error;
 mod preprocessor;
 mod lexer;
 mod ast;
+mod semantic;
+mod warnings;
+mod mir;
 mod codegen;
 use error::CompileError;
 
+// What the compiler produces
+#[derive(Clone, Copy, PartialEq)]
+enum Emit
+{
+    Executable,
+    Ir,         /* Print the MIR of the program, without generating code */
+    C,          /* Translate the program into C99 source, without generating code */
+}
+
+const USAGE: &str = "Correct usage: slowc [-W<warning>] [-Wno-<warning>] [-Werror[=<warning>]] [-O<level>] [-S] [--asm=<nasm|gnu>] [--target=<x86_64-linux|aarch64-linux|wasm32>] [--emit=<ir|c>] <FILE.slw>";
+
 fn main() {
     let argv: Vec<String> = std::env::args().collect();
-    if argv.len() < 2
+    let mut filepath = None;
+    let mut warning_options = warnings::WarningOptions::new();
+    let mut emit = Emit::Executable;
+    let mut optimization_level = mir::OPTIMIZATION_LEVEL_NONE;
+    let mut assembler = codegen::Assembler::Builtin;
+    let mut target = codegen::Target::X86_64Linux;
+    for argument in &argv[1..]
+    {
+        if let Some(option) = argument.strip_prefix("-W")
+        {
+            if !warning_options.apply_option(option)
+            {
+                print_err!(CompileError::Usage, "Unknown warning option \"{argument}\". {USAGE}");
+            }
+        } else if let Some(level) = argument.strip_prefix("-O")
+        {
+            // -O alone is the same as -O1
+            optimization_level = match level
+            {
+                "" => 1,
+                _ => level.parse().ok().filter(|level| *level <= mir::OPTIMIZATION_LEVEL_MAX).unwrap_or_else(|| {
+                    print_err!(CompileError::Usage, "Unknown optimization level \"{argument}\", the levels are 0 to {}. {USAGE}", mir::OPTIMIZATION_LEVEL_MAX);
+                }),
+            };
+        } else if argument == "-S"
+        {
+            // The assembly is written and assembled with nasm, instead of being encoded by slowc
+            if assembler == codegen::Assembler::Builtin
+            {
+                assembler = codegen::Assembler::Nasm;
+            }
+        } else if let Some(dialect) = argument.strip_prefix("--asm=")
+        {
+            // Like -S, in the syntax of the given assembler
+            assembler = match dialect
+            {
+                "nasm" => codegen::Assembler::Nasm,
+                "gnu" => codegen::Assembler::Gnu,
+                _ => { print_err!(CompileError::Usage, "Unknown assembler \"{dialect}\". {USAGE}"); },
+            };
+        } else if let Some(name) = argument.strip_prefix("--target=")
+        {
+            target = match name
+            {
+                "x86_64-linux" => codegen::Target::X86_64Linux,
+                "aarch64-linux" => codegen::Target::Aarch64Linux,
+                "wasm32" => codegen::Target::Wasm32,
+                _ => { print_err!(CompileError::Usage, "Unknown target \"{name}\". {USAGE}"); },
+            };
+        } else if let Some(output) = argument.strip_prefix("--emit=")
+        {
+            emit = match output
+            {
+                "ir" => Emit::Ir,
+                "c" => Emit::C,
+                _ => { print_err!(CompileError::Usage, "Unknown output \"{output}\". {USAGE}"); },
+            };
+        } else if argument.starts_with('-') || filepath.is_some()
+        {
+            print_err!(CompileError::Usage, "Unexpected argument \"{argument}\". {USAGE}");
+        } else
+        {
+            filepath = Some(argument.as_str());
+        }
+    }
+
+    let filepath = filepath.unwrap_or_else(|| {
+        print_err!(CompileError::Usage, "{USAGE}");
+    });
+
+    // The AArch64 assembly is written in the syntax of the GNU assembler
+    if target == codegen::Target::Aarch64Linux && assembler == codegen::Assembler::Nasm
     {
-        print_err!(CompileError::Usage, "Correct usage: slowc <FILE.slw>");
+        print_err!(CompileError::Usage, "nasm cannot assemble for the target aarch64-linux, use --asm=gnu. {USAGE}");
+    }
+
+    // The WebAssembly text is the output, there is nothing to assemble
+    if target == codegen::Target::Wasm32 && assembler != codegen::Assembler::Builtin
+    {
+        print_err!(CompileError::Usage, "The target wasm32 is not assembled, -S and --asm cannot be used with it. {USAGE}");
     }
 
     let executable_path = "a.out";
-    let obj_file = slowc_compile_file(&argv[1]);
+    let obj_file = slowc_compile_file(filepath, warning_options, optimization_level, emit, assembler, target);
+    let Some(obj_file) = obj_file else
+    {
+        return;
+    };
 
     // Linking with the C standard library is temporary. Il create my own in the future
-    std::process::Command::new("ld")
+    // The C runtime files are in /usr/lib, or in the directory of the architecture on Debian based distributions
+    let (linker, dynamic_linker, library_directories) = match target
+    {
+        codegen::Target::X86_64Linux => ("ld", "/lib64/ld-linux-x86-64.so.2", ["/usr/lib", "/usr/lib/x86_64-linux-gnu"]),
+        codegen::Target::Aarch64Linux if cfg!(target_arch = "aarch64") => ("ld", "/lib/ld-linux-aarch64.so.1", ["/usr/lib", "/usr/lib/aarch64-linux-gnu"]),
+        codegen::Target::Aarch64Linux => ("aarch64-linux-gnu-ld", "/lib/ld-linux-aarch64.so.1", ["/usr/aarch64-linux-gnu/lib", "/usr/lib/aarch64-linux-gnu"]),
+        codegen::Target::Wasm32 => unreachable!("Dev error! There is nothing to link for the target wasm32."),
+    };
+    let libraries = library_directories.into_iter()
+        .find(|directory| std::path::Path::new(directory).join("crt1.o").exists())
+        .unwrap_or(library_directories[0]);
+    let status = std::process::Command::new(linker)
         .args(["-o", executable_path])
-        .args(["-dynamic-linker", "/lib64/ld-linux-x86-64.so.2"])
-        .args(["/usr/lib/crt1.o", "/usr/lib/crti.o", "-lc", "/usr/lib/crtn.o"])
+        .args(["-dynamic-linker", dynamic_linker])
+        .args([format!("{libraries}/crt1.o"), format!("{libraries}/crti.o"), format!("-L{libraries}"), "-lc".to_string(), format!("{libraries}/crtn.o")])
         .arg(obj_file)
-        .spawn()
+        .status()
         .expect("Error, failed to link program.");
+    if !status.success()
+    {
+        // A linker that was killed by a signal has no exit code
+        print_err!(CompileError::LinkerError(status.code().unwrap_or(-1)), "Could not link \"{obj_file}\" into \"{executable_path}\".");
+    }
 }
 
-fn slowc_compile_file(filepath: &str) -> &str
+// Returns the path of the object file, or None if there is nothing to link
+fn slowc_compile_file(
+    filepath: &str, warning_options: warnings::WarningOptions, optimization_level: u8, emit: Emit, assembler: codegen::Assembler,
+    target: codegen::Target
+) -> Option<&str>
 {
     let mut source = std::fs::read_to_string(filepath)
         .unwrap_or_else(|err| {print_err!(CompileError::NoSuchFile(filepath), "Error: {err}");});
@@ -41,9 +160,44 @@ fn slowc_compile_file(filepath: &str) -> &str
 
     let ir = parser.generate_ir();
 
-    let code_generator = codegen::CodeGen::new(&ir);
+    let warnings = warnings::Warnings::new(warning_options, &source);
+
+    let semantic_analyzer = semantic::SemanticAnalyzer::new(ir, &source, &warnings);
+
+    let ir = semantic_analyzer.analyze();
+
+    warnings.check(&ir);
+
+    if emit == Emit::C
+    {
+        codegen::c::CodeGen::new(&ir).generate();
+        return None;
+    }
+
+    let mut program = mir::Program::lower(&ir);
+    program.optimize(optimization_level);
+
+    if emit == Emit::Ir
+    {
+        print!("{program}");
+        return None;
+    }
+
+    if target == codegen::Target::Wasm32
+    {
+        codegen::wasm::CodeGen::new(&ir).generate();
+        return None;
+    }
+
+    if target == codegen::Target::Aarch64Linux
+    {
+        let code_generator = codegen::aarch64::CodeGen::new(&program, optimization_level);
+        return Some(code_generator.generate());
+    }
+
+    let code_generator = codegen::CodeGen::new(&program, optimization_level, assembler);
 
-    return code_generator.generate();
+    return Some(code_generator.generate());
 
     // println!("{:#?}", ir);
     // return "aserdgf";
@@ -0,0 +1,422 @@
+mod lower;
+mod verify;
+mod display;
+mod cfg;
+mod liveness;
+mod optimize;
+mod promote;
+mod inline;
+mod loops;
+
+pub use cfg::Cfg;
+pub use liveness::Liveness;
+pub use optimize::*;
+
+use crate::ast::{attribute::{self, AttributeType}, define_id, FunctionId, FunctionType, FunctionTypeId, Type, TypeKind};
+
+// The mid-level intermediate representation (MIR) that the backends generate code from, lowered from the AST after the semantic analysis.
+// Each function is a list of basic blocks of three-address instructions over an unlimited amount of virtual registers.
+// Local variables live in stack slots, which are accessed with explicit loads and stores.
+// The MIR is not in SSA form, a virtual register can be assigned more than once.
+
+define_id!(
+	RegisterId,			/* The virtual registers index in its functions registers */
+	SlotId,				/* The stack slots index in its functions slots */
+	BlockId				/* The blocks index in its functions blocks */
+);
+
+pub struct Program
+{
+	pub functions: Vec<Function>,				/* In the same order as in the AST, so a FunctionId is also an index into this array */
+	pub function_types: Vec<FunctionType>,
+}
+
+pub struct Function
+{
+	pub identifier: String,
+	pub attributes: AttributeType,
+	pub signature: FunctionTypeId,
+	pub return_type: Type,
+	pub parameters: Vec<RegisterId>,	/* The virtual registers that hold the parameters when the function starts */
+	pub registers: Vec<Type>,			/* The data type of each virtual register */
+	pub slots: Vec<StackSlot>,
+	pub blocks: Vec<Block>,				/* The first block is the entry of the function. External functions have no blocks */
+}
+
+pub struct StackSlot
+{
+	pub data_type: Type,
+	pub name: String,					/* The name of the variable that is stored in the slot, for the textual dump */
+}
+
+pub struct Block
+{
+	pub instructions: Vec<Instruction>,
+	pub terminator: Terminator,
+}
+
+// A value that an instruction reads. Constants are stored as their bits, zero extended into 64 bits (an i8 -1 is 0xFF).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operand
+{
+	Register(RegisterId),
+	Integer(u64),
+	Float(f64),
+}
+
+// The memory that a load or a store accesses
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Address
+{
+	Slot(SlotId),
+	Register(RegisterId),		/* A register that holds a pointer */
+}
+
+// The data type of an instruction is the data type of its destination, unless written otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Instruction
+{
+	Copy { destination: RegisterId, source: Operand },
+	Binary { operator: BinaryOperator, destination: RegisterId, lhs: Operand, rhs: Operand },
+	Unary { operator: UnaryOperator, destination: RegisterId, source: Operand },
+	Compare { condition: Condition, data_type: Type, destination: RegisterId, lhs: Operand, rhs: Operand },	/* The destination is an u8, 1 or 0 */
+	Convert { destination: RegisterId, source: Operand, from: Type },
+	Load { destination: RegisterId, address: Address },
+	Store { data_type: Type, address: Address, value: Operand },
+	SlotAddress { destination: RegisterId, slot: SlotId },
+	FunctionAddress { destination: RegisterId, function: FunctionId },
+	Call { destination: Option<RegisterId>, target: CallTarget, signature: FunctionTypeId, arguments: Vec<Operand>, argument_types: Vec<Type> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOperator
+{
+	Add,
+	Sub,
+	Mul,
+	Div,
+	Modulo,
+	And,
+	Or,
+	Xor,
+	ShiftLeft,
+	ShiftRight,			/* A logical shift, also for signed integers */
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOperator
+{
+	Not,				/* Bitwise not */
+	IsZero,				/* 1 if the source is 0, 0 otherwise (in the data type of the destination) */
+}
+
+// Comparisons of signed integers are signed, and comparisons of unsigned integers and addresses are unsigned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Condition
+{
+	Equal,
+	NotEqual,
+	Greater,
+	Less,
+	GreaterEqual,
+	LessEqual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CallTarget
+{
+	Direct(FunctionId),
+	Indirect(Operand),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Terminator
+{
+	Jump(BlockId),
+	Branch { condition: Operand, then_block: BlockId, else_block: BlockId },	/* Goes to then_block if the condition is not 0 */
+	Return(Option<Operand>),
+}
+
+impl Function
+{
+	pub fn new(identifier: String, attributes: AttributeType, signature: FunctionTypeId, return_type: Type) -> Self
+	{
+		return Self {
+			identifier,
+			attributes,
+			signature,
+			return_type,
+			parameters: Vec::new(),
+			registers: Vec::new(),
+			slots: Vec::new(),
+			blocks: Vec::new(),
+		};
+	}
+
+	pub fn is_extern(&self) -> bool
+	{
+		return self.attributes & attribute::EXTERN != 0;
+	}
+
+	pub fn new_register(&mut self, data_type: Type) -> RegisterId
+	{
+		self.registers.push(data_type);
+		return RegisterId(self.registers.len() as u32 - 1);
+	}
+
+	pub fn new_slot(&mut self, data_type: Type, name: String) -> SlotId
+	{
+		self.slots.push(StackSlot::new(data_type, name));
+		return SlotId(self.slots.len() as u32 - 1);
+	}
+
+	pub fn register_type(&self, register: RegisterId) -> Type
+	{
+		return self.registers[register.index()];
+	}
+}
+
+impl StackSlot
+{
+	pub fn new(data_type: Type, name: String) -> Self
+	{
+		return Self {
+			data_type,
+			name,
+		};
+	}
+}
+
+impl Block
+{
+	pub fn new(instructions: Vec<Instruction>, terminator: Terminator) -> Self
+	{
+		return Self {
+			instructions,
+			terminator,
+		};
+	}
+}
+
+impl Instruction
+{
+	// The register that the instruction writes into, if there is one
+	pub fn destination(&self) -> Option<RegisterId>
+	{
+		return match *self
+		{
+			Self::Copy { destination, .. } 				|
+			Self::Binary { destination, .. } 			|
+			Self::Unary { destination, .. } 			|
+			Self::Compare { destination, .. } 			|
+			Self::Convert { destination, .. } 			|
+			Self::Load { destination, .. } 				|
+			Self::SlotAddress { destination, .. } 		|
+			Self::FunctionAddress { destination, .. } 	=> Some(destination),
+			Self::Call { destination, .. } 				=> destination,
+			Self::Store { .. } 							=> None,
+		};
+	}
+
+	pub fn destination_mut(&mut self) -> Option<&mut RegisterId>
+	{
+		return match self
+		{
+			Self::Copy { destination, .. } 				|
+			Self::Binary { destination, .. } 			|
+			Self::Unary { destination, .. } 			|
+			Self::Compare { destination, .. } 			|
+			Self::Convert { destination, .. } 			|
+			Self::Load { destination, .. } 				|
+			Self::SlotAddress { destination, .. } 		|
+			Self::FunctionAddress { destination, .. } 	=> Some(destination),
+			Self::Call { destination, .. } 				=> destination.as_mut(),
+			Self::Store { .. } 							=> None,
+		};
+	}
+
+	// Calls on_operand on each operand that the instruction reads, including the registers of addresses
+	pub fn for_each_operand(&self, on_operand: &mut dyn FnMut(Operand))
+	{
+		match self
+		{
+			Self::Copy { source, .. } | Self::Unary { source, .. } | Self::Convert { source, .. } => on_operand(*source),
+			Self::Binary { lhs, rhs, .. } | Self::Compare { lhs, rhs, .. } =>
+			{
+				on_operand(*lhs);
+				on_operand(*rhs);
+			},
+			Self::Load { address, .. } => Self::address_operand(address, on_operand),
+			Self::Store { address, value, .. } =>
+			{
+				Self::address_operand(address, on_operand);
+				on_operand(*value);
+			},
+			Self::SlotAddress { .. } | Self::FunctionAddress { .. } => (),
+			Self::Call { target, arguments, .. } =>
+			{
+				for argument in arguments
+				{
+					on_operand(*argument);
+				}
+				if let CallTarget::Indirect(function) = target
+				{
+					on_operand(*function);
+				}
+			},
+		}
+	}
+
+	// Calls on_register on each virtual register that the instruction reads
+	pub fn for_each_used_register(&self, on_register: &mut dyn FnMut(RegisterId))
+	{
+		self.for_each_operand(&mut |operand| {
+			if let Operand::Register(register) = operand
+			{
+				on_register(register);
+			}
+		});
+	}
+
+	// Calls on_operand on each value that the instruction reads. Unlike for_each_operand, the registers of addresses are not included,
+	// because an address cannot be replaced with a constant. (for_each_register_mut includes them)
+	pub fn for_each_value_operand_mut(&mut self, on_operand: &mut dyn FnMut(&mut Operand))
+	{
+		match self
+		{
+			Self::Copy { source, .. } | Self::Unary { source, .. } | Self::Convert { source, .. } => on_operand(source),
+			Self::Binary { lhs, rhs, .. } | Self::Compare { lhs, rhs, .. } =>
+			{
+				on_operand(lhs);
+				on_operand(rhs);
+			},
+			Self::Store { value, .. } => on_operand(value),
+			Self::Load { .. } | Self::SlotAddress { .. } | Self::FunctionAddress { .. } => (),
+			Self::Call { target, arguments, .. } =>
+			{
+				for argument in arguments
+				{
+					on_operand(argument);
+				}
+				if let CallTarget::Indirect(function) = target
+				{
+					on_operand(function);
+				}
+			},
+		}
+	}
+
+	// Calls on_register on each virtual register of the instruction, the ones that it reads and the one that it writes
+	pub fn for_each_register_mut(&mut self, on_register: &mut dyn FnMut(&mut RegisterId))
+	{
+		self.for_each_value_operand_mut(&mut |operand| {
+			if let Operand::Register(register) = operand
+			{
+				on_register(register);
+			}
+		});
+
+		match self
+		{
+			Self::Load { address: Address::Register(register), .. } | Self::Store { address: Address::Register(register), .. } => on_register(register),
+			_ => (),
+		}
+
+		if let Some(destination) = self.destination_mut()
+		{
+			on_register(destination);
+		}
+	}
+
+	fn address_operand(address: &Address, on_operand: &mut dyn FnMut(Operand))
+	{
+		if let Address::Register(register) = address
+		{
+			on_operand(Operand::Register(*register));
+		}
+	}
+}
+
+impl Terminator
+{
+	pub fn successors(&self) -> Vec<BlockId>
+	{
+		return match *self
+		{
+			Self::Jump(target) 								=> vec![target],
+			Self::Branch { then_block, else_block, .. } 	=> vec![then_block, else_block],
+			Self::Return(_) 								=> Vec::new(),
+		};
+	}
+
+	pub fn for_each_target_mut(&mut self, on_target: &mut dyn FnMut(&mut BlockId))
+	{
+		match self
+		{
+			Self::Jump(target) => on_target(target),
+			Self::Branch { then_block, else_block, .. } =>
+			{
+				on_target(then_block);
+				on_target(else_block);
+			},
+			Self::Return(_) => (),
+		}
+	}
+
+	pub fn for_each_operand_mut(&mut self, on_operand: &mut dyn FnMut(&mut Operand))
+	{
+		match self
+		{
+			Self::Branch { condition, .. } 	=> on_operand(condition),
+			Self::Return(Some(value)) 		=> on_operand(value),
+			_ 								=> (),
+		}
+	}
+
+	pub fn for_each_used_register(&self, on_register: &mut dyn FnMut(RegisterId))
+	{
+		match *self
+		{
+			Self::Branch { condition: Operand::Register(register), .. } 	=> on_register(register),
+			Self::Return(Some(Operand::Register(register))) 				=> on_register(register),
+			_ 																=> (),
+		}
+	}
+}
+
+impl Condition
+{
+	pub fn evaluate<T: PartialOrd>(&self, lhs: T, rhs: T) -> bool
+	{
+		return match self
+		{
+			Self::Equal 		=> lhs == rhs,
+			Self::NotEqual 		=> lhs != rhs,
+			Self::Greater 		=> lhs > rhs,
+			Self::Less 			=> lhs < rhs,
+			Self::GreaterEqual 	=> lhs >= rhs,
+			Self::LessEqual 	=> lhs <= rhs,
+		};
+	}
+}
+
+// The bits of an integer constant of the given data type, as stored in an Operand::Integer
+pub fn integer_bits(value: i128, data_type: Type) -> u64
+{
+	if data_type.size() == 8
+	{
+		return value as u64;
+	}
+	return value as u64 & ((1 << (data_type.size() as u32 * 8)) - 1);
+}
+
+// The value of an integer constant of the given data type, sign extended if the data type is signed
+pub fn integer_value(bits: u64, data_type: Type) -> i128
+{
+	let shift = 64 - data_type.size() as u32 * 8;
+	if data_type.is_signed()
+	{
+		return ((bits << shift) as i64 >> shift) as i128;
+	}
+	return bits as i128;
+}
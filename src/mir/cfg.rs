@@ -0,0 +1,331 @@
+use super::*;
+
+// The control flow graph of a function, the edges between its blocks.
+// It is a snapshot, so it must be built again after a pass changes the terminators of the function.
+pub struct Cfg
+{
+	pub successors: Vec<Vec<BlockId>>,
+	pub predecessors: Vec<Vec<BlockId>>,
+}
+
+// A loop in the control flow graph. Every path from outside of the loop into it goes through its header.
+pub struct Loop
+{
+	pub header: BlockId,
+	pub blocks: Vec<bool>,		/* Whether each block of the function is in the loop */
+}
+
+impl Cfg
+{
+	pub fn new(function: &Function) -> Self
+	{
+		let mut successors = Vec::with_capacity(function.blocks.len());
+		let mut predecessors = vec![Vec::new(); function.blocks.len()];
+		for (i, block) in function.blocks.iter().enumerate()
+		{
+			let block_successors = block.terminator.successors();
+			for successor in &block_successors
+			{
+				// A branch whose targets are the same block is a single edge
+				if !predecessors[successor.index()].contains(&BlockId(i as u32))
+				{
+					predecessors[successor.index()].push(BlockId(i as u32));
+				}
+			}
+			successors.push(block_successors);
+		}
+
+		return Self {
+			successors,
+			predecessors,
+		};
+	}
+
+	// The blocks that can be reached from the entry block
+	pub fn reachable(&self) -> Vec<bool>
+	{
+		let mut reachable = vec![false; self.successors.len()];
+		let mut stack = vec![BlockId(0)];
+		while let Some(block) = stack.pop()
+		{
+			if reachable[block.index()]
+			{
+				continue;
+			}
+			reachable[block.index()] = true;
+			stack.extend(self.successors[block.index()].iter().filter(|successor| !reachable[successor.index()]));
+		}
+		return reachable;
+	}
+
+	// The dominators of each block, dominators[block][other] is whether other dominates block (every path from the entry
+	// into block goes through other). Blocks that cannot be reached are dominated by every block.
+	pub fn dominators(&self) -> Vec<Vec<bool>>
+	{
+		let count = self.successors.len();
+		let mut dominators = vec![vec![true; count]; count];
+		if count == 0
+		{
+			return dominators;
+		}
+		dominators[0] = vec![false; count];
+		dominators[0][0] = true;
+
+		let mut changed = true;
+		while changed
+		{
+			changed = false;
+			for block in 1..count
+			{
+				let mut new_dominators = vec![true; count];
+				for predecessor in &self.predecessors[block]
+				{
+					for (other, dominates) in dominators[predecessor.index()].iter().enumerate()
+					{
+						new_dominators[other] &= *dominates;
+					}
+				}
+				new_dominators[block] = true;
+				if new_dominators != dominators[block]
+				{
+					dominators[block] = new_dominators;
+					changed = true;
+				}
+			}
+		}
+		return dominators;
+	}
+
+	// The amount of loops that each block is in
+	pub fn loop_depths(&self) -> Vec<u32>
+	{
+		let mut depths = vec![0; self.successors.len()];
+		for found in self.loops()
+		{
+			for (depth, in_loop) in depths.iter_mut().zip(found.blocks)
+			{
+				*depth += in_loop as u32;
+			}
+		}
+		return depths;
+	}
+
+	// The loops of the function, a loop for each block that is a loop header. A loop is found by an edge into a block that dominates the source
+	// of the edge (a back edge), and its blocks are the blocks that can reach the source of the edge without going through the loop header.
+	pub fn loops(&self) -> Vec<Loop>
+	{
+		let reachable = self.reachable();
+		let dominators = self.dominators();
+		let mut loops = Vec::new();
+		for (header, predecessors) in self.predecessors.iter().enumerate()
+		{
+			let mut in_loop = vec![false; self.successors.len()];
+			in_loop[header] = true;
+			let mut stack: Vec<BlockId> = predecessors.iter().copied().filter(|source| {
+				return reachable[source.index()] && dominators[source.index()][header];
+			}).collect();
+			if stack.is_empty()
+			{
+				continue;
+			}
+
+			while let Some(block) = stack.pop()
+			{
+				if in_loop[block.index()]
+				{
+					continue;
+				}
+				in_loop[block.index()] = true;
+				stack.extend(self.predecessors[block.index()].iter().filter(|predecessor| reachable[predecessor.index()]));
+			}
+
+			loops.push(Loop {
+				header: BlockId(header as u32),
+				blocks: in_loop,
+			});
+		}
+		return loops;
+	}
+}
+
+impl Function
+{
+	// Simplifies the control flow graph until nothing changes. Returns whether the function was changed.
+	// 	- A branch on a constant, or into the same block from both sides, becomes a jump.
+	// 	- Jumps into empty blocks that only jump somewhere else are threaded into their final target.
+	// 	- Jumps into empty blocks that only return are replaced with the return (so a call before the jump can be a tail call).
+	// 	- A block that is jumped into from a single block is merged into it.
+	// 	- Blocks that cannot be reached are removed.
+	pub fn simplify_cfg(&mut self) -> bool
+	{
+		let mut changed = false;
+		loop
+		{
+			let mut round_changed = self.fold_branches();
+			round_changed |= self.thread_jumps();
+			round_changed |= self.duplicate_returns();
+			round_changed |= self.merge_blocks();
+			round_changed |= self.remove_unreachable_blocks();
+			if !round_changed
+			{
+				return changed;
+			}
+			changed = true;
+		}
+	}
+
+	fn fold_branches(&mut self) -> bool
+	{
+		let mut changed = false;
+		for block in &mut self.blocks
+		{
+			match block.terminator
+			{
+				Terminator::Branch { condition: Operand::Integer(value), then_block, else_block } =>
+				{
+					block.terminator = Terminator::Jump(if value != 0 { then_block } else { else_block });
+					changed = true;
+				},
+				Terminator::Branch { then_block, else_block, .. } if then_block == else_block =>
+				{
+					block.terminator = Terminator::Jump(then_block);
+					changed = true;
+				},
+				_ => (),
+			}
+		}
+		return changed;
+	}
+
+	fn thread_jumps(&mut self) -> bool
+	{
+		// The block that each block forwards to, if it is empty and ends with a jump
+		let forwards: Vec<Option<BlockId>> = self.blocks.iter().map(|block| {
+			return match block.terminator
+			{
+				Terminator::Jump(target) if block.instructions.is_empty() 	=> Some(target),
+				_ 															=> None,
+			};
+		}).collect();
+
+		// Follows a chain of empty blocks. An endless loop of empty blocks is left as it is.
+		let final_target = |block: BlockId| -> BlockId {
+			let mut target = block;
+			for _ in 0..forwards.len()
+			{
+				match forwards[target.index()]
+				{
+					Some(next) if next != target 	=> target = next,
+					_ 								=> return target,
+				}
+			}
+			return block;
+		};
+
+		let mut changed = false;
+		for block in &mut self.blocks
+		{
+			match &mut block.terminator
+			{
+				Terminator::Jump(target) =>
+				{
+					let threaded = final_target(*target);
+					changed |= threaded != *target;
+					*target = threaded;
+				},
+				Terminator::Branch { then_block, else_block, .. } =>
+				{
+					let (then_threaded, else_threaded) = (final_target(*then_block), final_target(*else_block));
+					changed |= then_threaded != *then_block || else_threaded != *else_block;
+					*then_block = then_threaded;
+					*else_block = else_threaded;
+				},
+				Terminator::Return(_) => (),
+			}
+		}
+		return changed;
+	}
+
+	fn duplicate_returns(&mut self) -> bool
+	{
+		let mut changed = false;
+		for i in 0..self.blocks.len()
+		{
+			let Terminator::Jump(target) = self.blocks[i].terminator else
+			{
+				continue;
+			};
+
+			let target = &self.blocks[target.index()];
+			if let (true, Terminator::Return(value)) = (target.instructions.is_empty(), target.terminator)
+			{
+				self.blocks[i].terminator = Terminator::Return(value);
+				changed = true;
+			}
+		}
+		return changed;
+	}
+
+	fn merge_blocks(&mut self) -> bool
+	{
+		let mut changed = false;
+		let mut cfg = Cfg::new(self);
+		for i in 0..self.blocks.len()
+		{
+			let Terminator::Jump(target) = self.blocks[i].terminator else
+			{
+				continue;
+			};
+
+			// The entry block has an implicit predecessor, so it is never merged into another block
+			if target.index() == i || target.index() == 0 || cfg.predecessors[target.index()].len() != 1
+			{
+				continue;
+			}
+
+			// The merged block is left unreachable, and is removed later
+			let instructions = std::mem::take(&mut self.blocks[target.index()].instructions);
+			let terminator = self.blocks[target.index()].terminator;
+			self.blocks[target.index()].terminator = Terminator::Return(None);
+			self.blocks[i].instructions.extend(instructions);
+			self.blocks[i].terminator = terminator;
+			cfg = Cfg::new(self);
+			changed = true;
+		}
+		return changed;
+	}
+
+	fn remove_unreachable_blocks(&mut self) -> bool
+	{
+		let reachable = Cfg::new(self).reachable();
+		if reachable.iter().all(|reachable| *reachable)
+		{
+			return false;
+		}
+
+		// The remaining blocks keep their order, so the code generator can still fall through into the next block
+		let mut new_index: Vec<BlockId> = Vec::with_capacity(self.blocks.len());
+		let mut count = 0;
+		for reachable in &reachable
+		{
+			new_index.push(BlockId(count));
+			count += *reachable as u32;
+		}
+
+		let blocks = std::mem::take(&mut self.blocks);
+		self.blocks = blocks.into_iter().zip(&reachable).filter(|(_, reachable)| **reachable).map(|(mut block, _)| {
+			match &mut block.terminator
+			{
+				Terminator::Jump(target) => *target = new_index[target.index()],
+				Terminator::Branch { then_block, else_block, .. } =>
+				{
+					*then_block = new_index[then_block.index()];
+					*else_block = new_index[else_block.index()];
+				},
+				Terminator::Return(_) => (),
+			}
+			return block;
+		}).collect();
+		return true;
+	}
+}
@@ -0,0 +1,209 @@
+use std::fmt::{Display, Formatter, Result, Write};
+use super::*;
+
+// The textual form of the MIR, printed with --emit=ir. For example:
+//
+// func global main() -> i32
+// {
+// 	$0 i32 x
+// bb0:
+// 	store i32 [$0], 5
+// 	%0 = load i32 [$0]
+// 	%1 = add i32 %0, 1
+// 	return i32 %1
+// }
+impl Display for Program
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result
+	{
+		for (i, function) in self.functions.iter().enumerate()
+		{
+			if i != 0
+			{
+				writeln!(f)?;
+			}
+			self.write_function(f, function)?;
+		}
+		return Ok(());
+	}
+}
+
+impl Program
+{
+	pub fn write_function(&self, f: &mut dyn Write, function: &Function) -> Result
+	{
+		let signature = &self.function_types[function.signature.index()];
+		if function.is_extern()
+		{
+			return writeln!(f, "extern {}: {signature}", function.identifier);
+		}
+
+		write!(f, "func ")?;
+		if function.attributes & attribute::GLOBAL != 0
+		{
+			write!(f, "global ")?;
+		}
+		if function.attributes & attribute::INLINE != 0
+		{
+			write!(f, "inline ")?;
+		}
+		if function.attributes & attribute::TAIL_RECURSIVE != 0
+		{
+			write!(f, "tailrec ")?;
+		}
+		let parameters: Vec<String> = function.parameters.iter().map(|register| format!("%{} {}", register.0, function.register_type(*register))).collect();
+		writeln!(f, "{}({}) -> {}", function.identifier, parameters.join(", "), function.return_type)?;
+		writeln!(f, "{{")?;
+
+		for (i, slot) in function.slots.iter().enumerate()
+		{
+			writeln!(f, "\t${i} {} {}", slot.data_type, slot.name)?;
+		}
+
+		for (i, block) in function.blocks.iter().enumerate()
+		{
+			writeln!(f, "bb{i}:")?;
+			for instruction in &block.instructions
+			{
+				writeln!(f, "\t{}", self.instruction_text(function, instruction))?;
+			}
+			writeln!(f, "\t{}", Self::terminator_text(function, &block.terminator))?;
+		}
+		return writeln!(f, "}}");
+	}
+
+	pub fn instruction_text(&self, function: &Function, instruction: &Instruction) -> String
+	{
+		let destination = instruction.destination().map(|register| (register, function.register_type(register)));
+		let text = match instruction
+		{
+			Instruction::Copy { source, .. } =>
+			{
+				let data_type = destination.unwrap().1;
+				format!("copy {data_type} {}", Self::operand_text(*source, data_type))
+			},
+			Instruction::Binary { operator, lhs, rhs, .. } =>
+			{
+				let data_type = destination.unwrap().1;
+				format!("{} {data_type} {}, {}", operator.name(), Self::operand_text(*lhs, data_type), Self::operand_text(*rhs, data_type))
+			},
+			Instruction::Unary { operator, source, .. } =>
+			{
+				let data_type = destination.unwrap().1;
+				format!("{} {data_type} {}", operator.name(), Self::operand_text(*source, data_type))
+			},
+			Instruction::Compare { condition, data_type, lhs, rhs, .. } =>
+			{
+				format!("cmp {} {data_type} {}, {}", condition.name(), Self::operand_text(*lhs, *data_type), Self::operand_text(*rhs, *data_type))
+			},
+			Instruction::Convert { source, from, .. } => format!("convert {from} {} to {}", Self::operand_text(*source, *from), destination.unwrap().1),
+			Instruction::Load { address, .. } => format!("load {} {}", destination.unwrap().1, Self::address_text(address)),
+			Instruction::Store { data_type, address, value } =>
+			{
+				format!("store {data_type} {}, {}", Self::address_text(address), Self::operand_text(*value, *data_type))
+			},
+			Instruction::SlotAddress { slot, .. } 			=> format!("address ${}", slot.0),
+			Instruction::FunctionAddress { function, .. } 	=> format!("function_address {}", self.functions[function.index()].identifier),
+			Instruction::Call { target, signature, arguments, argument_types, .. } =>
+			{
+				let callee = match target
+				{
+					CallTarget::Direct(function) 	=> self.functions[function.index()].identifier.clone(),
+					CallTarget::Indirect(pointer) 	=> Self::operand_text(*pointer, Type::new(TypeKind::U64)),
+				};
+				let arguments: Vec<String> = arguments.iter().zip(argument_types).map(|(argument, data_type)| {
+					return format!("{data_type} {}", Self::operand_text(*argument, *data_type));
+				}).collect();
+				format!("call {} {callee}({})", self.function_types[signature.index()].return_type, arguments.join(", "))
+			},
+		};
+
+		if let Some((register, _)) = destination
+		{
+			return format!("%{} = {text}", register.0);
+		}
+		return text;
+	}
+
+	fn terminator_text(function: &Function, terminator: &Terminator) -> String
+	{
+		return match terminator
+		{
+			Terminator::Jump(target) => format!("jump bb{}", target.0),
+			Terminator::Branch { condition, then_block, else_block } =>
+			{
+				format!("branch {}, bb{}, bb{}", Self::operand_text(*condition, Type::new(TypeKind::U64)), then_block.0, else_block.0)
+			},
+			Terminator::Return(Some(value)) => format!("return {} {}", function.return_type, Self::operand_text(*value, function.return_type)),
+			Terminator::Return(None) 		=> String::from("return"),
+		};
+	}
+
+	// Integer constants are printed according to the signedness of their data type, so an i8 0xFF is printed as -1
+	fn operand_text(operand: Operand, data_type: Type) -> String
+	{
+		return match operand
+		{
+			Operand::Register(register) 	=> format!("%{}", register.0),
+			Operand::Integer(bits) 			=> integer_value(bits, data_type).to_string(),
+			Operand::Float(number) 			=> format!("{:?}", number),
+		};
+	}
+
+	fn address_text(address: &Address) -> String
+	{
+		return match address
+		{
+			Address::Slot(slot) 			=> format!("[${}]", slot.0),
+			Address::Register(register) 	=> format!("[%{}]", register.0),
+		};
+	}
+}
+
+impl BinaryOperator
+{
+	fn name(&self) -> &'static str
+	{
+		return match self
+		{
+			Self::Add 			=> "add",
+			Self::Sub 			=> "sub",
+			Self::Mul 			=> "mul",
+			Self::Div 			=> "div",
+			Self::Modulo 		=> "mod",
+			Self::And 			=> "and",
+			Self::Or 			=> "or",
+			Self::Xor 			=> "xor",
+			Self::ShiftLeft 	=> "shl",
+			Self::ShiftRight 	=> "shr",
+		};
+	}
+}
+
+impl UnaryOperator
+{
+	fn name(&self) -> &'static str
+	{
+		return match self
+		{
+			Self::Not 		=> "not",
+			Self::IsZero 	=> "is_zero",
+		};
+	}
+}
+
+impl Condition
+{
+	fn name(&self) -> &'static str
+	{
+		return match self
+		{
+			Self::Equal 		=> "eq",
+			Self::NotEqual 		=> "ne",
+			Self::Greater 		=> "gt",
+			Self::Less 			=> "lt",
+			Self::GreaterEqual 	=> "ge",
+			Self::LessEqual 	=> "le",
+		};
+	}
+}
@@ -0,0 +1,181 @@
+use super::*;
+
+// The amount of rounds of inlining. Each round inlines the calls that were in the functions before it started, so the calls that come
+// from an inlined body are inlined in the next round. This limits how deep recursive functions are inlined into their callers.
+const INLINE_DEPTH_LIMIT: usize = 4;
+
+// At -O3, functions whose size (instructions and terminators) is at most this are inlined even without the inline attribute
+const INLINE_SIZE_LIMIT: usize = 16;
+
+// Functions are not inlined (unless they have the inline attribute) into callers that have grown larger than this
+const CALLER_SIZE_LIMIT: usize = 2000;
+
+impl Program
+{
+	// Substitutes the bodies of functions into their callers. Functions with the inline attribute are inlined at -O1 and above,
+	// and at -O3 small functions are inlined too. A function is never inlined into itself, so a recursive function is inlined
+	// into its callers at most INLINE_DEPTH_LIMIT times. Returns which functions were changed.
+	pub fn inline_functions(&mut self, level: u8) -> Vec<bool>
+	{
+		let mut changed = vec![false; self.functions.len()];
+		for _ in 0..INLINE_DEPTH_LIMIT
+		{
+			let mut round_changed = false;
+			for (i, function_changed) in changed.iter_mut().enumerate()
+			{
+				if self.functions[i].is_extern()
+				{
+					continue;
+				}
+
+				// The caller is taken out of the program while its calls are replaced, and a call of the caller itself sees an external function
+				let placeholder = Function::new(String::new(), attribute::EXTERN, self.functions[i].signature, self.functions[i].return_type);
+				let mut caller = std::mem::replace(&mut self.functions[i], placeholder);
+				if self.inline_calls(&mut caller, level)
+				{
+					*function_changed = true;
+					round_changed = true;
+				}
+				self.functions[i] = caller;
+			}
+
+			if !round_changed
+			{
+				break;
+			}
+		}
+		return changed;
+	}
+
+	// Inlines the calls in the caller that should be inlined, except the ones that come from the bodies that are inlined now
+	fn inline_calls(&self, caller: &mut Function, level: u8) -> bool
+	{
+		let mut changed = false;
+		let mut block = 0;
+		while block < caller.blocks.len()
+		{
+			let call = caller.blocks[block].instructions.iter().position(|instruction| {
+				let Instruction::Call { target: CallTarget::Direct(callee), .. } = instruction else { return false; };
+				return self.should_inline(&self.functions[callee.index()], caller, level);
+			});
+
+			let Some(call) = call else
+			{
+				block += 1;
+				continue;
+			};
+
+			// The rest of the block is moved into a new block after the inlined body, which is where the search continues
+			let Instruction::Call { target: CallTarget::Direct(callee), .. } = caller.blocks[block].instructions[call] else { unreachable!() };
+			block = caller.inline_call(&self.functions[callee.index()], BlockId(block as u32), call).index();
+			changed = true;
+		}
+		return changed;
+	}
+
+	fn should_inline(&self, callee: &Function, caller: &Function, level: u8) -> bool
+	{
+		if callee.is_extern() || callee.blocks.is_empty()
+		{
+			return false;
+		}
+		if callee.attributes & attribute::INLINE != 0
+		{
+			return true;
+		}
+		return level >= OPTIMIZATION_LEVEL_INLINE && callee.size() <= INLINE_SIZE_LIMIT && caller.size() <= CALLER_SIZE_LIMIT;
+	}
+}
+
+impl Function
+{
+	// The amount of instructions and terminators in the function
+	fn size(&self) -> usize
+	{
+		return self.blocks.iter().map(|block| block.instructions.len() + 1).sum();
+	}
+
+	// Replaces the call at the given instruction of the given block with the body of the callee, with its own registers and slots.
+	// 	bb0: 							bb0:
+	// 		%2 = call i32 f(i32 %1) 		%3 = copy i32 %1 			(the parameter of f)
+	// 		return i32 %2 					jump bb1
+	// 									bb1: 							(the blocks of f)
+	// 										%2 = copy i32 %4 			(was return i32 %4)
+	// 										jump bb2
+	// 									bb2:
+	// 										return i32 %2
+	// The inlined blocks are placed right after the block of the call, so the blocks keep their order.
+	// Returns the block with the instructions that were after the call.
+	fn inline_call(&mut self, callee: &Function, block: BlockId, call: usize) -> BlockId
+	{
+		let registers: Vec<RegisterId> = callee.registers.iter().map(|data_type| self.new_register(*data_type)).collect();
+		let slots: Vec<SlotId> = callee.slots.iter().map(|slot| {
+			return self.new_slot(slot.data_type, format!("{}.{}", callee.identifier, slot.name));
+		}).collect();
+
+		// The blocks after the block of the call move forward, to make room for the blocks of the callee and the rest of the block
+		let first_block = block.0 + 1;
+		let continuation = BlockId(first_block + callee.blocks.len() as u32);
+		let moved = callee.blocks.len() as u32 + 1;
+		for other in &mut self.blocks
+		{
+			other.terminator.for_each_target_mut(&mut |target| {
+				if target.0 > block.0
+				{
+					target.0 += moved;
+				}
+			});
+		}
+
+		let caller_block = &mut self.blocks[block.index()];
+		let rest = Block::new(caller_block.instructions.split_off(call + 1), caller_block.terminator);
+		let Some(Instruction::Call { destination, arguments, .. }) = caller_block.instructions.pop() else
+		{
+			panic!("Dev error! inline_call called on an instruction that is not a call.");
+		};
+		for (parameter, argument) in callee.parameters.iter().zip(arguments)
+		{
+			caller_block.instructions.push(Instruction::Copy { destination: registers[parameter.index()], source: argument });
+		}
+		caller_block.terminator = Terminator::Jump(BlockId(first_block));
+
+		let mut inlined = Vec::with_capacity(callee.blocks.len() + 1);
+		for callee_block in &callee.blocks
+		{
+			let mut instructions = callee_block.instructions.clone();
+			for instruction in &mut instructions
+			{
+				instruction.for_each_register_mut(&mut |register| *register = registers[register.index()]);
+				match instruction
+				{
+					Instruction::Load { address: Address::Slot(slot), .. } 	|
+					Instruction::Store { address: Address::Slot(slot), .. } |
+					Instruction::SlotAddress { slot, .. } 					=> *slot = slots[slot.index()],
+					_ 														=> (),
+				}
+			}
+
+			let mut terminator = callee_block.terminator;
+			terminator.for_each_operand_mut(&mut |operand| {
+				if let Operand::Register(register) = operand
+				{
+					*register = registers[register.index()];
+				}
+			});
+			terminator.for_each_target_mut(&mut |target| target.0 += first_block);
+			if let Terminator::Return(value) = terminator
+			{
+				if let (Some(destination), Some(value)) = (destination, value)
+				{
+					instructions.push(Instruction::Copy { destination, source: value });
+				}
+				terminator = Terminator::Jump(continuation);
+			}
+			inlined.push(Block::new(instructions, terminator));
+		}
+		inlined.push(rest);
+
+		self.blocks.splice(first_block as usize..first_block as usize, inlined);
+		return continuation;
+	}
+}
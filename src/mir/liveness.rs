@@ -0,0 +1,68 @@
+use super::*;
+
+// The virtual registers that are live (may be read before they are written again) when each block starts and when it ends
+pub struct Liveness
+{
+	pub live_in: Vec<Vec<bool>>,
+	pub live_out: Vec<Vec<bool>>,
+}
+
+impl Liveness
+{
+	pub fn new(function: &Function, cfg: &Cfg) -> Self
+	{
+		let registers = function.registers.len();
+		let mut live_in = vec![vec![false; registers]; function.blocks.len()];
+		let mut live_out = live_in.clone();
+
+		// The blocks are visited backwards, which usually visits a block after its successors
+		let mut changed = true;
+		while changed
+		{
+			changed = false;
+			for i in (0..function.blocks.len()).rev()
+			{
+				let mut live = vec![false; registers];
+				for successor in &cfg.successors[i]
+				{
+					for (register, is_live) in live_in[successor.index()].iter().enumerate()
+					{
+						live[register] |= *is_live;
+					}
+				}
+				live_out[i] = live.clone();
+
+				Self::update_terminator(&mut live, &function.blocks[i].terminator);
+				for instruction in function.blocks[i].instructions.iter().rev()
+				{
+					Self::update(&mut live, instruction);
+				}
+				if live != live_in[i]
+				{
+					live_in[i] = live;
+					changed = true;
+				}
+			}
+		}
+
+		return Self {
+			live_in,
+			live_out,
+		};
+	}
+
+	// Moves the liveness of the registers from after the instruction to before it
+	pub fn update(live: &mut [bool], instruction: &Instruction)
+	{
+		if let Some(destination) = instruction.destination()
+		{
+			live[destination.index()] = false;
+		}
+		instruction.for_each_used_register(&mut |register| live[register.index()] = true);
+	}
+
+	pub fn update_terminator(live: &mut [bool], terminator: &Terminator)
+	{
+		terminator.for_each_used_register(&mut |register| live[register.index()] = true);
+	}
+}
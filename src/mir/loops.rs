@@ -0,0 +1,433 @@
+use std::collections::HashMap;
+
+use super::{*, cfg::Loop};
+
+// A register that a single instruction in a loop changes by a constant (i = i + 1)
+struct InductionVariable
+{
+	register: RegisterId,
+	update: (usize, usize),		/* The block and the index of the instruction that changes it */
+	step: i128,
+	no_wrap: bool,				/* The loop exits before the register wraps around, so it grows by the step also when extended into a larger integer */
+}
+
+// A register whose value is scale * variable + an invariant value, computed in a single block from an induction variable.
+// The chain is the instructions of the block that compute it, the last one writes the register.
+#[derive(Clone)]
+struct LinearValue
+{
+	variable: usize,			/* The index of the induction variable */
+	scale: i128,
+	chain: Vec<usize>,
+	reducible: bool,			/* A multiplication, a shift or an extension is computed, which an addition can replace */
+}
+
+impl Function
+{
+	// The loop optimizations (at -O2). The loops are optimized from the innermost one, and they are searched again after each change
+	// because a change adds a preheader block. Returns whether the function was changed.
+	pub fn optimize_loops(&mut self) -> bool
+	{
+		let mut changed = false;
+		'search: loop
+		{
+			let mut loops = Cfg::new(self).loops();
+			loops.sort_by_key(|found| found.blocks.iter().filter(|in_loop| **in_loop).count());
+			for found in &loops
+			{
+				if self.hoist_invariants(found) || self.reduce_strength(found)
+				{
+					changed = true;
+					continue 'search;
+				}
+			}
+			return changed;
+		}
+	}
+
+	// Moves the instructions whose operands do not change in the loop into its preheader (loop invariant code motion).
+	// 	bb1: 								bb0: 						(the preheader)
+	// 		%3 = mul i32 %2, 3 					%3 = mul i32 %2, 3
+	// 		%4 = add i32 %4, %3 			bb1:
+	// 		branch %5, bb1, bb2 				%4 = add i32 %4, %3
+	// 											branch %5, bb1, bb2
+	// An instruction is moved if it has no side effects and cannot fault (divisions are not moved, the loop may never run them),
+	// if it is the only instruction in the loop that writes its destination, and if the destination is not live when the loop starts
+	// (so the value that the destination had before the loop is never read).
+	fn hoist_invariants(&mut self, found: &Loop) -> bool
+	{
+		let live_in = Liveness::new(self, &Cfg::new(self)).live_in.swap_remove(found.header.index());
+		let mut assignments = self.loop_assignments(found);
+		let mut hoisted = Vec::new();
+		let mut changed = true;
+		while changed
+		{
+			changed = false;
+			for (_, block) in self.blocks.iter_mut().enumerate().filter(|(i, _)| found.blocks[*i])
+			{
+				let mut i = 0;
+				while i < block.instructions.len()
+				{
+					let instruction = &block.instructions[i];
+					let movable = matches!(
+						instruction,
+						Instruction::Copy { .. } | Instruction::Unary { .. } | Instruction::Compare { .. } | Instruction::Convert { .. } |
+						Instruction::SlotAddress { .. } | Instruction::FunctionAddress { .. }
+					) || matches!(instruction, Instruction::Binary { operator, .. } if !matches!(operator, BinaryOperator::Div | BinaryOperator::Modulo));
+
+					let mut invariant = movable;
+					instruction.for_each_used_register(&mut |register| invariant &= assignments[register.index()] == 0);
+					if invariant
+					{
+						let destination = instruction.destination().unwrap();
+						if assignments[destination.index()] == 1 && !live_in[destination.index()]
+						{
+							assignments[destination.index()] = 0;
+							hoisted.push(block.instructions.remove(i));
+							changed = true;
+							continue;
+						}
+					}
+					i += 1;
+				}
+			}
+		}
+
+		if hoisted.is_empty()
+		{
+			return false;
+		}
+		let preheader = self.preheader(found);
+		self.blocks[preheader].instructions.extend(hoisted);
+		return true;
+	}
+
+	// Replaces a value that is computed from an induction variable with a multiplication (or an extension) by a register that is
+	// incremented together with the induction variable (induction variable strength reduction). One value is replaced in each call.
+	// 	bb1: 								bb0: 						(the preheader)
+	// 		%4 = convert i32 %3 to i64 			%8 = convert i32 %3 to i64
+	// 		%5 = mul i64 %4, 4 					%9 = mul i64 %8, 4
+	// 		%6 = add *i32 %0, %5 				%10 = add *i32 %0, %9
+	// 		%7 = load i32 [%6] 				bb1:
+	// 		%3 = add i32 %3, 1 					%6 = copy *i32 %10
+	// 											%7 = load i32 [%6]
+	// 											%3 = add i32 %3, 1
+	// 											%10 = add *i32 %10, 4
+	// The old computations are no longer read, so they are removed as dead computations.
+	fn reduce_strength(&mut self, found: &Loop) -> bool
+	{
+		let assignments = self.loop_assignments(found);
+		let variables = self.induction_variables(found, &assignments);
+		if variables.is_empty()
+		{
+			return false;
+		}
+
+		for block in (0..self.blocks.len()).filter(|block| found.blocks[*block])
+		{
+			let values = self.linear_values(block, &variables, &assignments);
+
+			// A value that is computed only to compute a larger value is replaced as a part of the larger value
+			let parts: Vec<usize> = values.values().flat_map(|value| &value.chain[..value.chain.len() - 1]).copied().collect();
+			let mut uses = vec![0usize; self.registers.len()];
+			self.for_each_register_use(&mut |register| uses[register.index()] += 1);
+			let mut candidates: Vec<(&RegisterId, &LinearValue)> = values.iter().filter(|(register, value)| {
+				let last = *value.chain.last().unwrap();
+				return value.reducible && uses[register.index()] != 0 && !parts.contains(&last);
+			}).collect();
+			candidates.sort_by_key(|(_, value)| value.chain.last().copied());
+
+			let Some((register, value)) = candidates.first() else
+			{
+				continue;
+			};
+			self.replace_linear_value(found, block, **register, (*value).clone(), &variables[value.variable]);
+			return true;
+		}
+		return false;
+	}
+
+	fn replace_linear_value(&mut self, found: &Loop, block: usize, register: RegisterId, value: LinearValue, variable: &InductionVariable)
+	{
+		let data_type = self.register_type(register);
+		let reduced = self.new_register(data_type);
+
+		// The chain is computed again before the loop, from the value that the induction variable has when the loop starts
+		let mut renamed: HashMap<RegisterId, RegisterId> = HashMap::new();
+		let mut initial = Vec::with_capacity(value.chain.len());
+		for (i, index) in value.chain.iter().enumerate()
+		{
+			let mut instruction = self.blocks[block].instructions[*index].clone();
+			// The chain has only copies, arithmetic and conversions, which have no addresses
+			instruction.for_each_value_operand_mut(&mut |operand| {
+				if let Operand::Register(used) = operand
+				{
+					*used = renamed.get(used).copied().unwrap_or(*used);
+				}
+			});
+			let original = instruction.destination().unwrap();
+			let destination = if i + 1 == value.chain.len() { reduced } else { self.new_register(self.register_type(original)) };
+			*instruction.destination_mut().unwrap() = destination;
+			renamed.insert(original, destination);
+			initial.push(instruction);
+		}
+
+		let last = *value.chain.last().unwrap();
+		self.blocks[block].instructions[last] = Instruction::Copy { destination: register, source: Operand::Register(reduced) };
+
+		let (update_block, update_index) = variable.update;
+		let increment = Operand::Integer(integer_bits(variable.step.wrapping_mul(value.scale), data_type));
+		self.blocks[update_block].instructions.insert(update_index + 1, Instruction::Binary {
+			operator: BinaryOperator::Add,
+			destination: reduced,
+			lhs: Operand::Register(reduced),
+			rhs: increment,
+		});
+
+		let preheader = self.preheader(found);
+		self.blocks[preheader].instructions.extend(initial);
+	}
+
+	// The induction variables of the loop. An induction variable that is compared with an invariant value by the header
+	// (and the loop exits when the comparison fails), and that grows by 1 while it is less than that value (or shrinks by 1 while
+	// it is greater), never wraps around.
+	fn induction_variables(&self, found: &Loop, assignments: &[usize]) -> Vec<InductionVariable>
+	{
+		let mut variables = Vec::new();
+		for block in (0..self.blocks.len()).filter(|block| found.blocks[*block])
+		{
+			for (index, instruction) in self.blocks[block].instructions.iter().enumerate()
+			{
+				let Instruction::Binary { operator, destination, lhs, rhs } = *instruction else
+				{
+					continue;
+				};
+				let data_type = self.register_type(destination);
+				if assignments[destination.index()] != 1 || !data_type.is_integer() || data_type.is_pointer()
+				{
+					continue;
+				}
+
+				let step = match (operator, lhs, rhs)
+				{
+					(BinaryOperator::Add, Operand::Register(register), Operand::Integer(bits)) |
+					(BinaryOperator::Add, Operand::Integer(bits), Operand::Register(register)) if register == destination => integer_value(bits, data_type),
+					(BinaryOperator::Sub, Operand::Register(register), Operand::Integer(bits)) if register == destination => -integer_value(bits, data_type),
+					_ => continue,
+				};
+
+				variables.push(InductionVariable {
+					register: destination,
+					update: (block, index),
+					step,
+					no_wrap: block != found.header.index() && !self.repeats_in_iteration(found, block) && self.exits_before_wrapping(found, destination, step, assignments),
+				});
+			}
+		}
+		return variables;
+	}
+
+	fn exits_before_wrapping(&self, found: &Loop, register: RegisterId, step: i128, assignments: &[usize]) -> bool
+	{
+		let header = &self.blocks[found.header.index()];
+		let Terminator::Branch { condition: Operand::Register(condition), then_block, else_block } = header.terminator else
+		{
+			return false;
+		};
+		if !found.blocks[then_block.index()] || found.blocks[else_block.index()]
+		{
+			return false;
+		}
+
+		let comparison = header.instructions.iter().rev().find(|instruction| instruction.destination() == Some(condition));
+		let Some(Instruction::Compare { condition, data_type, lhs: Operand::Register(compared), rhs, .. }) = comparison else
+		{
+			return false;
+		};
+		let is_invariant = match rhs
+		{
+			Operand::Register(bound) 	=> assignments[bound.index()] == 0,
+			_ 							=> true,
+		};
+		return *compared == register && *data_type == self.register_type(register) && is_invariant
+			&& ((*condition == Condition::Less && step == 1) || (*condition == Condition::Greater && step == -1));
+	}
+
+	// Whether the block can run more than once before the loop goes back to its header (it is in an inner loop)
+	fn repeats_in_iteration(&self, found: &Loop, block: usize) -> bool
+	{
+		let mut visited = vec![false; self.blocks.len()];
+		let mut stack: Vec<BlockId> = self.blocks[block].terminator.successors();
+		while let Some(successor) = stack.pop()
+		{
+			if successor.index() == block
+			{
+				return true;
+			}
+			if successor == found.header || !found.blocks[successor.index()] || visited[successor.index()]
+			{
+				continue;
+			}
+			visited[successor.index()] = true;
+			stack.extend(self.blocks[successor.index()].terminator.successors());
+		}
+		return false;
+	}
+
+	// The registers of the block that are linear values of the induction variables, and that are written once in the loop
+	fn linear_values(&self, block: usize, variables: &[InductionVariable], assignments: &[usize]) -> HashMap<RegisterId, LinearValue>
+	{
+		let mut values: HashMap<RegisterId, LinearValue> = HashMap::new();
+		let mut finished: HashMap<RegisterId, LinearValue> = HashMap::new();
+		for (index, instruction) in self.blocks[block].instructions.iter().enumerate()
+		{
+			// The values that were computed before the induction variable changed can still be replaced,
+			// but the values that are computed from them after the change are not the same linear values
+			if let Some(variable) = variables.iter().position(|variable| variable.update == (block, index))
+			{
+				finished.extend(values.extract_if(|_, value| value.variable == variable));
+				continue;
+			}
+
+			let Some(destination) = instruction.destination() else
+			{
+				continue;
+			};
+			let data_type = self.register_type(destination);
+			if assignments[destination.index()] != 1 || !data_type.is_integer()
+			{
+				continue;
+			}
+
+			// An operand is None if it is neither linear nor invariant, and Some(None) if it is invariant
+			let linear = |operand: Operand| -> Option<Option<LinearValue>> {
+				let Operand::Register(register) = operand else
+				{
+					return if let Operand::Integer(_) = operand { Some(None) } else { None };
+				};
+				if let Some(variable) = variables.iter().position(|variable| variable.register == register)
+				{
+					return Some(Some(LinearValue { variable, scale: 1, chain: Vec::new(), reducible: false }));
+				}
+				if let Some(value) = values.get(&register)
+				{
+					return Some(Some(value.clone()));
+				}
+				return if assignments[register.index()] == 0 { Some(None) } else { None };
+			};
+
+			let value = match *instruction
+			{
+				Instruction::Copy { source, .. } => linear(source).flatten(),
+				Instruction::Binary { operator, lhs, rhs, .. } => match (operator, linear(lhs), linear(rhs))
+				{
+					(BinaryOperator::Add, Some(Some(value)), Some(None)) 	|
+					(BinaryOperator::Add, Some(None), Some(Some(value))) 	|
+					(BinaryOperator::Sub, Some(Some(value)), Some(None)) 	=> Some(value),
+					(BinaryOperator::Sub, Some(None), Some(Some(value))) 	=> Some(LinearValue { scale: value.scale.wrapping_neg(), ..value }),
+					(BinaryOperator::Mul, Some(Some(value)), Some(None)) 	|
+					(BinaryOperator::Mul, Some(None), Some(Some(value))) =>
+					{
+						let factor = if let (Operand::Integer(bits), _) | (_, Operand::Integer(bits)) = (lhs, rhs) { Some(integer_value(bits, data_type)) } else { None };
+						factor.map(|factor| LinearValue { scale: value.scale.wrapping_mul(factor), reducible: true, ..value })
+					},
+					(BinaryOperator::ShiftLeft, Some(Some(value)), Some(None)) =>
+					{
+						let count = if let Operand::Integer(bits) = rhs { Some(bits as u32 & if data_type.size() == 8 { 63 } else { 31 }) } else { None };
+						count.map(|count| LinearValue { scale: value.scale.wrapping_mul(1 << count), reducible: true, ..value })
+					},
+					_ => None,
+				},
+
+				// Extending a value into a larger integer keeps it linear only if it does not wrap around, which is known only for induction variables
+				Instruction::Convert { source, from, .. } if from.is_integer() => match linear(source).flatten()
+				{
+					Some(value) if data_type.size() > from.size() =>
+					{
+						let is_variable = value.chain.is_empty() && value.scale == 1;
+						if is_variable && variables[value.variable].no_wrap { Some(LinearValue { reducible: true, ..value }) } else { None }
+					},
+					value => value,
+				},
+				_ => None,
+			};
+
+			match value
+			{
+				Some(mut value) =>
+				{
+					value.chain.push(index);
+					values.insert(destination, value);
+				},
+				None => { values.remove(&destination); },
+			}
+		}
+		finished.extend(values);
+		return finished;
+	}
+
+	// The block that jumps into the header of the loop from outside of it. A new block is added before the header if there is no such block.
+	// Returns the index of the preheader.
+	fn preheader(&mut self, found: &Loop) -> usize
+	{
+		let header = found.header;
+		let outside: Vec<BlockId> = Cfg::new(self).predecessors[header.index()].iter().copied().filter(|block| !found.blocks[block.index()]).collect();
+		if let [predecessor] = outside[..]
+		{
+			if header.0 != 0 && self.blocks[predecessor.index()].terminator == Terminator::Jump(header)
+			{
+				return predecessor.index();
+			}
+		}
+
+		// The blocks from the header on move forward, and the jumps from outside of the loop go into the new block instead of the header
+		for block in &mut self.blocks
+		{
+			block.terminator.for_each_target_mut(&mut |target| {
+				if target.0 >= header.0
+				{
+					target.0 += 1;
+				}
+			});
+		}
+		let moved_header = BlockId(header.0 + 1);
+		for predecessor in outside
+		{
+			let predecessor = if predecessor.0 >= header.0 { predecessor.0 + 1 } else { predecessor.0 };
+			self.blocks[predecessor as usize].terminator.for_each_target_mut(&mut |target| {
+				if *target == moved_header
+				{
+					*target = header;
+				}
+			});
+		}
+		self.blocks.insert(header.index(), Block::new(Vec::new(), Terminator::Jump(moved_header)));
+		return header.index();
+	}
+
+	// The amount of instructions in the loop that write each register
+	fn loop_assignments(&self, found: &Loop) -> Vec<usize>
+	{
+		let mut assignments = vec![0; self.registers.len()];
+		for block in self.blocks.iter().enumerate().filter(|(i, _)| found.blocks[*i]).map(|(_, block)| block)
+		{
+			for destination in block.instructions.iter().filter_map(|instruction| instruction.destination())
+			{
+				assignments[destination.index()] += 1;
+			}
+		}
+		return assignments;
+	}
+
+	fn for_each_register_use(&self, on_register: &mut dyn FnMut(RegisterId))
+	{
+		for block in &self.blocks
+		{
+			for instruction in &block.instructions
+			{
+				instruction.for_each_used_register(on_register);
+			}
+			block.terminator.for_each_used_register(on_register);
+		}
+	}
+}
@@ -0,0 +1,458 @@
+use crate::ast::{self, *};
+use super::{*, Function};
+
+// Lowers the body of a single function. Blocks are collected with an optional terminator, which is set when the block ends.
+struct FunctionLowering<'a>
+{
+	root: &'a Root,
+	function: Function,
+	slots: Vec<Option<SlotId>>,				/* The stack slot of each variable of the AST function, indexed by VariableId */
+	blocks: Vec<(Vec<Instruction>, Option<Terminator>)>,
+	current_block: BlockId,
+}
+
+impl Program
+{
+	// Lowers the AST (after the semantic analysis) into the MIR
+	pub fn lower(root: &Root) -> Self
+	{
+		let functions = root.functions.iter().map(|function| FunctionLowering::lower(root, function)).collect();
+		let program = Self {
+			functions,
+			function_types: root.function_types.clone(),
+		};
+
+		if cfg!(debug_assertions)
+		{
+			program.verify();
+		}
+		return program;
+	}
+}
+
+impl<'a> FunctionLowering<'a>
+{
+	fn lower(root: &'a Root, ast_function: &ast::Function) -> Function
+	{
+		let function = Function::new(ast_function.identifier.clone(), ast_function.attributes, ast_function.signature, ast_function.return_type);
+		if function.is_extern()
+		{
+			return function;
+		}
+
+		let mut lowering = Self {
+			root,
+			function,
+			slots: vec![None; ast_function.locals.len()],
+			blocks: Vec::new(),
+			current_block: BlockId(0),
+		};
+		lowering.new_block();
+
+		// The parameters arrive in virtual registers, and are stored into their variables like any other assignment
+		for parameter in &ast_function.parameters
+		{
+			let register = lowering.function.new_register(parameter.data_type);
+			lowering.function.parameters.push(register);
+			let slot = lowering.variable_slot(parameter.index, parameter.data_type, &parameter.identifier);
+			lowering.emit(Instruction::Store { data_type: parameter.data_type, address: Address::Slot(slot), value: Operand::Register(register) });
+		}
+
+		for statement in &ast_function.code_block.statements
+		{
+			lowering.lower_statement(statement);
+		}
+
+		// Reaching the end of a function that returns a value is undefined, 0 is returned to keep the generated code simple
+		let return_value = match ast_function.return_type
+		{
+			data_type if data_type.kind == TypeKind::Void 	=> None,
+			data_type if data_type.is_integer() 			=> Some(Operand::Integer(0)),
+			_ 												=> Some(Operand::Float(0.0)),
+		};
+		lowering.terminate(Terminator::Return(return_value));
+
+		let mut function = lowering.function;
+		function.blocks = lowering.blocks.into_iter().map(|(instructions, terminator)| {
+			return Block::new(instructions, terminator.expect("Dev error! A MIR block was not terminated."));
+		}).collect();
+		return function;
+	}
+
+	fn lower_statement(&mut self, statement: &Statement)
+	{
+		match statement
+		{
+			Statement::Scope(scope) =>
+			{
+				for statement in &scope.statements
+				{
+					self.lower_statement(statement);
+				}
+			},
+			Statement::VarDecl(var_decl) 			=> self.lower_var_decl(var_decl),
+			Statement::Assign(assign) 				=> self.lower_assign(assign),
+			Statement::FunctionCall(function_call) 	=> { self.lower_function_call(function_call); },
+			Statement::Return(return_info) 			=> self.lower_return(return_info),
+			Statement::If(if_info) 					=> self.lower_if(if_info),
+			Statement::For(for_info) 				=> self.lower_for(for_info),
+		}
+	}
+
+	fn lower_var_decl(&mut self, var_decl: &VarDeclInfo)
+	{
+		// Constants were replaced by their values in the semantic analysis
+		if var_decl.attributes & attribute::CONSTANT != 0
+		{
+			return;
+		}
+
+		let slot = self.variable_slot(var_decl.index, var_decl.data_type, &var_decl.identifier);
+		if let Some(value) = &var_decl.value
+		{
+			let value = self.lower_expression(&value.root);
+			self.emit(Instruction::Store { data_type: var_decl.data_type, address: Address::Slot(slot), value });
+		}
+	}
+
+	// The value is evaluated before the destination
+	fn lower_assign(&mut self, assign: &VarUpdateInfo)
+	{
+		let value = self.lower_expression(&assign.value.root);
+		let address = self.lower_address(&assign.destination);
+		self.emit(Instruction::Store { data_type: assign.destination.data_type, address, value });
+	}
+
+	fn lower_return(&mut self, return_info: &ReturnInfo)
+	{
+		let value = return_info.value.as_ref().map(|value| self.lower_expression(&value.root));
+		self.terminate(Terminator::Return(value));
+
+		// The statements after a return are unreachable, they are lowered into a block that nothing jumps to
+		let block = self.new_block();
+		self.start_block(block);
+	}
+
+	fn lower_if(&mut self, if_info: &IfInfo)
+	{
+		let condition = self.lower_condition(&if_info.condition.root);
+		let then_block = self.new_block();
+		let end_block = self.new_block();
+		let else_block = if if_info.else_block.is_some() { self.new_block() } else { end_block };
+		self.terminate(Terminator::Branch { condition, then_block, else_block });
+
+		self.start_block(then_block);
+		self.lower_statement(&if_info.then_block);
+		self.terminate(Terminator::Jump(end_block));
+
+		if let Some(statement) = &if_info.else_block
+		{
+			self.start_block(else_block);
+			self.lower_statement(statement);
+			self.terminate(Terminator::Jump(end_block));
+		}
+		self.start_block(end_block);
+	}
+
+	fn lower_for(&mut self, for_info: &ForLoopInfo)
+	{
+		if let Some(initializer) = &for_info.initializer
+		{
+			self.lower_statement(initializer);
+		}
+
+		let condition_block = self.new_block();
+		let body_block = self.new_block();
+		let end_block = self.new_block();
+		self.terminate(Terminator::Jump(condition_block));
+
+		// There is no break statement, so a loop without a condition never ends
+		self.start_block(condition_block);
+		if let Some(condition) = &for_info.condition
+		{
+			let condition = self.lower_condition(&condition.root);
+			self.terminate(Terminator::Branch { condition, then_block: body_block, else_block: end_block });
+		} else
+		{
+			self.terminate(Terminator::Jump(body_block));
+		}
+
+		self.start_block(body_block);
+		self.lower_statement(&for_info.code_block);
+		if let Some(update) = &for_info.update
+		{
+			self.lower_statement(update);
+		}
+		self.terminate(Terminator::Jump(condition_block));
+
+		self.start_block(end_block);
+	}
+
+	// A condition is true if it is not 0. Floats are compared with 0 so the condition is always an integer.
+	fn lower_condition(&mut self, condition: &BinExprPart) -> Operand
+	{
+		let value = self.lower_expression(condition);
+		if condition.data_type.is_integer()
+		{
+			return value;
+		}
+		return self.lower_is_true(value, condition.data_type);
+	}
+
+	// An u8 that is 1 if the float is not 0
+	fn lower_is_true(&mut self, value: Operand, data_type: Type) -> Operand
+	{
+		let destination = self.function.new_register(Type::new(TypeKind::U8));
+		self.emit(Instruction::Compare { condition: Condition::NotEqual, data_type, destination, lhs: value, rhs: Operand::Float(0.0) });
+		return Operand::Register(destination);
+	}
+
+	fn lower_expression(&mut self, part: &BinExprPart) -> Operand
+	{
+		match &part.kind
+		{
+			BinExprPartKind::Val(value) 				=> return self.lower_value(value, part.data_type),
+			BinExprPartKind::SelfOperation(operation) 	=> return self.lower_self_operation(operation, part.data_type),
+			BinExprPartKind::Operation(operation) 		=> return self.lower_operation(operation, part.data_type),
+			BinExprPartKind::TypeCast(info) =>
+			{
+				let source = self.lower_expression(&info.expression);
+				let destination = self.function.new_register(info.into_type);
+				self.emit(Instruction::Convert { destination, source, from: info.expression.data_type });
+				return Operand::Register(destination);
+			},
+		}
+	}
+
+	fn lower_value(&mut self, value: &Value, data_type: Type) -> Operand
+	{
+		let destination;
+		match value
+		{
+			Value::I8(number) 	=> return Operand::Integer(*number as u8 as u64),
+			Value::U8(number) 	=> return Operand::Integer(*number as u64),
+			Value::I16(number) 	=> return Operand::Integer(*number as u16 as u64),
+			Value::U16(number) 	=> return Operand::Integer(*number as u64),
+			Value::I32(number) 	=> return Operand::Integer(*number as u32 as u64),
+			Value::U32(number) 	=> return Operand::Integer(*number as u64),
+			Value::I64(number) 	=> return Operand::Integer(*number as u64),
+			Value::U64(number) 	=> return Operand::Integer(*number),
+			Value::F32(number) 	=> return Operand::Float(*number as f64),
+			Value::F64(number) 	=> return Operand::Float(*number),
+			Value::Var(_) | Value::Dereference(_) =>
+			{
+				let address = self.lower_value_address(value);
+				destination = self.function.new_register(data_type);
+				self.emit(Instruction::Load { destination, address });
+			},
+			Value::FuncCall(function_call) => return Operand::Register(self.lower_function_call(function_call).unwrap()),
+			Value::FuncAddress(function) =>
+			{
+				destination = self.function.new_register(data_type);
+				self.emit(Instruction::FunctionAddress { destination, function: *function });
+			},
+			_ => panic!("Dev error! lower_value() called with a value that was not resolved by the semantic analysis. {:#?}", value),
+		}
+		return Operand::Register(destination);
+	}
+
+	fn lower_self_operation(&mut self, operation: &BinExprSelfOperation, data_type: Type) -> Operand
+	{
+		if operation.operator == BinExprOperator::AddressOf
+		{
+			return match self.lower_address(&operation.expression)
+			{
+				Address::Slot(slot) =>
+				{
+					let destination = self.function.new_register(data_type);
+					self.emit(Instruction::SlotAddress { destination, slot });
+					Operand::Register(destination)
+				},
+				Address::Register(pointer) => Operand::Register(pointer),
+			};
+		}
+
+		let source = self.lower_expression(&operation.expression);
+		let destination = self.function.new_register(data_type);
+		match operation.operator
+		{
+			BinExprOperator::BitwiseNot => self.emit(Instruction::Unary { operator: UnaryOperator::Not, destination, source }),
+			BinExprOperator::BoolNot if data_type.is_integer() =>
+			{
+				self.emit(Instruction::Unary { operator: UnaryOperator::IsZero, destination, source });
+			},
+			BinExprOperator::BoolNot =>
+			{
+				let is_zero = self.function.new_register(Type::new(TypeKind::U8));
+				self.emit(Instruction::Compare { condition: Condition::Equal, data_type, destination: is_zero, lhs: source, rhs: Operand::Float(0.0) });
+				self.emit(Instruction::Convert { destination, source: Operand::Register(is_zero), from: Type::new(TypeKind::U8) });
+			},
+			_ => panic!("Dev error! lower_self_operation() called with a two-side operator. {:#?}", operation),
+		}
+		return Operand::Register(destination);
+	}
+
+	fn lower_operation(&mut self, operation: &BinExprOperation, data_type: Type) -> Operand
+	{
+		let operand_type = operation.lhs.data_type;
+		let mut lhs = self.lower_expression(&operation.lhs);
+		let mut rhs = self.lower_expression(&operation.rhs);
+
+		if let Some(condition) = Self::condition(operation.operator)
+		{
+			let destination = self.function.new_register(Type::new(TypeKind::U8));
+			self.emit(Instruction::Compare { condition, data_type: operand_type, destination, lhs, rhs });
+			return Operand::Register(destination);
+		}
+
+		// && and || on floats are done on whether each side is true, and the result is converted back into the float
+		let is_boolean = operation.operator == BinExprOperator::BoolAnd || operation.operator == BinExprOperator::BoolOr;
+		let mut destination_type = data_type;
+		if is_boolean && !data_type.is_integer()
+		{
+			lhs = self.lower_is_true(lhs, operand_type);
+			rhs = self.lower_is_true(rhs, operand_type);
+			destination_type = Type::new(TypeKind::U8);
+		}
+
+		let operator = match operation.operator
+		{
+			BinExprOperator::Add 								=> BinaryOperator::Add,
+			BinExprOperator::Sub 								=> BinaryOperator::Sub,
+			BinExprOperator::Mul 								=> BinaryOperator::Mul,
+			BinExprOperator::Div 								=> BinaryOperator::Div,
+			BinExprOperator::Modulo 							=> BinaryOperator::Modulo,
+			BinExprOperator::BitwiseAnd | BinExprOperator::BoolAnd 	=> BinaryOperator::And,
+			BinExprOperator::BitwiseOr | BinExprOperator::BoolOr 	=> BinaryOperator::Or,
+			BinExprOperator::BitwiseXor 						=> BinaryOperator::Xor,
+			BinExprOperator::BitwiseLeftShift 					=> BinaryOperator::ShiftLeft,
+			BinExprOperator::BitwiseRightShift 					=> BinaryOperator::ShiftRight,
+			_ => panic!("Dev error! lower_operation() called with a self operator. {:#?}", operation),
+		};
+		let destination = self.function.new_register(destination_type);
+		self.emit(Instruction::Binary { operator, destination, lhs, rhs });
+
+		if destination_type != data_type
+		{
+			let converted = self.function.new_register(data_type);
+			self.emit(Instruction::Convert { destination: converted, source: Operand::Register(destination), from: destination_type });
+			return Operand::Register(converted);
+		}
+		return Operand::Register(destination);
+	}
+
+	// Returns the register that holds the result, or None if the function returns void.
+	// The arguments are evaluated from left to right, and the function pointer (if there is one) after them.
+	fn lower_function_call(&mut self, function_call: &FunctionCallInfo) -> Option<RegisterId>
+	{
+		let function_type = &self.root.function_types[function_call.signature.index()];
+		let argument_types = function_call.arguments.iter().enumerate().map(|(i, argument)| {
+			if i < function_type.parameters.len() { function_type.parameters[i] } else { argument.root.data_type }
+		}).collect();
+		let return_type = function_type.return_type;
+
+		let arguments = function_call.arguments.iter().map(|argument| self.lower_expression(&argument.root)).collect();
+		let target = match &function_call.callee
+		{
+			Callee::Direct(function) 	=> CallTarget::Direct(*function),
+			Callee::Indirect(callee) 	=> CallTarget::Indirect(self.lower_expression(&callee.root)),
+			Callee::Ident(_) 			=> panic!("Dev error! The called function was not resolved by the semantic analysis."),
+		};
+
+		let destination = if return_type.kind == TypeKind::Void { None } else { Some(self.function.new_register(return_type)) };
+		self.emit(Instruction::Call { destination, target, signature: function_call.signature, arguments, argument_types });
+		return destination;
+	}
+
+	// The address of a writable expression part (a variable or a pointer dereference)
+	fn lower_address(&mut self, part: &BinExprPart) -> Address
+	{
+		if let BinExprPartKind::Val(value) = &part.kind
+		{
+			return self.lower_value_address(value);
+		}
+		panic!("Dev error! lower_address() called with a none-writable expression. {:#?}", part);
+	}
+
+	fn lower_value_address(&mut self, value: &Value) -> Address
+	{
+		match value
+		{
+			Value::Var(variable) => return Address::Slot(self.slots[variable.index()].expect("Dev error! A variable was used before its declaration was lowered.")),
+			Value::Dereference(info) =>
+			{
+				// All dereferences except the last one load the pointers, the last one is the address itself
+				let pointer_type = info.expression.root.data_type;
+				let pointer = self.lower_expression(&info.expression.root);
+				let mut pointer = self.copy_to_register(pointer, pointer_type);
+				for count in 1..info.dereference_count
+				{
+					let destination = self.function.new_register(pointer_type.dereference(count));
+					self.emit(Instruction::Load { destination, address: Address::Register(pointer) });
+					pointer = destination;
+				}
+				return Address::Register(pointer);
+			},
+			_ => panic!("Dev error! lower_value_address() called with a none-writable value. {:#?}", value),
+		}
+	}
+
+	fn copy_to_register(&mut self, operand: Operand, data_type: Type) -> RegisterId
+	{
+		if let Operand::Register(register) = operand
+		{
+			return register;
+		}
+
+		let destination = self.function.new_register(data_type);
+		self.emit(Instruction::Copy { destination, source: operand });
+		return destination;
+	}
+
+	fn variable_slot(&mut self, variable: VariableId, data_type: Type, identifier: &str) -> SlotId
+	{
+		let slot = self.function.new_slot(data_type, identifier.to_string());
+		self.slots[variable.index()] = Some(slot);
+		return slot;
+	}
+
+	fn condition(operator: BinExprOperator) -> Option<Condition>
+	{
+		return Some(match operator
+		{
+			BinExprOperator::BoolEq 		=> Condition::Equal,
+			BinExprOperator::BoolNotEq 		=> Condition::NotEqual,
+			BinExprOperator::BoolGreater 	=> Condition::Greater,
+			BinExprOperator::BoolLess 		=> Condition::Less,
+			BinExprOperator::BoolGreaterEq 	=> Condition::GreaterEqual,
+			BinExprOperator::BoolLessEq 	=> Condition::LessEqual,
+			_ 								=> return None,
+		});
+	}
+
+	fn emit(&mut self, instruction: Instruction)
+	{
+		self.blocks[self.current_block.index()].0.push(instruction);
+	}
+
+	fn new_block(&mut self) -> BlockId
+	{
+		self.blocks.push((Vec::new(), None));
+		return BlockId(self.blocks.len() as u32 - 1);
+	}
+
+	fn start_block(&mut self, block: BlockId)
+	{
+		self.current_block = block;
+	}
+
+	// Ends the current block. A block that already ended (after a return) keeps its terminator.
+	fn terminate(&mut self, terminator: Terminator)
+	{
+		let current = &mut self.blocks[self.current_block.index()].1;
+		if current.is_none()
+		{
+			*current = Some(terminator);
+		}
+	}
+}
@@ -0,0 +1,462 @@
+use super::*;
+
+// The optimization levels, chosen with -O<level>:
+// 	0 - No optimizations, each statement is translated as it is.
+// 	1 - Folds constants, simplifies the control flow graph and removes dead stores and dead computations.
+// 	2 - Also keeps the variables whose address is never taken in registers instead of on the stack.
+// 	3 - Also inlines small functions, not only the ones with the inline attribute.
+pub const OPTIMIZATION_LEVEL_NONE: u8 = 0;
+pub const OPTIMIZATION_LEVEL_REGISTERS: u8 = 2;
+pub const OPTIMIZATION_LEVEL_INLINE: u8 = 3;
+pub const OPTIMIZATION_LEVEL_MAX: u8 = 3;
+
+impl Program
+{
+	pub fn optimize(&mut self, level: u8)
+	{
+		if level == OPTIMIZATION_LEVEL_NONE
+		{
+			return;
+		}
+
+		for function in &mut self.functions
+		{
+			if !function.is_extern()
+			{
+				function.optimize(level);
+			}
+		}
+
+		// The callees are optimized before they are inlined, and the callers are optimized again with the inlined bodies
+		let inlined = self.inline_functions(level);
+		for (function, inlined) in self.functions.iter_mut().zip(inlined)
+		{
+			if inlined
+			{
+				function.optimize(level);
+			}
+		}
+
+		if cfg!(debug_assertions)
+		{
+			self.verify();
+		}
+	}
+}
+
+impl Function
+{
+	// Runs the passes until none of them changes the function, because each pass can create more work for the others
+	// (a folded constant can make a branch constant, which can make blocks unreachable, which can make stores dead)
+	fn optimize(&mut self, level: u8)
+	{
+		if level >= OPTIMIZATION_LEVEL_REGISTERS
+		{
+			self.promote_slots();
+		}
+
+		loop
+		{
+			let mut changed = self.fold_constants();
+			changed |= self.propagate_copies();
+			changed |= self.coalesce_copies();
+			changed |= self.simplify_cfg();
+			changed |= self.remove_dead_stores();
+			changed |= self.remove_dead_computations();
+			if level >= OPTIMIZATION_LEVEL_REGISTERS
+			{
+				changed |= self.optimize_loops();
+			}
+			if !changed
+			{
+				break;
+			}
+		}
+		self.remove_unused_registers_and_slots();
+	}
+
+	// Replaces instructions whose operands are constants with copies of their results, and replaces the uses of registers that
+	// are only assigned a constant with the constant. Returns whether the function was changed.
+	fn fold_constants(&mut self) -> bool
+	{
+		let mut changed = false;
+		let registers = self.registers.clone();
+		for block in &mut self.blocks
+		{
+			for instruction in &mut block.instructions
+			{
+				if let Some(value) = Self::evaluate(&registers, instruction)
+				{
+					*instruction = Instruction::Copy { destination: instruction.destination().unwrap(), source: value };
+					changed = true;
+				}
+			}
+		}
+
+		// The value of each register that is assigned exactly once, with a constant. Parameters are assigned when the function starts.
+		let mut assignments = vec![0usize; self.registers.len()];
+		let mut constants: Vec<Option<Operand>> = vec![None; self.registers.len()];
+		for parameter in &self.parameters
+		{
+			assignments[parameter.index()] += 1;
+		}
+		for instruction in self.blocks.iter().flat_map(|block| &block.instructions)
+		{
+			if let Some(destination) = instruction.destination()
+			{
+				assignments[destination.index()] += 1;
+				if let Instruction::Copy { source: source @ (Operand::Integer(_) | Operand::Float(_)), .. } = instruction
+				{
+					constants[destination.index()] = Some(*source);
+				}
+			}
+		}
+
+		let mut propagate = |operand: &mut Operand| {
+			if let Operand::Register(register) = *operand
+			{
+				if let (1, Some(constant)) = (assignments[register.index()], constants[register.index()])
+				{
+					*operand = constant;
+					changed = true;
+				}
+			}
+		};
+		// The registers of addresses keep their pointers, an address cannot be a constant
+		for block in &mut self.blocks
+		{
+			for instruction in &mut block.instructions
+			{
+				instruction.for_each_value_operand_mut(&mut propagate);
+			}
+			block.terminator.for_each_operand_mut(&mut propagate);
+		}
+		return changed;
+	}
+
+	// The constant result of the instruction, if its operands are constants and it can be evaluated while compiling
+	fn evaluate(registers: &[Type], instruction: &Instruction) -> Option<Operand>
+	{
+		return match *instruction
+		{
+			Instruction::Binary { operator, destination, lhs, rhs } =>
+			{
+				let data_type = registers[destination.index()];
+				match (lhs, rhs)
+				{
+					(Operand::Integer(lhs), Operand::Integer(rhs)) 	=> Self::evaluate_integer_operation(operator, lhs, rhs, data_type),
+					(Operand::Float(lhs), Operand::Float(rhs)) 		=> Self::evaluate_float_operation(operator, lhs, rhs, data_type),
+					_ 												=> None,
+				}
+			},
+			Instruction::Unary { operator, destination, source: Operand::Integer(bits) } =>
+			{
+				let result = match operator
+				{
+					UnaryOperator::Not 		=> !bits,
+					UnaryOperator::IsZero 	=> (bits == 0) as u64,
+				};
+				Some(Operand::Integer(integer_bits(result as i128, registers[destination.index()])))
+			},
+			Instruction::Compare { condition, data_type, lhs, rhs, .. } =>
+			{
+				let result = match (lhs, rhs)
+				{
+					(Operand::Integer(lhs), Operand::Integer(rhs)) 	=> condition.evaluate(integer_value(lhs, data_type), integer_value(rhs, data_type)),
+					(Operand::Float(lhs), Operand::Float(rhs)) 		=> condition.evaluate(lhs, rhs),
+					_ 												=> return None,
+				};
+				Some(Operand::Integer(result as u64))
+			},
+			Instruction::Convert { destination, source, from } => Self::evaluate_conversion(source, from, registers[destination.index()]),
+			_ => None,
+		};
+	}
+
+	// The operator is evaluated like the processor does it. Divisions by zero (and the overflowing signed division) are left for runtime.
+	fn evaluate_integer_operation(operator: BinaryOperator, lhs: u64, rhs: u64, data_type: Type) -> Option<Operand>
+	{
+		let (lhs, rhs) = (integer_value(lhs, data_type), integer_value(rhs, data_type));
+		let result = match operator
+		{
+			BinaryOperator::Add 	=> lhs + rhs,
+			BinaryOperator::Sub 	=> lhs - rhs,
+			BinaryOperator::Mul 	=> lhs.wrapping_mul(rhs),
+			BinaryOperator::Div | BinaryOperator::Modulo =>
+			{
+				if rhs == 0 || (rhs == -1 && lhs == Self::integer_range(data_type).0)
+				{
+					return None;
+				}
+				if operator == BinaryOperator::Div { lhs / rhs } else { lhs % rhs }
+			},
+			BinaryOperator::And 	=> lhs & rhs,
+			BinaryOperator::Or 		=> lhs | rhs,
+			BinaryOperator::Xor 	=> lhs ^ rhs,
+
+			// The processor uses only the low 5 bits of the shift count (6 bits for 64 bit operands)
+			BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight =>
+			{
+				let count = rhs as u32 & if data_type.size() == 8 { 63 } else { 31 };
+				let lhs = integer_bits(lhs, data_type) as i128;
+				if operator == BinaryOperator::ShiftLeft { lhs << count } else { lhs >> count }
+			},
+		};
+		return Some(Operand::Integer(integer_bits(result, data_type)));
+	}
+
+	fn evaluate_float_operation(operator: BinaryOperator, lhs: f64, rhs: f64, data_type: Type) -> Option<Operand>
+	{
+		let result = match operator
+		{
+			BinaryOperator::Add => lhs + rhs,
+			BinaryOperator::Sub => lhs - rhs,
+			BinaryOperator::Mul => lhs * rhs,
+			BinaryOperator::Div => lhs / rhs,
+			_ 					=> return None,
+		};
+		return Some(Operand::Float(Self::round_float(result, data_type)));
+	}
+
+	fn evaluate_conversion(source: Operand, from: Type, into: Type) -> Option<Operand>
+	{
+		return match source
+		{
+			Operand::Integer(bits) if into.is_integer() 	=> Some(Operand::Integer(integer_bits(integer_value(bits, from), into))),
+			Operand::Integer(bits) 							=> Some(Operand::Float(Self::round_float(integer_value(bits, from) as f64, into))),
+			Operand::Float(number) if into.is_integer() =>
+			{
+				// The result of converting a float that does not fit in the integer is decided by the processor, so it is left for runtime
+				let number = number.trunc();
+				let (min, max) = Self::integer_range(into);
+				if number.is_nan() || number < min as f64 || number > max as f64
+				{
+					return None;
+				}
+				Some(Operand::Integer(integer_bits(number as i128, into)))
+			},
+			Operand::Float(number) 							=> Some(Operand::Float(Self::round_float(number, into))),
+			Operand::Register(_) 							=> None,
+		};
+	}
+
+	// The smallest and the largest values of the integer data type
+	fn integer_range(data_type: Type) -> (i128, i128)
+	{
+		let bits = data_type.size() as u32 * 8;
+		if data_type.is_signed()
+		{
+			return (-(1 << (bits - 1)), (1 << (bits - 1)) - 1);
+		}
+		return (0, (1 << bits) - 1);
+	}
+
+	// Float constants are stored as f64, an f32 constant must also be a valid f32
+	fn round_float(number: f64, data_type: Type) -> f64
+	{
+		if data_type.kind == TypeKind::F32
+		{
+			return number as f32 as f64;
+		}
+		return number;
+	}
+
+	// Removes stores into stack slots that are never read afterwards, found with a liveness analysis of the slots on the CFG.
+	// A slot whose address is taken can be accessed through pointers, so the stores into it are always kept.
+	fn remove_dead_stores(&mut self) -> bool
+	{
+		let mut address_taken = vec![false; self.slots.len()];
+		for instruction in self.blocks.iter().flat_map(|block| &block.instructions)
+		{
+			if let Instruction::SlotAddress { slot, .. } = instruction
+			{
+				address_taken[slot.index()] = true;
+			}
+		}
+
+		// The slots that are live (may be read before they are written) when each block starts
+		let cfg = Cfg::new(self);
+		let mut live_in = vec![vec![false; self.slots.len()]; self.blocks.len()];
+		let mut changed = true;
+		while changed
+		{
+			changed = false;
+			for i in (0..self.blocks.len()).rev()
+			{
+				let mut live = self.live_out(&cfg, &live_in, i);
+				for instruction in self.blocks[i].instructions.iter().rev()
+				{
+					Self::update_live_slots(&mut live, instruction);
+				}
+				if live != live_in[i]
+				{
+					live_in[i] = live;
+					changed = true;
+				}
+			}
+		}
+
+		let mut removed = false;
+		for i in 0..self.blocks.len()
+		{
+			let mut live = self.live_out(&cfg, &live_in, i);
+			let mut dead = vec![false; self.blocks[i].instructions.len()];
+			for (j, instruction) in self.blocks[i].instructions.iter().enumerate().rev()
+			{
+				if let Instruction::Store { address: Address::Slot(slot), .. } = instruction
+				{
+					dead[j] = !live[slot.index()] && !address_taken[slot.index()];
+				}
+				Self::update_live_slots(&mut live, instruction);
+			}
+
+			if dead.contains(&true)
+			{
+				let mut dead = dead.into_iter();
+				self.blocks[i].instructions.retain(|_| !dead.next().unwrap());
+				removed = true;
+			}
+		}
+		return removed;
+	}
+
+	fn live_out(&self, cfg: &Cfg, live_in: &[Vec<bool>], block: usize) -> Vec<bool>
+	{
+		let mut live = vec![false; self.slots.len()];
+		for successor in &cfg.successors[block]
+		{
+			for (slot, is_live) in live_in[successor.index()].iter().enumerate()
+			{
+				live[slot] |= *is_live;
+			}
+		}
+		return live;
+	}
+
+	// Moves the liveness of the slots from after the instruction to before it
+	fn update_live_slots(live: &mut [bool], instruction: &Instruction)
+	{
+		match instruction
+		{
+			Instruction::Store { address: Address::Slot(slot), .. } 	=> live[slot.index()] = false,
+			Instruction::Load { address: Address::Slot(slot), .. } 		=> live[slot.index()] = true,
+			_ 															=> (),
+		}
+	}
+
+	// Removes instructions whose result is never read afterwards and that have no other effect (everything except stores and calls),
+	// found with a liveness analysis of the registers on the CFG. A copy of a register into itself is also removed.
+	fn remove_dead_computations(&mut self) -> bool
+	{
+		let mut changed = false;
+		loop
+		{
+			let liveness = Liveness::new(self, &Cfg::new(self));
+			let mut removed = false;
+			for (i, block) in self.blocks.iter_mut().enumerate()
+			{
+				let mut live = liveness.live_out[i].clone();
+				Liveness::update_terminator(&mut live, &block.terminator);
+				let mut dead = vec![false; block.instructions.len()];
+				for (j, instruction) in block.instructions.iter().enumerate().rev()
+				{
+					dead[j] = match *instruction
+					{
+						Instruction::Store { .. } | Instruction::Call { .. } 						=> false,
+						Instruction::Copy { destination, source: Operand::Register(source) } if destination == source 	=> true,
+						_ 																			=> !live[instruction.destination().unwrap().index()],
+					};
+					if !dead[j]
+					{
+						Liveness::update(&mut live, instruction);
+					}
+				}
+
+				if dead.contains(&true)
+				{
+					let mut dead = dead.into_iter();
+					block.instructions.retain(|_| !dead.next().unwrap());
+					removed = true;
+				}
+			}
+
+			if !removed
+			{
+				return changed;
+			}
+			changed = true;
+		}
+	}
+
+	// Renumbers the registers and the slots, without the ones that are no longer used, so the code generator does not reserve space for them
+	fn remove_unused_registers_and_slots(&mut self)
+	{
+		let mut used_registers = vec![false; self.registers.len()];
+		let mut used_slots = vec![false; self.slots.len()];
+		for parameter in &self.parameters
+		{
+			used_registers[parameter.index()] = true;
+		}
+		for block in &self.blocks
+		{
+			for instruction in &block.instructions
+			{
+				instruction.for_each_used_register(&mut |register| used_registers[register.index()] = true);
+				if let Some(destination) = instruction.destination()
+				{
+					used_registers[destination.index()] = true;
+				}
+				match instruction
+				{
+					Instruction::Load { address: Address::Slot(slot), .. } 	|
+					Instruction::Store { address: Address::Slot(slot), .. } |
+					Instruction::SlotAddress { slot, .. } 					=> used_slots[slot.index()] = true,
+					_ 														=> (),
+				}
+			}
+			block.terminator.for_each_used_register(&mut |register| used_registers[register.index()] = true);
+		}
+
+		let register_index = Self::renumber(&used_registers);
+		let slot_index = Self::renumber(&used_slots);
+		self.registers = std::mem::take(&mut self.registers).into_iter().zip(&used_registers).filter(|(_, used)| **used).map(|(data_type, _)| data_type).collect();
+		self.slots = std::mem::take(&mut self.slots).into_iter().zip(&used_slots).filter(|(_, used)| **used).map(|(slot, _)| slot).collect();
+
+		let rename = |register: &mut RegisterId| *register = RegisterId(register_index[register.index()]);
+		for parameter in &mut self.parameters
+		{
+			rename(parameter);
+		}
+		for block in &mut self.blocks
+		{
+			for instruction in &mut block.instructions
+			{
+				instruction.for_each_register_mut(&mut |register| rename(register));
+				match instruction
+				{
+					Instruction::Load { address: Address::Slot(slot), .. } 	|
+					Instruction::Store { address: Address::Slot(slot), .. } |
+					Instruction::SlotAddress { slot, .. } 					=> *slot = SlotId(slot_index[slot.index()]),
+					_ 														=> (),
+				}
+			}
+			block.terminator.for_each_operand_mut(&mut |operand| {
+				if let Operand::Register(register) = operand
+				{
+					rename(register);
+				}
+			});
+		}
+	}
+
+	// The new index of each item, when only the used items are kept
+	fn renumber(used: &[bool]) -> Vec<u32>
+	{
+		let mut count = 0;
+		return used.iter().map(|used| {
+			let index = count;
+			count += *used as u32;
+			return index;
+		}).collect();
+	}
+}
@@ -0,0 +1,268 @@
+use super::*;
+
+impl Function
+{
+	// Keeps the variables whose address is never taken in virtual registers instead of stack slots (at -O2).
+	// Stores into such a variable become copies into its register, and loads become copies from it. Returns whether a slot was promoted.
+	// A variable that is never written keeps its slot, so every register that is read is still written somewhere.
+	pub fn promote_slots(&mut self) -> bool
+	{
+		let mut promotable = vec![true; self.slots.len()];
+		let mut written = vec![false; self.slots.len()];
+		for instruction in self.blocks.iter().flat_map(|block| &block.instructions)
+		{
+			match instruction
+			{
+				Instruction::SlotAddress { slot, .. } 					=> promotable[slot.index()] = false,
+				Instruction::Store { address: Address::Slot(slot), .. } => written[slot.index()] = true,
+				_ 														=> (),
+			}
+		}
+
+		let mut variables: Vec<Option<RegisterId>> = vec![None; self.slots.len()];
+		for slot in 0..self.slots.len()
+		{
+			if promotable[slot] && written[slot]
+			{
+				variables[slot] = Some(self.new_register(self.slots[slot].data_type));
+			}
+		}
+
+		let mut changed = false;
+		for instruction in self.blocks.iter_mut().flat_map(|block| &mut block.instructions)
+		{
+			match *instruction
+			{
+				Instruction::Store { address: Address::Slot(slot), value, .. } if variables[slot.index()].is_some() =>
+				{
+					*instruction = Instruction::Copy { destination: variables[slot.index()].unwrap(), source: value };
+					changed = true;
+				},
+				Instruction::Load { destination, address: Address::Slot(slot) } if variables[slot.index()].is_some() =>
+				{
+					*instruction = Instruction::Copy { destination, source: Operand::Register(variables[slot.index()].unwrap()) };
+					changed = true;
+				},
+				_ => (),
+			}
+		}
+		return changed;
+	}
+
+	// Replaces the uses of a register that is only a copy of another register with the other register. The copy is then removed as a dead computation.
+	// 	%1 = copy %0 			=> 	%2 = add i32 %0, 1
+	// 	%2 = add i32 %1, 1
+	// If both registers are written once, and the other register is not written inside a loop, they hold the same value wherever the copy
+	// can be read. Otherwise, all of the uses of the copy must be in its block, and the other register must not be written before the last one.
+	pub fn propagate_copies(&mut self) -> bool
+	{
+		let (assignments, uses) = self.count_register_accesses();
+		let mut changed = false;
+
+		// The registers that are written once, outside of loops. The parameters are written before the entry block.
+		let loop_depths = Cfg::new(self).loop_depths();
+		let mut written_once = vec![false; self.registers.len()];
+		for parameter in &self.parameters
+		{
+			written_once[parameter.index()] = assignments[parameter.index()] == 1;
+		}
+		for (block, depth) in self.blocks.iter().zip(&loop_depths)
+		{
+			for destination in block.instructions.iter().filter_map(|instruction| instruction.destination())
+			{
+				written_once[destination.index()] = *depth == 0 && assignments[destination.index()] == 1;
+			}
+		}
+
+		let mut replacements: Vec<Option<RegisterId>> = vec![None; self.registers.len()];
+		for instruction in self.blocks.iter().flat_map(|block| &block.instructions)
+		{
+			if let Instruction::Copy { destination, source: Operand::Register(source) } = *instruction
+			{
+				if destination != source && assignments[destination.index()] == 1 && written_once[source.index()]
+					&& self.registers[destination.index()] == self.registers[source.index()]
+				{
+					replacements[destination.index()] = Some(source);
+				}
+			}
+		}
+		if replacements.iter().any(|replacement| replacement.is_some())
+		{
+			// A chain of copies is replaced with its first register
+			let replacement = |mut register: RegisterId| -> RegisterId {
+				while let Some(source) = replacements[register.index()]
+				{
+					register = source;
+				}
+				return register;
+			};
+			for block in &mut self.blocks
+			{
+				for instruction in &mut block.instructions
+				{
+					let destination = instruction.destination();
+					instruction.for_each_register_mut(&mut |register| {
+						if Some(*register) != destination
+						{
+							*register = replacement(*register);
+						}
+					});
+				}
+				block.terminator.for_each_operand_mut(&mut |operand| {
+					if let Operand::Register(register) = operand
+					{
+						*register = replacement(*register);
+					}
+				});
+			}
+			return true;
+		}
+
+		for block in &mut self.blocks
+		{
+			for i in 0..block.instructions.len()
+			{
+				let Instruction::Copy { destination, source: Operand::Register(source) } = block.instructions[i] else
+				{
+					continue;
+				};
+				if destination == source || assignments[destination.index()] != 1 || self.registers[destination.index()] != self.registers[source.index()]
+				{
+					continue;
+				}
+
+				// Find the last use of the copy, the terminator is at the index after the last instruction
+				let mut remaining = uses[destination.index()];
+				let mut last_use = i;
+				for j in i + 1..=block.instructions.len()
+				{
+					let mut count = 0;
+					let mut writes_source = false;
+					if let Some(instruction) = block.instructions.get(j)
+					{
+						instruction.for_each_used_register(&mut |register| count += (register == destination) as usize);
+						writes_source = instruction.destination() == Some(source);
+					} else
+					{
+						block.terminator.for_each_used_register(&mut |register| count += (register == destination) as usize);
+					}
+
+					// The last use can write the source, because an instruction reads its operands before it writes its result
+					remaining -= count;
+					if remaining == 0
+					{
+						last_use = j;
+						break;
+					}
+					if writes_source
+					{
+						break;
+					}
+				}
+				if last_use == i
+				{
+					continue;
+				}
+
+				let mut replace = |register: &mut RegisterId| {
+					if *register == destination
+					{
+						*register = source;
+					}
+				};
+				let end = last_use.min(block.instructions.len() - 1);
+				for instruction in &mut block.instructions[i + 1..=end]
+				{
+					instruction.for_each_register_mut(&mut replace);
+				}
+				if last_use == block.instructions.len()
+				{
+					block.terminator.for_each_operand_mut(&mut |operand| {
+						if let Operand::Register(register) = operand
+						{
+							replace(register);
+						}
+					});
+				}
+				changed = true;
+			}
+		}
+		return changed;
+	}
+
+	// Writes the result of an instruction straight into the register that it is copied into, when the copy is the only use of the result
+	// and the register is not accessed between the instruction and the copy.
+	// 	%2 = add i32 %0, 1 		=> 	%0 = add i32 %0, 1
+	// 	%0 = copy %2
+	pub fn coalesce_copies(&mut self) -> bool
+	{
+		let (assignments, uses) = self.count_register_accesses();
+		let mut changed = false;
+		for block in &mut self.blocks
+		{
+			let mut removed = vec![false; block.instructions.len()];
+			for i in 0..block.instructions.len()
+			{
+				let Instruction::Copy { destination, source: Operand::Register(source) } = block.instructions[i] else
+				{
+					continue;
+				};
+				if destination == source || assignments[source.index()] != 1 || uses[source.index()] != 1
+					|| self.registers[destination.index()] != self.registers[source.index()]
+				{
+					continue;
+				}
+
+				let mut accessed = false;
+				for j in (0..i).rev()
+				{
+					if removed[j]
+					{
+						continue;
+					}
+					if block.instructions[j].destination() == Some(source)
+					{
+						if !accessed
+						{
+							*block.instructions[j].destination_mut().unwrap() = destination;
+							removed[i] = true;
+							changed = true;
+						}
+						break;
+					}
+
+					block.instructions[j].for_each_used_register(&mut |register| accessed |= register == destination);
+					accessed |= block.instructions[j].destination() == Some(destination);
+				}
+			}
+
+			let mut removed = removed.into_iter();
+			block.instructions.retain(|_| !removed.next().unwrap());
+		}
+		return changed;
+	}
+
+	// How many times each register is written (the parameters are written when the function starts) and how many times it is read
+	fn count_register_accesses(&self) -> (Vec<usize>, Vec<usize>)
+	{
+		let mut assignments = vec![0; self.registers.len()];
+		let mut uses = vec![0; self.registers.len()];
+		for parameter in &self.parameters
+		{
+			assignments[parameter.index()] += 1;
+		}
+		for block in &self.blocks
+		{
+			for instruction in &block.instructions
+			{
+				if let Some(destination) = instruction.destination()
+				{
+					assignments[destination.index()] += 1;
+				}
+				instruction.for_each_used_register(&mut |register| uses[register.index()] += 1);
+			}
+			block.terminator.for_each_used_register(&mut |register| uses[register.index()] += 1);
+		}
+		return (assignments, uses);
+	}
+}
@@ -0,0 +1,253 @@
+use super::*;
+
+// Checks that the MIR is well formed. A problem here is always a bug in the compiler (in the lowering or in an optimization pass),
+// so it panics with the textual form of the function.
+impl Program
+{
+	pub fn verify(&self)
+	{
+		for function in &self.functions
+		{
+			if let Err(problem) = self.verify_function(function)
+			{
+				let mut text = String::new();
+				let _ = self.write_function(&mut text, function);
+				panic!("Dev error! The MIR of the function \"{}\" is invalid: {problem}\n{text}", function.identifier);
+			}
+		}
+	}
+
+	fn verify_function(&self, function: &Function) -> Result<(), String>
+	{
+		let signature = self.function_types.get(function.signature.index()).ok_or("The signature does not exist.")?;
+		if function.is_extern()
+		{
+			return if function.blocks.is_empty() { Ok(()) } else { Err(String::from("An external function has blocks.")) };
+		}
+
+		if function.blocks.is_empty()
+		{
+			return Err(String::from("The function has no blocks."));
+		}
+
+		if function.parameters.len() != signature.parameters.len()
+		{
+			return Err(String::from("The amount of parameters does not match the signature."));
+		}
+
+		// Every register that is read must be written somewhere in the function (the MIR is not in SSA form, so the order is not checked)
+		let mut defined = vec![false; function.registers.len()];
+		for (i, parameter) in function.parameters.iter().enumerate()
+		{
+			Self::verify_register(function, *parameter)?;
+			if function.register_type(*parameter) != signature.parameters[i]
+			{
+				return Err(format!("The parameter %{} does not match the signature.", parameter.0));
+			}
+			defined[parameter.index()] = true;
+		}
+
+		for block in &function.blocks
+		{
+			for instruction in &block.instructions
+			{
+				if let Some(destination) = instruction.destination()
+				{
+					Self::verify_register(function, destination)?;
+					defined[destination.index()] = true;
+				}
+			}
+		}
+
+		for (i, block) in function.blocks.iter().enumerate()
+		{
+			for instruction in &block.instructions
+			{
+				let mut problem = None;
+				instruction.for_each_used_register(&mut |register| {
+					if register.index() >= defined.len() || !defined[register.index()]
+					{
+						problem = Some(format!("The register %{} is used but never written.", register.0));
+					}
+				});
+				if let Some(problem) = problem
+				{
+					return Err(problem);
+				}
+
+				self.verify_instruction(function, instruction).map_err(|problem| {
+					return format!("In bb{i}, \"{}\": {problem}", self.instruction_text(function, instruction));
+				})?;
+			}
+			Self::verify_terminator(function, &block.terminator, &defined).map_err(|problem| format!("In the terminator of bb{i}: {problem}"))?;
+		}
+		return Ok(());
+	}
+
+	fn verify_instruction(&self, function: &Function, instruction: &Instruction) -> Result<(), String>
+	{
+		let destination_type = instruction.destination().map(|register| function.register_type(register));
+		match instruction
+		{
+			Instruction::Copy { source, .. } => Self::verify_operand(function, *source, destination_type.unwrap()),
+			Instruction::Binary { operator, lhs, rhs, .. } =>
+			{
+				let data_type = destination_type.unwrap();
+				if !data_type.is_integer() && !matches!(operator, BinaryOperator::Add | BinaryOperator::Sub | BinaryOperator::Mul | BinaryOperator::Div)
+				{
+					return Err(format!("The operator is not defined on {data_type}."));
+				}
+				Self::verify_operand(function, *lhs, data_type)?;
+				return Self::verify_operand(function, *rhs, data_type);
+			},
+			Instruction::Unary { source, .. } =>
+			{
+				let data_type = destination_type.unwrap();
+				if !data_type.is_integer()
+				{
+					return Err(format!("The operator is not defined on {data_type}."));
+				}
+				return Self::verify_operand(function, *source, data_type);
+			},
+			Instruction::Compare { data_type, lhs, rhs, .. } =>
+			{
+				if destination_type.unwrap() != Type::new(TypeKind::U8)
+				{
+					return Err(String::from("The result of a comparison must be an u8."));
+				}
+				Self::verify_operand(function, *lhs, *data_type)?;
+				return Self::verify_operand(function, *rhs, *data_type);
+			},
+			Instruction::Convert { source, from, .. } => Self::verify_operand(function, *source, *from),
+			Instruction::Load { address, .. } => Self::verify_address(function, address),
+			Instruction::Store { data_type, address, value } =>
+			{
+				Self::verify_address(function, address)?;
+				return Self::verify_operand(function, *value, *data_type);
+			},
+			Instruction::SlotAddress { slot, .. } =>
+			{
+				if slot.index() >= function.slots.len()
+				{
+					return Err(format!("The slot ${} does not exist.", slot.0));
+				}
+				return Self::verify_type(destination_type.unwrap(), Type::new(TypeKind::U64));
+			},
+			Instruction::FunctionAddress { function: index, .. } =>
+			{
+				if index.index() >= self.functions.len()
+				{
+					return Err(String::from("The function does not exist."));
+				}
+				return Self::verify_type(destination_type.unwrap(), Type::new(TypeKind::U64));
+			},
+			Instruction::Call { target, signature, arguments, argument_types, destination } =>
+			{
+				let function_type = self.function_types.get(signature.index()).ok_or("The signature does not exist.")?;
+				if arguments.len() != argument_types.len() || arguments.len() < function_type.parameters.len()
+				{
+					return Err(String::from("The amount of arguments does not match the signature."));
+				}
+				for (argument, data_type) in arguments.iter().zip(argument_types)
+				{
+					Self::verify_operand(function, *argument, *data_type)?;
+				}
+
+				match target
+				{
+					CallTarget::Direct(index) if index.index() >= self.functions.len() 	=> return Err(String::from("The function does not exist.")),
+					CallTarget::Indirect(pointer) 										=> Self::verify_operand(function, *pointer, Type::new(TypeKind::U64))?,
+					_ 																	=> (),
+				}
+
+				return match destination
+				{
+					Some(destination) 	=> Self::verify_type(function.register_type(*destination), function_type.return_type),
+					None if function_type.return_type.kind == TypeKind::Void 	=> Ok(()),
+					None 				=> Err(String::from("The result of a function that returns a value must be written into a register.")),
+				};
+			},
+		}
+	}
+
+	fn verify_terminator(function: &Function, terminator: &Terminator, defined: &[bool]) -> Result<(), String>
+	{
+		for successor in terminator.successors()
+		{
+			if successor.index() >= function.blocks.len()
+			{
+				return Err(format!("The block bb{} does not exist.", successor.0));
+			}
+		}
+
+		let mut problem = None;
+		terminator.for_each_used_register(&mut |register| {
+			if register.index() >= defined.len() || !defined[register.index()]
+			{
+				problem = Some(format!("The register %{} is used but never written.", register.0));
+			}
+		});
+		if let Some(problem) = problem
+		{
+			return Err(problem);
+		}
+
+		return match *terminator
+		{
+			Terminator::Branch { condition: Operand::Float(_), .. } 	=> Err(String::from("A condition must be an integer.")),
+			Terminator::Branch { condition: Operand::Register(register), .. } if !function.register_type(register).is_integer() =>
+			{
+				Err(String::from("A condition must be an integer."))
+			},
+			Terminator::Return(Some(value)) if function.return_type.kind != TypeKind::Void 	=> Self::verify_operand(function, value, function.return_type),
+			Terminator::Return(None) if function.return_type.kind == TypeKind::Void 		=> Ok(()),
+			Terminator::Return(_) 		=> Err(String::from("The return value does not match the return type.")),
+			_ 							=> Ok(()),
+		};
+	}
+
+	fn verify_operand(function: &Function, operand: Operand, data_type: Type) -> Result<(), String>
+	{
+		match operand
+		{
+			Operand::Register(register) =>
+			{
+				Self::verify_register(function, register)?;
+				return Self::verify_type(function.register_type(register), data_type);
+			},
+			Operand::Integer(bits) if data_type.is_integer() && integer_bits(bits as i128, data_type) == bits 	=> return Ok(()),
+			Operand::Float(_) if !data_type.is_integer() && data_type.kind != TypeKind::Void 					=> return Ok(()),
+			_ => return Err(format!("The constant {:?} is not a valid {data_type}.", operand)),
+		}
+	}
+
+	fn verify_address(function: &Function, address: &Address) -> Result<(), String>
+	{
+		return match *address
+		{
+			Address::Slot(slot) if slot.index() >= function.slots.len() => Err(format!("The slot ${} does not exist.", slot.0)),
+			Address::Slot(_) 											=> Ok(()),
+			Address::Register(register) 								=> Self::verify_operand(function, Operand::Register(register), Type::new(TypeKind::U64)),
+		};
+	}
+
+	fn verify_register(function: &Function, register: RegisterId) -> Result<(), String>
+	{
+		if register.index() >= function.registers.len()
+		{
+			return Err(format!("The register %{} does not exist.", register.0));
+		}
+		return Ok(());
+	}
+
+	// Integers (including addresses) are compatible if they have the same size, floats must have the same data type
+	fn verify_type(given: Type, expected: Type) -> Result<(), String>
+	{
+		let compatible = if expected.is_integer() { given.is_integer() && given.size() == expected.size() } else { given == expected };
+		if !compatible
+		{
+			return Err(format!("Expected {expected} but got {given}."));
+		}
+		return Ok(());
+	}
+}
@@ -28,7 +28,7 @@ impl Preprocessor
 			}	
 
 			let mut command_end_index: usize = 0;
-			while let Some((i, ch)) = itr.next()
+			for (i, ch) in itr.by_ref()
 			{
 				if !ch.is_alphabetic()
 				{
@@ -40,7 +40,7 @@ impl Preprocessor
 			match command {
 				COMMAND_INTEXT => self.handle_intext(command_end_index+1),
 
-				_ => { print_errln!(CompileError::InvalidPreprocessorCommand(&command[..]), &self.source, i, ""); }
+				_ => { print_errln!(CompileError::InvalidPreprocessorCommand(command), &self.source, i, ""); }
 			}
 
 			// Reinitialize the iterator, because the command handlers will change self.source
@@ -64,7 +64,7 @@ impl Preprocessor
 		//  Because preprocessor commands start with '#', a 0 index is an invalid value. Hence use that for "uninitialized" variables
 		let mut filepath_start_index = 0;
 		let mut filepath_end_index = 0;
-		while let Some((i, ch)) = itr.next()
+		for (i, ch) in itr
 		{
 			if ch.is_whitespace() && filepath_start_index != 0
 			{
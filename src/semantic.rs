@@ -0,0 +1,53 @@
+mod variable;
+mod function;
+mod statement;
+mod expression;
+mod constant;
+mod tail_call;
+
+use function::FunctionManager;
+use crate::{ast::*, error::CompileError, print_errln, warnings::Warnings};
+
+// Resolves the names in the AST that the parser has generated, infers and checks the data types of the expressions,
+// and annotates each part of an expression with its data type. Also collects the local variables of each function.
+pub struct SemanticAnalyzer<'a>
+{
+	ir: Root,
+	func_manager: FunctionManager,
+	source: &'a str,
+	warnings: &'a Warnings<'a>,
+}
+
+impl<'a> SemanticAnalyzer<'a>
+{
+	pub fn new(ir: Root, source: &'a str, warnings: &'a Warnings<'a>) -> Self
+	{
+		return Self {
+			ir,
+			func_manager: FunctionManager::new(),
+			source,
+			warnings,
+		};
+	}
+
+	pub fn analyze(mut self) -> Root
+	{
+		// All functions are declared before analyzing their bodies, so a function can be called before its declaration.
+		for function in std::mem::take(&mut self.ir.functions)
+		{
+			if self.func_manager.get(&function.identifier).is_some()
+			{
+				print_errln!(CompileError::Syntax, self.source, function.span.start, "The function \"{}\" was already declared.", function.identifier);
+			}
+			self.func_manager.add(function);
+		}
+
+		for index in 0..self.func_manager.len()
+		{
+			self.analyze_function(FunctionId(index as u32));
+		}
+
+		self.ir.functions = self.func_manager.into_function_array();
+		return self.ir;
+	}
+}
@@ -0,0 +1,262 @@
+use crate::{ast::*, error::CompileError, lexer::TextSpan, print_errln, warnings::Warning};
+use super::SemanticAnalyzer;
+
+// The value of a constant expression while it is evaluated. Integers are evaluated with more bits than any data type has,
+// so an overflow can be detected before the result is wrapped into its data type.
+#[derive(Debug, Clone, Copy)]
+enum Constant
+{
+	Integer(i128),
+	Float(f64),
+}
+
+impl Constant
+{
+	fn from_value(value: &Value) -> Option<Self>
+	{
+		return match *value
+		{
+			Value::I8(number)  => Some(Self::Integer(number as i128)),
+			Value::U8(number)  => Some(Self::Integer(number as i128)),
+			Value::I16(number) => Some(Self::Integer(number as i128)),
+			Value::U16(number) => Some(Self::Integer(number as i128)),
+			Value::I32(number) => Some(Self::Integer(number as i128)),
+			Value::U32(number) => Some(Self::Integer(number as i128)),
+			Value::I64(number) => Some(Self::Integer(number as i128)),
+			Value::U64(number) => Some(Self::Integer(number as i128)),
+			Value::F32(number) => Some(Self::Float(number as f64)),
+			Value::F64(number) => Some(Self::Float(number)),
+			_ => None,
+		};
+	}
+
+	// Converts the constant into a value of the given data type. Integers are wrapped, like they are in a register of that size.
+	fn into_value(self, data_type: Type) -> Value
+	{
+		if let Self::Float(number) = self
+		{
+			match data_type.kind
+			{
+				TypeKind::F32 	=> return Value::F32(number as f32),
+				TypeKind::F64 	=> return Value::F64(number),
+				_ 				=> (),
+			}
+		}
+
+		let number = self.integer();
+		return match data_type.kind
+		{
+			TypeKind::I8  	=> Value::I8(number as i8),
+			TypeKind::U8  	=> Value::U8(number as u8),
+			TypeKind::I16 	=> Value::I16(number as i16),
+			TypeKind::U16 	=> Value::U16(number as u16),
+			TypeKind::I32 	=> Value::I32(number as i32),
+			TypeKind::U32 	=> Value::U32(number as u32),
+			TypeKind::I64 	=> Value::I64(number as i64),
+			TypeKind::U64 	=> Value::U64(number as u64),
+			TypeKind::F32 	=> Value::F32(number as f32),
+			TypeKind::F64 	=> Value::F64(number as f64),
+			_ 				=> panic!("Dev error! A constant was converted into {data_type}."),
+		};
+	}
+
+	fn integer(self) -> i128
+	{
+		return match self
+		{
+			Self::Integer(number) 	=> number,
+			Self::Float(number) 	=> number as i128,
+		};
+	}
+}
+
+impl<'a> SemanticAnalyzer<'a>
+{
+	// Replaces the parts of the expression that have only constants with their values (constant folding).
+	// The sub expressions of function calls and dereferences are folded when they are analyzed.
+	pub fn fold_constants(&self, part: &mut BinExprPart)
+	{
+		match &mut part.kind
+		{
+			BinExprPartKind::Operation(operation) =>
+			{
+				self.fold_constants(&mut operation.lhs);
+				self.fold_constants(&mut operation.rhs);
+			},
+			BinExprPartKind::SelfOperation(operation) 	=> self.fold_constants(&mut operation.expression),
+			BinExprPartKind::TypeCast(info) 			=> self.fold_constants(&mut info.expression),
+			BinExprPartKind::Val(_) 					=> return,
+		}
+
+		if let Some(value) = self.evaluate(part)
+		{
+			part.kind = BinExprPartKind::Val(value);
+		}
+	}
+
+	// Returns the value of the expression part if its operands are constants. Its sub expressions must be folded already.
+	fn evaluate(&self, part: &BinExprPart) -> Option<Value>
+	{
+		if !Self::is_arithmetic(part.data_type)
+		{
+			return None;
+		}
+
+		match &part.kind
+		{
+			BinExprPartKind::Val(_) => return None,
+			BinExprPartKind::TypeCast(info) =>
+			{
+				let constant = Self::operand(&info.expression)?;
+
+				// The result of converting a float that does not fit in the integer is decided by the processor, so it is left for runtime
+				if let Constant::Float(number) = constant
+				{
+					if info.into_type.is_integer() && !Self::fits_in_type(number.trunc() as i128, info.into_type)
+					{
+						return None;
+					}
+				}
+				return Some(constant.into_value(info.into_type));
+			},
+			BinExprPartKind::SelfOperation(operation) =>
+			{
+				let constant = Self::operand(&operation.expression)?;
+				return match (operation.operator, constant)
+				{
+					(BinExprOperator::BitwiseNot, Constant::Integer(number)) 	=> Some(Constant::Integer(!number).into_value(part.data_type)),
+					(BinExprOperator::BoolNot, Constant::Integer(number)) 		=> Some(Constant::Integer((number == 0) as i128).into_value(part.data_type)),
+					_ 															=> None,
+				};
+			},
+			BinExprPartKind::Operation(operation) =>
+			{
+				let lhs = Self::operand(&operation.lhs)?;
+				let rhs = Self::operand(&operation.rhs)?;
+				let operand_type = operation.lhs.data_type;
+				let result = match (lhs, rhs)
+				{
+					(Constant::Integer(lhs), Constant::Integer(rhs)) 	=> self.evaluate_integer_operation(operation.operator, lhs, rhs, operand_type, operation.rhs.span)?,
+					(Constant::Float(lhs), Constant::Float(rhs)) 		=> Self::evaluate_float_operation(operation.operator, lhs, rhs)?,
+					_ 													=> return None,
+				};
+
+				if let Constant::Integer(number) = result
+				{
+					if operand_type.is_signed() && Self::is_arithmetic_operator(operation.operator) && !Self::fits_in_type(number, operand_type)
+					{
+						self.warnings.warn(
+							Warning::Overflow,
+							part.span.start,
+							&format!("The result of this operation does not fit in {operand_type}, so it is wrapped.")
+						);
+					}
+				}
+				return Some(result.into_value(part.data_type));
+			},
+		}
+	}
+
+	// The operator is evaluated like the processor does it for the operands data type, but without wrapping the result.
+	fn evaluate_integer_operation(&self, operator: BinExprOperator, lhs: i128, rhs: i128, operand_type: Type, rhs_span: TextSpan) -> Option<Constant>
+	{
+		let bits = operand_type.size() as u32 * 8;
+		let result = match operator
+		{
+			BinExprOperator::Add 				=> lhs + rhs,
+			BinExprOperator::Sub 				=> lhs - rhs,
+			BinExprOperator::Mul 				=> lhs.wrapping_mul(rhs),		/* Only an u64 multiplication can overflow an i128, and it is wrapped anyway */
+			BinExprOperator::Div | BinExprOperator::Modulo =>
+			{
+				if rhs == 0
+				{
+					print_errln!(CompileError::DivisionByZero, self.source, rhs_span.start, "");
+				}
+				if operator == BinExprOperator::Div { lhs / rhs } else { lhs % rhs }
+			},
+			BinExprOperator::BitwiseOr | BinExprOperator::BoolOr 	=> lhs | rhs,
+			BinExprOperator::BitwiseAnd | BinExprOperator::BoolAnd 	=> lhs & rhs,
+			BinExprOperator::BitwiseXor 		=> lhs ^ rhs,
+
+			// The processor uses only the low 5 bits of the shift count (6 bits for 64 bit operands), and >> is a logical shift (shr)
+			BinExprOperator::BitwiseLeftShift 	=> lhs.wrapping_shl(Self::shift_count(rhs, bits)) & Self::mask(bits),
+			BinExprOperator::BitwiseRightShift 	=> (lhs & Self::mask(bits)) >> Self::shift_count(rhs, bits),
+
+			BinExprOperator::BoolEq 			=> (lhs == rhs) as i128,
+			BinExprOperator::BoolNotEq 			=> (lhs != rhs) as i128,
+			BinExprOperator::BoolGreater 		=> (lhs > rhs) as i128,
+			BinExprOperator::BoolLess 			=> (lhs < rhs) as i128,
+			BinExprOperator::BoolGreaterEq 		=> (lhs >= rhs) as i128,
+			BinExprOperator::BoolLessEq 		=> (lhs <= rhs) as i128,
+			_ 									=> return None,
+		};
+		return Some(Constant::Integer(result));
+	}
+
+	fn evaluate_float_operation(operator: BinExprOperator, lhs: f64, rhs: f64) -> Option<Constant>
+	{
+		return Some(match operator
+		{
+			BinExprOperator::Add 			=> Constant::Float(lhs + rhs),
+			BinExprOperator::Sub 			=> Constant::Float(lhs - rhs),
+			BinExprOperator::Mul 			=> Constant::Float(lhs * rhs),
+			BinExprOperator::Div 			=> Constant::Float(lhs / rhs),
+			BinExprOperator::BoolEq 		=> Constant::Integer((lhs == rhs) as i128),
+			BinExprOperator::BoolNotEq 		=> Constant::Integer((lhs != rhs) as i128),
+			BinExprOperator::BoolGreater 	=> Constant::Integer((lhs > rhs) as i128),
+			BinExprOperator::BoolLess 		=> Constant::Integer((lhs < rhs) as i128),
+			BinExprOperator::BoolGreaterEq 	=> Constant::Integer((lhs >= rhs) as i128),
+			BinExprOperator::BoolLessEq 	=> Constant::Integer((lhs <= rhs) as i128),
+			_ 								=> return None,
+		});
+	}
+
+	// The constant value of an operand, if it has one and its data type is a number (and not an address)
+	fn operand(part: &BinExprPart) -> Option<Constant>
+	{
+		if !Self::is_arithmetic(part.data_type)
+		{
+			return None;
+		}
+
+		if let BinExprPartKind::Val(value) = &part.kind
+		{
+			return Constant::from_value(value);
+		}
+		return None;
+	}
+
+	fn is_arithmetic(data_type: Type) -> bool
+	{
+		return data_type.kind != TypeKind::Void && !data_type.is_pointer() && !data_type.is_function();
+	}
+
+	// The operators whose result can overflow. The result of the bitwise operators is the bits that fit in the data type.
+	fn is_arithmetic_operator(operator: BinExprOperator) -> bool
+	{
+		return matches!(
+			operator,
+			BinExprOperator::Add | BinExprOperator::Sub | BinExprOperator::Mul | BinExprOperator::Div | BinExprOperator::Modulo
+		);
+	}
+
+	pub fn fits_in_type(number: i128, data_type: Type) -> bool
+	{
+		let bits = data_type.size() as u32 * 8;
+		if data_type.is_signed()
+		{
+			return number >= -(1 << (bits - 1)) && number < (1 << (bits - 1));
+		}
+		return number >= 0 && number <= Self::mask(bits);
+	}
+
+	fn mask(bits: u32) -> i128
+	{
+		return (1 << bits) - 1;
+	}
+
+	fn shift_count(count: i128, bits: u32) -> u32
+	{
+		return (count & if bits == 64 { 63 } else { 31 }) as u32;
+	}
+}
@@ -0,0 +1,614 @@
+use crate::{ast::*, error::CompileError, lexer::*, print_errln, warnings::Warning};
+use super::{SemanticAnalyzer, variable::*};
+
+impl<'a> SemanticAnalyzer<'a>
+{
+	// Analyzes the expression, and checks that its data type can be used where the expected data type is required.
+	// The parts of the expression that have only constants are folded into their values. Returns the data type of the expression.
+	pub fn analyze_expression(&self, expression: &mut BinExpr, expected: Option<Type>, variables: &LocalVariables) -> Type
+	{
+		let data_type = self.analyze_bin_expr_part(&mut expression.root, expected, variables);
+		self.fold_constants(&mut expression.root);
+		return data_type;
+	}
+
+	// Returns the identifier of the constant if the expression part is a constant (declared with const), which has no address and cannot be assigned.
+	pub fn constant_identifier<'p>(part: &'p BinExprPart, variables: &LocalVariables) -> Option<&'p str>
+	{
+		if let BinExprPartKind::Val(Value::Ident(identifier)) = &part.kind
+		{
+			let variable = variables.get_variable(identifier)?;
+			if variable.attributes & attribute::CONSTANT != 0
+			{
+				return Some(identifier);
+			}
+		}
+		return None;
+	}
+
+	// Resolves the names in the expression part, and annotates it (and its sub expressions) with their data types.
+	pub fn analyze_bin_expr_part(&self, part: &mut BinExprPart, expected: Option<Type>, variables: &LocalVariables) -> Type
+	{
+		self.infer_bin_expr_part(part, expected, variables);
+		if let Some(expected) = expected
+		{
+			self.expect_type(part, expected);
+		}
+		return part.data_type;
+	}
+
+	// Like analyze_bin_expr_part, but the expected data type is only a hint (for literals) and is not checked.
+	fn infer_bin_expr_part(&self, part: &mut BinExprPart, expected: Option<Type>, variables: &LocalVariables) -> Type
+	{
+		// Taking the address of a function, &function
+		if let Some(index) = self.function_address(part, variables)
+		{
+			part.kind = BinExprPartKind::Val(Value::FuncAddress(index));
+		}
+
+		let data_type = match &mut part.kind
+		{
+			BinExprPartKind::Val(value) 				=> self.analyze_value(value, expected, variables, part.span),
+			BinExprPartKind::SelfOperation(operation) 	=> self.analyze_self_operation(operation, expected, variables),
+			BinExprPartKind::Operation(operation) 		=> self.analyze_operation(operation, expected, variables, part.span),
+			BinExprPartKind::TypeCast(info) 			=>
+			{
+				self.analyze_bin_expr_part(&mut info.expression, None, variables);
+				if info.into_type == Type::new(TypeKind::Void)
+				{
+					print_errln!(
+						CompileError::Syntax,
+						self.source,
+						part.span.start,
+						"Cannot cast to {} data type.", Type::new(TypeKind::Void).to_string()
+					);
+				}
+				info.into_type
+			},
+		};
+		part.data_type = data_type;
+
+		// Casting to the same data type does nothing, so the cast is replaced by the expression that is casted
+		if let BinExprPartKind::TypeCast(info) = &part.kind
+		{
+			if info.expression.data_type == info.into_type
+			{
+				self.warnings.warn(Warning::RedundantCast, part.span.end, "Type cast ignored, casting to the same data type.");
+				if let BinExprPartKind::TypeCast(info) = std::mem::replace(&mut part.kind, BinExprPartKind::Val(Value::IntLit(0)))
+				{
+					*part = info.expression;
+				}
+			}
+		}
+
+		return part.data_type;
+	}
+
+	fn expect_type(&self, part: &mut BinExprPart, expected: Type)
+	{
+		let data_type = part.data_type;
+		if !Self::implicit_cast(part, expected)
+		{
+			self.type_error(expected, data_type, part.span);
+		}
+	}
+
+	// Converts the expression part into the given data type, if it can be done implicitly. Returns false if it cant.
+	// The conversion is done with a type cast node, as if the cast was written in the source.
+	fn implicit_cast(part: &mut BinExprPart, into_type: Type) -> bool
+	{
+		if part.data_type == into_type
+		{
+			return true;
+		}
+
+		if !Self::is_widening(part.data_type, into_type)
+		{
+			return false;
+		}
+
+		let expression = std::mem::replace(part, BinExprPart::new(BinExprPartKind::Val(Value::IntLit(0)), part.span));
+		*part = Self::typed_cast(expression, into_type);
+		return true;
+	}
+
+	// Returns true if every value of the from data type can be represented in the into data type.
+	// Integers can be widened into integers of the same signedness, and f32 into f64.
+	fn is_widening(from_type: Type, into_type: Type) -> bool
+	{
+		if from_type.kind == TypeKind::F32 && into_type.kind == TypeKind::F64
+		{
+			return true;
+		}
+
+		return
+			Self::is_arithmetic_integer(from_type) && Self::is_arithmetic_integer(into_type) &&
+			from_type.is_signed() == into_type.is_signed() && from_type.size() < into_type.size();
+	}
+
+	// Integers that arithmetic is done on, which are not addresses
+	fn is_arithmetic_integer(data_type: Type) -> bool
+	{
+		return data_type.is_integer() && !data_type.is_pointer() && !data_type.is_function();
+	}
+
+	// Returns the data type that both operands of a binary operator are converted into, or None if there is no such data type.
+	fn common_type(lhs_type: Type, rhs_type: Type) -> Option<Type>
+	{
+		if lhs_type == rhs_type || Self::is_widening(rhs_type, lhs_type)
+		{
+			return Some(lhs_type);
+		}
+
+		if Self::is_widening(lhs_type, rhs_type)
+		{
+			return Some(rhs_type);
+		}
+		return None;
+	}
+
+	fn type_error(&self, expected: Type, given: Type, span: TextSpan) -> !
+	{
+		if (Self::is_arithmetic_integer(expected) || !expected.is_integer()) && (Self::is_arithmetic_integer(given) || !given.is_integer()) &&
+			expected != Type::new(TypeKind::Void) && given != Type::new(TypeKind::Void)
+		{
+			print_errln!(
+				CompileError::TypeError(expected, given),
+				self.source,
+				span.start,
+				"Implicit conversions can only widen a value without changing its sign, use a type cast for other conversions."
+			);
+		}
+
+		if (expected.is_pointer() || expected.is_function()) != (given.is_pointer() || given.is_function()) &&
+			expected.is_integer() && given.is_integer()
+		{
+			print_errln!(
+				CompileError::TypeError(expected, given),
+				self.source,
+				span.start,
+				"Pointers and integers can only be converted into each other with a type cast."
+			);
+		}
+
+		if expected.is_function() && given.is_function()
+		{
+			print_errln!(
+				CompileError::TypeError(expected, given),
+				self.source,
+				span.start,
+				"Expected a function of type {}, but got a function of type {}.",
+				self.ir.function_types[expected.signature.index()], self.ir.function_types[given.signature.index()]
+			);
+		}
+		print_errln!(CompileError::TypeError(expected, given), self.source, span.start, "");
+	}
+
+	fn analyze_value(&self, value: &mut Value, expected: Option<Type>, variables: &LocalVariables, span: TextSpan) -> Type
+	{
+		match value
+		{
+			Value::I8(_)  => return Type::new(TypeKind::I8),
+			Value::U8(_)  => return Type::new(TypeKind::U8),
+			Value::I16(_) => return Type::new(TypeKind::I16),
+			Value::U16(_) => return Type::new(TypeKind::U16),
+			Value::I32(_) => return Type::new(TypeKind::I32),
+			Value::U32(_) => return Type::new(TypeKind::U32),
+			Value::I64(_) => return Type::new(TypeKind::I64),
+			Value::U64(_) => return Type::new(TypeKind::U64),
+			Value::F32(_) => return Type::new(TypeKind::F32),
+			Value::F64(_) => return Type::new(TypeKind::F64),
+
+			// A number literal takes the expected data type. If there is no expected data type it is the first of i32, i64 and u64 that it fits in.
+			Value::IntLit(number) =>
+			{
+				let number = *number;
+				let literal_type = Self::literal_type(number as u64);
+				let data_type = expected.unwrap_or(literal_type);
+
+				// The only number that is a pointer without a type cast is 0, the null pointer
+				if (data_type.is_pointer() || data_type.is_function()) && number != 0
+				{
+					print_errln!(
+						CompileError::TypeError(data_type, Type::new(TypeKind::I32)),
+						self.source,
+						span.start,
+						"Only 0 can be used as a pointer without a type cast."
+					);
+				}
+
+				// The lexer reads numbers up to u64::MAX, so the bits of the number are unsigned
+				if data_type.is_integer() && !data_type.is_pointer() && !data_type.is_function() && !Self::fits_in_type(number as u64 as i128, data_type)
+				{
+					print_errln!(
+						CompileError::TypeError(data_type, literal_type),
+						self.source,
+						span.start,
+						"The number {} does not fit in {data_type}, use a type cast to wrap it.", number as u64
+					);
+				}
+				*value = match data_type.kind
+				{
+					TypeKind::I8  		=> Value::I8(number as i8),
+					TypeKind::U8  		=> Value::U8(number as u8),
+					TypeKind::I16 		=> Value::I16(number as i16),
+					TypeKind::U16 		=> Value::U16(number as u16),
+					TypeKind::I32 		=> Value::I32(number as i32),
+					TypeKind::U32 		=> Value::U32(number as u32),
+					TypeKind::I64 		=> Value::I64(number),
+					TypeKind::U64 		=> Value::U64(number as u64),
+					TypeKind::Pointer	=> Value::U64(0),
+					TypeKind::Function	=> Value::U64(0),
+					TypeKind::F32 		=> Value::F32(number as f32),
+					TypeKind::F64 		=> Value::F64(number as f64),
+					_ => { print_errln!(CompileError::TypeError(data_type, Type::new(TypeKind::I32)), self.source, span.start, ""); }
+				};
+				return data_type;
+			},
+
+			Value::FloatLit(number) =>
+			{
+				let number = *number;
+				let data_type = expected.unwrap_or(Type::new(TypeKind::F64));
+				*value = match data_type.kind
+				{
+					TypeKind::F32 => Value::F32(number as f32),
+					TypeKind::F64 => Value::F64(number),
+					_ => { print_errln!(CompileError::TypeError(data_type, Type::new(TypeKind::F32)), self.source, span.start, ""); }
+				};
+				return data_type;
+			},
+
+			Value::Ident(identifier) =>
+			{
+				let variable = variables.get_variable(identifier).unwrap_or_else(|| {
+					if self.func_manager.get(identifier).is_some()
+					{
+						print_errln!(
+							CompileError::UnknownIdentifier(identifier),
+							self.source,
+							span.start,
+							"\"{identifier}\" is a function, use the address-of operator ( & ) to get its address."
+						);
+					}
+					print_errln!(CompileError::UnknownIdentifier(identifier), self.source, span.start, "");
+				});
+				let data_type = variable.data_type;
+
+				// A constant is replaced by its value
+				if let Some(constant) = variables.get_constant(variable.index)
+				{
+					*value = constant.clone();
+				} else
+				{
+					*value = Value::Var(variable.index);
+				}
+				return data_type;
+			},
+
+			Value::Var(index) => return variables.get_variable_by_index(*index).unwrap().data_type,
+
+			Value::Dereference(info) =>
+			{
+				let pointer_type = self.analyze_expression(&mut info.expression, None, variables);
+				if info.dereference_count > pointer_type.pointer_level()
+				{
+					let mut expected_type = pointer_type.dereference(pointer_type.pointer_level());
+					for _ in 0..info.dereference_count
+					{
+						expected_type = Type::new_ptr(expected_type);
+					}
+					print_errln!(
+						CompileError::TypeError(expected_type, pointer_type),
+						self.source,
+						span.start,
+						"Trying to dereference {} times a data type of {}.", info.dereference_count, pointer_type.to_string()
+					);
+				}
+				return pointer_type.dereference(info.dereference_count);
+			},
+
+			Value::FuncCall(function_call) =>
+			{
+				let return_type = self.analyze_function_call(function_call, variables);
+				if return_type == Type::new(TypeKind::Void)
+				{
+					print_errln!(
+						CompileError::TypeError(expected.unwrap_or(Type::new(TypeKind::I32)), return_type),
+						self.source,
+						span.start,
+						"The function returns \"{KEYWORD_VOID}\", so its result cannot be used in an expression."
+					);
+				}
+				return return_type;
+			},
+
+			Value::FuncAddress(index) => return Type::new_function(self.func_manager.get_by_index(*index).unwrap().signature),
+		}
+	}
+
+	// If the expression part takes the address of a function (and not of a variable), returns the index of the function.
+	fn function_address(&self, part: &BinExprPart, variables: &LocalVariables) -> Option<FunctionId>
+	{
+		if let BinExprPartKind::SelfOperation(operation) = &part.kind
+		{
+			if operation.operator != BinExprOperator::AddressOf
+			{
+				return None;
+			}
+
+			if let BinExprPartKind::Val(Value::Ident(identifier)) = &operation.expression.kind
+			{
+				if variables.get_variable(identifier).is_some()
+				{
+					return None;
+				}
+				return Some(self.func_manager.get(identifier)?.index);
+			}
+		}
+		return None;
+	}
+
+	fn analyze_self_operation(&self, operation: &mut BinExprSelfOperation, expected: Option<Type>, variables: &LocalVariables) -> Type
+	{
+		match operation.operator
+		{
+			// &x is a pointer to the data type of x, so the address of a *i32 is a **i32
+			BinExprOperator::AddressOf =>
+			{
+				if let Some(identifier) = Self::constant_identifier(&operation.expression, variables)
+				{
+					print_errln!(CompileError::Syntax, self.source, operation.expression.span.start, "Cannot take the address of the constant \"{identifier}\".");
+				}
+				return Type::new_ptr(self.analyze_bin_expr_part(&mut operation.expression, None, variables));
+			},
+
+			BinExprOperator::BitwiseNot =>
+			{
+				let data_type = self.analyze_bin_expr_part(&mut operation.expression, expected, variables);
+				self.check_integer_operator(operation.operator, data_type, operation.expression.span);
+				return data_type;
+			},
+			BinExprOperator::BoolNot => return self.analyze_bin_expr_part(&mut operation.expression, expected, variables),
+			_ => panic!("Dev error! analyze_self_operation() called with a two-side operator. {:#?}", operation),
+		}
+	}
+
+	fn analyze_operation(&self, operation: &mut BinExprOperation, expected: Option<Type>, variables: &LocalVariables, span: TextSpan) -> Type
+	{
+		let operator = operation.operator;
+
+		// The sides of a && or a || are separate conditions, so each side has its own data type. For example: if 5 > 6 && 1.420 < 2.5
+		if operator == BinExprOperator::BoolAnd || operator == BinExprOperator::BoolOr
+		{
+			let lhs_type = self.analyze_bin_expr_part(&mut operation.lhs, None, variables);
+			let rhs_type = self.analyze_bin_expr_part(&mut operation.rhs, None, variables);
+			return self.convert_operands(operation, lhs_type, rhs_type, span);
+		}
+
+		// The result of a comparison is a u8, so the data type of its operands is not related to the expected data type
+		let operand_type = if operator.is_boolean() { None } else { expected };
+
+		// Without an expected data type, an expression that has only integer literals takes the data type of its largest literal.
+		// For example: 5 < 5000000000 compares i64 values
+		let operand_type = operand_type.or_else(|| {
+			if !Self::is_untyped(&operation.lhs) || !Self::is_untyped(&operation.rhs)
+			{
+				return None;
+			}
+			let largest = Self::largest_literal(&operation.lhs)?.max(Self::largest_literal(&operation.rhs)?);
+			return Some(Self::literal_type(largest));
+		});
+
+		// A side that has only literals takes the data type of the other side. For example: x + 1
+		if Self::is_untyped(&operation.lhs) && !Self::is_untyped(&operation.rhs)
+		{
+			let rhs_type = self.analyze_bin_expr_part(&mut operation.rhs, operand_type, variables);
+			self.check_function_pointer_operator(operator, rhs_type, span);
+			let lhs_type = self.analyze_bin_expr_part(&mut operation.lhs, Some(rhs_type), variables);
+			return self.convert_operands(operation, lhs_type, rhs_type, span);
+		}
+
+		// The expected data type is not checked on a pointer, because the difference between two pointers is an i64. (let distance i64 = end - start)
+		let lhs_type = self.infer_bin_expr_part(&mut operation.lhs, operand_type, variables);
+		if lhs_type.is_pointer() && !operator.is_boolean()
+		{
+			return self.analyze_pointer_arithmetic(operation, lhs_type, variables, span);
+		}
+
+		if let Some(operand_type) = operand_type
+		{
+			self.expect_type(&mut operation.lhs, operand_type);
+		}
+		let lhs_type = operation.lhs.data_type;
+		self.check_function_pointer_operator(operator, lhs_type, span);
+
+		let rhs_type;
+		if Self::is_untyped(&operation.rhs)
+		{
+			rhs_type = self.analyze_bin_expr_part(&mut operation.rhs, Some(lhs_type), variables);
+		} else
+		{
+			rhs_type = self.analyze_bin_expr_part(&mut operation.rhs, None, variables);
+			self.check_function_pointer_operator(operator, rhs_type, span);
+		}
+		return self.convert_operands(operation, lhs_type, rhs_type, span);
+	}
+
+	// Offsetting a pointer (pointer + integer, pointer - integer) or subtracting two pointers. (pointer - pointer)
+	// Both are counted in elements, so the offset is multiplied by the size of the data type that the pointer points to,
+	// and the difference between the pointers is divided by it.
+	fn analyze_pointer_arithmetic(&self, operation: &mut BinExprOperation, lhs_type: Type, variables: &LocalVariables, span: TextSpan) -> Type
+	{
+		let operator = operation.operator;
+		if operator != BinExprOperator::Add && operator != BinExprOperator::Sub
+		{
+			print_errln!(CompileError::Syntax, self.source, span.start, "Can only offset pointers by addition or subtraction.");
+		}
+
+		let offset_type = Type::new(TypeKind::I64);
+		let element_size = lhs_type.dereference(1).size() as i64;
+		if element_size == 0
+		{
+			print_errln!(
+				CompileError::TypeError(Type::new_ptr(Type::new(TypeKind::U8)), lhs_type),
+				self.source,
+				span.start,
+				"Cannot offset or subtract pointers to void, because void has no size. Cast the pointer to *u8 first."
+			);
+		}
+		let rhs_type = if Self::is_untyped(&operation.rhs)
+		{
+			self.analyze_bin_expr_part(&mut operation.rhs, Some(offset_type), variables)
+		} else
+		{
+			self.analyze_bin_expr_part(&mut operation.rhs, None, variables)
+		};
+
+		if rhs_type.is_pointer()
+		{
+			if operator != BinExprOperator::Sub || rhs_type != lhs_type
+			{
+				print_errln!(
+					CompileError::TypeError(lhs_type, rhs_type),
+					self.source,
+					span.start,
+					"Can only subtract a pointer from a pointer of the same data type."
+				);
+			}
+
+			let lhs = Self::typed_cast(std::mem::replace(&mut operation.lhs, Self::typed_value(Value::I64(0), offset_type, span)), offset_type);
+			let rhs = Self::typed_cast(std::mem::replace(&mut operation.rhs, Self::typed_value(Value::I64(0), offset_type, span)), offset_type);
+			if element_size == 1
+			{
+				operation.lhs = lhs;
+				operation.rhs = rhs;
+				return offset_type;
+			}
+
+			operation.lhs = BinExprPart::new(BinExprPartKind::Operation(Box::new(BinExprOperation::new(BinExprOperator::Sub, lhs, rhs))), span);
+			operation.lhs.data_type = offset_type;
+			operation.rhs = Self::typed_value(Value::I64(element_size), offset_type, span);
+			operation.operator = BinExprOperator::Div;
+			return offset_type;
+		}
+
+		if !Self::is_arithmetic_integer(rhs_type)
+		{
+			print_errln!(CompileError::TypeError(offset_type, rhs_type), self.source, operation.rhs.span.start, "A pointer can only be offset by an integer.");
+		}
+
+		let offset_span = operation.rhs.span;
+		let offset = std::mem::replace(&mut operation.rhs, Self::typed_value(Value::I64(0), offset_type, offset_span));
+		operation.rhs = BinExprPart::new(
+			BinExprPartKind::Operation(Box::new(BinExprOperation::new(
+				BinExprOperator::Mul,
+				Self::typed_cast(offset, offset_type),
+				Self::typed_value(Value::I64(element_size), offset_type, offset_span)
+			))),
+			offset_span
+		);
+		operation.rhs.data_type = offset_type;
+		return lhs_type;
+	}
+
+	fn typed_value(value: Value, data_type: Type, span: TextSpan) -> BinExprPart
+	{
+		let mut part = BinExprPart::new(BinExprPartKind::Val(value), span);
+		part.data_type = data_type;
+		return part;
+	}
+
+	// Casts the expression part into the given data type, unless it already is of that data type.
+	fn typed_cast(expression: BinExprPart, into_type: Type) -> BinExprPart
+	{
+		if expression.data_type == into_type
+		{
+			return expression;
+		}
+
+		let span = expression.span;
+		let mut part = BinExprPart::new(BinExprPartKind::TypeCast(Box::new(TypeCastInfo::new(into_type, expression))), span);
+		part.data_type = into_type;
+		return part;
+	}
+
+	// Converts both operands into their common data type (the usual arithmetic conversions), and returns the data type of the result.
+	fn convert_operands(&self, operation: &mut BinExprOperation, lhs_type: Type, rhs_type: Type, span: TextSpan) -> Type
+	{
+		let common_type = Self::common_type(lhs_type, rhs_type).unwrap_or_else(|| {
+			print_errln!(
+				CompileError::TypeError(lhs_type, rhs_type),
+				self.source,
+				span.start,
+				"The operands of a binary operator must have the same data type, or one must be implicitly convertible into the other."
+			);
+		});
+		self.check_integer_operator(operation.operator, common_type, span);
+		Self::implicit_cast(&mut operation.lhs, common_type);
+		Self::implicit_cast(&mut operation.rhs, common_type);
+
+		// Comparisons set AL, while && and || are done on the operands data type.
+		if operation.operator.is_boolean() && operation.operator != BinExprOperator::BoolAnd && operation.operator != BinExprOperator::BoolOr
+		{
+			return Type::new(TypeKind::U8);
+		}
+		return common_type;
+	}
+
+	fn check_integer_operator(&self, operator: BinExprOperator, data_type: Type, span: TextSpan)
+	{
+		if operator.is_integer_only() && !data_type.is_integer()
+		{
+			print_errln!(
+				CompileError::TypeError(Type::new(TypeKind::I32), data_type),
+				self.source,
+				span.start,
+				"The operators %, &, |, ^, <<, >> and ~ can only be used on integers."
+			);
+		}
+	}
+
+	fn check_function_pointer_operator(&self, operator: BinExprOperator, data_type: Type, span: TextSpan)
+	{
+		if data_type.is_function() && operator != BinExprOperator::BoolEq && operator != BinExprOperator::BoolNotEq
+		{
+			print_errln!(CompileError::Syntax, self.source, span.start, "Function pointers can only be compared with == and !=.");
+		}
+	}
+
+	// Returns true if the expression part has only literals, which dont have a data type of their own. 5 * (2 + 10)
+	// The data type of an integer literal without an expected data type, the first of i32, i64 and u64 that it fits in
+	fn literal_type(number: u64) -> Type
+	{
+		return [TypeKind::I32, TypeKind::I64, TypeKind::U64].into_iter()
+			.map(Type::new)
+			.find(|data_type| Self::fits_in_type(number as i128, *data_type))
+			.unwrap();
+	}
+
+	// The largest integer literal of an expression that has only literals, or None if it has a float literal
+	fn largest_literal(part: &BinExprPart) -> Option<u64>
+	{
+		return match &part.kind
+		{
+			BinExprPartKind::Val(Value::IntLit(number)) 	=> Some(*number as u64),
+			BinExprPartKind::Operation(operation) 			=> Some(Self::largest_literal(&operation.lhs)?.max(Self::largest_literal(&operation.rhs)?)),
+			BinExprPartKind::SelfOperation(operation) 		=> Self::largest_literal(&operation.expression),
+			_ 												=> None,
+		};
+	}
+
+	fn is_untyped(part: &BinExprPart) -> bool
+	{
+		return match &part.kind
+		{
+			BinExprPartKind::Val(Value::IntLit(_)) | BinExprPartKind::Val(Value::FloatLit(_)) 	=> true,
+			BinExprPartKind::Operation(operation) 												=>
+				!operation.operator.is_boolean() && Self::is_untyped(&operation.lhs) && Self::is_untyped(&operation.rhs),
+			BinExprPartKind::SelfOperation(operation) 											=>
+				operation.operator != BinExprOperator::AddressOf && Self::is_untyped(&operation.expression),
+			_ 																					=> false,
+		};
+	}
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use crate::{ast::*, error::CompileError, lexer::KEYWORD_VOID, print_errln};
+use super::{SemanticAnalyzer, variable::*};
+
+pub struct FunctionManager
+{
+	functions: Vec<Function>,						/* Ordered by the functions indices */
+	indices: HashMap<String, FunctionId>,
+}
+
+impl FunctionManager
+{
+	pub fn new() -> Self
+	{
+		return Self {
+			functions: Vec::new(),
+			indices: HashMap::new(),
+		};
+	}
+
+	// Returns the index of the new function
+	pub fn add(&mut self, mut function: Function) -> FunctionId
+	{
+		let index = FunctionId(self.functions.len() as u32);
+		function.index = index;
+		self.indices.insert(function.identifier.clone(), index);
+		self.functions.push(function);
+		return index;
+	}
+
+	pub fn get(&self, identifier: &str) -> Option<&Function>
+	{
+		let index = self.indices.get(identifier)?;
+		return self.get_by_index(*index);
+	}
+
+	pub fn get_by_index(&self, index: FunctionId) -> Option<&Function>
+	{
+		return self.functions.get(index.index());
+	}
+
+	pub fn get_by_index_mut(&mut self, index: FunctionId) -> Option<&mut Function>
+	{
+		return self.functions.get_mut(index.index());
+	}
+
+	pub fn len(&self) -> usize
+	{
+		return self.functions.len();
+	}
+
+	pub fn into_function_array(self) -> Vec<Function>
+	{
+		return self.functions;
+	}
+}
+
+impl<'a> SemanticAnalyzer<'a>
+{
+	pub fn analyze_function(&mut self, index: FunctionId)
+	{
+		let function = self.func_manager.get_by_index_mut(index).unwrap();
+		let mut parameters = std::mem::take(&mut function.parameters);
+		let mut code_block = std::mem::replace(&mut function.code_block, Scope::new(Vec::new()));
+		let return_type = function.return_type;
+
+		// The parameters and the functions body are in the same scope.
+		let mut variables = LocalVariables::new();
+		variables.start_scope();
+		for parameter in &mut parameters
+		{
+			parameter.index = variables.add_variable(parameter.identifier.clone(), attribute::FUNCTION_PARAMETER, parameter.data_type).index;
+		}
+
+		for statement in &mut code_block.statements
+		{
+			self.analyze_statement(statement, &mut variables, return_type);
+		}
+		variables.end_scope();
+
+		if self.func_manager.get_by_index(index).unwrap().attributes & attribute::TAIL_RECURSIVE != 0
+		{
+			self.check_tail_calls(index, &code_block.statements, true);
+		}
+
+		let function = self.func_manager.get_by_index_mut(index).unwrap();
+		function.parameters = parameters;
+		function.code_block = code_block;
+		function.locals = variables.get_variables();
+	}
+
+	// Resolves the called function (a function or a function pointer) and checks the arguments against its parameters.
+	// Returns the return type of the function.
+	pub fn analyze_function_call(&self, function_call: &mut FunctionCallInfo, variables: &LocalVariables) -> Type
+	{
+		if let Callee::Ident(identifier) = &function_call.callee
+		{
+			let identifier = identifier.clone();
+			if let Some(variable) = variables.get_variable(&identifier)
+			{
+				if !variable.data_type.is_function()
+				{
+					print_errln!(
+						CompileError::Syntax,
+						self.source,
+						function_call.span.start,
+						"\"{identifier}\" is a variable of type {}, which is not a function pointer.", variable.data_type
+					);
+				}
+
+				let mut callee = BinExprPart::new(BinExprPartKind::Val(Value::Var(variable.index)), function_call.span);
+				callee.data_type = variable.data_type;
+				function_call.callee = Callee::Indirect(Box::new(BinExpr::new(callee)));
+				function_call.signature = variable.data_type.signature;
+			} else
+			{
+				let function = self.func_manager.get(&identifier).unwrap_or_else(|| {
+					print_errln!(CompileError::UnknownIdentifier(&identifier), self.source, function_call.span.start, "No such function.");
+				});
+				function_call.callee = Callee::Direct(function.index);
+				function_call.signature = function.signature;
+			}
+		}
+
+		let identifier = &self.source[function_call.span.start..function_call.span.end];
+		let function_type = &self.ir.function_types[function_call.signature.index()];
+		let parameter_count = function_type.parameters.len();
+		let argument_count = function_call.arguments.len();
+		if argument_count < parameter_count || (function_type.attributes & attribute::VARIADIC == 0 && argument_count != parameter_count)
+		{
+			print_errln!(
+				CompileError::Syntax,
+				self.source,
+				function_call.span.start,
+				"The function \"{identifier}\" takes {parameter_count} parameters but {argument_count} were given."
+			);
+		}
+
+		// The variadic arguments dont have a parameter to take the data type from, so they are typed from their expressions.
+		for (i, argument) in function_call.arguments.iter_mut().enumerate()
+		{
+			if i < parameter_count
+			{
+				self.analyze_expression(argument, Some(function_type.parameters[i]), variables);
+			} else
+			{
+				let data_type = self.analyze_expression(argument, None, variables);
+				if data_type == Type::new(TypeKind::Void)
+				{
+					print_errln!(
+						CompileError::TypeError(Type::new(TypeKind::I32), data_type),
+						self.source,
+						argument.root.span.start,
+						"A variadic argument cannot be of type \"{KEYWORD_VOID}\"."
+					);
+				}
+				Self::promote_variadic_argument(argument);
+			}
+		}
+
+		return function_type.return_type;
+	}
+
+	// Applies the default argument promotions of C on an argument that was passed as a variadic argument.
+	// Floats are promoted into doubles, and integers smaller than 32 bits are promoted into 32 bit integers.
+	fn promote_variadic_argument(argument: &mut BinExpr)
+	{
+		let data_type = argument.root.data_type;
+		let promoted_type = match data_type.kind
+		{
+			TypeKind::F32 					=> Type::new(TypeKind::F64),
+			TypeKind::I8  | TypeKind::I16 	=> Type::new(TypeKind::I32),
+			TypeKind::U8  | TypeKind::U16 	=> Type::new(TypeKind::U32),
+			_ 								=> return,
+		};
+
+		let span = argument.root.span;
+		let expression = std::mem::replace(&mut argument.root, BinExprPart::new(BinExprPartKind::Val(Value::IntLit(0)), span));
+		argument.root = BinExprPart::new(BinExprPartKind::TypeCast(Box::new(TypeCastInfo::new(promoted_type, expression))), span);
+		argument.root.data_type = promoted_type;
+	}
+}
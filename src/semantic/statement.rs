@@ -0,0 +1,138 @@
+use crate::{ast::*, error::CompileError, lexer::*, print_errln};
+use super::{SemanticAnalyzer, variable::*};
+
+impl<'a> SemanticAnalyzer<'a>
+{
+	pub fn analyze_statement(&self, statement: &mut Statement, variables: &mut LocalVariables, return_type: Type)
+	{
+		match statement
+		{
+			Statement::Scope(scope) 				=> self.analyze_scope(scope, variables, return_type),
+			Statement::VarDecl(var_decl) 			=> self.analyze_var_decl(var_decl, variables),
+			Statement::Assign(assign) 				=> self.analyze_var_update(assign, variables),
+			Statement::FunctionCall(function_call) 	=> { self.analyze_function_call(function_call, variables); },
+			Statement::Return(return_info) 			=> self.analyze_return_stmt(return_info, variables, return_type),
+			Statement::If(if_info) 					=> self.analyze_if_stmt(if_info, variables, return_type),
+			Statement::For(for_info) 				=> self.analyze_for_stmt(for_info, variables, return_type),
+		}
+	}
+
+	fn analyze_scope(&self, scope: &mut Scope, variables: &mut LocalVariables, return_type: Type)
+	{
+		variables.start_scope();
+		for statement in &mut scope.statements
+		{
+			self.analyze_statement(statement, variables, return_type);
+		}
+		variables.end_scope();
+	}
+
+	fn analyze_var_decl(&self, var_decl: &mut VarDeclInfo, variables: &mut LocalVariables)
+	{
+		// The initial value is analyzed before the variable is declared, so the variable cannot be used inside its own declaration.
+		// If the data type was not written, it is the data type of the initial value. (Integer literals are i32, float literals are f64)
+		if var_decl.data_type == Type::new(TypeKind::Void)
+		{
+			let value = var_decl.value.as_mut().unwrap_or_else(|| {
+				print_errln!(CompileError::Syntax, self.source, var_decl.span.end, "Cannot infer the data type of a variable without an initial value.");
+			});
+			var_decl.data_type = self.analyze_expression(value, None, variables);
+		} else if let Some(value) = &mut var_decl.value
+		{
+			self.analyze_expression(value, Some(var_decl.data_type), variables);
+		}
+
+		var_decl.index = variables.add_variable(var_decl.identifier.clone(), var_decl.attributes, var_decl.data_type).index;
+
+		// The value of a constant is known while compiling, so it is used instead of the constant
+		if var_decl.attributes & attribute::CONSTANT != 0
+		{
+			let value = &var_decl.value.as_ref().unwrap().root;
+			match &value.kind
+			{
+				BinExprPartKind::Val(constant) if constant.is_constant() => variables.set_constant(var_decl.index, constant.clone()),
+				_ =>
+				{
+					print_errln!(CompileError::Syntax, self.source, value.span.start, "The value of a constant must be known while compiling.");
+				},
+			}
+		}
+	}
+
+	fn analyze_var_update(&self, assign: &mut VarUpdateInfo, variables: &mut LocalVariables)
+	{
+		if let Some(identifier) = Self::constant_identifier(&assign.destination, variables)
+		{
+			print_errln!(CompileError::Syntax, self.source, assign.destination.span.start, "Cannot assign into the constant \"{identifier}\".");
+		}
+
+		let data_type = self.analyze_bin_expr_part(&mut assign.destination, None, variables);
+		self.analyze_expression(&mut assign.value, Some(data_type), variables);
+	}
+
+	fn analyze_return_stmt(&self, return_info: &mut ReturnInfo, variables: &mut LocalVariables, return_type: Type)
+	{
+		match &mut return_info.value
+		{
+			Some(value) =>
+			{
+				if return_type == Type::new(TypeKind::Void)
+				{
+					print_errln!(
+						CompileError::Syntax,
+						self.source,
+						value.root.span.start,
+						"The function returns \"{KEYWORD_VOID}\", so {KEYWORD_RETURN} cannot have a value."
+					);
+				}
+				self.analyze_expression(value, Some(return_type), variables);
+			},
+			None =>
+			{
+				if return_type != Type::new(TypeKind::Void)
+				{
+					print_errln!(
+						CompileError::TypeError(return_type, Type::new(TypeKind::Void)),
+						self.source,
+						return_info.span.end,
+						"Expected a return value."
+					);
+				}
+			},
+		}
+	}
+
+	fn analyze_if_stmt(&self, if_info: &mut IfInfo, variables: &mut LocalVariables, return_type: Type)
+	{
+		self.analyze_expression(&mut if_info.condition, None, variables);
+		self.analyze_statement(&mut if_info.then_block, variables, return_type);
+		if let Some(else_block) = &mut if_info.else_block
+		{
+			self.analyze_statement(else_block, variables, return_type);
+		}
+	}
+
+	fn analyze_for_stmt(&self, for_info: &mut ForLoopInfo, variables: &mut LocalVariables, return_type: Type)
+	{
+		// Start a scope before the initializer (because it is most likely to be a variable declaration)
+		variables.start_scope();
+
+		if let Some(initializer) = &mut for_info.initializer
+		{
+			self.analyze_statement(initializer, variables, return_type);
+		}
+
+		if let Some(condition) = &mut for_info.condition
+		{
+			self.analyze_expression(condition, None, variables);
+		}
+
+		if let Some(update) = &mut for_info.update
+		{
+			self.analyze_statement(update, variables, return_type);
+		}
+
+		self.analyze_statement(&mut for_info.code_block, variables, return_type);
+		variables.end_scope();
+	}
+}
@@ -0,0 +1,125 @@
+use crate::{ast::*, error::CompileError, lexer::KEYWORD_TAILREC, print_errln};
+use super::SemanticAnalyzer;
+
+impl<'a> SemanticAnalyzer<'a>
+{
+	// Checks that the function (which is tailrec) can call itself with a jump, at every optimization level.
+	// Each recursive call must be the value of a return statement (return f(...);), or for functions that return void,
+	// a call statement that is followed by a return statement or that is the last statement of the function.
+	// The frame of the function is reused by the recursive calls, so the address of its variables cannot be taken.
+	pub fn check_tail_calls(&self, function: FunctionId, statements: &[Statement], is_function_body: bool)
+	{
+		for (i, statement) in statements.iter().enumerate()
+		{
+			match statement
+			{
+				Statement::Scope(scope) => self.check_tail_calls(function, &scope.statements, false),
+				Statement::If(if_info) =>
+				{
+					self.check_tail_call_expression(function, &if_info.condition.root);
+					self.check_tail_calls(function, std::slice::from_ref(&if_info.then_block), false);
+					if let Some(else_block) = &if_info.else_block
+					{
+						self.check_tail_calls(function, std::slice::from_ref(else_block), false);
+					}
+				},
+				Statement::For(for_info) =>
+				{
+					if let Some(initializer) = &for_info.initializer
+					{
+						self.check_tail_calls(function, std::slice::from_ref(initializer), false);
+					}
+					if let Some(condition) = &for_info.condition
+					{
+						self.check_tail_call_expression(function, &condition.root);
+					}
+					if let Some(update) = &for_info.update
+					{
+						self.check_tail_calls(function, std::slice::from_ref(update), false);
+					}
+					self.check_tail_calls(function, std::slice::from_ref(&for_info.code_block), false);
+				},
+				Statement::VarDecl(var_decl) =>
+				{
+					if let Some(value) = &var_decl.value
+					{
+						self.check_tail_call_expression(function, &value.root);
+					}
+				},
+				Statement::Assign(assign) =>
+				{
+					self.check_tail_call_expression(function, &assign.destination);
+					self.check_tail_call_expression(function, &assign.value.root);
+				},
+				Statement::FunctionCall(function_call) =>
+				{
+					let is_tail_call = match statements.get(i + 1)
+					{
+						Some(Statement::Return(ReturnInfo { value: None, .. })) => true,
+						None 													=> is_function_body,
+						_ 														=> false,
+					};
+					self.check_tail_call(function, function_call, is_tail_call);
+				},
+				Statement::Return(return_info) => match return_info.value.as_ref().map(|value| &value.root.kind)
+				{
+					Some(BinExprPartKind::Val(Value::FuncCall(function_call))) 	=> self.check_tail_call(function, function_call, true),
+					Some(_) 													=> self.check_tail_call_expression(function, &return_info.value.as_ref().unwrap().root),
+					None 														=> (),
+				},
+			}
+		}
+	}
+
+	fn check_tail_call(&self, function: FunctionId, function_call: &FunctionCallInfo, is_tail_call: bool)
+	{
+		match &function_call.callee
+		{
+			Callee::Direct(callee) if *callee == function && !is_tail_call =>
+			{
+				print_errln!(
+					CompileError::TailCall,
+					self.source,
+					function_call.span.start,
+					"A {KEYWORD_TAILREC} function can call itself only as the value of a return statement."
+				);
+			},
+			Callee::Indirect(callee) => self.check_tail_call_expression(function, &callee.root),
+			_ => (),
+		}
+
+		for argument in &function_call.arguments
+		{
+			self.check_tail_call_expression(function, &argument.root);
+		}
+	}
+
+	fn check_tail_call_expression(&self, function: FunctionId, part: &BinExprPart)
+	{
+		match &part.kind
+		{
+			BinExprPartKind::Val(Value::FuncCall(function_call)) 	=> self.check_tail_call(function, function_call, false),
+			BinExprPartKind::Val(Value::Dereference(info)) 		=> self.check_tail_call_expression(function, &info.expression.root),
+			BinExprPartKind::Val(_) 								=> (),
+			BinExprPartKind::SelfOperation(operation) =>
+			{
+				if operation.operator == BinExprOperator::AddressOf && matches!(operation.expression.kind, BinExprPartKind::Val(Value::Var(_)))
+				{
+					print_errln!(
+						CompileError::TailCall,
+						self.source,
+						part.span.start,
+						"The address of a variable cannot be taken in a {KEYWORD_TAILREC} function, because its recursive calls reuse its stack frame."
+					);
+				}
+				self.check_tail_call_expression(function, &operation.expression);
+			},
+			BinExprPartKind::Operation(operation) =>
+			{
+				self.check_tail_call_expression(function, &operation.lhs);
+				self.check_tail_call_expression(function, &operation.rhs);
+			},
+			BinExprPartKind::TypeCast(cast) => self.check_tail_call_expression(function, &cast.expression),
+		}
+	}
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use crate::{ast::{*, attribute::AttributeType}, print_err, CompileError};
+
+pub struct LocalVariables
+{
+	next_scope_idx: u32,
+	variables: HashMap<String, Vec<Variable>>,
+	variables_arr: Vec<Variable>,
+	constants: HashMap<VariableId, Value>,		// The values of the variables that were declared with const
+}
+
+impl LocalVariables
+{
+	pub fn new() -> Self
+	{
+		return Self {
+			next_scope_idx: 0,
+			variables: HashMap::new(),
+			variables_arr: Vec::new(),
+			constants: HashMap::new(),
+		};
+	}
+
+	pub fn add_variable(&mut self, identifier: String, attributes: AttributeType, data_type: Type) -> Variable
+	{
+		let scope = if attributes & attribute::FUNCTION_PARAMETER != 0 { ScopeId(0) } else { self.current_scope() };
+		
+		let variable = Variable::new(data_type, attributes, VariableId(self.variables_arr.len() as u32), scope);
+
+		if let Some(vars) = self.variables.get_mut(&identifier)
+		{
+			if vars[vars.len() - 1].scope == scope
+			{
+				print_err!(CompileError::Syntax, "Variable \"{identifier}\" was already declared in the current scope.");
+			}
+			vars.push(variable);
+		} else
+		{
+			self.variables.insert(identifier, Vec::from([variable]));
+		}
+		self.variables_arr.push(variable);
+		return variable;
+	}
+
+	pub fn set_constant(&mut self, index: VariableId, value: Value)
+	{
+		self.constants.insert(index, value);
+	}
+
+	pub fn get_constant(&self, index: VariableId) -> Option<&Value>
+	{
+		return self.constants.get(&index);
+	}
+
+	pub fn get_variable(&self, identifier: &str) -> Option<&Variable>
+	{
+		if let Some(vars) = self.variables.get(identifier)
+		{
+			return Some(&vars[vars.len() - 1]);
+		}
+		return None;
+	}
+
+	pub fn start_scope(&mut self)
+	{
+		self.advance_scope();
+	}
+
+	pub fn end_scope(&mut self)
+	{
+		for (identifier, vars) in self.variables.clone().into_iter()
+		{
+			for (i, variable) in vars.iter().enumerate()
+			{
+				if variable.scope >= self.current_scope()
+				{
+					if i == 0
+					{
+						self.variables.remove(&identifier);
+					} else
+					{
+						let vars = self.variables.get_mut(&identifier).unwrap();
+						vars.drain(i..vars.len());
+						break;
+					}
+				}
+			}
+		}
+		self.next_scope_idx -= 1;
+	}
+
+	pub fn get_variable_by_index(&self, index: VariableId) -> Option<&Variable>
+	{
+		let variable = &self.variables_arr[index.index()];
+		if variable.scope > self.current_scope()
+		{
+			return None;
+		}
+		return Some(variable);
+	}
+
+	pub fn get_variables(self) -> Vec<Variable>
+	{
+		return self.variables_arr;
+	}
+
+	fn current_scope(&self) -> ScopeId
+	{
+		return ScopeId(self.next_scope_idx - 1);
+	}
+
+	fn advance_scope(&mut self)
+	{
+		self.next_scope_idx += 1;
+	}
+}
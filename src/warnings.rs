@@ -0,0 +1,336 @@
+mod variables;
+mod control_flow;
+mod initialization;
+
+use std::cell::Cell;
+use crate::{ast::*, error::CompileError, print_err, print_errln_no_exit, print_wrnln};
+
+// The warnings that slowc reports. Each one can be enabled with -W<name> and disabled with -Wno-<name>
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Warning
+{
+	UnusedVariable,
+	UnusedParameter,
+	UnusedAssignment,		/* A value that is assigned into a variable and is never read */
+	UnreachableCode,
+	MissingReturn,			/* A function that returns a value, and may reach its end without returning */
+	ConstantCondition,
+	RedundantCast,			/* Casting into the same data type */
+	Uninitialized,			/* Reading a variable that may not have a value yet, an error by default */
+	Overflow,				/* A signed constant expression whose result does not fit in its data type */
+}
+
+const WARNINGS: [Warning; 9] = [
+	Warning::UnusedVariable,
+	Warning::UnusedParameter,
+	Warning::UnusedAssignment,
+	Warning::UnreachableCode,
+	Warning::MissingReturn,
+	Warning::ConstantCondition,
+	Warning::RedundantCast,
+	Warning::Uninitialized,
+	Warning::Overflow,
+];
+
+impl Warning
+{
+	pub fn name(&self) -> &'static str
+	{
+		return match self
+		{
+			Self::UnusedVariable 		=> "unused-variable",
+			Self::UnusedParameter 		=> "unused-parameter",
+			Self::UnusedAssignment 		=> "unused-assignment",
+			Self::UnreachableCode 		=> "unreachable-code",
+			Self::MissingReturn 		=> "missing-return",
+			Self::ConstantCondition 	=> "constant-condition",
+			Self::RedundantCast 		=> "redundant-cast",
+			Self::Uninitialized 		=> "uninitialized",
+			Self::Overflow 				=> "overflow",
+		};
+	}
+
+	fn from_name(name: &str) -> Option<Self>
+	{
+		return WARNINGS.into_iter().find(|warning| warning.name() == name);
+	}
+
+	fn flag(&self) -> u16
+	{
+		return 0b1 << *self as u16;
+	}
+}
+
+pub struct WarningOptions
+{
+	enabled: u16,		/* A bit for each warning, see Warning::flag() */
+	errors: u16,		/* The warnings that are reported as errors, if one of them is reported the compilation fails. */
+}
+
+impl WarningOptions
+{
+	// All warnings are enabled by default, and only reading uninitialized variables is an error
+	pub fn new() -> Self
+	{
+		return Self {
+			enabled: u16::MAX,
+			errors: Warning::Uninitialized.flag(),
+		};
+	}
+
+	// Applies a -W option (without the "-W"), for example "no-unused-variable" or "error=missing-return".
+	// Returns false if there is no such option.
+	pub fn apply_option(&mut self, option: &str) -> bool
+	{
+		let (enable, name) = match option.strip_prefix("no-")
+		{
+			Some(name) 	=> (false, name),
+			None 		=> (true, option),
+		};
+
+		if let Some(name) = name.strip_prefix("error=")
+		{
+			return Self::set_flag(&mut self.errors, name, enable);
+		}
+
+		if name == "error"
+		{
+			self.errors = if enable { u16::MAX } else { 0 };
+			return true;
+		}
+		return Self::set_flag(&mut self.enabled, name, enable);
+	}
+
+	// Sets the bit of the warning with the given name (or all bits for "all")
+	fn set_flag(flags: &mut u16, name: &str, enable: bool) -> bool
+	{
+		let flag = if name == "all"
+		{
+			u16::MAX
+		} else
+		{
+			match Warning::from_name(name)
+			{
+				Some(warning) 	=> warning.flag(),
+				None 			=> return false,
+			}
+		};
+
+		if enable
+		{
+			*flags |= flag;
+		} else
+		{
+			*flags &= !flag;
+		}
+		return true;
+	}
+}
+
+// Reports the warnings of the compiled file, and runs the warnings pass over the AST after the semantic analysis.
+pub struct Warnings<'a>
+{
+	options: WarningOptions,
+	source: &'a str,
+	error_count: Cell<u32>,
+}
+
+impl<'a> Warnings<'a>
+{
+	pub fn new(options: WarningOptions, source: &'a str) -> Self
+	{
+		return Self {
+			options,
+			source,
+			error_count: Cell::new(0),
+		};
+	}
+
+	pub fn warn(&self, warning: Warning, source_index: usize, message: &str)
+	{
+		if self.options.enabled & warning.flag() == 0
+		{
+			return;
+		}
+
+		if self.options.errors & warning.flag() != 0
+		{
+			self.error_count.set(self.error_count.get() + 1);
+			print_errln_no_exit!(self.source, source_index, "{message} [-Werror={}]", warning.name());
+			return;
+		}
+		print_wrnln!(self.source, source_index, "{message} [-W{}]", warning.name());
+	}
+
+	// Checks the functions for code that is most likely a mistake.
+	// Exits if there were warnings that are treated as errors (including the ones that were reported before the warnings pass)
+	pub fn check(&self, ir: &Root)
+	{
+		for function in &ir.functions
+		{
+			if function.attributes & attribute::EXTERN != 0
+			{
+				continue;
+			}
+
+			self.check_initialization(function);
+			self.check_variables(function);
+			self.check_control_flow(function);
+		}
+
+		if self.error_count.get() != 0
+		{
+			print_err!(CompileError::WarningsAsErrors, "{} errors were generated.", self.error_count.get());
+		}
+	}
+}
+
+// Calls on_statement on each statement in the statements, including the statements that are nested in other statements.
+fn for_each_statement<'s>(statements: &'s [Statement], on_statement: &mut dyn FnMut(&'s Statement))
+{
+	for statement in statements
+	{
+		on_statement(statement);
+		match statement
+		{
+			Statement::Scope(scope) => for_each_statement(&scope.statements, on_statement),
+			Statement::If(if_info) =>
+			{
+				for_each_statement(std::slice::from_ref(&if_info.then_block), on_statement);
+				if let Some(else_block) = &if_info.else_block
+				{
+					for_each_statement(std::slice::from_ref(else_block), on_statement);
+				}
+			},
+			Statement::For(for_info) =>
+			{
+				if let Some(initializer) = &for_info.initializer
+				{
+					for_each_statement(std::slice::from_ref(initializer), on_statement);
+				}
+				if let Some(update) = &for_info.update
+				{
+					for_each_statement(std::slice::from_ref(update), on_statement);
+				}
+				for_each_statement(std::slice::from_ref(&for_info.code_block), on_statement);
+			},
+			_ => (),
+		}
+	}
+}
+
+// Calls on_expression on each expression of the statement (not including nested statements)
+fn for_each_statement_expression(statement: &Statement, on_expression: &mut dyn FnMut(&BinExprPart))
+{
+	match statement
+	{
+		Statement::VarDecl(var_decl) =>
+		{
+			if let Some(value) = &var_decl.value
+			{
+				on_expression(&value.root);
+			}
+		},
+		Statement::Assign(assign) =>
+		{
+			on_expression(&assign.destination);
+			on_expression(&assign.value.root);
+		},
+		Statement::FunctionCall(function_call) =>
+		{
+			if let Callee::Indirect(callee) = &function_call.callee
+			{
+				on_expression(&callee.root);
+			}
+			for argument in &function_call.arguments
+			{
+				on_expression(&argument.root);
+			}
+		},
+		Statement::Return(return_info) =>
+		{
+			if let Some(value) = &return_info.value
+			{
+				on_expression(&value.root);
+			}
+		},
+		Statement::If(if_info) => on_expression(&if_info.condition.root),
+		Statement::For(for_info) =>
+		{
+			if let Some(condition) = &for_info.condition
+			{
+				on_expression(&condition.root);
+			}
+		},
+		Statement::Scope(_) => (),
+	}
+}
+
+// The index in the source of the start of the statement, used for pointing at the statement in warnings.
+fn statement_position(statement: &Statement) -> Option<usize>
+{
+	return match statement
+	{
+		Statement::Scope(scope) 				=> statement_position(scope.statements.first()?),
+		Statement::If(if_info) 					=> Some(if_info.condition.root.span.start),
+		Statement::For(for_info) 				=>
+		{
+			if let Some(initializer) = &for_info.initializer
+			{
+				return statement_position(initializer);
+			}
+			if let Some(condition) = &for_info.condition
+			{
+				return Some(condition.root.span.start);
+			}
+			statement_position(&for_info.code_block)
+		},
+		Statement::VarDecl(var_decl) 			=> Some(var_decl.span.start),
+		Statement::Assign(assign) 				=> Some(assign.destination.span.start),
+		Statement::FunctionCall(function_call) 	=> Some(function_call.span.start),
+		Statement::Return(return_info) 			=> Some(return_info.span.start),
+	};
+}
+
+// Calls on_variable on each variable that the expression uses, with true if the address of the variable is taken. (&x)
+fn for_each_variable(part: &BinExprPart, on_variable: &mut dyn FnMut(VariableId, bool))
+{
+	match &part.kind
+	{
+		BinExprPartKind::Val(value) => match value
+		{
+			Value::Var(index) 				=> on_variable(*index, false),
+			Value::Dereference(info) 		=> for_each_variable(&info.expression.root, on_variable),
+			Value::FuncCall(function_call) 	=> for_each_function_call_variable(function_call, on_variable),
+			_ 								=> (),
+		},
+		BinExprPartKind::SelfOperation(operation) =>
+		{
+			if let BinExprPartKind::Val(Value::Var(index)) = &operation.expression.kind
+			{
+				on_variable(*index, operation.operator == BinExprOperator::AddressOf);
+			} else
+			{
+				for_each_variable(&operation.expression, on_variable);
+			}
+		},
+		BinExprPartKind::Operation(operation) =>
+		{
+			for_each_variable(&operation.lhs, on_variable);
+			for_each_variable(&operation.rhs, on_variable);
+		},
+		BinExprPartKind::TypeCast(info) => for_each_variable(&info.expression, on_variable),
+	}
+}
+
+fn for_each_function_call_variable(function_call: &FunctionCallInfo, on_variable: &mut dyn FnMut(VariableId, bool))
+{
+	if let Callee::Indirect(callee) = &function_call.callee
+	{
+		for_each_variable(&callee.root, on_variable);
+	}
+	for argument in &function_call.arguments
+	{
+		for_each_variable(&argument.root, on_variable);
+	}
+}
@@ -0,0 +1,84 @@
+use crate::ast::*;
+use super::*;
+
+impl<'a> Warnings<'a>
+{
+	pub(super) fn check_control_flow(&self, function: &Function)
+	{
+		let reaches_end = self.check_statements(&function.code_block.statements);
+		if reaches_end && function.return_type != Type::new(TypeKind::Void)
+		{
+			self.warn(
+				Warning::MissingReturn,
+				function.span.start,
+				&format!("The function \"{}\" may reach its end without returning a value.", function.identifier)
+			);
+		}
+	}
+
+	// Reports unreachable statements and constant conditions. Returns true if the execution may continue after the statements.
+	fn check_statements(&self, statements: &[Statement]) -> bool
+	{
+		let mut reachable = true;
+		for statement in statements
+		{
+			if !reachable
+			{
+				if let Some(position) = statement_position(statement)
+				{
+					self.warn(Warning::UnreachableCode, position, "This code will never be executed.");
+				}
+				return false;
+			}
+			reachable = self.check_statement(statement);
+		}
+		return reachable;
+	}
+
+	fn check_statement(&self, statement: &Statement) -> bool
+	{
+		match statement
+		{
+			Statement::Scope(scope) => return self.check_statements(&scope.statements),
+			Statement::Return(_) 	=> return false,
+			Statement::If(if_info) =>
+			{
+				self.check_condition(&if_info.condition);
+				let then_reaches_end = self.check_statement(&if_info.then_block);
+				if let Some(else_block) = &if_info.else_block
+				{
+					return self.check_statement(else_block) || then_reaches_end;
+				}
+				return true;
+			},
+			Statement::For(for_info) =>
+			{
+				if let Some(initializer) = &for_info.initializer
+				{
+					self.check_statement(initializer);
+				}
+				if let Some(condition) = &for_info.condition
+				{
+					self.check_condition(condition);
+				}
+				self.check_statement(&for_info.code_block);
+
+				// There is no break statement, so a loop without a condition never ends
+				return for_info.condition.is_some();
+			},
+			_ => return true,
+		}
+	}
+
+	fn check_condition(&self, condition: &BinExpr)
+	{
+		// Expressions that have only constants were folded into a single value by the semantic analyzer
+		if let BinExprPartKind::Val(value) = &condition.root.kind
+		{
+			if value.is_constant()
+			{
+				self.warn(Warning::ConstantCondition, condition.root.span.start, "The condition is a constant, so it is always true or always false.");
+			}
+		}
+	}
+}
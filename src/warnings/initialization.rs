@@ -0,0 +1,174 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::*;
+use super::*;
+
+// Definite assignment analysis, finds reads of variables that may not have been assigned a value yet.
+struct Initialization<'f>
+{
+	names: HashMap<VariableId, &'f str>,
+	address_taken: HashSet<VariableId>,		/* May be assigned through a pointer, so they are never reported */
+	all_variables: HashSet<VariableId>,		/* The state after a return, where nothing is executed so everything counts as assigned */
+	reported: HashSet<VariableId>,			/* Each variable is reported once */
+}
+
+impl<'a> Warnings<'a>
+{
+	pub(super) fn check_initialization(&self, function: &Function)
+	{
+		let mut initialization = Initialization {
+			names: HashMap::new(),
+			address_taken: HashSet::new(),
+			all_variables: (0..function.locals.len()).map(|index| VariableId(index as u32)).collect(),
+			reported: HashSet::new(),
+		};
+
+		for_each_statement(&function.code_block.statements, &mut |statement| {
+			if let Statement::VarDecl(var_decl) = statement
+			{
+				initialization.names.insert(var_decl.index, &var_decl.identifier);
+			}
+			for_each_statement_expression(statement, &mut |expression| {
+				for_each_variable(expression, &mut |index, address_taken| {
+					if address_taken
+					{
+						initialization.address_taken.insert(index);
+					}
+				});
+			});
+		});
+
+		// The parameters are assigned by the caller
+		let assigned = function.parameters.iter().map(|parameter| parameter.index).collect();
+		self.assigned_after(&function.code_block.statements, assigned, &mut initialization);
+	}
+
+	// Returns the variables that are definitely assigned after the statements, given the variables that are definitely assigned before them.
+	fn assigned_after(&self, statements: &[Statement], mut assigned: HashSet<VariableId>, initialization: &mut Initialization) -> HashSet<VariableId>
+	{
+		for statement in statements
+		{
+			assigned = self.statement_assigned_after(statement, assigned, initialization);
+		}
+		return assigned;
+	}
+
+	fn statement_assigned_after(&self, statement: &Statement, mut assigned: HashSet<VariableId>, initialization: &mut Initialization) -> HashSet<VariableId>
+	{
+		match statement
+		{
+			Statement::Scope(scope) => return self.assigned_after(&scope.statements, assigned, initialization),
+			Statement::VarDecl(var_decl) =>
+			{
+				// A variable that is declared in a loop has no value at the start of each iteration, even if it was assigned in the previous one
+				assigned.remove(&var_decl.index);
+				if let Some(value) = &var_decl.value
+				{
+					self.check_reads(&value.root, &assigned, initialization);
+					assigned.insert(var_decl.index);
+				}
+			},
+			Statement::Assign(assign) =>
+			{
+				self.check_reads(&assign.value.root, &assigned, initialization);
+				match &assign.destination.kind
+				{
+					BinExprPartKind::Val(Value::Var(index)) => { assigned.insert(*index); },
+					_ => self.check_reads(&assign.destination, &assigned, initialization),
+				}
+			},
+			Statement::FunctionCall(_) =>
+			{
+				for_each_statement_expression(statement, &mut |expression| self.check_reads(expression, &assigned, initialization));
+			},
+			Statement::Return(return_info) =>
+			{
+				if let Some(value) = &return_info.value
+				{
+					self.check_reads(&value.root, &assigned, initialization);
+				}
+				return initialization.all_variables.clone();
+			},
+			Statement::If(if_info) =>
+			{
+				// A variable is assigned after the if statement only if it is assigned in both branches
+				self.check_reads(&if_info.condition.root, &assigned, initialization);
+				let then_assigned = self.statement_assigned_after(&if_info.then_block, assigned.clone(), initialization);
+				if let Some(else_block) = &if_info.else_block
+				{
+					assigned = self.statement_assigned_after(else_block, assigned, initialization);
+				}
+				return then_assigned.intersection(&assigned).copied().collect();
+			},
+			Statement::For(for_info) =>
+			{
+				if let Some(initializer) = &for_info.initializer
+				{
+					assigned = self.statement_assigned_after(initializer, assigned, initialization);
+				}
+
+				// The loop body may not run at all, so the variables that it assigns are not assigned after the loop.
+				// Later iterations start with more assigned variables than the first one, so checking the first iteration is enough.
+				if let Some(condition) = &for_info.condition
+				{
+					self.check_reads(&condition.root, &assigned, initialization);
+				}
+				let body_assigned = self.statement_assigned_after(&for_info.code_block, assigned.clone(), initialization);
+				if let Some(update) = &for_info.update
+				{
+					self.statement_assigned_after(update, body_assigned, initialization);
+				}
+
+				// There is no break statement, so a loop without a condition never ends
+				if for_info.condition.is_none()
+				{
+					return initialization.all_variables.clone();
+				}
+			},
+		}
+		return assigned;
+	}
+
+	fn check_reads(&self, expression: &BinExprPart, assigned: &HashSet<VariableId>, initialization: &mut Initialization)
+	{
+		for_each_variable(expression, &mut |index, _| {
+			if assigned.contains(&index) || initialization.address_taken.contains(&index) || !initialization.reported.insert(index)
+			{
+				return;
+			}
+
+			let identifier = initialization.names[&index];
+			self.warn(
+				Warning::Uninitialized,
+				Self::variable_position(expression, index).unwrap_or(expression.span.start),
+				&format!("The variable \"{identifier}\" may be used before it is assigned a value.")
+			);
+		});
+	}
+
+	// The position of the first use of the variable in the expression part
+	fn variable_position(part: &BinExprPart, variable: VariableId) -> Option<usize>
+	{
+		return match &part.kind
+		{
+			BinExprPartKind::Val(Value::Var(index)) 				=> if *index == variable { Some(part.span.start) } else { None },
+			BinExprPartKind::Val(Value::Dereference(info)) 			=> Self::variable_position(&info.expression.root, variable),
+			BinExprPartKind::Val(Value::FuncCall(function_call)) 	=>
+			{
+				if let Callee::Indirect(callee) = &function_call.callee
+				{
+					if let Some(position) = Self::variable_position(&callee.root, variable)
+					{
+						return Some(position);
+					}
+				}
+				function_call.arguments.iter().find_map(|argument| Self::variable_position(&argument.root, variable))
+			},
+			BinExprPartKind::Val(_) 								=> None,
+			BinExprPartKind::SelfOperation(operation) 				=> Self::variable_position(&operation.expression, variable),
+			BinExprPartKind::Operation(operation) 					=>
+				Self::variable_position(&operation.lhs, variable).or_else(|| Self::variable_position(&operation.rhs, variable)),
+			BinExprPartKind::TypeCast(info) 						=> Self::variable_position(&info.expression, variable),
+		};
+	}
+}
@@ -0,0 +1,198 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::ast::*;
+use super::*;
+
+// The variables of a function, as needed for the unused variables and unused assignments warnings
+struct VariableUsage<'f>
+{
+	names: HashMap<VariableId, &'f str>,
+	read: HashSet<VariableId>,
+	address_taken: HashSet<VariableId>,		/* Can be read through a pointer, so assignments into them are never reported */
+}
+
+impl<'a> Warnings<'a>
+{
+	pub(super) fn check_variables(&self, function: &Function)
+	{
+		let mut usage = VariableUsage {
+			names: HashMap::new(),
+			read: HashSet::new(),
+			address_taken: HashSet::new(),
+		};
+
+		let mut declarations = Vec::new();
+		for_each_statement(&function.code_block.statements, &mut |statement| {
+			// Constants are replaced by their values, so their uses are not seen here
+			if let Statement::VarDecl(var_decl) = statement
+			{
+				if var_decl.attributes & attribute::CONSTANT != 0
+				{
+					return;
+				}
+				declarations.push(var_decl);
+			}
+			Self::statement_variables(statement, &mut |index, address_taken| {
+				usage.read.insert(index);
+				if address_taken
+				{
+					usage.address_taken.insert(index);
+				}
+			});
+		});
+
+		for parameter in &function.parameters
+		{
+			usage.names.insert(parameter.index, &parameter.identifier);
+			if !usage.read.contains(&parameter.index)
+			{
+				self.warn(Warning::UnusedParameter, parameter.span.start, &format!("The parameter \"{}\" is never used.", parameter.identifier));
+			}
+		}
+
+		for var_decl in declarations
+		{
+			usage.names.insert(var_decl.index, &var_decl.identifier);
+			if !usage.read.contains(&var_decl.index)
+			{
+				self.warn(Warning::UnusedVariable, var_decl.span.start, &format!("The variable \"{}\" is never used.", var_decl.identifier));
+			}
+		}
+
+		self.live_before(&function.code_block.statements, HashSet::new(), &usage, true);
+	}
+
+	// Calls on_variable on each variable that the statement reads (not including nested statements). Assigning into a variable does not read it.
+	fn statement_variables(statement: &Statement, on_variable: &mut dyn FnMut(VariableId, bool))
+	{
+		match statement
+		{
+			Statement::VarDecl(var_decl) =>
+			{
+				if let Some(value) = &var_decl.value
+				{
+					for_each_variable(&value.root, on_variable);
+				}
+			},
+			Statement::Assign(assign) =>
+			{
+				if let BinExprPartKind::Val(Value::Dereference(_)) = &assign.destination.kind
+				{
+					for_each_variable(&assign.destination, on_variable);
+				}
+				for_each_variable(&assign.value.root, on_variable);
+			},
+			Statement::FunctionCall(function_call) 	=> for_each_function_call_variable(function_call, on_variable),
+			Statement::Return(return_info) 			=>
+			{
+				if let Some(value) = &return_info.value
+				{
+					for_each_variable(&value.root, on_variable);
+				}
+			},
+			Statement::If(if_info) 					=> for_each_variable(&if_info.condition.root, on_variable),
+			Statement::For(for_info) 				=>
+			{
+				if let Some(condition) = &for_info.condition
+				{
+					for_each_variable(&condition.root, on_variable);
+				}
+			},
+			Statement::Scope(_) 					=> (),
+		}
+	}
+
+	// Liveness analysis, goes over the statements backwards and returns the variables that may be read after the start of the statements,
+	// given the variables that may be read after their end. If report is set, assignments into variables that are not live are reported.
+	fn live_before(&self, statements: &[Statement], mut live: HashSet<VariableId>, usage: &VariableUsage, report: bool) -> HashSet<VariableId>
+	{
+		for statement in statements.iter().rev()
+		{
+			live = self.statement_live_before(statement, live, usage, report);
+		}
+		return live;
+	}
+
+	fn statement_live_before(&self, statement: &Statement, mut live: HashSet<VariableId>, usage: &VariableUsage, report: bool) -> HashSet<VariableId>
+	{
+		match statement
+		{
+			Statement::Scope(scope) => return self.live_before(&scope.statements, live, usage, report),
+			Statement::VarDecl(var_decl) 	=> { live.remove(&var_decl.index); },
+			Statement::Assign(assign) =>
+			{
+				if let BinExprPartKind::Val(Value::Var(index)) = &assign.destination.kind
+				{
+					// Variables that are never read are already reported as unused
+					if report && !live.contains(index) && usage.read.contains(index) && !usage.address_taken.contains(index)
+					{
+						self.warn(
+							Warning::UnusedAssignment,
+							assign.destination.span.start,
+							&format!("The value that is assigned into \"{}\" is never read.", usage.names[index])
+						);
+					}
+					live.remove(index);
+				}
+			},
+			Statement::Return(_) => live.clear(),
+			Statement::If(if_info) =>
+			{
+				let mut then_live = self.statement_live_before(&if_info.then_block, live.clone(), usage, report);
+				if let Some(else_block) = &if_info.else_block
+				{
+					live = self.statement_live_before(else_block, live, usage, report);
+				}
+				then_live.extend(live);
+				live = then_live;
+			},
+			Statement::For(for_info) =>
+			{
+				// There is no break statement, so a loop without a condition never ends
+				let exit_live = if for_info.condition.is_some() { live } else { HashSet::new() };
+				let mut condition_live = exit_live.clone();
+				if let Some(condition) = &for_info.condition
+				{
+					for_each_variable(&condition.root, &mut |index, _| { condition_live.insert(index); });
+				}
+
+				// The variables that are live at the condition depend on the loop body, which depends on the variables that are live at the condition.
+				let mut head_live = condition_live.clone();
+				loop
+				{
+					let mut new_head_live = self.loop_body_live_before(for_info, head_live.clone(), usage, false);
+					new_head_live.extend(condition_live.iter().copied());
+					if new_head_live == head_live
+					{
+						break;
+					}
+					head_live = new_head_live;
+				}
+
+				if report
+				{
+					self.loop_body_live_before(for_info, head_live.clone(), usage, true);
+				}
+
+				if let Some(initializer) = &for_info.initializer
+				{
+					return self.statement_live_before(initializer, head_live, usage, report);
+				}
+				return head_live;
+			},
+			Statement::FunctionCall(_) => (),
+		}
+
+		Self::statement_variables(statement, &mut |index, _| { live.insert(index); });
+		return live;
+	}
+
+	fn loop_body_live_before(&self, for_info: &ForLoopInfo, mut live: HashSet<VariableId>, usage: &VariableUsage, report: bool) -> HashSet<VariableId>
+	{
+		if let Some(update) = &for_info.update
+		{
+			live = self.statement_live_before(update, live, usage, report);
+		}
+		return self.statement_live_before(&for_info.code_block, live, usage, report);
+	}
+}
@@ -0,0 +1,86 @@
+// Tests for the AArch64 backend (--target=aarch64-linux). The object files are assembled with a cross assembler (or llvm-mc),
+// and the programs are run with qemu-aarch64 when a cross compiler and qemu are available.
+#![cfg(not(feature = "hebrew"))]
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::process::Command;
+
+const OPTIMIZATION_LEVELS: [&str; 4] = ["-O0", "-O1", "-O2", "-O3"];
+
+const TARGET: &str = "--target=aarch64-linux";
+
+// Whether slowc can assemble AArch64 assembly on this machine
+fn aarch64_assembler_exists() -> bool
+{
+	let native = cfg!(target_arch = "aarch64") && common::tool_exists("as");
+	return native || common::tool_exists("aarch64-linux-gnu-as") || common::tool_exists("llvm-mc");
+}
+
+// slowc writes an AArch64 ELF64 object file
+#[test]
+fn writes_aarch64_object()
+{
+	if !aarch64_assembler_exists()
+	{
+		eprintln!("Skipping writes_aarch64_object: an AArch64 assembler is required for this test.");
+		return;
+	}
+
+	let source = std::fs::read_to_string("tests/assembler/encoding.slw").unwrap();
+	for level in OPTIMIZATION_LEVELS
+	{
+		let object = common::compile_object(&format!("aarch64_object{level}"), &source, &[TARGET, level]).expect("slowc failed");
+		assert_eq!(&object[0..4], b"\x7FELF");
+		assert_eq!(object[4], 2, "not a 64 bit file");
+		assert_eq!(object[5], 1, "not little endian");
+		assert_eq!(u16::from_le_bytes([object[16], object[17]]), 1, "not a relocatable file");
+		assert_eq!(u16::from_le_bytes([object[18], object[19]]), 183, "not an AArch64 file");
+	}
+}
+
+// The programs behave the same when compiled for AArch64 and run with qemu
+#[test]
+fn runs_under_emulation()
+{
+	for tool in ["aarch64-linux-gnu-gcc", "qemu-aarch64"]
+	{
+		if !common::tool_exists(tool)
+		{
+			eprintln!("Skipping runs_under_emulation: {tool} is required for this test.");
+			return;
+		}
+	}
+
+	let source = std::fs::read_to_string("tests/assembler/encoding.slw").unwrap();
+	for level in OPTIMIZATION_LEVELS
+	{
+		let name = format!("aarch64_run{level}");
+		let object = common::compile_object(&name, &source, &[TARGET, level]).expect("slowc failed");
+
+		let work_dir = std::env::temp_dir().join(format!("slowc_test_{name}"));
+		let object_file = work_dir.join(format!("{name}.o"));
+		let executable = work_dir.join(&name);
+		std::fs::write(&object_file, object).unwrap();
+		let status = Command::new("aarch64-linux-gnu-gcc")
+			.args(["-static", "-z", "noexecstack", "-o"])
+			.arg(&executable)
+			.arg(&object_file)
+			.status()
+			.unwrap();
+		assert!(status.success(), "Failed to link {name}");
+
+		let output = Command::new("qemu-aarch64").arg(&executable).output().unwrap();
+		assert_eq!(output.status.code(), Some(0), "{level}: {}", String::from_utf8_lossy(&output.stdout));
+	}
+}
+
+// nasm only assembles x86-64
+#[test]
+fn nasm_cannot_assemble_aarch64()
+{
+	let (code, _, error) = common::compile_with_arguments("aarch64_nasm", "func global main() -> i32 { return 0; }", &[TARGET, "-S"]);
+	assert_ne!(code, 0);
+	assert!(error.contains("nasm cannot assemble for the target aarch64-linux"), "{error}");
+}
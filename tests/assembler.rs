@@ -0,0 +1,192 @@
+// Tests for the built-in x86-64 encoder and the ELF64 object files that slowc writes.
+// The programs that are compiled with the built-in encoder are also compiled with -S (nasm) and with --asm=gnu, and must behave the same.
+#![cfg(not(feature = "hebrew"))]
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::path::Path;
+
+/* The exit codes of slowc are the index of the error in error::ExitCodes, plus 1 */
+const EXIT_CODE_LINKER_ERROR: i32 = 13;
+
+const OPTIMIZATION_LEVELS: [&str; 4] = ["-O0", "-O1", "-O2", "-O3"];
+
+const PROGRAM: &str = "
+func extern abs(value i32) -> i32;
+
+func global main() -> i32
+{
+	const scale i64 = 1000;
+	let value i64 = 5;
+	let ratio f64 = 2.5;
+	return abs((i32)(0 - value * scale)) - 4982 + (i32)(ratio * 2.0);
+}
+";
+
+// The section headers of an ELF64 file, as (name, type, offset, size, link)
+fn sections(object: &[u8]) -> Vec<(String, u32, usize, usize, u32)>
+{
+	let read_u16 = |offset: usize| u16::from_le_bytes(object[offset..offset + 2].try_into().unwrap()) as usize;
+	let read_u32 = |offset: usize| u32::from_le_bytes(object[offset..offset + 4].try_into().unwrap());
+	let read_u64 = |offset: usize| u64::from_le_bytes(object[offset..offset + 8].try_into().unwrap()) as usize;
+
+	let table = read_u64(0x28);
+	let count = read_u16(0x3C);
+	let names = read_u64(table + read_u16(0x3E) * 64 + 0x18);
+
+	let mut sections = Vec::new();
+	for index in 0..count
+	{
+		let header = table + index * 64;
+		let name_offset = names + read_u32(header) as usize;
+		let name_end = name_offset + object[name_offset..].iter().position(|byte| *byte == 0).unwrap();
+		sections.push((
+			String::from_utf8_lossy(&object[name_offset..name_end]).to_string(),
+			read_u32(header + 0x04),
+			read_u64(header + 0x18),
+			read_u64(header + 0x20),
+			read_u32(header + 0x28),
+		));
+	}
+	return sections;
+}
+
+// The symbols of an ELF64 file, as (name, binding, section index)
+fn symbols(object: &[u8]) -> Vec<(String, u8, u16)>
+{
+	let sections = sections(object);
+	let (_, _, offset, size, link) = sections.iter().find(|section| section.0 == ".symtab").unwrap();
+	let strings = sections[*link as usize].2;
+
+	let mut symbols = Vec::new();
+	for symbol in object[*offset..*offset + *size].chunks(24)
+	{
+		let name_offset = strings + u32::from_le_bytes(symbol[0..4].try_into().unwrap()) as usize;
+		let name_end = name_offset + object[name_offset..].iter().position(|byte| *byte == 0).unwrap();
+		symbols.push((
+			String::from_utf8_lossy(&object[name_offset..name_end]).to_string(),
+			symbol[4] >> 4,
+			u16::from_le_bytes(symbol[6..8].try_into().unwrap()),
+		));
+	}
+	return symbols;
+}
+
+// slowc writes a relocatable x86-64 ELF64 object file without an assembler
+#[test]
+fn writes_relocatable_object()
+{
+	let object = common::compile_object("relocatable_object", PROGRAM, &[]).expect("slowc failed");
+	assert_eq!(&object[0..4], b"\x7FELF");
+	assert_eq!(object[4], 2, "not a 64 bit file");
+	assert_eq!(object[5], 1, "not little endian");
+	assert_eq!(u16::from_le_bytes([object[16], object[17]]), 1, "not a relocatable file");
+	assert_eq!(u16::from_le_bytes([object[18], object[19]]), 62, "not an x86-64 file");
+
+	let sections = sections(&object);
+	for name in [".text", ".data", ".bss", ".rela.text", ".symtab", ".strtab"]
+	{
+		assert!(sections.iter().any(|section| section.0 == name), "no {name} section in {sections:?}");
+	}
+	let data = sections.iter().find(|section| section.0 == ".data").unwrap();
+	assert!(data.3 >= 16, "the float constants are not in .data");
+	let rela = sections.iter().find(|section| section.0 == ".rela.text").unwrap();
+	assert_eq!(rela.3, 3 * 24, "the call of abs and the references to the constants are not relocations");
+
+	let symbols = symbols(&object);
+	assert!(symbols.contains(&("main".to_string(), 1, 1)), "main is not a global symbol in .text: {symbols:?}");
+	assert!(symbols.contains(&("abs".to_string(), 1, 0)), "abs is not an undefined global symbol: {symbols:?}");
+}
+
+// The object files of the built-in encoder link and run
+#[test]
+fn runs_without_assembler()
+{
+	let directory = std::env::temp_dir().join("slowc_test_runs_without_assembler");
+	std::fs::create_dir_all(&directory).unwrap();
+	let file = directory.join("program.slw");
+	std::fs::write(&file, PROGRAM).unwrap();
+	for level in OPTIMIZATION_LEVELS
+	{
+		if let Some(code) = common::compile_and_run_with_arguments(&format!("runs_without_assembler{level}"), &file, None, &[level])
+		{
+			assert_eq!(code, 23, "wrong exit code at {level}");
+		}
+	}
+}
+
+// slowc fails when the linker fails, here because of a function that is not defined anywhere
+#[test]
+fn linker_error()
+{
+	if !common::tool_exists("ld")
+	{
+		eprintln!("Skipping linker_error: ld is required for this test.");
+		return;
+	}
+	let source = "func extern slowc_undefined_function() -> i32;\nfunc global main() -> i32\n{\n\treturn slowc_undefined_function();\n}\n";
+	let (code, _, error) = common::compile_with_arguments("linker_error", source, &[]);
+	assert_eq!(code, EXIT_CODE_LINKER_ERROR, "{error}");
+	assert!(error.contains("The linker failed with exit code"), "{error}");
+}
+
+// Narrow, wide and float instructions, stack arguments, function pointers and extern calls give the same results
+// with the built-in encoder, with nasm and with the GNU assembler
+#[test]
+fn same_results_with_assemblers()
+{
+	let file = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("assembler").join("encoding.slw");
+	for level in OPTIMIZATION_LEVELS
+	{
+		for arguments in [vec![level], vec![level, "-S"], vec![level, "--asm=gnu"]]
+		{
+			let name = format!("encoding{}", arguments.concat());
+			if let Some(code) = common::compile_and_run_with_arguments(&name, &file, None, &arguments)
+			{
+				assert_eq!(code, 0, "{code} checks failed with {arguments:?}");
+			}
+		}
+	}
+}
+
+// The instructions of an object file as objdump disassembles them (in the Intel syntax), without their addresses and bytes
+fn disassemble(name: &str, object: &[u8]) -> Vec<String>
+{
+	let directory = std::env::temp_dir().join(format!("slowc_test_{name}"));
+	std::fs::create_dir_all(&directory).unwrap();
+	let path = directory.join(format!("{name}.o"));
+	std::fs::write(&path, object).unwrap();
+	let output = std::process::Command::new("objdump")
+		.args(["-d", "-M", "intel", "--no-show-raw-insn"])
+		.arg(&path)
+		.output()
+		.unwrap();
+	assert!(output.status.success(), "objdump failed on {}", path.display());
+	return String::from_utf8_lossy(&output.stdout).lines()
+		.filter_map(|line| line.split_once(":\t"))
+		.map(|(_, instruction)| instruction.split_whitespace().next().unwrap_or("").to_string())
+		.collect();
+}
+
+// The NASM and the GNU dialects are assembled into the same instructions
+#[test]
+fn dialects_assemble_the_same()
+{
+	if !["nasm", "as", "objdump"].iter().all(|tool| common::tool_exists(tool))
+	{
+		eprintln!("Skipping dialects_assemble_the_same: nasm, as and objdump are required for this test.");
+		return;
+	}
+
+	let source = std::fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("assembler").join("encoding.slw")).unwrap();
+	for level in OPTIMIZATION_LEVELS
+	{
+		let name = format!("dialects{level}");
+		let nasm = common::compile_object(&name, &source, &[level, "--asm=nasm"]).expect("slowc failed with nasm");
+		let gnu = common::compile_object(&name, &source, &[level, "--asm=gnu"]).expect("slowc failed with the GNU assembler");
+		let nasm_instructions = disassemble(&format!("{name}_nasm"), &nasm);
+		assert!(!nasm_instructions.is_empty());
+		assert_eq!(nasm_instructions, disassemble(&format!("{name}_gnu"), &gnu), "different instructions at {level}");
+	}
+}
@@ -0,0 +1,298 @@
+// Shared code for the integration tests, which compile Slow programs with slowc, link them (possibly with C code) and run them.
+// The tests require a C compiler (and nasm or the GNU assembler, for the tests of -S and --asm), and are skipped if they are not available.
+// Each test file uses only some of these functions.
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::process::Command;
+use std::sync::Mutex;
+
+// slowc always writes its output to the same object file, so the tests must not compile in parallel
+static COMPILE_LOCK: Mutex<()> = Mutex::new(());
+
+const SLOWC_OBJECT_FILE: &str = "/tmp/slowc_compiled.obj";
+const SLOWC_C_FILE: &str = "/tmp/slowc_compiled.c";
+const SLOWC_WAT_FILE: &str = "/tmp/slowc_compiled.wat";
+
+// Runs a WebAssembly module with node, with the C library functions that the tests call as its imports.
+// Prints the output of the program, and exits with the return value of main.
+const NODE_WASM_RUNNER: &str = "
+const memory = { heap: 1 << 20 };
+const output = [];
+const env = {
+	putchar: (character) => { output.push(character & 255); return character; },
+	abs: (value) => Math.abs(value) | 0,
+	malloc: (size) => {
+		const address = memory.heap;
+		memory.heap += (Number(size) + 15) & ~15;
+		const missing = memory.heap - memory.exports.memory.buffer.byteLength;
+		if (missing > 0) memory.exports.memory.grow(Math.ceil(missing / 65536));
+		return address;
+	},
+	free: () => {},
+};
+WebAssembly.instantiate(require('fs').readFileSync(process.argv[1]), { env }).then(({ instance }) => {
+	memory.exports = instance.exports;
+	const code = instance.exports.main();
+	process.stdout.write(Buffer.from(output));
+	process.exit(code & 255);
+});
+";
+
+pub fn tool_exists(tool: &str) -> bool
+{
+	return Command::new(tool).arg("--version").output().is_ok();
+}
+
+// The assembler that slowc runs with the given command line arguments, if it does not write the object file itself
+pub fn required_assembler(arguments: &[&str]) -> Option<&'static str>
+{
+	if arguments.contains(&"--asm=gnu")
+	{
+		return Some("as");
+	} else if arguments.contains(&"-S") || arguments.contains(&"--asm=nasm")
+	{
+		return Some("nasm");
+	}
+	return None;
+}
+
+// Compiles the Slow file (and the C file, if given), links them and returns the exit code of the program.
+// Returns None if the tools that are required for running the test are not available.
+pub fn compile_and_run(name: &str, slow_file: &Path, c_file: Option<&Path>) -> Option<i32>
+{
+	return compile_and_run_with_arguments(name, slow_file, c_file, &[]);
+}
+
+// Like compile_and_run, with the given command line arguments for slowc
+pub fn compile_and_run_with_arguments(name: &str, slow_file: &Path, c_file: Option<&Path>, arguments: &[&str]) -> Option<i32>
+{
+	if !tool_exists("cc")
+	{
+		eprintln!("Skipping {name}: a C compiler is required for this test.");
+		return None;
+	}
+	if let Some(assembler) = required_assembler(arguments).filter(|assembler| !tool_exists(assembler))
+	{
+		eprintln!("Skipping {name}: {assembler} is required for {arguments:?}.");
+		return None;
+	}
+
+	let _lock = COMPILE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+
+	let work_dir = std::env::temp_dir().join(format!("slowc_test_{name}"));
+	std::fs::create_dir_all(&work_dir).unwrap();
+	let _ = std::fs::remove_file(SLOWC_OBJECT_FILE);
+
+	/* slowc also tries to link an executable, which is not needed here (and fails if the C file has main), so only the object file is used. */
+	let slowc = Command::new(env!("CARGO_BIN_EXE_slowc"))
+		.args(arguments)
+		.arg(slow_file)
+		.current_dir(&work_dir)
+		.output()
+		.unwrap();
+	assert!(
+		Path::new(SLOWC_OBJECT_FILE).exists(),
+		"slowc failed to compile {}:\n{}{}", slow_file.display(), String::from_utf8_lossy(&slowc.stdout), String::from_utf8_lossy(&slowc.stderr)
+	);
+
+	let mut objects = vec![work_dir.join(format!("{name}_slow.o"))];
+	std::fs::copy(SLOWC_OBJECT_FILE, &objects[0]).unwrap();
+
+	if let Some(c_file) = c_file
+	{
+		let c_object = work_dir.join(format!("{name}_c.o"));
+		let status = Command::new("cc")
+			.args(["-O0", "-fno-omit-frame-pointer", "-c"])
+			.arg(c_file)
+			.arg("-o").arg(&c_object)
+			.status()
+			.unwrap();
+		assert!(status.success(), "Failed to compile {}", c_file.display());
+		objects.push(c_object);
+	}
+
+	let executable = work_dir.join(name);
+	let status = Command::new("cc")
+		.args(["-no-pie", "-z", "noexecstack", "-o"])
+		.arg(&executable)
+		.args(&objects)
+		.status()
+		.unwrap();
+	assert!(status.success(), "Failed to link {name}");
+
+	let output = Command::new(&executable).output().unwrap();
+	print!("{}", String::from_utf8_lossy(&output.stdout));
+	return output.status.code();
+}
+
+// Translates a Slow program into C with --emit=c, and returns the C source. Returns None if slowc failed.
+pub fn translate_to_c(name: &str, source: &str) -> Option<String>
+{
+	let work_dir = std::env::temp_dir().join(format!("slowc_test_{name}"));
+	std::fs::create_dir_all(&work_dir).unwrap();
+	let slow_file = work_dir.join(format!("{name}.slw"));
+	std::fs::write(&slow_file, source).unwrap();
+
+	let _lock = COMPILE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+	let _ = std::fs::remove_file(SLOWC_C_FILE);
+	Command::new(env!("CARGO_BIN_EXE_slowc"))
+		.arg("--emit=c")
+		.arg(&slow_file)
+		.current_dir(&work_dir)
+		.output()
+		.unwrap();
+	return std::fs::read_to_string(SLOWC_C_FILE).ok();
+}
+
+// Translates the Slow file into C with --emit=c, compiles it as C99 (with the C file, if given) and returns the exit code of the program and its output.
+// Returns None if a C compiler is not available.
+pub fn compile_c_and_run(name: &str, slow_file: &Path, c_file: Option<&Path>) -> Option<(i32, String)>
+{
+	if !tool_exists("cc")
+	{
+		eprintln!("Skipping {name}: a C compiler is required for this test.");
+		return None;
+	}
+
+	let work_dir = std::env::temp_dir().join(format!("slowc_test_{name}"));
+	std::fs::create_dir_all(&work_dir).unwrap();
+	let translated = work_dir.join(format!("{name}.c"));
+	{
+		let _lock = COMPILE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+		let _ = std::fs::remove_file(SLOWC_C_FILE);
+		let slowc = Command::new(env!("CARGO_BIN_EXE_slowc"))
+			.arg("--emit=c")
+			.arg(slow_file)
+			.current_dir(&work_dir)
+			.output()
+			.unwrap();
+		assert!(
+			Path::new(SLOWC_C_FILE).exists(),
+			"slowc failed to translate {}:\n{}{}", slow_file.display(), String::from_utf8_lossy(&slowc.stdout), String::from_utf8_lossy(&slowc.stderr)
+		);
+		std::fs::copy(SLOWC_C_FILE, &translated).unwrap();
+	}
+
+	/* The external functions are declared with the Slow data types, which may not match the built-in declarations of the C library functions */
+	let executable = work_dir.join(name);
+	let compiler = Command::new("cc")
+		.args(["-std=c99", "-pedantic-errors", "-fno-builtin", "-o"])
+		.arg(&executable)
+		.arg(&translated)
+		.args(c_file)
+		.output()
+		.unwrap();
+	assert!(compiler.status.success(), "Failed to compile the C source of {name}:\n{}", String::from_utf8_lossy(&compiler.stderr));
+
+	let output = Command::new(&executable).output().unwrap();
+	return Some((output.status.code().unwrap_or(-1), String::from_utf8_lossy(&output.stdout).to_string()));
+}
+
+// Compiles a Slow program with --target=wasm32, and returns the WebAssembly text. Returns None if slowc failed.
+pub fn translate_to_wat(name: &str, source: &str) -> Option<String>
+{
+	let work_dir = std::env::temp_dir().join(format!("slowc_test_{name}"));
+	std::fs::create_dir_all(&work_dir).unwrap();
+	let slow_file = work_dir.join(format!("{name}.slw"));
+	std::fs::write(&slow_file, source).unwrap();
+
+	let _lock = COMPILE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+	let _ = std::fs::remove_file(SLOWC_WAT_FILE);
+	Command::new(env!("CARGO_BIN_EXE_slowc"))
+		.arg("--target=wasm32")
+		.arg(&slow_file)
+		.current_dir(&work_dir)
+		.output()
+		.unwrap();
+	return std::fs::read_to_string(SLOWC_WAT_FILE).ok();
+}
+
+// Compiles the Slow file with --target=wasm32, assembles the module with wat2wasm and runs it with node.
+// Returns the exit code of the program and its output, or None if wat2wasm or node are not available.
+pub fn compile_wasm_and_run(name: &str, slow_file: &Path) -> Option<(i32, String)>
+{
+	for tool in ["wat2wasm", "node"]
+	{
+		if !tool_exists(tool)
+		{
+			eprintln!("Skipping {name}: {tool} is required for this test.");
+			return None;
+		}
+	}
+
+	let work_dir = std::env::temp_dir().join(format!("slowc_test_{name}"));
+	std::fs::create_dir_all(&work_dir).unwrap();
+	let text = work_dir.join(format!("{name}.wat"));
+	let module = work_dir.join(format!("{name}.wasm"));
+	{
+		let _lock = COMPILE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+		let _ = std::fs::remove_file(SLOWC_WAT_FILE);
+		let slowc = Command::new(env!("CARGO_BIN_EXE_slowc"))
+			.arg("--target=wasm32")
+			.arg(slow_file)
+			.current_dir(&work_dir)
+			.output()
+			.unwrap();
+		assert!(
+			Path::new(SLOWC_WAT_FILE).exists(),
+			"slowc failed to compile {}:\n{}{}", slow_file.display(), String::from_utf8_lossy(&slowc.stdout), String::from_utf8_lossy(&slowc.stderr)
+		);
+		std::fs::copy(SLOWC_WAT_FILE, &text).unwrap();
+	}
+
+	let assembler = Command::new("wat2wasm").arg(&text).arg("-o").arg(&module).output().unwrap();
+	assert!(assembler.status.success(), "Failed to assemble the module of {name}:\n{}", String::from_utf8_lossy(&assembler.stderr));
+
+	let output = Command::new("node").args(["-e", NODE_WASM_RUNNER]).arg(&module).output().unwrap();
+	return Some((output.status.code().unwrap_or(-1), String::from_utf8_lossy(&output.stdout).to_string()));
+}
+
+// Compiles a Slow program that is expected to fail, and returns the exit code of slowc and its error output.
+pub fn compile_error(name: &str, source: &str) -> (i32, String)
+{
+	let (code, _, error) = compile_with_arguments(name, source, &[]);
+	return (code, error);
+}
+
+// Compiles a Slow program with the given command line arguments, and returns the exit code of slowc, its output and its error output.
+pub fn compile_with_arguments(name: &str, source: &str, arguments: &[&str]) -> (i32, String, String)
+{
+	let work_dir = std::env::temp_dir().join(format!("slowc_test_{name}"));
+	std::fs::create_dir_all(&work_dir).unwrap();
+	let slow_file = work_dir.join(format!("{name}.slw"));
+	std::fs::write(&slow_file, source).unwrap();
+
+	let _lock = COMPILE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+	let slowc = Command::new(env!("CARGO_BIN_EXE_slowc"))
+		.args(arguments)
+		.arg(&slow_file)
+		.current_dir(&work_dir)
+		.output()
+		.unwrap();
+	return (
+		slowc.status.code().unwrap_or(-1),
+		String::from_utf8_lossy(&slowc.stdout).to_string(),
+		String::from_utf8_lossy(&slowc.stderr).to_string()
+	);
+}
+
+// Compiles a Slow program with the given command line arguments, and returns the object file that slowc wrote.
+// Returns None if slowc failed.
+pub fn compile_object(name: &str, source: &str, arguments: &[&str]) -> Option<Vec<u8>>
+{
+	let work_dir = std::env::temp_dir().join(format!("slowc_test_{name}"));
+	std::fs::create_dir_all(&work_dir).unwrap();
+	let slow_file = work_dir.join(format!("{name}.slw"));
+	std::fs::write(&slow_file, source).unwrap();
+
+	let _lock = COMPILE_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+	let _ = std::fs::remove_file(SLOWC_OBJECT_FILE);
+	Command::new(env!("CARGO_BIN_EXE_slowc"))
+		.args(arguments)
+		.arg(&slow_file)
+		.current_dir(&work_dir)
+		.output()
+		.unwrap();
+	return std::fs::read(SLOWC_OBJECT_FILE).ok();
+}
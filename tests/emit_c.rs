@@ -0,0 +1,217 @@
+// Tests for the C backend (--emit=c), which translates a Slow program into a C99 translation unit.
+// The translated programs are compiled with the system C compiler, and must behave like the programs that slowc compiles itself.
+#![cfg(not(feature = "hebrew"))]
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::path::Path;
+
+const SOURCE: &str = "
+func extern printf(format *u8, ...) -> i32;
+
+func apply(function func(i32) -> i32, value i32) -> i32
+{
+	return function(value);
+}
+
+func inline square(x i32) -> i32
+{
+	return x * x;
+}
+
+func global main() -> i32
+{
+	let total i64 = 0;
+	for let i i32 = 0; i < 10; i = i + 1;
+	{
+		total = total + (i64)apply(&square, i);
+	}
+	return (i32)(total >> 2);
+}
+";
+
+// The functions print in the order they are called, and the checks fail if the operands are not evaluated from left to right.
+// Also checks that the integers wrap, that >> is a logical shift and that && and || are done on the bits of integers.
+const EVALUATION: &str = "
+func extern putchar(character i32) -> i32;
+func extern malloc(size u64) -> *i64;
+func extern free(pointer *i64) -> void;
+
+func say(character i32, value i64) -> i64
+{
+	putchar(character);
+	return value;
+}
+
+func bump(pointer *i64, value i64) -> i64
+{
+	*pointer = *pointer + value;
+	putchar(98);
+	return value;
+}
+
+func countdown(pointer *i32) -> i32
+{
+	*pointer = *pointer - 1;
+	putchar(99);
+	return *pointer;
+}
+
+func pick(a i64, b i64, c i64) -> i64
+{
+	return a * 100 + b * 10 + c;
+}
+
+func global main() -> i32
+{
+	let failed i32 = 0;
+	let x i64 = 1;
+	if x + bump(&x, 5) != 6 { failed = failed + 1; }
+	if bump(&x, 1) + x != 8 { failed = failed + 1; }
+	if pick(say(49, 1), say(50, 2), say(51, 3)) != 123 { failed = failed + 1; }
+	if pick(x, bump(&x, 2), x) != 729 { failed = failed + 1; }
+
+	let memory *i64 = malloc(16);
+	*memory = 0;
+	*(memory + 1) = 0;
+	*(memory + (i64)bump(memory, 1)) = say(52, 7);
+	if *(memory + 1) != 7 || *memory != 1 { failed = failed + 1; }
+	if (memory + 2) - memory != 2 { failed = failed + 1; }
+	free(memory);
+
+	let n i32 = 3;
+	let loops i32 = 0;
+	for ; countdown(&n) > 0; loops = loops + 1;
+	{
+	}
+	if loops != 2 { failed = failed + 1; }
+	putchar(10);
+
+	let small i8 = 127;
+	small = small + 1;
+	if small != (i8)(0 - 128) { failed = failed + 1; }
+	let half u16 = 65535;
+	if half * half != 1 { failed = failed + 1; }
+	let negative i8 = 0 - 2;
+	if negative >> 1 != 127 { failed = failed + 1; }
+	let wide i64 = 1;
+	if wide << 65 != 2 { failed = failed + 1; }
+	if (5 && 2) != 0 || (5 || 2) != 7 { failed = failed + 1; }
+	let ratio f64 = 2.5;
+	let zero f64 = 0.0;
+	if (ratio && zero) != 0.0 || (ratio || zero) != 1.0 || !zero != 1.0 { failed = failed + 1; }
+	let minimum i32 = 0 - 2147483647 - 1;
+	if minimum - 1 != 2147483647 { failed = failed + 1; }
+	if ~(u8)5 != 250 { failed = failed + 1; }
+	return failed;
+}
+";
+
+// Integers are the fixed width integers of stdint.h, functions that are not global are static, and function pointers have a typedef
+#[test]
+fn emit_c()
+{
+	let source = common::translate_to_c("emit_c", SOURCE).expect("slowc failed");
+	for line in [
+		"#include <stdint.h>",
+		"typedef int32_t (*slow_function_type1)(int32_t);",
+		"extern int32_t printf(uint8_t*, ...);",
+		"static int32_t apply(slow_function_type1, int32_t);",
+		"static inline int32_t square(int32_t);",
+		"int32_t main(void)",
+		"\tint64_t total_0 = INT64_C(0);",
+		"(&square)",
+	]
+	{
+		assert!(source.contains(line), "Expected \"{line}\" in the C source:\n{source}");
+	}
+}
+
+#[test]
+fn same_results_as_native()
+{
+	let directory = std::env::temp_dir().join("slowc_test_same_results_as_native");
+	std::fs::create_dir_all(&directory).unwrap();
+	let program = directory.join("program.slw");
+	let evaluation = directory.join("evaluation.slw");
+	std::fs::write(&program, SOURCE).unwrap();
+	std::fs::write(&evaluation, EVALUATION).unwrap();
+	let encoding = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("assembler").join("encoding.slw");
+
+	for (name, slow_file, expected) in [("c_program", &program, 71), ("c_evaluation", &evaluation, 0), ("c_encoding", &encoding, 0)]
+	{
+		if let Some(code) = common::compile_and_run(&format!("{name}_native"), slow_file, None)
+		{
+			assert_eq!(code, expected, "{name} compiled by slowc");
+		}
+		if let Some((code, _)) = common::compile_c_and_run(name, slow_file, None)
+		{
+			assert_eq!(code, expected, "{name} compiled as C");
+		}
+	}
+}
+
+// The function calls happen in the same order as in the native code
+#[test]
+fn evaluation_order()
+{
+	let directory = std::env::temp_dir().join("slowc_test_evaluation_order");
+	std::fs::create_dir_all(&directory).unwrap();
+	let slow_file = directory.join("evaluation.slw");
+	std::fs::write(&slow_file, EVALUATION).unwrap();
+
+	if let Some((code, output)) = common::compile_c_and_run("evaluation_order", &slow_file, None)
+	{
+		assert_eq!(code, 0, "{code} checks failed");
+		assert_eq!(output, "bb123b4bccc\n");
+	}
+}
+
+// The translated programs follow the C calling convenction, so they work with the C side of the calling convenction tests
+#[test]
+fn calls_c()
+{
+	let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("sys_v_abi");
+	for name in ["slow_calls_c", "c_calls_slow", "function_pointers", "alignment"]
+	{
+		let slow_file = directory.join(format!("{name}.slw"));
+		let c_file = directory.join(format!("{name}.c"));
+		if let Some((code, _)) = common::compile_c_and_run(&format!("c_{name}"), &slow_file, Some(&c_file))
+		{
+			assert_eq!(code, 0, "{code} checks failed in {name}");
+		}
+	}
+}
+
+// The smallest i32 or i64 divided by -1 traps in the native code, and is undefined in C, so the C source wraps it around
+#[test]
+fn division_overflow()
+{
+	let source = "
+func global main() -> i32
+{
+	let failed i32 = 0;
+	let minus i32 = 0 - 1;
+	let minimum i32 = 0 - 2147483647 - 1;
+	if minimum / minus != minimum || minimum % minus != 0 { failed = failed + 1; }
+	let wide i64 = (i64)minimum * 4294967296;
+	if wide / (i64)minus != wide || wide % (i64)minus != 0 { failed = failed + 1; }
+	if minimum / 2 != 0 - 1073741824 || (minimum + 1) % 2 != 0 - 1 { failed = failed + 1; }
+	let small i8 = (i8)(0 - 128);
+	if small / (i8)minus != small { failed = failed + 1; }
+	return failed;
+}
+";
+	let text = common::translate_to_c("division_overflow", source).expect("slowc failed");
+	assert!(text.contains("static int32_t slow_div_i32(int32_t lhs, int32_t rhs)"), "{text}");
+
+	let directory = std::env::temp_dir().join("slowc_test_division_overflow");
+	std::fs::create_dir_all(&directory).unwrap();
+	let slow_file = directory.join("program.slw");
+	std::fs::write(&slow_file, source).unwrap();
+	if let Some((code, _)) = common::compile_c_and_run("division_overflow", &slow_file, None)
+	{
+		assert_eq!(code, 0, "{code} checks failed");
+	}
+}
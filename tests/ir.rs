@@ -0,0 +1,73 @@
+// Tests for the MIR (the three-address intermediate representation), printed with --emit=ir.
+// The tests build slowc in debug mode, so the MIR verifier also runs on every program here.
+#![cfg(not(feature = "hebrew"))]
+#![allow(clippy::needless_return)]
+
+mod common;
+
+/* The exit codes of slowc are the index of the error in error::ExitCodes, plus 1 */
+const EXIT_CODE_USAGE: i32 = 1;
+
+const SOURCE: &str = "
+func extern printf(format *u8, ...) -> i32;
+
+func square(x i32) -> i32
+{
+	return x * x;
+}
+
+func global main() -> i32
+{
+	let sum i32 = 0;
+	for let i i32 = 0; i < 10; i = i + 1;
+	{
+		if i > 5
+		{
+			sum = sum + square(i);
+		}
+	}
+	return sum;
+}
+";
+
+// Each function is printed with its stack slots and its basic blocks, and every instruction is in three-address form
+#[test]
+fn emit_ir()
+{
+	let (code, output, error) = common::compile_with_arguments("emit_ir", SOURCE, &["--emit=ir"]);
+	assert_eq!(code, 0, "{output}{error}");
+	for line in [
+		"extern printf: func(*u8, ...) -> i32",
+		"func square(%0 i32) -> i32",
+		"func global main() -> i32",
+		"\t$0 i32 sum",
+		"\t$1 i32 i",
+		"\tstore i32 [$0], 0",
+		" = cmp lt i32 %0, 10",
+		" = mul i32 %",
+		"\tbranch %",
+		"call i32 square(i32 %",
+		"\treturn i32 %",
+	]
+	{
+		assert!(output.contains(line), "Expected \"{line}\" in the MIR:\n{output}");
+	}
+}
+
+// Only the MIR is printed, so nothing is assembled or linked
+#[test]
+fn emit_ir_does_not_link()
+{
+	let (code, output, error) = common::compile_with_arguments("emit_ir_does_not_link", SOURCE, &["--emit=ir"]);
+	assert_eq!(code, 0, "{output}{error}");
+	let executable = std::env::temp_dir().join("slowc_test_emit_ir_does_not_link").join("a.out");
+	assert!(!executable.exists(), "An executable was linked with --emit=ir");
+}
+
+#[test]
+fn unknown_emit()
+{
+	let (code, _, error) = common::compile_with_arguments("unknown_emit", SOURCE, &["--emit=bytecode"]);
+	assert_eq!(code, EXIT_CODE_USAGE, "{error}");
+	assert!(error.contains("Unknown output \"bytecode\""), "{error}");
+}
@@ -0,0 +1,78 @@
+// Tests for programs that are larger than the old 255 functions / 255 variables limits.
+// The programs are generated, compiled, and exit with 42 if they ran correctly.
+#![cfg(not(feature = "hebrew"))]
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::fmt::Write;
+use std::path::PathBuf;
+
+const COUNT: usize = 3000;
+
+fn write_program(name: &str, source: &str) -> PathBuf
+{
+	let path = std::env::temp_dir().join(format!("slowc_test_{name}.slw"));
+	std::fs::write(&path, source).unwrap();
+	return path;
+}
+
+// Each function calls the previous one and adds 1, so function{n}(0) returns n
+#[test]
+fn thousands_of_functions()
+{
+	let mut source = String::from("func function0(x i32) -> i32\n{\n\treturn x;\n}\n");
+	for i in 1..COUNT
+	{
+		let _ = write!(source, "func function{i}(x i32) -> i32\n{{\n\treturn function{}(x) + 1;\n}}\n", i - 1);
+	}
+	let _ = write!(
+		source,
+		"func global main() -> i32\n{{\n\tlet last func(i32) -> i32 = &function{};\n\treturn last(0) - {};\n}}\n",
+		COUNT - 1, COUNT - 1 - 42
+	);
+
+	let path = write_program("thousands_of_functions", &source);
+	if let Some(code) = common::compile_and_run("thousands_of_functions", &path, None)
+	{
+		assert_eq!(code, 42);
+	}
+}
+
+#[test]
+fn thousands_of_locals()
+{
+	let mut source = String::from("func global main() -> i32\n{\n\tlet v0 i32 = 0;\n");
+	for i in 1..COUNT
+	{
+		let _ = writeln!(source, "\tlet v{i} i32 = v{} + 1;", i - 1);
+	}
+	let _ = write!(source, "\treturn v{} - {};\n}}\n", COUNT - 1, COUNT - 1 - 42);
+
+	let path = write_program("thousands_of_locals", &source);
+	if let Some(code) = common::compile_and_run("thousands_of_locals", &path, None)
+	{
+		assert_eq!(code, 42);
+	}
+}
+
+#[test]
+fn hundreds_of_nested_scopes()
+{
+	const DEPTH: usize = 300;
+
+	let mut source = String::from("func global main() -> i32\n{\n\tlet result i32 = 0;\n\tlet s0 i32 = 0;\n");
+	for i in 1..DEPTH
+	{
+		let _ = writeln!(source, "{{\n\tlet s{i} i32 = s{} + 1;", i - 1);
+	}
+	let _ = writeln!(source, "\tresult = s{} - {};", DEPTH - 1, DEPTH - 1 - 42);
+	source.push_str(&"}\n".repeat(DEPTH - 1));
+	source.push_str("\treturn result;\n}\n");
+
+	let path = write_program("hundreds_of_nested_scopes", &source);
+	if let Some(code) = common::compile_and_run("hundreds_of_nested_scopes", &path, None)
+	{
+		assert_eq!(code, 42);
+	}
+}
@@ -0,0 +1,639 @@
+// Tests for the optimizations on the MIR, which are chosen with -O<level>.
+// The optimized MIR is checked with --emit=ir, and the optimized programs must behave like the unoptimized ones.
+#![cfg(not(feature = "hebrew"))]
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::path::Path;
+
+/* The exit codes of slowc are the index of the error in error::ExitCodes, plus 1 */
+const EXIT_CODE_USAGE: i32 = 1;
+
+const OPTIMIZATION_LEVELS: [&str; 4] = ["-O0", "-O1", "-O2", "-O3"];
+
+const DEAD_CODE: &str = "
+func compute(a i32) -> i32
+{
+	let result i32 = a;
+	result = 5;
+	result = a * 2;
+	if 2 > 1
+	{
+		return result;
+	}
+	return result + 1;
+	result = 0;
+}
+
+func global main() -> i32
+{
+	let x i32 = 4;
+	for ; x > 100; x = x + 1;
+	{
+	}
+	return compute(x);
+}
+";
+
+// Exits with the amount of checks that failed
+const PROGRAM: &str = "
+func check(value i64, expected i64) -> i32
+{
+	if value != expected
+	{
+		return 1;
+	}
+	return 0;
+}
+
+func shifts(value u8, count u8) -> u8
+{
+	return (value << count) >> 1;
+}
+
+func high(value u64) -> i64
+{
+	return (i64)(value >> 32);
+}
+
+// The i8 is sign extended, and converting it to u32 and then to u64 clears the high 32 bits
+func widen(lhs i8, rhs i8) -> i64
+{
+	let value i8 = lhs + rhs;
+	value = value * value + value;
+	return high((u64)(u32)value);
+}
+
+func divide(lhs i32, rhs i32) -> i32
+{
+	return lhs / rhs + lhs % rhs;
+}
+
+func powers(value i32, small u8) -> i64
+{
+	let wide i64 = (i64)value * 1024;
+	return (i64)(value * 8 + value * 1 - value * 16 * 2) + (i64)(small * 2) + wide;
+}
+
+func inline distance(a i32, b i32) -> i32
+{
+	let difference i32 = a - b;
+	if difference < 0
+	{
+		return b - a;
+	}
+	return difference;
+}
+
+func inline factorial(n i64) -> i64
+{
+	if n <= 1
+	{
+		return 1;
+	}
+	return n * factorial(n - 1);
+}
+
+func inline increment(pointer *i32) -> void
+{
+	*pointer = *pointer + 1;
+}
+
+func loop(count i32) -> i32
+{
+	let sum i32 = 0;
+	for let i i32 = 0; i < count; i = i + 1;
+	{
+		if i > 5
+		{
+			sum = sum + i;
+		} else
+		{
+			sum = sum + 1;
+		}
+	}
+	return sum;
+	sum = 5;
+}
+
+func floats(count i32) -> f64
+{
+	let sum f64 = 0.0;
+	let step f32 = 1.5;
+	for let i i32 = 0; i < count; i = i + 1;
+	{
+		sum = sum + (f64)i * (f64)step;
+		step = step + (f32)0.5;
+	}
+	return sum;
+}
+
+func global main() -> i32
+{
+	let failed i32 = 0;
+	if floats(10) != 210.0
+	{
+		failed = failed + 1;
+	}
+	failed = failed + check((i64)shifts(200, 1), 72);
+	failed = failed + check((i64)divide(0 - 7, 2), 0 - 4);
+	failed = failed + check(widen(6, 35), 0);
+	failed = failed + check(powers(0 - 3, 200), 69 + 144 - 3072);
+	failed = failed + check((i64)loop(10), 36);
+	failed = failed + check((i64)loop(0), 0);
+	failed = failed + check((i64)distance(3, 10) + (i64)distance(10, 3), 14);
+	failed = failed + check(factorial(12), 479001600);
+
+	let counter i32 = 0;
+	for let i i32 = 0; i < 5; i = i + 1;
+	{
+		increment(&counter);
+	}
+	failed = failed + check((i64)counter, 5);
+
+	let unused i32 = loop(3);
+	if 1 == 2
+	{
+		failed = failed + 100;
+	}
+	return failed;
+}
+";
+
+// More values are live at once than there are registers, and many of them are live while functions are called
+const REGISTER_PRESSURE: &str = "
+func id(value i64) -> i64
+{
+	return value;
+}
+
+func scale(value f64, factor f32) -> f64
+{
+	return value * (f64)factor;
+}
+
+func global main() -> i32
+{
+	let a i64 = id(1);
+	let b i64 = id(2);
+	let c i64 = id(3);
+	let d i64 = id(4);
+	let e i64 = id(5);
+	let f i64 = id(6);
+	let g i64 = id(7);
+	let h i64 = id(8);
+	let x f64 = scale(1.5, (f32)2.0);
+	let y f64 = scale(x, (f32)0.5);
+	let total i64 = 0;
+	for let i i64 = 0; i < 4; i = i + 1;
+	{
+		total = total + id(a * b + c * d - e * f + g * h + i) + (i64)scale(x + y, (f32)i);
+	}
+
+	let sum u8 = (u8)a + (u8)b * ((u8)c + (u8)d * ((u8)e + (u8)f * ((u8)g + (u8)h)));
+	if total != 4 * (2 + 12 - 30 + 56) + 6 + 0 + 4 + 9 + 13
+	{
+		return 1;
+	}
+	if sum != (u8)((1 + 2 * (3 + 4 * (5 + 6 * (7 + 8)))) % 256)
+	{
+		return 2;
+	}
+	return 0;
+}
+";
+
+// Recurses deeper than the stack allows, unless the recursive calls are tail calls. The tailrec functions are compiled with
+// tail calls at every level, and spread passes arguments on the stack (and swaps them, so they are read before they are written).
+const TAIL_RECURSION: &str = "
+func tailrec count(n i64, total i64) -> i64
+{
+	if n == 0
+	{
+		return total;
+	}
+	return count(n - 1, total + 2);
+}
+
+func tailrec spread(n i64, b i64, c i64, d i64, e i64, f i64, g f64, h i64, i i64) -> i64
+{
+	if n == 0
+	{
+		return b + c + d + e + f + (i64)g + h + i;
+	}
+	return spread(n - 1, b, c, d, e, f, g + 1.0, i, h + 1);
+}
+
+func tailrec decrement(n i64, counter *i64) -> void
+{
+	if n == 0
+	{
+		return;
+	}
+	*counter = *counter + 1;
+	decrement(n - 1, counter);
+}
+
+func global main() -> i32
+{
+	if count(10000000, 0) != 20000000
+	{
+		return 1;
+	}
+	if spread(1000001, 1, 2, 3, 4, 5, 6.0, 7, 8) != 2000038
+	{
+		return 2;
+	}
+	let counter i64 = 0;
+	decrement(5000000, &counter);
+	if counter != 5000000
+	{
+		return 3;
+	}
+	return 0;
+}
+";
+
+// Calls in tail position of functions without the tailrec attribute are tail calls from -O1
+const SIBLING_CALLS: &str = "
+func even(n i64) -> i32
+{
+	if n == 0
+	{
+		return 1;
+	}
+	return odd(n - 1);
+}
+
+func odd(n i64) -> i32
+{
+	if n == 0
+	{
+		return 0;
+	}
+	return even(n - 1);
+}
+
+func global main() -> i32
+{
+	return even(10000001) + odd(10000000) * 2;
+}
+";
+
+// Loops that index memory with their counters, and compute values that do not change in the loop.
+// small extends a counter that wraps around, so its extension cannot be replaced with an addition.
+const LOOPS: &str = "
+func extern malloc(size u64) -> *i32;
+
+func check(value i64, expected i64) -> i32
+{
+	if value != expected
+	{
+		return 1;
+	}
+	return 0;
+}
+
+func fill(values *i32, count i32) -> void
+{
+	for let i i32 = 0; i < count; i = i + 1;
+	{
+		*(values + i) = i * i + 1;
+	}
+	return;
+}
+
+func matrix(values *i32, rows i32, columns i32) -> i32
+{
+	let total i32 = 0;
+	for let r i32 = 0; r < rows; r = r + 1;
+	{
+		for let c i32 = 0; c < columns; c = c + 1;
+		{
+			total = total + *(values + r * columns + c) * (c + 1);
+		}
+	}
+	return total;
+}
+
+func backwards(values *i32, count i32) -> i32
+{
+	let total i32 = 0;
+	for let i i32 = count - 1; i > 0 - 1; i = i - 1;
+	{
+		total = total * 3 + *(values + i) + i * 7;
+	}
+	return total;
+}
+
+func strided(values *i32, count i64) -> i64
+{
+	let total i64 = 0;
+	for let i i64 = 1; i < count; i = i + 2;
+	{
+		total = total + (i64)*(values + i) * (i << 2) - (i * 5);
+	}
+	return total;
+}
+
+func small(count u8) -> u32
+{
+	let total u32 = 0;
+	for let i u8 = 250; i != count; i = i + 3;
+	{
+		total = total + (u32)i * 10;
+	}
+	return total;
+}
+
+func before(values *i32, count i32) -> i32
+{
+	let total i32 = 0;
+	let i i32 = 0;
+	for ; i < count; ;
+	{
+		let index i32 = i * 2;
+		i = i + 1;
+		total = total + *(values + index) + index;
+	}
+	return total;
+}
+
+func global main() -> i32
+{
+	let failed i32 = 0;
+	let buffer *i32 = malloc(400);
+	fill(buffer, 100);
+	failed = failed + check((i64)*(buffer + 7), 50);
+	failed = failed + check((i64)matrix(buffer, 5, 8), 100800);
+	failed = failed + check((i64)backwards(buffer, 10), 3941524);
+	failed = failed + check(strided(buffer, 20), 79500);
+	failed = failed + check((i64)small(7), 223530);
+	failed = failed + check((i64)before(buffer, 10), 1240);
+	return failed;
+}
+";
+
+// Code after a return, a branch on a constant condition, and stores that are overwritten before they are read are removed
+#[test]
+fn removes_dead_code()
+{
+	let (code, output, error) = common::compile_with_arguments("removes_dead_code", DEAD_CODE, &["-O1", "--emit=ir"]);
+	assert_eq!(code, 0, "{output}{error}");
+	assert!(!output.contains("branch 1"), "The constant branch was not folded:\n{output}");
+	assert!(!output.contains("store i32 [$1], 5"), "The dead store was not removed:\n{output}");
+	assert!(!output.contains("store i32 [$1], 0"), "The unreachable store was not removed:\n{output}");
+	assert!(!output.contains("return i32 0"), "The unreachable return was not removed:\n{output}");
+	assert!(!output.contains("bb1:\n\tjump"), "The empty block was not threaded:\n{output}");
+	assert!(output.contains("call i32 compute("), "{output}");
+}
+
+// Variables whose address is not taken are kept in registers, and are no longer loaded and stored
+#[test]
+fn variables_in_registers()
+{
+	let source = "
+func sum(count i32) -> i32
+{
+	let total i32 = 0;
+	let pointed i32 = 0;
+	let pointer *i32 = &pointed;
+	for let i i32 = 0; i < count; i = i + 1;
+	{
+		total = total + i;
+		*pointer = *pointer + 1;
+	}
+	return total + pointed;
+}
+";
+	let (code, output, error) = common::compile_with_arguments("variables_in_registers", source, &["-O2", "--emit=ir"]);
+	assert_eq!(code, 0, "{output}{error}");
+	assert!(output.contains("\t$0 i32 pointed\n"), "The variable whose address is taken must stay on the stack:\n{output}");
+	assert!(!output.contains("$1"), "Only the variable whose address is taken needs a stack slot:\n{output}");
+
+	// The loop counter is incremented in place
+	let incremented_in_place = output.lines().any(|line| {
+		return line.trim().split_once(" = ").is_some_and(|(destination, operation)| operation == format!("add i32 {destination}, 1"));
+	});
+	assert!(incremented_in_place, "The counter is not incremented in place:\n{output}");
+}
+
+// Functions with the inline attribute are inlined at -O1, with their own copies of the locals of the inlined function.
+// A recursive function is inlined into its callers a limited amount of times, so a call of it is left.
+#[test]
+fn inlines_functions()
+{
+	let source = "
+func inline square(value i32) -> i32
+{
+	let result i32 = value * value;
+	return result;
+}
+
+func inline sum(count i64) -> i64
+{
+	if count == 0
+	{
+		return 0;
+	}
+	return count + sum(count - 1);
+}
+
+func global main() -> i32
+{
+	return square(3) + square(4) + (i32)sum(100);
+}
+";
+	let (code, output, error) = common::compile_with_arguments("inlines_functions", source, &["-O1", "--emit=ir"]);
+	assert_eq!(code, 0, "{output}{error}");
+	let main = &output[output.find("func global main").unwrap()..];
+	assert!(!main.contains("call i32 square("), "The inline function was not inlined:\n{output}");
+	assert!(main.contains("i32 square.result"), "The locals of the inlined function were not renamed:\n{output}");
+	assert!(main.contains("call i64 sum("), "The recursive function must not be inlined without a limit:\n{output}");
+}
+
+// At -O3 small functions are inlined without the inline attribute, and at -O2 they are not
+#[test]
+fn inlines_small_functions()
+{
+	let source = "
+func add(a i32, b i32) -> i32
+{
+	return a + b;
+}
+
+func global main() -> i32
+{
+	return add(1, 2);
+}
+";
+	let (code, output, error) = common::compile_with_arguments("inlines_small_functions", source, &["-O3", "--emit=ir"]);
+	assert_eq!(code, 0, "{output}{error}");
+	assert!(!output.contains("call i32 add("), "The small function was not inlined:\n{output}");
+	assert!(output.contains("return i32 3"), "The inlined body was not folded:\n{output}");
+
+	let (code, output, error) = common::compile_with_arguments("inlines_small_functions", source, &["-O2", "--emit=ir"]);
+	assert_eq!(code, 0, "{output}{error}");
+	assert!(output.contains("call i32 add("), "{output}");
+}
+
+// At -O2 values that do not change in a loop are computed before it, and the multiplications by the loop counter
+// (and the extensions of it into 64 bit offsets) are replaced with additions to registers that move with the counter
+#[test]
+fn loop_optimizations()
+{
+	let source = "
+func sum(values *i32, count i32, scale i32) -> i32
+{
+	let total i32 = 0;
+	for let i i32 = 0; i < count; i = i + 1;
+	{
+		total = total + *(values + i) * (scale * 3 + 1);
+	}
+	return total;
+}
+
+func global main() -> i32
+{
+	return 0;
+}
+";
+	let (code, output, error) = common::compile_with_arguments("loop_optimizations", source, &["-O2", "--emit=ir"]);
+	assert_eq!(code, 0, "{output}{error}");
+	let body = loop_instructions(&output).join("\n");
+	assert!(!body.contains(", 3"), "The invariant multiplication was not moved out of the loop:\n{output}");
+	assert!(!body.contains("mul i64") && !body.contains("convert"), "The offset is still computed from the counter:\n{output}");
+	assert!(body.contains("add *i32"), "The pointer is not incremented in the loop:\n{output}");
+
+	// The benchmark runs fewer instructions in its loops at -O2 than at -O1
+	let (code, unoptimized, error) = common::compile_with_arguments("loop_optimizations", LOOPS, &["-O1", "--emit=ir"]);
+	assert_eq!(code, 0, "{unoptimized}{error}");
+	let (code, optimized, error) = common::compile_with_arguments("loop_optimizations", LOOPS, &["-O2", "--emit=ir"]);
+	assert_eq!(code, 0, "{optimized}{error}");
+	let before = loop_instructions(&unoptimized).len();
+	let after = loop_instructions(&optimized).len();
+	assert!(after < before, "{after} instructions in loops at -O2, {before} at -O1:\n{optimized}");
+}
+
+// Without -O the MIR is not optimized
+#[test]
+fn no_optimizations_by_default()
+{
+	let (code, output, error) = common::compile_with_arguments("no_optimizations_by_default", DEAD_CODE, &["--emit=ir"]);
+	assert_eq!(code, 0, "{output}{error}");
+	assert!(output.contains("branch 1"), "{output}");
+	assert!(output.contains("store i32 [$1], 5"), "{output}");
+}
+
+#[test]
+fn same_result_at_each_level()
+{
+	let directory = std::env::temp_dir().join("slowc_test_same_result_at_each_level");
+	std::fs::create_dir_all(&directory).unwrap();
+	let slow_file = directory.join("program.slw");
+	std::fs::write(&slow_file, PROGRAM).unwrap();
+
+	for level in OPTIMIZATION_LEVELS
+	{
+		if let Some(code) = common::compile_and_run_with_arguments(&format!("same_result{level}"), Path::new(&slow_file), None, &[level])
+		{
+			assert_eq!(code, 0, "{code} checks failed with {level}");
+		}
+	}
+}
+
+#[test]
+fn register_pressure()
+{
+	let directory = std::env::temp_dir().join("slowc_test_register_pressure");
+	std::fs::create_dir_all(&directory).unwrap();
+	let slow_file = directory.join("program.slw");
+	std::fs::write(&slow_file, REGISTER_PRESSURE).unwrap();
+
+	for level in OPTIMIZATION_LEVELS
+	{
+		if let Some(code) = common::compile_and_run_with_arguments(&format!("register_pressure{level}"), Path::new(&slow_file), None, &[level])
+		{
+			assert_eq!(code, 0, "Check {code} failed with {level}");
+		}
+	}
+}
+
+#[test]
+fn tail_calls()
+{
+	let directory = std::env::temp_dir().join("slowc_test_tail_calls");
+	std::fs::create_dir_all(&directory).unwrap();
+	let recursion_file = directory.join("recursion.slw");
+	let sibling_file = directory.join("sibling.slw");
+	std::fs::write(&recursion_file, TAIL_RECURSION).unwrap();
+	std::fs::write(&sibling_file, SIBLING_CALLS).unwrap();
+
+	for level in OPTIMIZATION_LEVELS
+	{
+		if let Some(code) = common::compile_and_run_with_arguments(&format!("tail_recursion{level}"), Path::new(&recursion_file), None, &[level])
+		{
+			assert_eq!(code, 0, "Check {code} failed with {level}");
+		}
+		if level == "-O0"
+		{
+			continue;
+		}
+		if let Some(code) = common::compile_and_run_with_arguments(&format!("sibling_calls{level}"), Path::new(&sibling_file), None, &[level])
+		{
+			assert_eq!(code, 0, "{level}");
+		}
+	}
+}
+
+#[test]
+fn loops()
+{
+	let directory = std::env::temp_dir().join("slowc_test_loops");
+	std::fs::create_dir_all(&directory).unwrap();
+	let slow_file = directory.join("program.slw");
+	std::fs::write(&slow_file, LOOPS).unwrap();
+
+	for level in OPTIMIZATION_LEVELS
+	{
+		if let Some(code) = common::compile_and_run_with_arguments(&format!("loops{level}"), Path::new(&slow_file), None, &[level])
+		{
+			assert_eq!(code, 0, "{code} checks failed with {level}");
+		}
+	}
+}
+
+#[test]
+fn unknown_level()
+{
+	let (code, _, error) = common::compile_with_arguments("unknown_level", DEAD_CODE, &["-O9"]);
+	assert_eq!(code, EXIT_CODE_USAGE, "{error}");
+	assert!(error.contains("Unknown optimization level \"-O9\""), "{error}");
+}
+
+// The instructions of the blocks that are in loops in the MIR. The blocks of a loop are between its header and the block that jumps back to it.
+fn loop_instructions(output: &str) -> Vec<&str>
+{
+	let mut instructions = Vec::new();
+	for function in output.split("\nfunc ")
+	{
+		let blocks: Vec<Vec<&str>> = function.split("\nbb").skip(1).map(|block| {
+			return block.lines().skip(1).map(str::trim).filter(|line| !line.is_empty() && *line != "}").collect();
+		}).collect();
+		let mut in_loop = vec![false; blocks.len()];
+		for (latch, block) in blocks.iter().enumerate()
+		{
+			let targets = block.last().unwrap().split(" bb").skip(1).map(|target| target.trim_end_matches(',').parse::<usize>().unwrap());
+			for header in targets.filter(|header| *header <= latch)
+			{
+				in_loop[header..=latch].fill(true);
+			}
+		}
+		for (block, _) in blocks.iter().zip(in_loop).filter(|(_, in_loop)| *in_loop)
+		{
+			instructions.extend(&block[..block.len() - 1]);
+		}
+	}
+	return instructions;
+}
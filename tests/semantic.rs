@@ -0,0 +1,439 @@
+// Tests for the semantic analysis, which resolves names and infers and checks the data types of expressions.
+#![cfg(not(feature = "hebrew"))]
+#![allow(clippy::needless_return)]
+
+mod common;
+
+/* The exit codes of slowc are the index of the error in error::ExitCodes, plus 1 */
+const EXIT_CODE_SYNTAX: i32 = 5;
+const EXIT_CODE_UNKNOWN_IDENTIFIER: i32 = 6;
+const EXIT_CODE_TYPE_ERROR: i32 = 7;
+const EXIT_CODE_DIVISION_BY_ZERO: i32 = 11;
+const EXIT_CODE_TAIL_CALL: i32 = 12;
+
+fn run_program(name: &str, source: &str) -> Option<i32>
+{
+	let path = std::env::temp_dir().join(format!("slowc_test_{name}.slw"));
+	std::fs::write(&path, source).unwrap();
+	return common::compile_and_run(name, &path, None);
+}
+
+// The data type of an expression is not taken from its first operand, literals take the data type of the other side
+#[test]
+fn literals_take_the_type_of_the_other_operand()
+{
+	let source = "
+func twice(x i64) -> i64
+{
+	return 2 * x;
+}
+func global main() -> i32
+{
+	let x i64 = 5;
+	let result i32 = 0;
+	if 10 == twice(x)
+	{
+		result = result + 2;
+	}
+	if (1 + 2) * x == 15 && 2.5 > 1.0
+	{
+		result = result + 40;
+	}
+	return result;
+}
+";
+	if let Some(code) = run_program("literals_take_the_type_of_the_other_operand", source)
+	{
+		assert_eq!(code, 42);
+	}
+}
+
+// Functions are resolved after parsing, so they can be called before they are declared
+#[test]
+fn call_before_declaration()
+{
+	let source = "
+func global main() -> i32
+{
+	let f func(i32) -> i32 = &later;
+	return later(40) + f(0);
+}
+func later(x i32) -> i32
+{
+	return x + 1;
+}
+";
+	if let Some(code) = run_program("call_before_declaration", source)
+	{
+		assert_eq!(code, 42);
+	}
+}
+
+#[test]
+fn variable_used_in_its_own_declaration()
+{
+	let (code, error) = common::compile_error("own_declaration", "func global main() -> i32\n{\n\tlet y i32 = y + 1;\n\treturn y;\n}\n");
+	assert_eq!(code, EXIT_CODE_UNKNOWN_IDENTIFIER, "{error}");
+}
+
+// The error points at the second definition
+#[test]
+fn function_declared_twice()
+{
+	let source = "func twice() -> i32\n{\n\treturn 1;\n}\nfunc twice() -> i32\n{\n\treturn 2;\n}\nfunc global main() -> i32\n{\n\treturn twice();\n}\n";
+	let (code, error) = common::compile_error("function_declared_twice", source);
+	assert_eq!(code, EXIT_CODE_SYNTAX, "{error}");
+	assert!(error.contains("The function \"twice\" was already declared."), "{error}");
+	assert!(error.contains("On line 5"), "{error}");
+}
+
+#[test]
+fn mismatched_operand_types()
+{
+	let (code, error) = common::compile_error("mismatched_operands", "func global main() -> i32\n{\n\tlet y i64 = 3;\n\tlet z i32 = 2;\n\treturn z + y;\n}\n");
+	assert_eq!(code, EXIT_CODE_TYPE_ERROR, "{error}");
+}
+
+// The operators that work on the bits of integers cannot be used on floats
+#[test]
+fn integer_operators_on_floats()
+{
+	for (name, expression) in [("modulo", "a % b"), ("and", "a & b"), ("or", "a | b"), ("xor", "a ^ b"), ("left_shift", "a << b"), ("right_shift", "a >> b"), ("not", "~a")]
+	{
+		let source = format!("func global main() -> i32\n{{\n\tlet a f64 = 1.5;\n\tlet b f64 = 2.0;\n\tlet c f64 = {expression};\n\treturn 0;\n}}\n");
+		let (code, error) = common::compile_error(&format!("float_{name}"), &source);
+		assert_eq!(code, EXIT_CODE_TYPE_ERROR, "{expression}: {error}");
+		assert!(error.contains("can only be used on integers"), "{expression}: {error}");
+	}
+}
+
+#[test]
+fn void_function_used_as_value()
+{
+	let (code, error) = common::compile_error("void_as_value", "func f() -> void\n{\n\treturn;\n}\nfunc global main() -> i32\n{\n\treturn f();\n}\n");
+	assert_eq!(code, EXIT_CODE_TYPE_ERROR, "{error}");
+}
+
+#[test]
+fn return_without_value()
+{
+	let (code, error) = common::compile_error("return_without_value", "func global main() -> i32\n{\n\treturn;\n}\n");
+	assert_eq!(code, EXIT_CODE_TYPE_ERROR, "{error}");
+}
+
+#[test]
+fn pointer_multiplication()
+{
+	let (code, error) = common::compile_error("pointer_multiplication", "func global main() -> i32\n{\n\tlet x i32 = 1;\n\tlet p *i32 = &x;\n\tlet q *i32 = p * 2;\n\treturn 0;\n}\n");
+	assert_eq!(code, EXIT_CODE_SYNTAX, "{error}");
+}
+
+// Integers are widened into larger integers of the same signedness, and f32 into f64, without a type cast
+#[test]
+fn implicit_widening()
+{
+	let source = "
+func take64(x i64) -> i64
+{
+	return x;
+}
+func take_f64(x f64) -> f64
+{
+	return x;
+}
+func global main() -> i32
+{
+	let result i32 = 0;
+	let a i8 = 0 - 3;
+	let b i32 = 100000;
+	let c i64 = a + b;
+	if c == 99997
+	{
+		result = result + 2;
+	}
+	let u u8 = 200;
+	let v u32 = 4000000000;
+	if u + v == 4000000200 && take64(b) == 100000
+	{
+		result = result + 4;
+	}
+	let f f32 = 1.5;
+	let d f64 = 2;
+	if take_f64(f) * d == 3.0
+	{
+		result = result + 8;
+	}
+	let s i16 = 0 - 2;
+	if take64(s) * a == 6
+	{
+		result = result + 28;
+	}
+	return result;
+}
+";
+	if let Some(code) = run_program("implicit_widening", source)
+	{
+		assert_eq!(code, 42);
+	}
+}
+
+#[test]
+fn implicit_narrowing()
+{
+	let (code, error) = common::compile_error("implicit_narrowing", "func global main() -> i32\n{\n\tlet a i64 = 1;\n\tlet b i32 = a;\n\treturn b;\n}\n");
+	assert_eq!(code, EXIT_CODE_TYPE_ERROR, "{error}");
+}
+
+#[test]
+fn implicit_sign_change()
+{
+	let (code, error) = common::compile_error("implicit_sign_change", "func global main() -> i32\n{\n\tlet a u32 = 1;\n\tlet b i64 = a;\n\treturn 0;\n}\n");
+	assert_eq!(code, EXIT_CODE_TYPE_ERROR, "{error}");
+}
+
+// The address of a variable is a pointer to its full data type, so the address of a *i32 is a **i32
+#[test]
+fn pointer_types()
+{
+	let source = "
+func global main() -> i32
+{
+	let numbers i32 = 40;
+	let pointer *i32 = &numbers;
+	let pointer_to_pointer **i32 = &pointer;
+	**pointer_to_pointer = **pointer_to_pointer + 2;
+
+	let first *i64 = (*i64)pointer;
+	let last *i64 = first + 5;
+	let distance i64 = last - first;
+	if distance != 5 || last <= first || (u64)last - (u64)first != 40
+	{
+		return 1;
+	}
+	return *pointer;
+}
+";
+	if let Some(code) = run_program("pointer_types", source)
+	{
+		assert_eq!(code, 42);
+	}
+}
+
+#[test]
+fn pointer_level_mismatch()
+{
+	let (code, error) = common::compile_error("pointer_level_mismatch", "func global main() -> i32\n{\n\tlet x i32 = 1;\n\tlet p *i32 = &x;\n\tlet q *i32 = &p;\n\treturn 0;\n}\n");
+	assert_eq!(code, EXIT_CODE_TYPE_ERROR, "{error}");
+}
+
+#[test]
+fn integer_into_pointer_without_cast()
+{
+	let (code, error) = common::compile_error("integer_into_pointer_without_cast", "func global main() -> i32\n{\n\tlet x i32 = 1;\n\tlet a u64 = (u64)&x;\n\tlet p *i32 = a;\n\treturn 0;\n}\n");
+	assert_eq!(code, EXIT_CODE_TYPE_ERROR, "{error}");
+}
+
+// void has no size, so pointers to it cannot be offset or subtracted until they are cast
+#[test]
+fn void_pointer_arithmetic()
+{
+	for expression in ["let q *void = p + 3;", "let distance i64 = p - p;"]
+	{
+		let source = format!("func global main() -> i32\n{{\n\tlet x i32 = 1;\n\tlet p *void = (*void)&x;\n\t{expression}\n\treturn 0;\n}}\n");
+		let (code, error) = common::compile_error("void_pointer_arithmetic", &source);
+		assert_eq!(code, EXIT_CODE_TYPE_ERROR, "{expression}: {error}");
+		assert!(error.contains("pointers to void"), "{expression}: {error}");
+		assert!(error.contains("On line 5"), "{expression}: {error}");
+	}
+
+	let source = "
+func global main() -> i32
+{
+	let x i64 = 1;
+	let p *void = (*void)&x;
+	let q *u8 = (*u8)p + 3;
+	return (i32)(q - (*u8)p);
+}
+";
+	if let Some(code) = run_program("void_pointer_arithmetic", source)
+	{
+		assert_eq!(code, 3);
+	}
+}
+
+// The data type of a variable without a written data type is the data type of its initial value
+#[test]
+fn inferred_variable_types()
+{
+	let source = "
+func half(x f64) -> f64
+{
+	return x / 2.0;
+}
+func global main() -> i32
+{
+	let small i8 = 3;
+	let big i64 = 10;
+	let count = 20;
+	let sum = small + big;
+	let ratio = half(5.0);
+	let pointer = &sum;
+	let result i32 = count + (i32)*pointer;
+	if ratio == 2.5
+	{
+		result = result + 9;
+	}
+	return result;
+}
+";
+	if let Some(code) = run_program("inferred_variable_types", source)
+	{
+		assert_eq!(code, 42);
+	}
+}
+
+// An inline function needs a body to substitute into its callers
+#[test]
+fn inline_without_body()
+{
+	let (code, error) = common::compile_error("inline_without_body", "func extern inline putchar(character i32) -> i32;\nfunc global main() -> i32\n{\n\treturn 0;\n}\n");
+	assert_eq!(code, EXIT_CODE_SYNTAX, "{error}");
+	assert!(error.contains("Only functions with a body can be inline."), "{error}");
+}
+
+// The recursive calls of a tailrec function must be the values of return statements, and it cannot take the address of its variables
+#[test]
+fn recursive_call_not_in_tail_position()
+{
+	let source = "func tailrec sum(n i32) -> i32\n{\n\tif n == 0\n\t{\n\t\treturn 0;\n\t}\n\treturn n + sum(n - 1);\n}\n";
+	let (code, error) = common::compile_error("recursive_call_not_in_tail_position", source);
+	assert_eq!(code, EXIT_CODE_TAIL_CALL, "{error}");
+	assert!(error.contains("can call itself only as the value of a return statement"), "{error}");
+
+	let source = "func tailrec find(n i32) -> i32\n{\n\tlet value i32 = n;\n\tlet pointer *i32 = &value;\n\tif n == 0\n\t{\n\t\treturn *pointer;\n\t}\n\treturn find(n - 1);\n}\n";
+	let (code, error) = common::compile_error("tail_recursive_address_taken", source);
+	assert_eq!(code, EXIT_CODE_TAIL_CALL, "{error}");
+	assert!(error.contains("The address of a variable cannot be taken"), "{error}");
+}
+
+#[test]
+fn inferred_variable_without_value()
+{
+	let (code, error) = common::compile_error("inferred_variable_without_value", "func global main() -> i32\n{\n\tlet x;\n\tx = 5;\n\treturn x;\n}\n");
+	assert_eq!(code, EXIT_CODE_SYNTAX, "{error}");
+}
+
+#[test]
+fn inferred_integer_literal_is_i32()
+{
+	let (code, error) = common::compile_error("inferred_integer_literal_is_i32", "func global main() -> i32\n{\n\tlet x = 5;\n\tlet y i16 = 1;\n\ty = x;\n\treturn x;\n}\n");
+	assert_eq!(code, EXIT_CODE_TYPE_ERROR, "{error}");
+}
+
+// A literal that does not fit in the data type it takes is not wrapped implicitly
+#[test]
+fn literal_out_of_range()
+{
+	for (name, declaration) in [("u8", "let y u8 = 300;"), ("i8", "let y i8 = 200;")]
+	{
+		let source = format!("func global main() -> i32\n{{\n\t{declaration}\n\treturn (i32)y;\n}}\n");
+		let (code, error) = common::compile_error(&format!("literal_out_of_range_{name}"), &source);
+		assert_eq!(code, EXIT_CODE_TYPE_ERROR, "{declaration}: {error}");
+		assert!(error.contains("does not fit in"), "{declaration}: {error}");
+	}
+}
+
+// A literal without an expected data type that does not fit in an i32 is an i64
+#[test]
+fn large_literal_is_i64()
+{
+	let source = "func global main() -> i32\n{\n\tlet big = 5000000000;\n\tlet small u8 = 255;\n\treturn (i32)(big / 100000000) - (i32)small;\n}\n";
+	if let Some(code) = run_program("large_literal_is_i64", source)
+	{
+		assert_eq!(code, (50 - 255) & 0xFF);
+	}
+}
+
+// An expression that has only literals takes the data type of its largest literal, also in a comparison and in a type cast
+#[test]
+fn mixed_literals()
+{
+	let source = "
+func global main() -> i32
+{
+	let failed i32 = 0;
+	let sum = 1 + 5000000000;
+	if sum != 5000000001 { failed = failed + 1; }
+	if 5 < 5000000000 { } else { failed = failed + 2; }
+	if (i64)(1 + 5000000000) != 5000000001 { failed = failed + 4; }
+	if (u16)(0 | 9223372036854775807) != 65535 { failed = failed + 8; }
+	return failed;
+}
+";
+	if let Some(code) = run_program("mixed_literals", source)
+	{
+		assert_eq!(code, 0, "{code}");
+	}
+
+	// The expected data type still decides the data type of the literals
+	let source = "func global main() -> i32\n{\n\tlet y i32 = 1 + 5000000000;\n\treturn y;\n}\n";
+	let (code, error) = common::compile_error("mixed_literals_expected", source);
+	assert_eq!(code, EXIT_CODE_TYPE_ERROR, "{error}");
+	assert!(error.contains("The number 5000000000 does not fit in i32"), "{error}");
+}
+
+// Expressions that have only constants are evaluated while compiling, and must give the same result as they would at runtime
+#[test]
+fn constant_folding()
+{
+	let source = "
+func global main() -> i32
+{
+	const size = 4 * 5;
+	const big i64 = (1 << 20) * 1000;
+	const small u8 = (u8)300;
+	let result i32 = size + 2;
+	if 3 > 2 && 2.5 * 2.0 == 5.0
+	{
+		result = result + (8 >> 1) - (0 - 7) % 4 + (i32)2.9;
+	}
+	if big / 1000 != 1048576 || small != 44 || ~0 != 0 - 1 || !0 != 1
+	{
+		return 1;
+	}
+	if (0 - 1) >> 28 != 15 || (u32)(0 - 1) != 4294967295
+	{
+		return 2;
+	}
+	return result + 11;
+}
+";
+	if let Some(code) = run_program("constant_folding", source)
+	{
+		assert_eq!(code, 42);
+	}
+}
+
+#[test]
+fn constant_division_by_zero()
+{
+	let (code, error) = common::compile_error("constant_division_by_zero", "func global main() -> i32\n{\n\tconst zero = 2 - 2;\n\treturn 5 % zero;\n}\n");
+	assert_eq!(code, EXIT_CODE_DIVISION_BY_ZERO, "{error}");
+}
+
+#[test]
+fn assign_into_constant()
+{
+	let (code, error) = common::compile_error("assign_into_constant", "func global main() -> i32\n{\n\tconst x = 5;\n\tx = 6;\n\treturn x;\n}\n");
+	assert_eq!(code, EXIT_CODE_SYNTAX, "{error}");
+
+	let (code, error) = common::compile_error("constant_address", "func global main() -> i32\n{\n\tconst x = 5;\n\tlet p = &x;\n\treturn 0;\n}\n");
+	assert_eq!(code, EXIT_CODE_SYNTAX, "{error}");
+}
+
+#[test]
+fn constant_value_unknown_while_compiling()
+{
+	let (code, error) = common::compile_error("constant_value_unknown", "func global main() -> i32\n{\n\tlet x = 5;\n\tconst y = x + 1;\n\treturn y;\n}\n");
+	assert_eq!(code, EXIT_CODE_SYNTAX, "{error}");
+}
@@ -0,0 +1,72 @@
+// C interop tests for the System V AMD64 ABI.
+// Each test compiles a Slow file with slowc, compiles a C file with the system C compiler, links them together and runs the result.
+// The program exits with 0 if every check on both sides of the call passed.
+#![cfg(not(feature = "hebrew"))]
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::path::Path;
+
+// The calling convenction must hold at every optimization level
+const OPTIMIZATION_LEVELS: [&str; 3] = ["-O0", "-O1", "-O2"];
+
+// Runs the fixture at each optimization level, and returns the exit code of the first run that did not exit with 0
+fn run_fixture(name: &str) -> Option<i32>
+{
+	let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("sys_v_abi");
+	for level in OPTIMIZATION_LEVELS
+	{
+		let code = common::compile_and_run_with_arguments(
+			&format!("{name}{level}"),
+			&directory.join(format!("{name}.slw")),
+			Some(&directory.join(format!("{name}.c"))),
+			&[level]
+		)?;
+		if code != 0
+		{
+			return Some(code);
+		}
+	}
+	return Some(0);
+}
+
+// Slow calls C functions with many integer, float and mixed arguments, float returns and a variadic call
+#[test]
+fn slow_calls_c()
+{
+	if let Some(code) = run_fixture("slow_calls_c")
+	{
+		assert_eq!(code, 0, "{code} checks failed");
+	}
+}
+
+// C calls Slow functions, and checks that the callee-saved registers are preserved
+#[test]
+fn c_calls_slow()
+{
+	if let Some(code) = run_fixture("c_calls_slow")
+	{
+		assert_eq!(code, 0, "{code} checks failed");
+	}
+}
+
+// The stack is 16 byte aligned at every call, including calls with stack arguments and nested calls
+#[test]
+fn stack_alignment()
+{
+	if let Some(code) = run_fixture("alignment")
+	{
+		assert_eq!(code, 0, "{code} checks failed");
+	}
+}
+
+// Function pointers passed between Slow and C, called indirectly, stored in memory and used as qsort callbacks
+#[test]
+fn function_pointers()
+{
+	if let Some(code) = run_fixture("function_pointers")
+	{
+		assert_eq!(code, 0, "{code} checks failed");
+	}
+}
@@ -0,0 +1,11 @@
+#include <stdint.h>
+
+int c_is_aligned(void)
+{
+	return ((uintptr_t)__builtin_frame_address(0) & 15) == 0;
+}
+
+int c_is_aligned_args(int64_t a, int64_t b, int64_t c, int64_t d, int64_t e, int64_t f, int64_t g, int32_t h)
+{
+	return c_is_aligned() && a == 1 && b == 2 && c == 3 && d == 4 && e == 5 && f == 6 && g == 7 && h == 1;
+}
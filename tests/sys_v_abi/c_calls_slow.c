@@ -0,0 +1,74 @@
+#include <stdint.h>
+
+int64_t slow_sum_ints(int8_t a, int16_t b, int32_t c, int64_t d, uint8_t e, uint16_t f, uint32_t g, uint64_t h);
+double slow_sum_floats(double a, float b, double c, float d, double e, float f, double g, float h, double i, float j);
+double slow_mixed(int32_t a, double b, int64_t c, float d, int64_t e, double f, int64_t g, double h, int64_t i, float j,
+	int64_t k, double l, int64_t m, float n, double o, float p, double q);
+float slow_return_f32(float value);
+double slow_calls_back(int32_t value);
+int64_t slow_clobber(int64_t a);
+
+static int callback_was_aligned = 0;
+
+double c_callback(int32_t value, double factor)
+{
+	callback_was_aligned = ((uintptr_t)__builtin_frame_address(0) & 15) == 0;
+	return value * factor;
+}
+
+// Calls slow_clobber() with known values in the callee-saved registers, and checks that they were preserved.
+static int64_t saved_registers[5];
+static int check_callee_saved(void)
+{
+	__asm__ volatile(
+		"push %%rbx\n\t"
+		"push %%r12\n\t"
+		"push %%r13\n\t"
+		"push %%r14\n\t"
+		"push %%r15\n\t"
+		"push %%rbp\n\t"
+		"mov %%rsp, %%rbp\n\t"
+		"and $-16, %%rsp\n\t"
+		"mov $0x1111, %%rbx\n\t"
+		"mov $0x1212, %%r12\n\t"
+		"mov $0x1313, %%r13\n\t"
+		"mov $0x1414, %%r14\n\t"
+		"mov $0x1515, %%r15\n\t"
+		"mov $1, %%edi\n\t"
+		"call slow_clobber\n\t"
+		"mov %%rbx, saved_registers(%%rip)\n\t"
+		"mov %%r12, saved_registers+8(%%rip)\n\t"
+		"mov %%r13, saved_registers+16(%%rip)\n\t"
+		"mov %%r14, saved_registers+24(%%rip)\n\t"
+		"mov %%r15, saved_registers+32(%%rip)\n\t"
+		"mov %%rbp, %%rsp\n\t"
+		"pop %%rbp\n\t"
+		"pop %%r15\n\t"
+		"pop %%r14\n\t"
+		"pop %%r13\n\t"
+		"pop %%r12\n\t"
+		"pop %%rbx\n\t"
+		::: "rax", "rcx", "rdx", "rsi", "rdi", "r8", "r9", "r10", "r11", "memory", "cc",
+		"xmm0", "xmm1", "xmm2", "xmm3", "xmm4", "xmm5", "xmm6", "xmm7",
+		"xmm8", "xmm9", "xmm10", "xmm11", "xmm12", "xmm13", "xmm14", "xmm15"
+	);
+	return saved_registers[0] == 0x1111 && saved_registers[1] == 0x1212 && saved_registers[2] == 0x1313 &&
+		saved_registers[3] == 0x1414 && saved_registers[4] == 0x1515;
+}
+
+int main(void)
+{
+	int failures = 0;
+	failures += slow_sum_ints(1, 2, 3, 4, 5, 6, 7, 8) != 87654321;
+	failures += slow_sum_ints(-1, -1, -1, -1, 0, 0, 0, 0) != -1111;
+	failures += slow_sum_floats(1, 1, 1, 1, 1, 1, 1, 1, 1, 1) != 55;
+	failures += slow_sum_floats(0, 0, 0, 0, 0, 0, 0, 0, 0, 0.5f) != 5;
+	failures += slow_mixed(1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1) != 28055;
+	failures += slow_mixed(0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0.5) != 14005;
+	failures += slow_return_f32(1.25f) != 2.5f;
+	failures += slow_calls_back(4) != 11;
+	failures += !callback_was_aligned;
+	failures += slow_clobber(1) != 104;
+	failures += !check_callee_saved();
+	return failures;
+}
@@ -0,0 +1,32 @@
+#include <stdarg.h>
+#include <stdint.h>
+
+int32_t c_apply(int32_t (*function)(int32_t, int32_t), int32_t a, int32_t b)
+{
+	return function(a, b);
+}
+
+static int32_t multiply(int32_t a, int32_t b)
+{
+	return a * b;
+}
+
+int32_t (*c_get_multiply(void))(int32_t, int32_t)
+{
+	return multiply;
+}
+
+int c_variadic(int count, ...)
+{
+	va_list arguments;
+	va_start(arguments, count);
+	int8_t small = va_arg(arguments, int);
+	double a = va_arg(arguments, double);
+	double quarter = va_arg(arguments, double);
+	int64_t b = va_arg(arguments, int64_t);
+	int c = va_arg(arguments, int);
+	int d = va_arg(arguments, int);
+	int e = va_arg(arguments, int);
+	va_end(arguments);
+	return count == 7 && small == -3 && a == 2.5 && quarter == 0.25 && b == 4 && c == 5 && d == 6 && e == 7;
+}
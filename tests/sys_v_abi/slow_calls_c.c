@@ -0,0 +1,51 @@
+#include <stdarg.h>
+#include <stdint.h>
+
+int c_many_ints(int8_t a, int16_t b, int32_t c, int64_t d, uint8_t e, uint16_t f, uint32_t g, uint64_t h)
+{
+	return a == -1 && b == 2 && c == 3 && d == 4 && e == 5 && f == 6 && g == 7 && h == 8;
+}
+
+int c_many_floats(double a, float b, double c, float d, double e, float f, double g, float h, double i, float j)
+{
+	return a == 1.5 && b == 2.5f && c == 3.5 && d == 4.5f && e == 5.5 && f == 6.5f && g == 7.5 && h == 8.5f && i == 9.5 && j == 10.5f;
+}
+
+int c_mixed(int32_t a, double b, int64_t c, float d, uint8_t e, double f, int16_t g, double h, uint32_t i, float j,
+	int64_t k, double l, int64_t m, float n, double o, float p, double q)
+{
+	return a == 1 && b == 2.5 && c == 3 && d == 4.5f && e == 5 && f == 6.5 && g == 7 && h == 8.5 && i == 9 && j == 10.5f &&
+		k == 11 && l == 12.5 && m == 13 && n == 14.5f && o == 15.5 && p == 16.5f && q == 17.5;
+}
+
+double c_return_f64(double value)
+{
+	return value * 2;
+}
+
+float c_return_f32(float value)
+{
+	return value * 2;
+}
+
+int8_t c_return_i8(int8_t value)
+{
+	return value * 2;
+}
+
+// Expects: int, double, double, long, int, int, int
+int c_variadic(int count, ...)
+{
+	va_list arguments;
+	va_start(arguments, count);
+	int ok = count == 7;
+	ok &= va_arg(arguments, int) == -3;
+	ok &= va_arg(arguments, double) == 2.5;
+	ok &= va_arg(arguments, double) == 0.5;
+	ok &= va_arg(arguments, int64_t) == 4;
+	ok &= va_arg(arguments, int) == 5;
+	ok &= va_arg(arguments, int) == 6;
+	ok &= va_arg(arguments, int) == 7;
+	va_end(arguments);
+	return ok;
+}
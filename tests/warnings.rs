@@ -0,0 +1,162 @@
+// Tests for the warnings pass, and the -W command line options that control it.
+#![cfg(not(feature = "hebrew"))]
+#![allow(clippy::needless_return)]
+
+mod common;
+
+/* The exit codes of slowc are the index of the error in error::ExitCodes, plus 1 */
+const EXIT_CODE_USAGE: i32 = 1;
+const EXIT_CODE_WARNINGS_AS_ERRORS: i32 = 10;
+
+const SOURCE: &str = "
+func compute(a i32, unused i32) -> i32
+{
+	let result i32 = a;
+	result = 5;
+	result = a * 2;
+	let nothing i32 = 3;
+	if 2 > 1
+	{
+		return result;
+	}
+	return result + 1;
+	result = 0;
+}
+
+func maybe(a i32) -> i32
+{
+	if a > 3
+	{
+		return 1;
+	}
+}
+
+func global main() -> i32
+{
+	return compute(1, 2) + maybe(4) + (i32)maybe(5);
+}
+";
+
+// Each warning is reported with the option that controls it, and -Werror reports them as errors which fail the compilation
+#[test]
+fn all_warnings()
+{
+	let (code, output, error) = common::compile_with_arguments("all_warnings", SOURCE, &["-Werror"]);
+	assert_eq!(code, EXIT_CODE_WARNINGS_AS_ERRORS, "{output}{error}");
+	for name in ["unused-parameter", "unused-variable", "unused-assignment", "constant-condition", "unreachable-code", "missing-return", "redundant-cast"]
+	{
+		assert!(error.contains(&format!("[-Werror={name}]")), "Expected a -W{name} error:\n{error}");
+	}
+	assert!(error.contains("\"unused\""), "{error}");
+	assert!(error.contains("\"nothing\""), "{error}");
+	assert!(!error.contains("\"a\""), "{output}{error}");
+}
+
+#[test]
+fn disabled_warnings()
+{
+	let arguments = [
+		"-Wno-unused-parameter", "-Wno-unused-variable", "-Wno-unused-assignment", "-Wno-constant-condition",
+		"-Wno-unreachable-code", "-Wno-missing-return", "-Wno-redundant-cast", "-Werror", "-Wno-error"
+	];
+	let (code, output, error) = common::compile_with_arguments("disabled_warnings", SOURCE, &arguments);
+	assert!(!output.contains("[-W"), "{output}");
+	assert_ne!(code, EXIT_CODE_WARNINGS_AS_ERRORS, "{error}");
+}
+
+#[test]
+fn enable_single_warning()
+{
+	let (code, output, error) = common::compile_with_arguments("enable_single_warning", SOURCE, &["-Wno-all", "-Wmissing-return", "-Werror"]);
+	assert_eq!(code, EXIT_CODE_WARNINGS_AS_ERRORS, "{error}");
+	assert_eq!(error.matches("[-W").count(), 1, "{output}{error}");
+	assert!(error.contains("\"maybe\""), "{error}");
+}
+
+// Values that are read in a later iteration of a loop are not reported
+#[test]
+fn loops_keep_values_alive()
+{
+	let source = "
+func global main() -> i32
+{
+	let previous i32 = 0;
+	let current i32 = 1;
+	for let i i32 = 0; i < 10; i = i + 1;
+	{
+		let next i32 = previous + current;
+		previous = current;
+		current = next;
+	}
+	return current;
+}
+";
+	let (code, output, error) = common::compile_with_arguments("loops_keep_values_alive", source, &["-Werror"]);
+	assert_ne!(code, EXIT_CODE_WARNINGS_AS_ERRORS, "{output}{error}");
+	assert!(!output.contains("[-W"), "{output}");
+}
+
+#[test]
+fn unknown_warning_option()
+{
+	let (code, _, error) = common::compile_with_arguments("unknown_warning_option", SOURCE, &["-Wsomething"]);
+	assert_eq!(code, EXIT_CODE_USAGE, "{error}");
+}
+
+// A variable is assigned after an if statement only if both branches assign it
+#[test]
+fn uninitialized_variables()
+{
+	let source = "
+func pick(condition i32) -> i32
+{
+	let both i32;
+	let one i32;
+	let in_loop i32;
+	if condition > 0
+	{
+		both = 1;
+		one = 1;
+	} else
+	{
+		both = 2;
+	}
+	for let i i32 = 0; i < condition; i = i + 1;
+	{
+		in_loop = i;
+	}
+	return both + one + in_loop;
+}
+
+func global main() -> i32
+{
+	return pick(1);
+}
+";
+	let (code, output, error) = common::compile_with_arguments("uninitialized_variables", source, &[]);
+	assert_eq!(code, EXIT_CODE_WARNINGS_AS_ERRORS, "{output}{error}");
+	assert!(error.contains("\"one\"") && error.contains("\"in_loop\""), "{error}");
+	assert!(!error.contains("\"both\""), "{error}");
+
+	let (_, output, error) = common::compile_with_arguments("uninitialized_variables", source, &["-Wno-error=uninitialized"]);
+	assert_eq!(output.matches("[-Wuninitialized]").count(), 2, "{output}{error}");
+	assert!(!error.contains("[-Werror"), "{error}");
+}
+
+// Signed constant expressions whose result does not fit in their data type are wrapped, unsigned ones are wrapped silently
+#[test]
+fn constant_overflow()
+{
+	let source = "
+func global main() -> i32
+{
+	let big i32 = 2147483647 + 1;
+	let wrapped u8 = 255 + 1;
+	let product i64 = 4611686018427387904 * 2;
+	return big + (i32)wrapped + (i32)product;
+}
+";
+	let (code, output, error) = common::compile_with_arguments("constant_overflow", source, &["-Werror=overflow"]);
+	assert_eq!(code, EXIT_CODE_WARNINGS_AS_ERRORS, "{output}{error}");
+	assert_eq!(error.matches("[-Werror=overflow]").count(), 2, "{output}{error}");
+}
@@ -0,0 +1,133 @@
+// Tests for the WebAssembly backend (--target=wasm32), which writes a module in the text format.
+// The modules are assembled with wat2wasm and run with node when both are available.
+#![cfg(not(feature = "hebrew"))]
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::path::Path;
+
+const TARGET: &str = "--target=wasm32";
+
+// Uses the variables and parameters whose address is taken (which live on the stack in the linear memory), function pointers,
+// imported functions and a tailrec function. The functions print in the order they are called.
+const SOURCE: &str = "
+func extern putchar(character i32) -> i32;
+func extern malloc(size u64) -> *i64;
+
+func say(character i32, value i64) -> i64
+{
+	putchar(character);
+	return value;
+}
+
+func bump(pointer *i64, value i64) -> i64
+{
+	*pointer = *pointer + value;
+	return value;
+}
+
+func apply(function func(i32) -> i32, value i32) -> i32
+{
+	return function(value);
+}
+
+func square(x i32) -> i32
+{
+	return x * x;
+}
+
+func tailrec sum(count i64, total i64) -> i64
+{
+	if count == 0
+	{
+		return total;
+	}
+	return sum(count - 1, total + count);
+}
+
+func swap(first i16, second i16) -> i16
+{
+	let pointer *i16 = &first;
+	*pointer = second;
+	return first + second;
+}
+
+func global main() -> i32
+{
+	let failed i32 = 0;
+	let x i64 = 1;
+	if x + bump(&x, 5) != 6 { failed = failed + 1; }
+	if say(49, 1) * 100 + say(50, 2) * 10 + say(51, 3) != 123 { failed = failed + 1; }
+	if apply(&square, 9) != 81 { failed = failed + 1; }
+	if sum(1000000, 0) != 500000500000 { failed = failed + 1; }
+	if swap(30000, 20000) != (i16)(0 - 25536) { failed = failed + 1; }
+
+	let memory *i64 = malloc(16);
+	*(memory + (i64)bump(memory, 1)) = say(52, 7);
+	let pointer **i64 = &memory;
+	if *(*pointer + 1) != 7 || (memory + 2) - memory != 2 { failed = failed + 1; }
+
+	let small u8 = 250;
+	small = small + 10;
+	let negative i8 = 0 - 2;
+	if small != 4 || negative >> 1 != 127 || negative >> 0 != negative || ~(u8)5 != 250 { failed = failed + 1; }
+	let ratio f64 = 2.5;
+	if (i32)(ratio * 3.0) != 7 || (u32)(f32)3000000000.0 != 3000000000 { failed = failed + 1; }
+	putchar(10);
+	return failed;
+}
+";
+
+// Integers are mapped onto the value types of WebAssembly, external functions are imported and global functions are exported
+#[test]
+fn emit_wat()
+{
+	let text = common::translate_to_wat("emit_wat", SOURCE).expect("slowc failed");
+	for line in [
+		"(import \"env\" \"putchar\" (func $putchar (type $slow_function_type0)))",
+		"(memory (export \"memory\") 16)",
+		"(global $__stack_pointer (mut i32) (i32.const 1048576))",
+		"(elem (i32.const 1) func $square)",
+		"(func $say (param $character_0 i32) (param $value_1 i64) (result i64)",
+		"(func $main (export \"main\") (result i32)",
+		"call_indirect (type $slow_function_type0)",
+		"br $__tail",
+		"i32.extend16_s",
+		"i32.trunc_sat_f64_s",
+	]
+	{
+		assert!(text.contains(line), "Expected \"{line}\" in the WebAssembly text:\n{text}");
+	}
+}
+
+#[test]
+fn same_results_as_native()
+{
+	let directory = std::env::temp_dir().join("slowc_test_wasm_same_results_as_native");
+	std::fs::create_dir_all(&directory).unwrap();
+	let program = directory.join("program.slw");
+	std::fs::write(&program, SOURCE).unwrap();
+	let encoding = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("assembler").join("encoding.slw");
+
+	for (name, slow_file, expected) in [("wasm_program", &program, (0, "1234\n")), ("wasm_encoding", &encoding, (0, ""))]
+	{
+		if let Some(code) = common::compile_and_run(&format!("{name}_native"), slow_file, None)
+		{
+			assert_eq!(code, expected.0, "{name} compiled by slowc");
+		}
+		if let Some((code, output)) = common::compile_wasm_and_run(name, slow_file)
+		{
+			assert_eq!((code, output.as_str()), expected, "{name} compiled for wasm32");
+		}
+	}
+}
+
+// There is no object file, so there is nothing to assemble
+#[test]
+fn wasm32_is_not_assembled()
+{
+	let (code, _, error) = common::compile_with_arguments("wasm32_assembler", "func global main() -> i32 { return 0; }", &[TARGET, "-S"]);
+	assert_ne!(code, 0);
+	assert!(error.contains("The target wasm32 is not assembled"), "{error}");
+}